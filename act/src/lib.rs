@@ -14,6 +14,7 @@ pub mod parse;
 
 
 /// The rules used pre2020 for the ACT Legislative Assembly, when votes were integers
+#[derive(Default)]
 pub struct ACTPre2020 {
 }
 
@@ -22,7 +23,7 @@ impl PreferenceDistributionRules for ACTPre2020 {
     type SplitByNumber = DoNotSplitByCountNumber;
 
     /// See below comment, (5)
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { LastParcelUse::LiterallyLast }
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::LiterallyLast }
 
     /// Electoral Act 1992, Schedule 4, 1C
     /// ```text
@@ -53,24 +54,24 @@ impl PreferenceDistributionRules for ACTPre2020 {
     ///        a next available preference.
     ///     S means the surplus.
     /// ```
-    fn transfer_value_method() -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallotsLimitedToPriorTransferValue }
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallotsLimitedToPriorTransferValue }
 
-    fn make_transfer_value(surplus: usize, ballots: BallotPaperCount) -> TransferValue {
+    fn make_transfer_value(&self,surplus: usize, ballots: BallotPaperCount) -> TransferValue {
         TransferValue::from_surplus(surplus,ballots)
     }
     fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { convert_usize_to_rational(tally)  }
     fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { round_rational_down_to_usize(rational)  }
 
-    fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
         transfer_value.mul_rounding_down(ballots)
     }
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool { true } // not applicable as distribute_surplus_all_with_same_transfer_value.
-    fn check_elected_if_in_middle_of_exclusion() -> bool { true }
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod { SurplusTransferMethod::JustOneTransferValue }
-    fn sort_exclusions_by_transfer_value() -> bool { true }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { true } // not applicable as distribute_surplus_all_with_same_transfer_value.
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { true }
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::JustOneTransferValue }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { true }
 
     /// Not applicable.
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { MethodOfTieResolution::None }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
     /// Electoral Act 1992, Part 4.2 7 (3)(c)
     /// ```text
     /// if 2 or more successful candidates (contemporary candidates)
@@ -96,11 +97,11 @@ impl PreferenceDistributionRules for ACTPre2020 {
     /// ```
     /// Note that this applies to the order of surplus distribution, which I am assuming is
     /// also the order of election.
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
     /// The act doesn't really talk about order of election, which doesn't appear to matter. So anything is OK, may as well be same as others.
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
     /// Electoral Act 1992, Part 4.2 8 (2) is very similar to 7(3)(c) except reversed as it deals with identifying lowest rather than highest.
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
 
 
 
@@ -113,12 +114,12 @@ impl PreferenceDistributionRules for ACTPre2020 {
     /// 3(3) is first preferences
     /// 6(4) is surplus distribution
     /// 9(2)(d) is excluded candidates, for a single transfer value.
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool { false }
-    fn finish_all_surplus_distributions_when_all_elected() -> bool { false }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
 
 
     /// The ACT legislation is rather minimilist, and has no such rule.
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
 
 
     /// Electoral Act 1992, Part 4.2 4 (2)
@@ -136,16 +137,16 @@ impl PreferenceDistributionRules for ACTPre2020 {
     /// 3(3) is first preferences
     /// 6(4) is surplus distribution
     /// 9(2)(d) is excluded candidates, for a single transfer value.
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
 
     /// If the TV calculation is limited due to incoming TV (such as in ACT) this causes votes to be set aside.
     /// These will normally be counted as set aside, but Elections ACT counts them as lost to rounding.
     /// I presume this is because the sane way to compute rounding is to compute the total votes in and subtract votes out, and if you forget about set aside votes, they go to rounding, so this bizarre decision could just be forgetting to deal with them. Then when someone asked why the votes lost due to rounding was so big, and realised why, they maybe decided it was not worth adding a new column for votes set aside, and rationalized not doing anything about it. Just a guess.
     /// Anyway, it doesn't really matter, there is no legislative requirement to count rounding. Although it would be darkly amusing if the rules changes to truncate to 6 decimal digits instead of to an integer was caused by seeing a large number of votes ostensibly lost due to rounding and wanting to do something about it. Just a guess.
-    fn count_set_aside_due_to_transfer_value_limit_as_rounding() -> bool { true }
+    fn count_set_aside_due_to_transfer_value_limit_as_rounding(&self) -> bool { true }
 
-    fn name() -> String { "ACTPre2020".to_string() }
+    fn name(&self) -> String { "ACTPre2020".to_string() }
 
 }
 
@@ -164,6 +165,7 @@ impl PreferenceDistributionRules for ACTPre2020 {
 /// had three classes of bugs. After we pointed them out, they denied the worst, but then
 /// in 2021 quietly fixed them and replaced their transcript of distributions of preferences on
 /// their website, and used the corrected rules when an elected candidate had to be replaced in 2021.
+#[derive(Default)]
 pub struct ACT2021 {
 }
 
@@ -171,35 +173,35 @@ impl PreferenceDistributionRules for ACT2021 {
     type Tally = FixedPrecisionDecimal<6>;
     type SplitByNumber = DoNotSplitByCountNumber;
 
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { LastParcelUse::LiterallyLast }
-    fn transfer_value_method() -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallotsLimitedToPriorTransferValue }
-    fn make_transfer_value(surplus: Self::Tally, ballots: BallotPaperCount) -> TransferValue {
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::LiterallyLast }
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallotsLimitedToPriorTransferValue }
+    fn make_transfer_value(&self,surplus: Self::Tally, ballots: BallotPaperCount) -> TransferValue {
         TransferValue::from_surplus(surplus.get_scaled_value() as usize,BallotPaperCount(ballots.0*(Self::Tally::SCALE as usize)))
     }
     fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { tally.to_rational()  }
     fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { Self::Tally::from_rational_rounding_down(rational) }
 
-    fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> Self::Tally {
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> Self::Tally {
         Self::Tally::from_scaled_value(transfer_value.mul_rounding_down(BallotPaperCount(ballots.0*(Self::Tally::SCALE as usize))) as u64)
     }
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool { true } // not applicable as distribute_surplus_all_with_same_transfer_value.
-    fn check_elected_if_in_middle_of_exclusion() -> bool { true }
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod { SurplusTransferMethod::JustOneTransferValue }
-    fn sort_exclusions_by_transfer_value() -> bool { true }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { true } // not applicable as distribute_surplus_all_with_same_transfer_value.
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { true }
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::JustOneTransferValue }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { true }
 
     // all below same as ACTpre2020.
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { MethodOfTieResolution::None }
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool { false }
-    fn finish_all_surplus_distributions_when_all_elected() -> bool { false }
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
-    fn count_set_aside_due_to_transfer_value_limit_as_rounding() -> bool { true }
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
-
-    fn name() -> String { "ACT2021".to_string() }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn count_set_aside_due_to_transfer_value_limit_as_rounding(&self) -> bool { true }
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+
+    fn name(&self) -> String { "ACT2021".to_string() }
 }
 
 
@@ -211,6 +213,7 @@ impl PreferenceDistributionRules for ACT2021 {
 ///  * Round exhausted votes to an integer when doing exclusions (instead of 6 decimal places). This can't change who is elected, just the transcript.
 ///  * Surplus distribution is completed even after everyone is elected. This can't change who is elected, just the transcript.
 /// See our report for more details.
+#[derive(Default)]
 pub struct ACT2020 {
 }
 
@@ -220,9 +223,9 @@ impl PreferenceDistributionRules for ACT2020 {
     /// E.g. Ginninderra Count 39
     type SplitByNumber = SplitByWhenTransferValueWasCreated;
 
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { LastParcelUse::LiterallyLast }
-    fn transfer_value_method() -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallotsLimitedToPriorTransferValue }
-    fn make_transfer_value(surplus: Self::Tally, ballots: BallotPaperCount) -> TransferValue {
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::LiterallyLast }
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallotsLimitedToPriorTransferValue }
+    fn make_transfer_value(&self,surplus: Self::Tally, ballots: BallotPaperCount) -> TransferValue {
         TransferValue::from_surplus(surplus.get_scaled_value() as usize,BallotPaperCount(ballots.0*(Self::Tally::SCALE as usize)))
     }
     fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { tally.to_rational()  }
@@ -230,36 +233,36 @@ impl PreferenceDistributionRules for ACT2020 {
 
     /// Round to nearest instead of down
     /// E.g. Murrumbidgee count 22
-    fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> Self::Tally {
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> Self::Tally {
         Self::Tally::from_scaled_value(transfer_value.mul_rounding_nearest(BallotPaperCount(ballots.0*(Self::Tally::SCALE as usize))) as u64)
     }
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool { true } // not applicable as distribute_surplus_all_with_same_transfer_value.
-    fn check_elected_if_in_middle_of_exclusion() -> bool { true }
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod { SurplusTransferMethod::JustOneTransferValue }
-    fn sort_exclusions_by_transfer_value() -> bool { true }
-
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { MethodOfTieResolution::None }
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool { false }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { true } // not applicable as distribute_surplus_all_with_same_transfer_value.
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { true }
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::JustOneTransferValue }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { true }
+
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
     /// Surplus distribution is completed even after everyone is elected. This can't change who is elected, just the transcript.
-    fn finish_all_surplus_distributions_when_all_elected() -> bool { true }
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
-    fn count_set_aside_due_to_transfer_value_limit_as_rounding() -> bool { true }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { true }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn count_set_aside_due_to_transfer_value_limit_as_rounding(&self) -> bool { true }
 
     /// Round exhausted votes to an integer when doing exclusions (instead of 6 decimal places).
     /// e.g. Ginninderra count 25
-    fn munge_exhausted_votes(exhausted:Self::Tally,is_exclusion:bool) -> Self::Tally { if is_exclusion { exhausted.round_down() } else {exhausted} }
+    fn munge_exhausted_votes(&self,exhausted:Self::Tally,is_exclusion:bool) -> Self::Tally { if is_exclusion { exhausted.round_down() } else {exhausted} }
 
     /// Round transfer values to 6 decimal places when rule 1C(4) is used.
     /// e.g Murrumbidgee count 32
-    fn munge_transfer_value_when_used_as_limit(original:TransferValue) -> TransferValue {
+    fn munge_transfer_value_when_used_as_limit(&self,original:TransferValue) -> TransferValue {
         let num = original.mul_rounding_nearest(BallotPaperCount(1000000));
         TransferValue::from_surplus(num,BallotPaperCount(1000000))
     }
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
 
-    fn name() -> String { "ACT2020".to_string() }
+    fn name(&self) -> String { "ACT2020".to_string() }
 }
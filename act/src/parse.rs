@@ -12,12 +12,12 @@
 
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
-use stv::parse_util::{FileFinder, KnowsAboutRawMarkings, MissingFile, RawDataSource, read_raw_data_checking_against_official_transcript_to_deduce_ec_resolutions};
+use stv::parse_util::{CanReadRawMarkings, FileFinder, MissingFile, RawBallotPaperMetadata, RawDataSource, read_raw_data_checking_against_official_transcript_to_deduce_ec_resolutions};
 use std::path::PathBuf;
 use stv::ballot_metadata::{Party, CandidateIndex, Candidate, PartyIndex, ElectionMetadata, DataSource, ElectionName, NumberOfCandidates};
 use stv::election_data::ElectionData;
 use stv::tie_resolution::TieResolutionsMadeByEC;
-use stv::ballot_paper::UniqueBTLBuilder;
+use stv::ballot_paper::{RawBallotMarking, RawBallotMarkings, RawMarkingsComingOutOfOrderHelper, UniqueBTLBuilder};
 use anyhow::anyhow;
 use serde::Deserialize;
 use stv::official_dop_transcript::{OfficialDistributionOfPreferencesTranscript, OfficialDOPForOneCount};
@@ -74,7 +74,38 @@ pub struct ACTDataLoader {
     electorate_to_ecode : HashMap<String,usize>, // convert a human readable electorate to a ecode used in Elections ACT datafiles. An ecode is a small integer.
 }
 
-impl KnowsAboutRawMarkings for ACTDataLoader {}
+impl CanReadRawMarkings for ACTDataLoader {
+    /// Elections ACT only publish formal preferences, so the markings are just the preference numbers.
+    fn iterate_over_raw_markings<F>(&self,electorate:&str,mut callback:F)  -> anyhow::Result<ElectionMetadata>
+        where F:FnMut(&RawBallotMarkings,RawBallotPaperMetadata)
+    {
+        let mut metadata = self.read_raw_metadata(electorate)?;
+        let filename = electorate.to_string()+"Total.txt";
+        let preferences_text_file = self.find_raw_data_file(&filename)?;
+        println!("Parsing {}",&preferences_text_file.to_string_lossy());
+        metadata.source[0].files.push(filename);
+        let candidate_of_pcode_and_ccode : HashMap<(String,usize),CandidateIndex> = metadata.candidates.iter().enumerate().map(|(i,c)|((metadata.parties[c.party.unwrap().0].column_id.clone(),c.position.unwrap()),CandidateIndex(i))).collect();
+        #[derive(Deserialize)]
+        struct VoteRecord {
+            batch : String,
+            pindex : String,
+            pref:u16,
+            pcode:String,
+            ccode:usize,
+        }
+        let mut rdr = csv::Reader::from_path(&preferences_text_file)?;
+        let mut helper = RawMarkingsComingOutOfOrderHelper::new(&metadata);
+        for result in rdr.deserialize() {
+            let record: VoteRecord = result?;
+            let vote_type = if record.batch.ends_with("000") {"Electronic"} else {"Paper"};
+            helper.set_current_paper(&(record.batch.clone()+"_"+&record.pindex),&[("Vote Type",vote_type),("Batch",&record.batch),("Paper",&record.pindex)],&mut callback);
+            let candidate = *candidate_of_pcode_and_ccode.get(&(record.pcode,record.ccode)).ok_or_else(||anyhow!("Bad candidate"))?;
+            helper.set_btl_marking(candidate,RawBallotMarking::Number(record.pref))?;
+        }
+        helper.done(&mut callback);
+        Ok(metadata)
+    }
+}
 
 impl RawDataSource for ACTDataLoader {
     fn name(&self,electorate:&str) -> ElectionName {
@@ -88,8 +119,8 @@ impl RawDataSource for ACTDataLoader {
         }
     }
 
-    fn candidates_to_be_elected(&self,region:&str) -> NumberOfCandidates {
-        NumberOfCandidates( if region=="Molonglo" {7} else {5})
+    fn candidates_to_be_elected(&self,region:&str) -> anyhow::Result<NumberOfCandidates> {
+        Ok(NumberOfCandidates( if region=="Molonglo" {7} else {5}))
     }
 
     /// These are deduced by looking at the actual transcript of results.
@@ -99,8 +130,8 @@ impl RawDataSource for ACTDataLoader {
     }
 
     /// These are due to a variety of events.
-    fn excluded_candidates(&self,_electorate:&str) -> Vec<CandidateIndex> {
-        Default::default()
+    fn excluded_candidates(&self,_electorate:&str) -> anyhow::Result<Vec<CandidateIndex>> {
+        Ok(Default::default())
     }
 
     // This below should be made more general and most of it factored out into a separate function.
@@ -164,7 +195,7 @@ impl RawDataSource for ACTDataLoader {
         let ecode = self.electorate_to_ecode.get(electorate).cloned().ok_or_else(||self.bad_electorate(electorate))?;
         let mut parties = self.load_parties(ecode)?;
         let candidates = self.load_candidates(ecode,&mut parties)?;
-        let vacancies = self.candidates_to_be_elected(electorate);
+        let vacancies = self.candidates_to_be_elected(electorate)?;
         Ok(ElectionMetadata{
             name: self.name(electorate),
             candidates,
@@ -178,7 +209,7 @@ impl RawDataSource for ACTDataLoader {
             vacancies: Some(vacancies),
             enrolment: None,
             secondary_vacancies: None,
-            excluded: self.excluded_candidates(electorate),
+            excluded: self.excluded_candidates(electorate)?,
             tie_resolutions : self.ec_decisions(electorate),
         })
     }
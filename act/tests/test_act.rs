@@ -19,11 +19,12 @@ mod tests {
     use stv::parse_util::{RawDataSource, FileFinder};
     use act::{ACTPre2020, ACT2021, ACT2020};
     use stv::random_util::Randomness;
+    use stv::count_observer::PrintProgressToStdout;
 
-    fn test<Rules:PreferenceDistributionRules>(electorate:&str,loader:ACTDataLoader,sub_folder:Option<&str>) -> anyhow::Result<()> {
+    fn test<Rules:PreferenceDistributionRules+Default>(electorate:&str,loader:ACTDataLoader,sub_folder:Option<&str>) -> anyhow::Result<()> {
         let data = loader.read_raw_data(electorate)?;
         data.print_summary();
-        let transcript = distribute_preferences::<Rules>(&data, loader.candidates_to_be_elected(electorate), &HashSet::default(), &TieResolutionsMadeByEC::default(),None,true,&mut Randomness::ReverseDonkeyVote);
+        let transcript = distribute_preferences::<Rules>(&data, loader.candidates_to_be_elected(electorate)?, &HashSet::default(), &TieResolutionsMadeByEC::default(),None,&mut PrintProgressToStdout::default(),&mut Randomness::ReverseDonkeyVote).unwrap();
         let transcript = TranscriptWithMetadata{ metadata: data.metadata, transcript };
         std::fs::create_dir_all("test_transcripts")?;
         let file = File::create(format!("test_transcripts/transcript{}{}{}.json",electorate,transcript.metadata.name.year,sub_folder.unwrap_or("")))?;
@@ -11,7 +11,7 @@
 #[cfg(test)]
 mod tests {
     use act::parse::{get_act_data_loader_2020};
-    use stv::preference_distribution::{distribute_preferences_with_extractors, PreferenceDistributionRules};
+    use stv::preference_distribution::{distribute_preferences_with_extractors, CountOptions, PreferenceDistributionRules};
     use std::collections::HashSet;
     use std::fs::File;
     use std::str::FromStr;
@@ -26,9 +26,10 @@ mod tests {
     use stv::extract_votes_in_pile::{ExtractionRequest, WhatToDoWithExtractedVotes, WhatToExtract};
     use stv::fixed_precision_decimal::FixedPrecisionDecimal;
     use stv::random_util::Randomness;
+    use stv::count_observer::PrintProgressToStdout;
 
     
-    fn test_extract_votes2020<Rules:PreferenceDistributionRules>(electorate:&str, ex_mla:&str,excluded_names_in_recount:&[&str]) -> anyhow::Result<TranscriptWithMetadata<Rules::Tally>> {
+    fn test_extract_votes2020<Rules:PreferenceDistributionRules+Default>(electorate:&str, ex_mla:&str,excluded_names_in_recount:&[&str]) -> anyhow::Result<TranscriptWithMetadata<Rules::Tally>> {
         let loader = get_act_data_loader_2020(&FileFinder::find_ec_data_repository())?;
         let data = loader.read_raw_data(electorate)?;
         println!("{:?}",data.metadata.candidates);
@@ -39,7 +40,7 @@ mod tests {
         let cloned_extracted_data = extracted_data.clone();
         let what_to_do_with_it = WhatToDoWithExtractedVotes::CallFunction(Arc::new(Mutex::new(move |e:ElectionData|{cloned_extracted_data.set(e).unwrap();})));
         let extractors = vec![ExtractionRequest{ what_to_extract, what_to_do_with_it  }];
-        let transcript = distribute_preferences_with_extractors::<Rules>(&data, loader.candidates_to_be_elected(electorate), &HashSet::default(), &TieResolutionsMadeByEC::default(),None,true,&mut Randomness::ReverseDonkeyVote,&extractors,false);
+        let transcript = distribute_preferences_with_extractors(&Rules::default(),&data,&CountOptions{ extractors: &extractors, ..CountOptions::new(loader.candidates_to_be_elected(electorate)?,HashSet::default(),&TieResolutionsMadeByEC::default()) },&mut PrintProgressToStdout::default(),&mut Randomness::ReverseDonkeyVote).unwrap();
         let mut excluded_in_recount: HashSet<CandidateIndex> = HashSet::default();
         for &c in &transcript.elected {
             excluded_in_recount.insert(c);
@@ -56,7 +57,7 @@ mod tests {
         serde_json::to_writer_pretty(file,&extracted_data)?;
         extracted_data.print_summary();
         // TODO make correct rules that handle quota correctly - recompute at each round.
-        let transcript = distribute_preferences_with_extractors::<Rules>(&extracted_data, extracted_data.metadata.vacancies.unwrap(), &excluded_in_recount, &TieResolutionsMadeByEC::default(),None,true,&mut Randomness::ReverseDonkeyVote,&[],false);
+        let transcript = distribute_preferences_with_extractors(&Rules::default(),extracted_data,&CountOptions::new(extracted_data.metadata.vacancies.unwrap(),excluded_in_recount,&TieResolutionsMadeByEC::default()),&mut PrintProgressToStdout::default(),&mut Randomness::ReverseDonkeyVote).unwrap();
         let transcript = TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript };
         let file = File::create(format!("test_transcripts/extract/Casual Vacancy {} Transcript {} {}.json",ex_mla,electorate,transcript.metadata.name.year))?;
         serde_json::to_writer_pretty(file,&transcript)?;
@@ -0,0 +1,24 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that fractional quotas are rounded to the precision of a decimal tally.
+
+use act::ACT2021;
+use stv::ballot_metadata::NumberOfCandidates;
+use stv::ballot_pile::BallotPaperCount;
+use stv::quota::QuotaMethod;
+
+#[test]
+fn test_decimal_quotas() {
+    let quota = |method:QuotaMethod,papers:usize,vacancies:usize|method.compute::<ACT2021>(BallotPaperCount(papers),NumberOfCandidates(vacancies)).unwrap().to_string();
+    assert_eq!("34",quota(QuotaMethod::Droop,100,2));
+    assert_eq!("33.333334",quota(QuotaMethod::ExactDroop,100,2));
+    assert_eq!("33.333334",quota(QuotaMethod::HagenbachBischoff,100,2));
+    assert_eq!("33.000001",quota(QuotaMethod::ExactDroop,99,2));
+    assert_eq!("33",quota(QuotaMethod::HagenbachBischoff,99,2));
+    assert_eq!("33.333334",quota(QuotaMethod::Hare,100,3));
+    assert_eq!("20",quota(QuotaMethod::Imperiali,100,3));
+}
@@ -0,0 +1,47 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check the casual vacancy countback API on a small synthetic election.
+
+use act::ACT2021;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::casual_vacancy::{count_casual_vacancy_with_rules, ACTCasualVacancy, CasualVacancyError};
+use stv::count_error::CountError;
+use stv::election_data::ElectionData;
+use stv::random_util::Randomness;
+
+/// 2 vacancies, quota 34. A is elected on first preferences, with most of A's votes going next to C.
+fn make_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C","D","E"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(2)], n: 30 },
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(3)], n: 10 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 30 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 10 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 12 },
+        BTL{ candidates: vec![CandidateIndex(4),CandidateIndex(3)], n: 8 },
+    ])
+}
+
+#[test]
+fn test_countback() {
+    let data = make_election();
+    let report = count_casual_vacancy_with_rules(&ACT2021::default(),&data,&ACTCasualVacancy{},CandidateIndex(0),&[CandidateIndex(2),CandidateIndex(3)],&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert!(report.original_count.elected.contains(&CandidateIndex(0)));
+    assert_eq!(report.recounted_votes.metadata.vacancies,Some(NumberOfCandidates(1)));
+    assert_eq!(report.recounted_votes.metadata.excluded,vec![CandidateIndex(0),CandidateIndex(1),CandidateIndex(4)]);
+    assert_eq!(report.countback.elected,vec![CandidateIndex(2)]);
+}
+
+#[test]
+fn test_countback_errors() {
+    let data = make_election();
+    assert_eq!(CasualVacancyError::NoVotesExtracted(CandidateIndex(4)),count_casual_vacancy_with_rules(&ACT2021::default(),&data,&ACTCasualVacancy{},CandidateIndex(4),&[CandidateIndex(2),CandidateIndex(3)],&mut Randomness::ReverseDonkeyVote).unwrap_err());
+    assert_eq!(CasualVacancyError::VacatingCandidateIsContesting(CandidateIndex(0)),count_casual_vacancy_with_rules(&ACT2021::default(),&data,&ACTCasualVacancy{},CandidateIndex(0),&[CandidateIndex(0),CandidateIndex(3)],&mut Randomness::ReverseDonkeyVote).unwrap_err());
+    assert_eq!(CasualVacancyError::NoCandidatesContesting,count_casual_vacancy_with_rules(&ACT2021::default(),&data,&ACTCasualVacancy{},CandidateIndex(0),&[],&mut Randomness::ReverseDonkeyVote).unwrap_err());
+    assert_eq!(CasualVacancyError::Count(CountError::InvalidCandidate{ candidate: CandidateIndex(5), num_candidates: 5 }),count_casual_vacancy_with_rules(&ACT2021::default(),&data,&ACTCasualVacancy{},CandidateIndex(0),&[CandidateIndex(2),CandidateIndex(5)],&mut Randomness::ReverseDonkeyVote).unwrap_err());
+    assert_eq!(CasualVacancyError::Count(CountError::InvalidCandidate{ candidate: CandidateIndex(7), num_candidates: 5 }),count_casual_vacancy_with_rules(&ACT2021::default(),&data,&ACTCasualVacancy{},CandidateIndex(7),&[CandidateIndex(2)],&mut Randomness::ReverseDonkeyVote).unwrap_err());
+}
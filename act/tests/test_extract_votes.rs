@@ -0,0 +1,97 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check extracting pile contents, exhausted votes and set aside votes at a given count.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, OnceLock};
+use act::ACT2021;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::count_error::CountError;
+use stv::count_observer::IgnoreCountProgress;
+use stv::distribution_of_preferences_transcript::CountIndex;
+use stv::election_data::ElectionData;
+use stv::extract_votes_in_pile::{write_votes_as_csv, ExtractionRequest, WhatToDoWithExtractedVotes, WhatToExtract};
+use stv::preference_distribution::{distribute_preferences_with_extractors, CountOptions};
+use stv::random_util::Randomness;
+
+/// 3 vacancies, quota 26. A is elected on first preferences, and B on A's surplus. Most of the
+/// papers in B's last parcel then exhaust, so the transfer value of B's surplus is limited to that of A's surplus.
+fn make_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C","D","E"],3,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(1)], n: 50 },
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(1),CandidateIndex(2)], n: 10 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 10 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 9 },
+        BTL{ candidates: vec![CandidateIndex(4),CandidateIndex(2)], n: 1 },
+    ])
+}
+
+fn try_extract(what_to_extract:&str) -> Result<Option<ElectionData>,CountError> {
+    let data = make_election();
+    let extracted : Arc<OnceLock<ElectionData>> = Arc::new(OnceLock::new());
+    let extracted_clone = extracted.clone();
+    let what_to_do_with_it = WhatToDoWithExtractedVotes::CallFunction(Arc::new(Mutex::new(move |e:ElectionData|{ extracted_clone.set(e).unwrap(); })));
+    let extractors = [ExtractionRequest{ what_to_extract: what_to_extract.parse().unwrap(), what_to_do_with_it }];
+    distribute_preferences_with_extractors(&ACT2021::default(),&data,&CountOptions{ extractors: &extractors, ..CountOptions::new(NumberOfCandidates(3),HashSet::default(),&data.metadata.tie_resolutions) },&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote)?;
+    Ok(extracted.get().cloned())
+}
+
+fn extract(what_to_extract:&str) -> ElectionData { try_extract(what_to_extract).unwrap().unwrap() }
+
+fn num_papers(data:&ElectionData) -> usize { data.btl.iter().map(|v|v.n).sum() }
+
+#[test]
+fn test_parse_extraction_requests() {
+    assert!(matches!("Pile:3@5".parse::<WhatToExtract>().unwrap(),WhatToExtract::PileAtCount { candidate: CandidateIndex(3), count: CountIndex(5) }));
+    assert!(matches!("Exhausted@4".parse::<WhatToExtract>().unwrap(),WhatToExtract::ExhaustedAtCount(CountIndex(4))));
+    assert!(matches!("SetAside@2".parse::<WhatToExtract>().unwrap(),WhatToExtract::SetAsideAtCount(CountIndex(2))));
+    assert!("Pile:3".parse::<WhatToExtract>().is_err());
+    assert!(matches!("csv:out.csv".parse::<WhatToDoWithExtractedVotes>().unwrap(),WhatToDoWithExtractedVotes::SaveToCSV(_)));
+    assert!("Exhausted@2;csv:out.csv".parse::<ExtractionRequest>().is_ok());
+    assert!("Exhausted@2".parse::<ExtractionRequest>().is_err());
+}
+
+#[test]
+fn test_extract_pile() {
+    let first_preferences = extract("Pile:0@0");
+    assert_eq!(num_papers(&first_preferences),60);
+    assert_eq!(first_preferences.btl_transfer_values.len(),1);
+    let after_surplus = extract("Pile:1@1");
+    assert_eq!(num_papers(&after_surplus),80);
+    assert_eq!(after_surplus.btl_transfer_values.len(),2); // 20 at transfer value 1, and 60 from A's surplus.
+}
+
+#[test]
+fn test_extract_exhausted_and_set_aside() {
+    assert_eq!(num_papers(&extract("Exhausted@1")),0);
+    let exhausted = extract("Exhausted@2");
+    assert_eq!(num_papers(&exhausted),50);
+    assert!(exhausted.btl.iter().all(|v|v.candidates==vec![CandidateIndex(0),CandidateIndex(1)]));
+    // B's surplus transfer value only counts continuing papers, so these papers exhausted without any value.
+    assert_eq!(exhausted.btl_transfer_values.len(),1);
+    assert_eq!(exhausted.btl_transfer_values[0].value.to_string(),"0");
+    assert_eq!(num_papers(&extract("SetAside@1")),0);
+    let set_aside = extract("SetAside@2");
+    assert_eq!(num_papers(&set_aside),10);
+    assert_eq!(set_aside.btl_transfer_values.len(),1);
+    let mut csv = vec![];
+    write_votes_as_csv(&set_aside,&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines : Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(),2);
+    assert_eq!(lines[0],"Transfer Value,Ballot Papers,Type,Preferences");
+    assert!(lines[1].ends_with(",10,BTL,0 1 2"));
+}
+
+#[test]
+fn test_bad_extraction_requests_are_errors() {
+    assert_eq!(Some(CountError::InvalidCandidate{candidate:CandidateIndex(7),num_candidates:5}),try_extract("Pile:7@1").err());
+    let counts = match try_extract("Exhausted@1000").err() { Some(CountError::ExtractionCountNotReached{count:CountIndex(1000),counts}) => counts, other => panic!("Unexpected {:?}",other) };
+    assert!(try_extract(&format!("Exhausted@{}",counts-1)).unwrap().is_some());
+}
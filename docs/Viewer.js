@@ -332,7 +332,7 @@ function RenderTranscript(full_transcript,render_div) {
             if (count.created_transfer_value.excluded_exhausted_tally) title+=" exhausted tally "+count.created_transfer_value.excluded_exhausted_tally;
             tv_td.title=title;
         }
-        fullSpanTD("CountAction").innerText=count.reason==="FirstPreferenceCount"?"First Preference Count":count.reason.hasOwnProperty("ExcessDistribution")?"Surplus distribution for "+cname(count.reason.ExcessDistribution):"Exclusion of "+count.reason.Elimination.map(cname).join(" & "); // TODO prettify
+        fullSpanTD("CountAction").innerText=count.reason==="FirstPreferenceCount"?"First Preference Count":count.reason.hasOwnProperty("ExcessDistribution")?"Surplus distribution for "+cname(count.reason.ExcessDistribution):count.reason.hasOwnProperty("ExcessDistributionByKeepValues")?"Surplus distribution by keep values for "+count.reason.ExcessDistributionByKeepValues.map(cname).join(" & "):"Exclusion of "+count.reason.Elimination.map(cname).join(" & "); // TODO prettify
         function candidate_index_array_to_string(candidate_list) {
             return candidate_list.map(candidate=>metadata.candidates[candidate].name+(show_candidate_numbers_in_decisions?" ("+candidate+")":"")).join(",");
         }
@@ -187,17 +187,12 @@ impl <T:SimpleExampleFromText> RawDataSource for ExampleDataLoader<T> {
         }
     }
 
-    fn candidates_to_be_elected(&self, electorate: &str) -> NumberOfCandidates {
-        if let Ok(metadata) = self.read_raw_metadata(electorate) {
-            if let Some(vacancies) = metadata.vacancies {
-                return vacancies
-            }
-        }
-        NumberOfCandidates(0)
+    fn candidates_to_be_elected(&self, electorate: &str) -> anyhow::Result<NumberOfCandidates> {
+        self.read_raw_metadata(electorate)?.vacancies.ok_or_else(||anyhow!("The number of vacancies for {} is not in the data",electorate))
     }
 
     fn ec_decisions(&self, _electorate: &str) -> TieResolutionsMadeByEC { Default::default() }
-    fn excluded_candidates(&self, _electorate: &str) -> Vec<CandidateIndex> { Default::default()  }
+    fn excluded_candidates(&self, _electorate: &str) -> anyhow::Result<Vec<CandidateIndex>> { Ok(Default::default())  }
 
     fn read_raw_data(&self, electorate: &str) -> anyhow::Result<ElectionData> {
         let json = T::get_raw_data_as_string(electorate)?;
@@ -8,6 +8,7 @@ use stv::parse_util::{FileFinder, RawDataSource};
 use stv::preference_distribution::distribute_preferences;
 use stv::random_util::Randomness;
 use stv::tie_resolution::TieResolutionsMadeByEC;
+use stv::count_observer::IgnoreCountProgress;
 
 fn load2019(state:&str) -> anyhow::Result<ElectionData> {
     let loader = get_federal_data_loader_2019(&FileFinder::find_ec_data_repository());
@@ -15,7 +16,7 @@ fn load2019(state:&str) -> anyhow::Result<ElectionData> {
 }
 
 fn count2019(data:&ElectionData) -> Transcript<usize> {
-    distribute_preferences::<FederalRulesUsed2019>(&data, data.metadata.vacancies.unwrap(), &HashSet::default(), &TieResolutionsMadeByEC::default(),None,false,&mut Randomness::ReverseDonkeyVote)
+    distribute_preferences::<FederalRulesUsed2019>(&data, data.metadata.vacancies.unwrap(), &HashSet::default(), &TieResolutionsMadeByEC::default(),None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap()
 }
 
 fn load_tas2019(c: &mut Criterion) {
@@ -20,7 +20,7 @@ fn main()  -> anyhow::Result<()> {
 
     let data = loader.load_cached_data("ACT")?;
     data.print_summary();
-    let transcript = data.distribute_preferences::<FederalRulesPre2021>(&mut Randomness::ReverseDonkeyVote);
+    let transcript = data.distribute_preferences::<FederalRulesPre2021>(&mut Randomness::ReverseDonkeyVote)?;
     let transcript = TranscriptWithMetadata{ metadata: data.metadata, transcript };
     let file = File::create("transcript.json")?;
     serde_json::to_writer_pretty(file,&transcript)?;
@@ -0,0 +1,75 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Allocating long and short terms after a double dissolution.
+//!
+//! After a double dissolution, all 12 senators for a state are elected in one count, and the Senate
+//! then decides which 6 get long (6 year) terms. There are two methods it can use:
+//! * Order elected : the first 6 elected get long terms.
+//! * Section 282 recount : the AEC recounts the ballot papers as if only 6 senators were to be elected,
+//!   with the same candidates excluded. As best I can tell, the candidates elected in the recount who
+//!   were also elected in the full count get long terms, in the order elected in the recount, with any
+//!   remaining long terms going to the others elected in the full count, in the order they were elected.
+//!
+//! The number of long terms is [stv::ballot_metadata::ElectionMetadata::secondary_vacancies], which
+//! the federal data loaders set to 6 for double dissolutions.
+
+use std::collections::HashSet;
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+use serde::{Serialize,Deserialize};
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::count_error::CountError;
+use stv::distribution_of_preferences_transcript::Transcript;
+use stv::election_data::ElectionData;
+use stv::preference_distribution::{distribute_preferences, PreferenceDistributionRules};
+use stv::count_observer::CountObserver;
+use stv::random_util::Randomness;
+
+/// The full count and the section 282 recount, and the long terms given by each method.
+#[derive(Clone,Serialize,Deserialize,Debug)]
+pub struct DoubleDissolutionCount<Tally:PartialEq+Clone+Display+FromStr+Debug> {
+    /// The count for all vacancies.
+    pub full_count : Transcript<Tally>,
+    /// The recount as if there were only as many vacancies as long terms.
+    pub recount : Transcript<Tally>,
+    /// The candidates given long terms if they go to the first elected in the full count.
+    pub long_terms_by_order_elected : Vec<CandidateIndex>,
+    /// The candidates given long terms using the section 282 recount.
+    pub long_terms_by_recount : Vec<CandidateIndex>,
+}
+
+/// The first `long_terms` candidates elected in the full count.
+pub fn long_terms_by_order_elected(elected_in_full_count:&[CandidateIndex],long_terms:NumberOfCandidates) -> Vec<CandidateIndex> {
+    elected_in_full_count.iter().take(long_terms.0).cloned().collect()
+}
+
+/// The candidates given long terms by the section 282 recount method. Candidates elected in the recount but
+/// not in the full count (which would be very unusual) are ignored.
+pub fn long_terms_by_recount(elected_in_full_count:&[CandidateIndex],elected_in_recount:&[CandidateIndex],long_terms:NumberOfCandidates) -> Vec<CandidateIndex> {
+    let mut res : Vec<CandidateIndex> = elected_in_recount.iter().filter(|c|elected_in_full_count.contains(c)).cloned().collect();
+    for &c in elected_in_full_count {
+        if !res.contains(&c) { res.push(c); }
+    }
+    res.truncate(long_terms.0);
+    res
+}
+
+/// Count a double dissolution election, and do the section 282 recount, using the vacancies, secondary vacancies,
+/// excluded candidates and EC tie resolutions in the metadata.
+pub fn count_double_dissolution<Rules:PreferenceDistributionRules+Default>(data:&ElectionData,observer:&mut impl CountObserver<Rules::Tally>,randomness:&mut Randomness) -> Result<DoubleDissolutionCount<Rules::Tally>,CountError> {
+    let vacancies = data.metadata.vacancies.ok_or(CountError::MissingVacancies)?;
+    let long_terms = data.metadata.secondary_vacancies.ok_or(CountError::MissingSecondaryVacancies)?;
+    let excluded : HashSet<CandidateIndex> = data.metadata.excluded.iter().cloned().collect();
+    let full_count = distribute_preferences::<Rules>(data,vacancies,&excluded,&data.metadata.tie_resolutions,None,&mut *observer,randomness)?;
+    let recount = distribute_preferences::<Rules>(data,long_terms,&excluded,&data.metadata.tie_resolutions,None,&mut *observer,randomness)?;
+    Ok(DoubleDissolutionCount{
+        long_terms_by_order_elected: long_terms_by_order_elected(&full_count.elected,long_terms),
+        long_terms_by_recount: long_terms_by_recount(&full_count.elected,&recount.elected,long_terms),
+        full_count,
+        recount,
+    })
+}
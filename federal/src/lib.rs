@@ -14,6 +14,7 @@ use stv::tie_resolution::MethodOfTieResolution;
 
 pub mod parse;
 pub mod parse2013;
+pub mod double_dissolution;
 
 /// Many variants on the federal rules are used, partly due to legislation changes
 /// and partly due to deviations between what the AEC did and the legislation.
@@ -30,28 +31,33 @@ pub struct FederalVariant<V:FederalVariations> {
     phantom : PhantomData<V>
 }
 
+// Not derived, as that would require V:Default.
+impl <V:FederalVariations> Default for FederalVariant<V> {
+    fn default() -> Self { FederalVariant{ phantom: PhantomData } }
+}
+
 impl <V:FederalVariations> PreferenceDistributionRules for FederalVariant<V> {
     type Tally = usize;
     type SplitByNumber = DoNotSplitByCountNumber;
 
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { LastParcelUse::No }
-    fn transfer_value_method() -> TransferValueMethod { TransferValueMethod::SurplusOverBallots }
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::No }
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverBallots }
 
     fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { convert_usize_to_rational(tally)  }
     fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { round_rational_down_to_usize(rational)  }
 
-    fn make_transfer_value(surplus: usize, ballots: BallotPaperCount) -> TransferValue {
+    fn make_transfer_value(&self,surplus: usize, ballots: BallotPaperCount) -> TransferValue {
         TransferValue::from_surplus(surplus,ballots)
     }
 
-    fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
         transfer_value.mul_rounding_down(ballots)
     }
 
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool { true } // not applicable as distribute_surplus_all_with_same_transfer_value.
-    fn check_elected_if_in_middle_of_exclusion() -> bool { true }
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod { SurplusTransferMethod::JustOneTransferValue }
-    fn sort_exclusions_by_transfer_value() -> bool { true }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { true } // not applicable as distribute_surplus_all_with_same_transfer_value.
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { true }
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::JustOneTransferValue }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { true }
 
     /// Require that at some prior point *all* the counts were different
     /// ```text
@@ -83,9 +89,9 @@ impl <V:FederalVariations> PreferenceDistributionRules for FederalVariant<V> {
     /// State shall determine the order in which the surpluses shall be dealt
     /// with.
     ///```
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
     /// Also covered by section 20.
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
     /// This is not actually covered by section 20.
     /// ```text
     /// (17) In respect of the last vacancy for which two continuing candidates
@@ -98,14 +104,14 @@ impl <V:FederalVariations> PreferenceDistributionRules for FederalVariant<V> {
     ///
     /// I incorrectly counted this as MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent
     /// in an earlier version.
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { MethodOfTieResolution::None }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
 
     /// This was changed in legislation in 2021, to what the AEC had been doing prior.
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { V::resolve_ties_choose_lowest_candidate_for_exclusion() }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { V::resolve_ties_choose_lowest_candidate_for_exclusion() }
 
 
 
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool { false }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
     /// Commonwealth Electoral Act 1918, Section 273, (9)
     /// ```text
     /// Unless all the vacancies have been filled, the number (if any) of
@@ -114,7 +120,7 @@ impl <V:FederalVariations> PreferenceDistributionRules for FederalVariant<V> {
     /// continuing candidates as follows: ...
     /// ```
     /// Similarly (10) and (14) have the crucial "Unless all the vacancies have been filled"
-    fn finish_all_surplus_distributions_when_all_elected() -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
 
 
     /// Commonwealth Electoral Act 1918, Section 273, (17)
@@ -152,7 +158,7 @@ impl <V:FederalVariations> PreferenceDistributionRules for FederalVariant<V> {
     /// as that will produce the same person elected as not having this rule
     /// at all, which seems the most in keeping with STV general purposes, although
     /// I am not claiming that this is what the legislation says.
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
 
     /// Commonwealth Electoral Act 1918, Section 273, (18)
     /// ```text
@@ -195,18 +201,18 @@ impl <V:FederalVariations> PreferenceDistributionRules for FederalVariant<V> {
     ///
     /// I am assigning it to require everything else to be finished first,
     /// as I have done the same for section 17, although this is debatable.
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { V::when_to_check_if_all_remaining_should_get_elected() }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { V::when_to_check_if_all_remaining_should_get_elected() }
 
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
 
     /// Commonwealth Electoral Act 1918, Section 273, subsection (13)(b)
     /// ```text
     /// (b) if a bulk exclusion of candidates may be effected under
     ///     subsection (13A), those candidates must be excluded;
     /// ```
-    fn should_eliminate_multiple_candidates_federal_rule_13a() -> bool { V::should_eliminate_multiple_candidates_federal_rule_13a() }
+    fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { V::should_eliminate_multiple_candidates_federal_rule_13a() }
 
-    fn name() -> String { V::name() }
+    fn name(&self) -> String { V::name() }
 
 }
 
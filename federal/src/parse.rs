@@ -9,7 +9,7 @@ use std::borrow::Cow;
 use std::path::{Path, PathBuf};
 use std::fs::File;
 use stv::ballot_metadata::{ElectionName, Candidate, CandidateIndex, PartyIndex, ElectionMetadata, DataSource, NumberOfCandidates};
-use stv::ballot_paper::{RawBallotMarking, parse_marking, RawBallotMarkings, UniqueVoteBuilderMultipleTypes};
+use stv::ballot_paper::{RawBallotMarking, parse_marking, RawBallotMarkings, UniqueVoteBuilderMultipleTypes, FormalityRules};
 use std::collections::{HashMap};
 use csv::{StringRecord, StringRecordsIntoIter};
 use zip::ZipArchive;
@@ -17,6 +17,7 @@ use zip::read::ZipFile;
 use anyhow::anyhow;
 use stv::election_data::ElectionData;
 use stv::distribution_of_preferences_transcript::{CountIndex, QuotaInfo};
+use stv::quota::QuotaMethod;
 use serde::Deserialize;
 use stv::ballot_pile::BallotPaperCount;
 use stv::datasource_description::{AssociatedRules, Copyright, ElectionDataSource};
@@ -89,12 +90,12 @@ impl RawDataSource for FederalDataLoader {
         }
     }
 
-    fn candidates_to_be_elected(&self,state:&str) -> NumberOfCandidates {
-        NumberOfCandidates(
+    fn candidates_to_be_elected(&self,state:&str) -> anyhow::Result<NumberOfCandidates> {
+        Ok(NumberOfCandidates(
             if state=="ACT" || state=="NT" { 2 }
             else if self.double_dissolution { 12 }
             else { 6 }
-        )
+        ))
     }
 
     /// These are deduced by looking at the actual transcript of results.
@@ -111,15 +112,15 @@ impl RawDataSource for FederalDataLoader {
     }
 
     /// These are due to a variety of events.
-    fn excluded_candidates(&self,state:&str) -> Vec<CandidateIndex> {
-        match self.year.as_str() {
+    fn excluded_candidates(&self,state:&str) -> anyhow::Result<Vec<CandidateIndex>> {
+        Ok(match self.year.as_str() {
             "2016" => match state {
                 "SA" => vec![CandidateIndex(38)], // Bob Day was excluded because of indirect pecuniary interest.
                 "WA" => vec![CandidateIndex(45)], // Rod Cullerton was excluded because of bankruptcy and larceny.
                 _ => Default::default(),
             },
             _ => Default::default(),
-        }
+        })
     }
 
     fn find_raw_data_file(&self,filename:&str) -> Result<PathBuf,MissingFile> {
@@ -138,6 +139,7 @@ impl RawDataSource for FederalDataLoader {
             "2013" | "2014" => self.read_raw_data2013(state),
             _ => {
                 let mut builder = UniqueVoteBuilderMultipleTypes::default();
+                let formality_rules = self.formality_rules(state);
                 let callback = |markings:&RawBallotMarkings,_meta:&[(&str,&str)]| {
                     let collection_point = _meta[1].1;
                     let vote_type = if collection_point.starts_with("PROVISIONAL") { Some("PROVISIONAL") }
@@ -145,7 +147,7 @@ impl RawDataSource for FederalDataLoader {
                     else if collection_point.starts_with("POSTAL") { Some("POSTAL") }
                     else if collection_point.starts_with("ABSENT") { Some("ABSENT") }
                     else {None};
-                    builder.add_vote(markings.interpret_vote(1,6),vote_type);
+                    builder.add_vote(markings.interpret_vote_with_rules(formality_rules),vote_type);
                 };
                 let metadata = self.iterate_over_raw_markings(state,callback)?;
                 Ok(builder.into_election_data(metadata))
@@ -169,7 +171,7 @@ impl RawDataSource for FederalDataLoader {
         if self.year=="2013" || self.year=="2014" { read_from_senate_group_voting_tickets_download_file2013(&mut builder,self.find_raw_data_file(&self.name_of_candidate_source_post_election())?.as_path(),state)?; }
         else if !self.can_load_full_data(state) { read_candidate_list_file_available_before_election2022(&mut builder,self.find_raw_data_file(&self.name_of_candidate_source_pre_election()?)?.as_path(),state)?; }
         else { read_from_senate_first_prefs_by_state_by_vote_typ_download_file2016(&mut builder,self.find_raw_data_file(&self.name_of_candidate_source_post_election())?.as_path(),state)?; }
-        let vacancies = self.candidates_to_be_elected(state);
+        let vacancies = self.candidates_to_be_elected(state)?;
         Ok(ElectionMetadata{
             name: self.name(state),
             candidates: builder.candidates.clone(),
@@ -183,7 +185,7 @@ impl RawDataSource for FederalDataLoader {
             vacancies: Some(vacancies),
             enrolment: None,
             secondary_vacancies: if vacancies==NumberOfCandidates(12) { Some(NumberOfCandidates(6)) } else {None},
-            excluded: self.excluded_candidates(state),
+            excluded: self.excluded_candidates(state)?,
             tie_resolutions : self.ec_decisions(state),
         })
     }
@@ -232,6 +234,7 @@ impl RawDataSource for FederalDataLoader {
         }
     }
     fn can_read_raw_markings(&self) -> bool  { self.year=="2016" || self.year=="2019" || self.year=="2022" }
+    fn formality_rules(&self,_state:&str) -> FormalityRules { FormalityRules{ min_atl_prefs_needed: 1, min_btl_prefs_needed: 6 } }
     fn can_load_full_data(&self,_state:&str) -> bool { true }
 
     fn read_official_dop_transcript(&self,metadata:&ElectionMetadata) -> anyhow::Result<OfficialDistributionOfPreferencesTranscript> {
@@ -365,7 +368,8 @@ fn read_official_dop_transcript_work(file : ZipFile,metadata : &ElectionMetadata
             res.quota=Some(QuotaInfo{
                 papers: BallotPaperCount(record.formal_papers),
                 vacancies : NumberOfCandidates(record.vacancies),
-                quota: record.quota as f64
+                quota: record.quota as f64,
+                method: QuotaMethod::Droop,
             });
         }
         if record.count!=last_count {
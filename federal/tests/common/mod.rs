@@ -0,0 +1,21 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Elections shared between several integration tests.
+
+use stv::ballot_metadata::CandidateIndex;
+use stv::ballot_paper::BTL;
+use stv::election_data::ElectionData;
+
+/// A elected on first preferences with a surplus that exhausts, then B, C and D are tied on every count.
+pub fn make_tied_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C","D"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 15 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 15 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 15 },
+    ])
+}
@@ -19,7 +19,7 @@ use stv::tie_resolution::TieResolutionExplicitDecisionInCount;
 /// Test a particular year & electorate against a particular set of rules.
 /// Outermost error is IO type errors.
 /// Innermost error is discrepancies with the official DoP.
-fn test<Rules:PreferenceDistributionRules>(year:&str,state:&str) -> anyhow::Result<Result<Option<TieResolutionExplicitDecisionInCount>, DifferenceBetweenOfficialDoPAndComputed<Rules::Tally>>> where <Rules as PreferenceDistributionRules>::Tally: Send+Sync+'static {
+fn test<Rules:PreferenceDistributionRules+Default>(year:&str,state:&str) -> anyhow::Result<Result<Option<TieResolutionExplicitDecisionInCount>, DifferenceBetweenOfficialDoPAndComputed<Rules::Tally>>> where <Rules as PreferenceDistributionRules>::Tally: Send+Sync+'static {
     test_official_dop_without_actual_votes::<Rules,_>(&FederalDataSource{},year,state,false)
 }
 
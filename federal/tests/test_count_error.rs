@@ -0,0 +1,95 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that bad input and unresolvable ties give a CountError rather than a panic.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use federal::FederalRulesPost2021;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::count_error::CountError;
+use stv::distribution_of_preferences_transcript::CountIndex;
+use stv::dynamic_rules::{DynamicRules, RuleDescription};
+use stv::election_data::ElectionData;
+use stv::fixed_precision_decimal::FixedPrecisionDecimal;
+use stv::preference_distribution::{distribute_preferences, distribute_preferences_with_extractors, CountOptions};
+use stv::random_util::Randomness;
+use stv::tie_resolution::TieResolutionsMadeByEC;
+use stv::count_observer::IgnoreCountProgress;
+
+const FEDERAL_POST_2021_DECIMAL : &str = r#"
+name = "MyFederalPost2021Decimal"
+tally = "SixDigitDecimals"
+split_by_number = "DoNotSplitByCountNumber"
+last_parcel = "No"
+transfer_value_method = "SurplusOverBallots"
+surplus_transfer_method = "JustOneTransferValue"
+sort_exclusions_by_transfer_value = true
+resolve_ties_elected_one_of_last_two = "None"
+resolve_ties_elected_by_quota = "RequireHistoricalCountsToBeAllDifferent"
+resolve_ties_elected_all_remaining = "RequireHistoricalCountsToBeAllDifferent"
+resolve_ties_choose_lowest_candidate_for_exclusion = "AnyDifferenceIsADiscriminator"
+check_elected_if_in_middle_of_surplus_distribution = true
+check_elected_if_in_middle_of_exclusion = true
+finish_all_counts_in_elimination_when_all_elected = false
+finish_all_surplus_distributions_when_all_elected = false
+when_to_check_if_just_two_standing_for_shortcut_election = "AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing"
+when_to_check_if_all_remaining_should_get_elected = "AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing"
+when_to_check_if_top_few_have_overwhelming_votes = "Never"
+"#;
+
+/// A elected on first preferences with a surplus that exhausts, then C and D are tied for exclusion on every count.
+fn make_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C","D"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 30 },
+        BTL{ candidates: vec![CandidateIndex(2),CandidateIndex(1)], n: 10 },
+        BTL{ candidates: vec![CandidateIndex(3),CandidateIndex(1)], n: 10 },
+    ])
+}
+
+#[test]
+fn test_missing_vacancies() {
+    let mut data = make_election();
+    data.metadata.vacancies=None;
+    assert_eq!(CountError::MissingVacancies,data.distribute_preferences::<FederalRulesPost2021>(&mut Randomness::ReverseDonkeyVote).unwrap_err());
+}
+
+#[test]
+fn test_invalid_candidate() {
+    let mut data = make_election();
+    data.btl.push(BTL{ candidates: vec![CandidateIndex(7)], n: 1 });
+    assert_eq!(CountError::InvalidCandidate{ candidate: CandidateIndex(7), num_candidates: 4 },data.distribute_preferences::<FederalRulesPost2021>(&mut Randomness::ReverseDonkeyVote).unwrap_err());
+    let data = make_election();
+    let excluded : HashSet<CandidateIndex> = [CandidateIndex(4)].into_iter().collect();
+    assert_eq!(CountError::InvalidCandidate{ candidate: CandidateIndex(4), num_candidates: 4 },distribute_preferences::<FederalRulesPost2021>(&data,NumberOfCandidates(2),&excluded,&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap_err());
+}
+
+#[test]
+fn test_unresolvable_tie() {
+    let data = make_election();
+    let transcript = data.distribute_preferences::<FederalRulesPost2021>(&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!(transcript.elected,vec![CandidateIndex(0),CandidateIndex(1)]);
+    let error = data.distribute_preferences::<FederalRulesPost2021>(&mut Randomness::NoDraws(None)).unwrap_err();
+    assert_eq!(CountError::UnresolvableTie{ candidates: vec![CandidateIndex(2),CandidateIndex(3)], count: CountIndex(2) },error);
+    // an EC decision means no draw is needed.
+    let mut data = data;
+    data.metadata.tie_resolutions=TieResolutionsMadeByEC::new(vec![vec![CandidateIndex(2),CandidateIndex(3)]]).unwrap();
+    let transcript = data.distribute_preferences::<FederalRulesPost2021>(&mut Randomness::NoDraws(None)).unwrap();
+    assert_eq!(transcript.elected,vec![CandidateIndex(0),CandidateIndex(1)]);
+}
+
+#[test]
+fn test_arithmetic_overflow() {
+    let description : RuleDescription = toml::from_str(FEDERAL_POST_2021_DECIMAL).unwrap();
+    let mut data = make_election();
+    let too_many = FixedPrecisionDecimal::<6>::MAX as usize+1;
+    data.btl[1].n=too_many;
+    let rules = DynamicRules::<FixedPrecisionDecimal<6>>::new(Arc::new(description)).unwrap();
+    let error = distribute_preferences_with_extractors(&rules,&data,&CountOptions::new(NumberOfCandidates(2),HashSet::default(),&data.metadata.tie_resolutions),&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap_err();
+    assert_eq!(CountError::ArithmeticOverflow{ ballots: too_many+60, max_ballots: FixedPrecisionDecimal::<6>::MAX as usize },error);
+}
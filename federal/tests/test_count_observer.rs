@@ -0,0 +1,76 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that a CountObserver is told about the events in a distribution of preferences.
+
+use std::collections::HashSet;
+use federal::FederalRulesPost2021;
+use stv::ballot_metadata::{CandidateIndex, ElectionMetadata, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::count_observer::CountObserver;
+use stv::distribution_of_preferences_transcript::{ElectionReason, QuotaInfo, SingleCount};
+use stv::election_data::ElectionData;
+use stv::preference_distribution::{distribute_preferences_with_extractors, CountOptions};
+use stv::random_util::Randomness;
+use stv::tie_resolution::TieResolutionUsage;
+
+#[derive(Debug,PartialEq)]
+enum Event {
+    Started(usize),
+    Quota(usize),
+    Elected(CandidateIndex),
+    Excluded(Vec<CandidateIndex>),
+    Surplus(CandidateIndex,usize),
+    Tie(TieResolutionUsage,Vec<CandidateIndex>,bool),
+    Finished,
+}
+
+#[derive(Default)]
+struct RecordEvents {
+    events : Vec<Event>,
+}
+
+impl CountObserver<usize> for RecordEvents {
+    fn count_started(&mut self, metadata: &ElectionMetadata) { self.events.push(Event::Started(metadata.candidates.len())); }
+    fn quota_computed(&mut self, quota: &QuotaInfo<usize>) { self.events.push(Event::Quota(quota.quota)); }
+    fn candidate_elected(&mut self, who: CandidateIndex, _why: ElectionReason) { self.events.push(Event::Elected(who)); }
+    fn candidates_excluded(&mut self, who: &[CandidateIndex]) { self.events.push(Event::Excluded(who.to_vec())); }
+    fn surplus_distribution_started(&mut self, who: CandidateIndex, surplus: &usize) { self.events.push(Event::Surplus(who,*surplus)); }
+    fn tie_resolved(&mut self, usage: TieResolutionUsage, resolved_order: &[CandidateIndex], needed_ec_decision: bool) { self.events.push(Event::Tie(usage,resolved_order.to_vec(),needed_ec_decision)); }
+    fn count_finished(&mut self, _count: &SingleCount<usize>) { self.events.push(Event::Finished); }
+}
+
+/// A gets elected with a surplus that goes to B, and then C and D are tied for exclusion.
+fn make_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C","D"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(1)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 20 },
+    ])
+}
+
+#[test]
+fn test_observer_sees_events_in_order() {
+    let data = make_election();
+    let mut observer = RecordEvents::default();
+    let transcript = distribute_preferences_with_extractors(&FederalRulesPost2021::default(),&data,&CountOptions::new(NumberOfCandidates(2),HashSet::default(),&data.metadata.tie_resolutions),&mut observer,&mut Randomness::ReverseDonkeyVote).unwrap();
+    let events = observer.events;
+    assert_eq!(&events[..4],&[Event::Started(4),Event::Quota(34),Event::Elected(CandidateIndex(0)),Event::Finished]);
+    assert_eq!(Event::Surplus(CandidateIndex(0),6),events[4]);
+    // C and D have had 20 votes at every count, so the tie has to be resolved by lot.
+    let tie = events.iter().position(|e|matches!(e,Event::Tie(..))).unwrap();
+    let Event::Tie(usage,order,needed_ec_decision) = &events[tie] else { panic!() };
+    assert_eq!(TieResolutionUsage::Exclusion,*usage);
+    assert!(*needed_ec_decision);
+    assert_eq!(2,order.len());
+    assert!(order.contains(&CandidateIndex(2)) && order.contains(&CandidateIndex(3)));
+    assert_eq!(Event::Excluded(vec![order[0]]),events[tie+1]);
+    assert_eq!(transcript.counts.len(),events.iter().filter(|e|**e==Event::Finished).count());
+    let elected : Vec<CandidateIndex> = events.iter().filter_map(|e|if let Event::Elected(c) = e { Some(*c) } else { None }).collect();
+    assert_eq!(transcript.elected,elected);
+    assert_eq!(Event::Finished,*events.last().unwrap());
+}
@@ -0,0 +1,70 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check the section 282 recount used to allocate long terms after a double dissolution.
+
+use federal::double_dissolution::{count_double_dissolution, long_terms_by_order_elected, long_terms_by_recount};
+use federal::parse::get_federal_data_loader_2016;
+use federal::FederalRulesUsed2016;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::count_error::CountError;
+use stv::election_data::ElectionData;
+use stv::parse_util::{FileFinder, RawDataSource};
+use stv::random_util::Randomness;
+use stv::count_observer::IgnoreCountProgress;
+
+/// 4 vacancies, 2 long terms. A and B are elected on first preferences, C and D on their surpluses.
+fn make_election() -> ElectionData {
+    let mut data = ElectionData::from_btl(&["A","B","C","D","E"],4,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(2)], n: 30 },
+        BTL{ candidates: vec![CandidateIndex(1),CandidateIndex(3)], n: 25 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 15 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 14 },
+        BTL{ candidates: vec![CandidateIndex(4),CandidateIndex(2)], n: 6 },
+    ]);
+    data.metadata.secondary_vacancies=Some(NumberOfCandidates(2));
+    data
+}
+
+#[test]
+fn test_allocation_methods() {
+    let full = [CandidateIndex(3),CandidateIndex(1),CandidateIndex(4),CandidateIndex(0)];
+    assert_eq!(long_terms_by_order_elected(&full,NumberOfCandidates(2)),vec![CandidateIndex(3),CandidateIndex(1)]);
+    assert_eq!(long_terms_by_recount(&full,&[CandidateIndex(0),CandidateIndex(1)],NumberOfCandidates(2)),vec![CandidateIndex(0),CandidateIndex(1)]);
+    // candidate 2 was not elected in the full count, so the next long term goes to the first elected in the full count.
+    assert_eq!(long_terms_by_recount(&full,&[CandidateIndex(2),CandidateIndex(4)],NumberOfCandidates(2)),vec![CandidateIndex(4),CandidateIndex(3)]);
+}
+
+#[test]
+fn test_count_double_dissolution() {
+    let data = make_election();
+    let result = count_double_dissolution::<FederalRulesUsed2016>(&data,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!(result.full_count.elected.len(),4);
+    assert_eq!(result.recount.elected.len(),2);
+    let mut recount_only = data.clone();
+    recount_only.metadata.vacancies=Some(NumberOfCandidates(2));
+    assert_eq!(result.recount.elected,recount_only.distribute_preferences::<FederalRulesUsed2016>(&mut Randomness::ReverseDonkeyVote).unwrap().elected);
+    assert_eq!(result.long_terms_by_order_elected,result.full_count.elected[..2].to_vec());
+    assert_eq!(result.long_terms_by_recount,long_terms_by_recount(&result.full_count.elected,&result.recount.elected,NumberOfCandidates(2)));
+    let mut data = data;
+    data.metadata.secondary_vacancies=None;
+    assert_eq!(CountError::MissingSecondaryVacancies,count_double_dissolution::<FederalRulesUsed2016>(&data,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap_err());
+}
+
+/// Needs the 2016 AEC data files.
+#[test]
+#[allow(non_snake_case)]
+fn test_NSW2016_long_terms() {
+    let loader = get_federal_data_loader_2016(&FileFinder::find_ec_data_repository());
+    let data = loader.read_raw_data("NSW").unwrap();
+    assert_eq!(data.metadata.secondary_vacancies,Some(NumberOfCandidates(6)));
+    let result = count_double_dissolution::<FederalRulesUsed2016>(&data,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!(result.full_count.elected.len(),12);
+    assert_eq!(result.long_terms_by_order_elected.len(),6);
+    assert_eq!(result.long_terms_by_recount.len(),6);
+    assert!(result.long_terms_by_recount.iter().all(|c|result.full_count.elected.contains(c)));
+}
@@ -0,0 +1,96 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that rules loaded from a data file behave the same as the equivalent rules written in Rust.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use federal::FederalRulesPost2021;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::ballot_pile::FullySplitByCountNumber;
+use stv::count_error::CountError;
+use stv::dynamic_rules::{DynamicRules, DynamicTallyType, RuleDescription};
+use stv::election_data::ElectionData;
+use stv::fixed_precision_decimal::FixedPrecisionDecimal;
+use stv::preference_distribution::{distribute_preferences, distribute_preferences_with_extractors, CountOptions};
+use stv::random_util::Randomness;
+use stv::rule_card::RuleCard;
+use stv::count_observer::IgnoreCountProgress;
+
+const FEDERAL_POST_2021 : &str = r#"
+name = "MyFederalPost2021"
+tally = "Integers"
+split_by_number = "DoNotSplitByCountNumber"
+last_parcel = "No"
+transfer_value_method = "SurplusOverBallots"
+surplus_transfer_method = "JustOneTransferValue"
+sort_exclusions_by_transfer_value = true
+resolve_ties_elected_one_of_last_two = "None"
+resolve_ties_elected_by_quota = "RequireHistoricalCountsToBeAllDifferent"
+resolve_ties_elected_all_remaining = "RequireHistoricalCountsToBeAllDifferent"
+resolve_ties_choose_lowest_candidate_for_exclusion = "AnyDifferenceIsADiscriminator"
+check_elected_if_in_middle_of_surplus_distribution = true
+check_elected_if_in_middle_of_exclusion = true
+finish_all_counts_in_elimination_when_all_elected = false
+finish_all_surplus_distributions_when_all_elected = false
+when_to_check_if_just_two_standing_for_shortcut_election = "AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing"
+when_to_check_if_all_remaining_should_get_elected = "AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing"
+when_to_check_if_top_few_have_overwhelming_votes = "Never"
+"#;
+
+fn make_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C","D","E"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(3),CandidateIndex(1)], n: 43 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(2),CandidateIndex(4)], n: 21 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 9 },
+        BTL{ candidates: vec![CandidateIndex(4),CandidateIndex(1)], n: 7 },
+    ])
+}
+
+#[test]
+fn test_dynamic_rules_match_compiled_rules() {
+    let description : RuleDescription = toml::from_str(FEDERAL_POST_2021).unwrap();
+    assert_eq!(DynamicTallyType::Integers,description.tally);
+    let data = make_election();
+    let compiled = distribute_preferences::<FederalRulesPost2021>(&data,NumberOfCandidates(2),&HashSet::default(),&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    let rules = DynamicRules::<usize>::new(Arc::new(description)).unwrap();
+    let mut dynamic = distribute_preferences_with_extractors(&rules,&data,&CountOptions::new(NumberOfCandidates(2),HashSet::default(),&data.metadata.tie_resolutions),&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!("MyFederalPost2021",dynamic.rules);
+    assert!(compiled.counts.len()>2);
+    dynamic.rules=compiled.rules.clone();
+    assert_eq!(serde_json::to_string(&compiled).unwrap(),serde_json::to_string(&dynamic).unwrap());
+}
+
+#[test]
+fn test_load_json_rule_description() {
+    let description : RuleDescription = toml::from_str(FEDERAL_POST_2021).unwrap();
+    let path = std::env::temp_dir().join(format!("concrete_stv_dynamic_rules_test_{}.json",std::process::id()));
+    std::fs::write(&path,serde_json::to_string(&description).unwrap()).unwrap();
+    let loaded = RuleDescription::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!("MyFederalPost2021",loaded.name);
+    assert!(loaded.prohibit_negative_surplus_fraction); // defaulted
+    assert!(toml::from_str::<RuleDescription>(&(FEDERAL_POST_2021.to_string()+"no_such_option = true\n")).is_err());
+}
+
+#[test]
+fn test_dynamic_rules_have_same_rule_card_as_compiled_rules() {
+    let description : RuleDescription = toml::from_str(FEDERAL_POST_2021).unwrap();
+    let dynamic = RuleCard::new(&DynamicRules::<usize>::new(Arc::new(description)).unwrap());
+    let compiled = RuleCard::new(&FederalRulesPost2021::default());
+    assert_eq!("MyFederalPost2021",dynamic.name);
+    assert!(compiled.diff(&dynamic).is_empty());
+}
+
+#[test]
+fn test_dynamic_rules_must_match_description_types() {
+    let description = Arc::new(toml::from_str::<RuleDescription>(FEDERAL_POST_2021).unwrap());
+    assert!(DynamicRules::<usize>::new(description.clone()).is_ok());
+    assert_eq!(Some(CountError::RuleDescriptionTypeMismatch{ name: "MyFederalPost2021".to_string() }),DynamicRules::<FixedPrecisionDecimal<6>>::new(description.clone()).err());
+    assert_eq!(Some(CountError::RuleDescriptionTypeMismatch{ name: "MyFederalPost2021".to_string() }),DynamicRules::<usize,FullySplitByCountNumber>::new(description).err());
+}
@@ -0,0 +1,54 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that counting with exact arithmetic removes rounding, and that the first count affected by rounding can be found.
+
+use std::collections::HashSet;
+use federal::FederalRulesPost2021;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::compare_transcripts::{compare_transcripts, DifferenceBetweenTranscripts};
+use stv::distribution_of_preferences_transcript::CountIndex;
+use stv::election_data::ElectionData;
+use stv::exact_arithmetic::ExactArithmetic;
+use stv::preference_distribution::{distribute_preferences, PreferenceDistributionRules};
+use stv::random_util::Randomness;
+use stv::count_observer::IgnoreCountProgress;
+
+fn make_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(1)], n: 30 },
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(2)], n: 25 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 15 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 30 },
+    ])
+}
+
+#[test]
+fn test_exact_arithmetic() {
+    let data = make_election();
+    let count = |data:&ElectionData|(
+        distribute_preferences::<FederalRulesPost2021>(data,NumberOfCandidates(2),&HashSet::default(),&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap(),
+        distribute_preferences::<ExactArithmetic<FederalRulesPost2021>>(data,NumberOfCandidates(2),&HashSet::default(),&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap());
+    let (rounded,exact) = count(&data);
+    assert_eq!("FederalPost2021_Exact",ExactArithmetic::<FederalRulesPost2021>::default().name());
+    assert_eq!("34",exact.quota.as_ref().unwrap().quota.to_string());
+    // A's surplus of 21 is distributed with transfer value 21/55, giving B 30*21/55 = 11 5/11 and C 25*21/55 = 9 6/11.
+    let surplus = &exact.counts[1].status.tallies;
+    assert_eq!(vec!["34","291/11","435/11"],surplus.candidate.iter().map(|t|t.to_string()).collect::<Vec<_>>());
+    assert_eq!("0",surplus.rounding.value.to_string());
+    assert_eq!(vec![34,26,39],rounded.counts[1].status.tallies.candidate);
+    assert_eq!(1,rounded.counts[1].status.tallies.rounding.value);
+    // The same candidates are elected, but the rounding shows up in the surplus distribution.
+    assert_eq!(rounded.elected,exact.elected);
+    assert_eq!(DifferenceBetweenTranscripts::DifferentValues(CountIndex(1)),compare_transcripts(&rounded,&exact));
+    // If there is no rounding, the transcripts are the same.
+    let mut data = data;
+    data.btl[1].n=0;
+    data.btl[0].n=55;
+    let (rounded,exact) = count(&data);
+    assert_eq!(DifferenceBetweenTranscripts::Same,compare_transcripts(&rounded,&exact));
+}
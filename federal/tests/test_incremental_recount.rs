@@ -0,0 +1,63 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that an incremental recount starts from the right place and gives the same transcript as a full recount.
+
+use federal::FederalRulesPost2021;
+use stv::ballot_metadata::CandidateIndex;
+use stv::ballot_paper::BTL;
+use stv::count_observer::IgnoreCountProgress;
+use stv::election_data::ElectionData;
+use stv::incremental_recount::RecordedCount;
+use stv::prepared_election::{PreparedElection, VoteDeltas};
+use stv::random_util::Randomness;
+
+fn make_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C","D","E"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(1),CandidateIndex(3)], n: 25 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(3),CandidateIndex(2)], n: 10 },
+        BTL{ candidates: vec![CandidateIndex(4),CandidateIndex(1)], n: 6 },
+    ])
+}
+
+#[test]
+fn test_incremental_recount() {
+    let data = make_election();
+    let prepared = PreparedElection::new(&data,None);
+    let no_changes = VoteDeltas::default();
+    let votes = prepared.votes(&no_changes);
+    let mut observer = IgnoreCountProgress{};
+    let mut randomness = Randomness::ReverseDonkeyVote;
+    let rules = FederalRulesPost2021::default();
+    let recorded = RecordedCount::new(&rules,&prepared,&votes,&mut observer,&mut randomness).unwrap();
+    // A elected on first preferences, A's surplus exhausts, E excluded (to B), D excluded (to C), B elected.
+    assert_eq!(recorded.transcript.elected,vec![CandidateIndex(0),CandidateIndex(1)]);
+    let check = |deltas:&VoteDeltas,expected_last_unaffected:Option<usize>,expected_elected:Vec<CandidateIndex>| {
+        assert_eq!(recorded.last_unaffected_state(deltas),expected_last_unaffected);
+        let incremental = recorded.recount(deltas).unwrap();
+        let full = prepared.distribute_preferences(&rules,deltas,&mut Randomness::ReverseDonkeyVote).unwrap();
+        assert_eq!(serde_json::to_string(&incremental).unwrap(),serde_json::to_string(&full).unwrap());
+        assert_eq!(incremental.elected,expected_elected);
+    };
+    // B,D votes changed to B,C never leave B, so have no effect at all.
+    let mut deltas = VoteDeltas::default();
+    deltas.change_btl(1,BTL{ candidates: vec![CandidateIndex(1),CandidateIndex(2)], n: 5 });
+    check(&deltas,Some(recorded.transcript.counts.len()-1),vec![CandidateIndex(0),CandidateIndex(1)]);
+    // E,B votes changed to E,C are only different when E is excluded on the third count, which gets C elected instead of B.
+    let mut deltas = VoteDeltas::default();
+    deltas.change_btl(4,BTL{ candidates: vec![CandidateIndex(4),CandidateIndex(2)], n: 6 });
+    check(&deltas,Some(1),vec![CandidateIndex(0),CandidateIndex(2)]);
+    // changing first preferences needs a full recount.
+    let mut deltas = VoteDeltas::default();
+    deltas.change_btl(0,BTL{ candidates: vec![CandidateIndex(2)], n: 10 });
+    check(&deltas,None,vec![CandidateIndex(2),CandidateIndex(1)]);
+    // as do added votes.
+    let mut deltas = VoteDeltas::default();
+    deltas.add_btl(BTL{ candidates: vec![CandidateIndex(4),CandidateIndex(2)], n: 3 });
+    check(&deltas,None,vec![CandidateIndex(0),CandidateIndex(2)]);
+}
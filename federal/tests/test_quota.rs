@@ -0,0 +1,72 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check the different quota formulae, and that the quota of an existing set of rules can be overridden.
+
+use std::collections::HashSet;
+use federal::FederalRulesPost2021;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::ballot_pile::BallotPaperCount;
+use stv::election_data::ElectionData;
+use stv::count_error::CountError;
+use stv::preference_distribution::{distribute_preferences_with_extractors, CountOptions};
+use stv::quota::QuotaMethod;
+use stv::random_util::Randomness;
+use stv::count_observer::IgnoreCountProgress;
+
+#[test]
+fn test_integer_quotas() {
+    let quota = |method:QuotaMethod,papers:usize|method.compute::<FederalRulesPost2021>(BallotPaperCount(papers),NumberOfCandidates(2)).unwrap();
+    assert_eq!(34,quota(QuotaMethod::Droop,100));
+    assert_eq!(34,quota(QuotaMethod::ExactDroop,100));
+    assert_eq!(34,quota(QuotaMethod::HagenbachBischoff,100));
+    assert_eq!(50,quota(QuotaMethod::Hare,100));
+    assert_eq!(25,quota(QuotaMethod::Imperiali,100));
+    // 99/3 is exactly 33, which must be exceeded for exact Droop but just reached for Hagenbach-Bischoff.
+    assert_eq!(34,quota(QuotaMethod::Droop,99));
+    assert_eq!(34,quota(QuotaMethod::ExactDroop,99));
+    assert_eq!(33,quota(QuotaMethod::HagenbachBischoff,99));
+    assert_eq!(50,quota(QuotaMethod::Hare,99));
+    assert_eq!(25,quota(QuotaMethod::Imperiali,99));
+    assert_eq!(Ok(QuotaMethod::HagenbachBischoff),"HagenbachBischoff".parse());
+    assert!("Sainte-Laguë".parse::<QuotaMethod>().is_err());
+    assert_eq!(Err(CountError::QuotaUndefined{method:QuotaMethod::Hare,vacancies:NumberOfCandidates(0)}),QuotaMethod::Hare.compute::<FederalRulesPost2021>(BallotPaperCount(100),NumberOfCandidates(0)));
+    assert_eq!(Ok(101),QuotaMethod::Droop.compute::<FederalRulesPost2021>(BallotPaperCount(100),NumberOfCandidates(0)));
+}
+
+fn make_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(1)], n: 55 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 15 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 30 },
+    ])
+}
+
+#[test]
+fn test_override_quota_method() {
+    let data = make_election();
+    let count = |quota_method:Option<QuotaMethod>|distribute_preferences_with_extractors(&FederalRulesPost2021::default(),&data,&CountOptions{ quota_method, ..CountOptions::new(NumberOfCandidates(2),HashSet::default(),&data.metadata.tie_resolutions) },&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    let droop = count(None);
+    let quota = droop.quota.as_ref().unwrap();
+    assert_eq!(QuotaMethod::Droop,quota.method);
+    assert_eq!(34,quota.quota);
+    // With a Droop quota, A's surplus of 21 elects B.
+    assert_eq!(vec![CandidateIndex(0),CandidateIndex(1)],droop.elected);
+    let hare = count(Some(QuotaMethod::Hare));
+    let quota = hare.quota.as_ref().unwrap();
+    assert_eq!(QuotaMethod::Hare,quota.method);
+    assert_eq!(50,quota.quota);
+    // With a Hare quota, A's surplus of just 5 is not enough to get B ahead of C.
+    assert_eq!(vec![CandidateIndex(0),CandidateIndex(2)],hare.elected);
+}
+
+#[test]
+fn test_hare_quota_with_no_vacancies_is_an_error() {
+    let data = make_election();
+    let result = distribute_preferences_with_extractors(&FederalRulesPost2021::default(),&data,&CountOptions{ quota_method: Some(QuotaMethod::Hare), ..CountOptions::new(NumberOfCandidates(0),HashSet::default(),&data.metadata.tie_resolutions) },&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote);
+    assert_eq!(Some(CountError::QuotaUndefined{method:QuotaMethod::Hare,vacancies:NumberOfCandidates(0)}),result.err());
+}
@@ -0,0 +1,56 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that recorded draws are put in the transcript, and can be replayed to reproduce the count.
+
+mod common;
+
+use common::make_tied_election;
+use federal::FederalRulesPost2021;
+use rand::SeedableRng;
+use stv::ballot_metadata::CandidateIndex;
+use stv::count_error::CountError;
+use stv::distribution_of_preferences_transcript::{CountIndex, TranscriptWithMetadata};
+use stv::random_util::{read_draws_from_transcript, RandomDraw, Randomness, ReplayDraws};
+use stv::tie_resolution::TieResolutionUsage;
+
+#[test]
+fn test_record_and_replay() {
+    let data = make_tied_election();
+    let mut recorder = Randomness::Record(Box::new(Randomness::PRNG(rand_chacha::ChaCha20Rng::seed_from_u64(7))),vec![]);
+    let recorded = data.distribute_preferences::<FederalRulesPost2021>(&mut recorder).unwrap();
+    assert!(!recorded.draws.is_empty());
+    match &recorded.draws[0] {
+        RandomDraw::Tie { usage, tied, .. } => {
+            assert_eq!(*usage,TieResolutionUsage::Exclusion);
+            assert_eq!(*tied,vec![CandidateIndex(1),CandidateIndex(2),CandidateIndex(3)]);
+        }
+        draw => panic!("Unexpected draw {:?}",draw),
+    }
+    // read back from the file format, and replay.
+    let file = serde_json::to_string(&TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript: recorded.clone() }).unwrap();
+    let draws = read_draws_from_transcript(file.as_bytes()).unwrap();
+    assert_eq!(draws,recorded.draws);
+    let replayed = data.distribute_preferences::<FederalRulesPost2021>(&mut Randomness::Replay(ReplayDraws::new(draws))).unwrap();
+    assert_eq!(serde_json::to_string(&replayed).unwrap(),serde_json::to_string(&recorded).unwrap());
+}
+
+#[test]
+fn test_replay_mismatch() {
+    let data = make_tied_election();
+    let mut recorder = Randomness::Record(Box::new(Randomness::ReverseDonkeyVote),vec![]);
+    let recorded = data.distribute_preferences::<FederalRulesPost2021>(&mut recorder).unwrap();
+    // D has more votes, so the first draw is between B and C instead.
+    let mut different = make_tied_election();
+    different.btl[3].n=16;
+    let error = different.distribute_preferences::<FederalRulesPost2021>(&mut Randomness::Replay(ReplayDraws::new(recorded.draws.clone()))).unwrap_err();
+    assert_eq!(CountError::ReplayedDrawMismatch{ draw: 0, count: CountIndex(2) },error);
+    // extra draws that are not needed.
+    let mut draws = recorded.draws.clone();
+    draws.push(draws[0].clone());
+    let error = data.distribute_preferences::<FederalRulesPost2021>(&mut Randomness::Replay(ReplayDraws::new(draws))).unwrap_err();
+    assert_eq!(CountError::UnusedReplayedDraws{ used: recorded.draws.len(), recorded: recorded.draws.len()+1 },error);
+}
@@ -0,0 +1,33 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that rule cards show the documented differences between the federal rule variants.
+
+use federal::{FederalRulesPost2021, FederalRulesPost2021Manual, FederalRulesUsed2019};
+use stv::quota::QuotaMethod;
+use stv::rule_card::RuleCard;
+
+#[test]
+fn test_rule_card_differences() {
+    let post2021 = RuleCard::new(&FederalRulesPost2021::default());
+    assert_eq!("FederalPost2021",post2021.name);
+    assert_eq!("usize",post2021.tally_type);
+    assert_eq!("DoNotSplitByCountNumber",post2021.split_by_number);
+    assert!(post2021.has_quota);
+    assert_eq!(QuotaMethod::Droop,post2021.quota_method);
+    assert_eq!("6",post2021.votes_for_10_ballots_at_transfer_value_2_3);
+    assert!(post2021.diff(&post2021).is_empty());
+    // AEC2019 differs in tie resolution for exclusion and when rule (18) is applied.
+    let differences : Vec<String> = RuleCard::new(&FederalRulesUsed2019::default()).diff(&post2021).into_iter().map(|d|d.field).collect();
+    assert_eq!(vec!["resolve_ties_choose_lowest_candidate_for_exclusion","when_to_check_if_all_remaining_should_get_elected"],differences);
+    // The manual variant just adds bulk exclusion.
+    let manual = RuleCard::new(&FederalRulesPost2021Manual::default());
+    let differences = post2021.diff(&manual);
+    assert_eq!(1,differences.len());
+    assert_eq!("should_eliminate_multiple_candidates_federal_rule_13a",differences[0].field);
+    assert_eq!("false",differences[0].first);
+    assert_eq!("true",differences[0].second);
+}
@@ -0,0 +1,86 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that stepping through a distribution of preferences one count at a time gives the same result as doing it all at once.
+
+use std::collections::HashSet;
+use federal::FederalRulesPost2021;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::distribution_of_preferences_transcript::ReasonForCount;
+use stv::election_data::ElectionData;
+use stv::preference_distribution::{distribute_preferences, CountOptions, PreferenceDistributor};
+use stv::random_util::Randomness;
+use stv::count_observer::IgnoreCountProgress;
+
+fn make_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C","D"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(3),CandidateIndex(1)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 24 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 25 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 11 },
+    ])
+}
+
+#[test]
+fn test_step_gives_same_transcript_as_go() {
+    let data = make_election();
+    let all_at_once = distribute_preferences::<FederalRulesPost2021>(&data,NumberOfCandidates(2),&HashSet::default(),&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    let arena = typed_arena::Arena::<CandidateIndex>::new();
+    let votes = data.resolve_atl_including_weights(&arena,None);
+    let mut randomness = Randomness::ReverseDonkeyVote;
+    let mut observer = IgnoreCountProgress{};
+    let rules = FederalRulesPost2021::default();
+    let mut work : PreferenceDistributor<'_,FederalRulesPost2021> = PreferenceDistributor::new(&rules,&data,&votes,&CountOptions::new(NumberOfCandidates(2),HashSet::default(),&data.metadata.tie_resolutions),&mut observer,None,&mut randomness);
+    assert!(!work.is_finished());
+    // first preferences
+    assert!(matches!(work.step().unwrap().unwrap().reason,ReasonForCount::FirstPreferenceCount));
+    assert_eq!(vec![CandidateIndex(0)],work.elected_candidates());
+    assert_eq!(1,work.pending_surplus_distributions().len());
+    // A's surplus of 6 goes to D.
+    assert!(matches!(work.step().unwrap().unwrap().reason,ReasonForCount::ExcessDistribution(CandidateIndex(0))));
+    assert_eq!(17,work.tally(CandidateIndex(3)));
+    assert_eq!(CandidateIndex(3),work.continuing_candidates_sorted_by_tally()[0]);
+    // D is excluded, in two counts, one for each transfer value. The whole exclusion is done by the first step.
+    let first_part = work.step().unwrap().unwrap();
+    assert!(matches!(&first_part.reason,ReasonForCount::Elimination(c) if c==&vec![CandidateIndex(3)]));
+    assert!(!first_part.reason_completed);
+    assert_eq!(30,work.tally(CandidateIndex(1)));
+    // Only B and C remain for the last vacancy, so B, who has more votes, is elected.
+    let second_part = work.step().unwrap().unwrap();
+    assert!(second_part.reason_completed);
+    assert_eq!(CandidateIndex(1),second_part.elected[0].who);
+    assert!(work.is_finished());
+    assert!(work.step().unwrap().is_none());
+    let stepped = work.into_transcript();
+    assert_eq!(4,stepped.counts.len());
+    assert_eq!(vec![CandidateIndex(0),CandidateIndex(1)],stepped.elected);
+    assert_eq!(serde_json::to_string(&all_at_once).unwrap(),serde_json::to_string(&stepped).unwrap());
+}
+
+#[test]
+fn test_step_action_state_matches_last_count() {
+    let data = make_election();
+    let all_at_once = distribute_preferences::<FederalRulesPost2021>(&data,NumberOfCandidates(2),&HashSet::default(),&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    let arena = typed_arena::Arena::<CandidateIndex>::new();
+    let votes = data.resolve_atl_including_weights(&arena,None);
+    let mut randomness = Randomness::ReverseDonkeyVote;
+    let mut observer = IgnoreCountProgress{};
+    let rules = FederalRulesPost2021::default();
+    let mut work : PreferenceDistributor<'_,FederalRulesPost2021> = PreferenceDistributor::new(&rules,&data,&votes,&CountOptions::new(NumberOfCandidates(2),HashSet::default(),&data.metadata.tie_resolutions),&mut observer,None,&mut randomness);
+    let mut actions = vec![];
+    while let Some(counts) = work.step_action().unwrap() {
+        let last = counts.last().unwrap();
+        assert!(last.reason_completed);
+        let tallies = last.status.tallies.candidate.clone();
+        actions.push(counts.len());
+        assert!(work.is_between_actions());
+        for (c,tally) in tallies.iter().enumerate() { assert_eq!(*tally,work.tally(CandidateIndex(c))); }
+    }
+    // first preferences, A's surplus, and D's exclusion in two counts.
+    assert_eq!(vec![1,1,2],actions);
+    assert_eq!(serde_json::to_string(&all_at_once).unwrap(),serde_json::to_string(&work.into_transcript()).unwrap());
+}
@@ -0,0 +1,58 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that enumerating the results of draws finds every possible outcome.
+
+mod common;
+
+use common::make_tied_election;
+use federal::FederalRulesPost2021;
+use stv::ballot_metadata::CandidateIndex;
+use stv::count_error::CountError;
+use stv::prepared_election::PreparedElection;
+use stv::tie_enumeration::{enumerate_tie_outcomes, enumerate_tie_outcomes_of_election};
+use stv::tie_resolution::TieResolutionsMadeByEC;
+
+const MAX_BRANCHES : usize = 100;
+
+#[test]
+fn test_every_tied_candidate_can_win() {
+    let data = make_tied_election();
+    let outcomes = enumerate_tie_outcomes(MAX_BRANCHES,|randomness|data.distribute_preferences::<FederalRulesPost2021>(randomness).map(|transcript|transcript.elected)).unwrap();
+    assert_eq!(outcomes.outcomes.len(),3);
+    for (outcome,winner) in outcomes.outcomes.iter().zip([1,2,3]) {
+        assert_eq!(outcome.elected,vec![CandidateIndex(0),CandidateIndex(winner)]);
+        assert!((outcome.probability-1.0/3.0).abs()<1e-9);
+    }
+    let total : f64 = outcomes.outcomes.iter().map(|o|o.probability).sum();
+    assert!((total-1.0).abs()<1e-9);
+    // the prepared election gives the same result.
+    let prepared = PreparedElection::new(&data,None);
+    assert_eq!(outcomes,enumerate_tie_outcomes_of_election(&FederalRulesPost2021::default(),&prepared,MAX_BRANCHES).unwrap());
+}
+
+#[test]
+fn test_ec_decision_removes_draw() {
+    let mut data = make_tied_election();
+    data.metadata.tie_resolutions=TieResolutionsMadeByEC::new(vec![vec![CandidateIndex(3),CandidateIndex(2),CandidateIndex(1)]]).unwrap();
+    let outcomes = enumerate_tie_outcomes(MAX_BRANCHES,|randomness|data.distribute_preferences::<FederalRulesPost2021>(randomness).map(|transcript|transcript.elected)).unwrap();
+    assert_eq!(outcomes.outcomes.len(),1);
+    assert_eq!(outcomes.outcomes[0].probability,1.0);
+}
+
+#[test]
+fn test_too_many_branches_is_an_error() {
+    let data = make_tied_election();
+    // the first draw has 3 possible results.
+    let result = enumerate_tie_outcomes(2,|randomness|data.distribute_preferences::<FederalRulesPost2021>(randomness).map(|transcript|transcript.elected));
+    assert_eq!(Err(CountError::TooManyTieBranches{max_branches:2}),result);
+    let prepared = PreparedElection::new(&data,None);
+    assert_eq!(Err(CountError::TooManyTieBranches{max_branches:2}),enumerate_tie_outcomes_of_election(&FederalRulesPost2021::default(),&prepared,2));
+    // exactly enough branches is fine, one fewer is not.
+    let branches = enumerate_tie_outcomes_of_election(&FederalRulesPost2021::default(),&prepared,MAX_BRANCHES).unwrap().branches;
+    assert!(enumerate_tie_outcomes_of_election(&FederalRulesPost2021::default(),&prepared,branches).is_ok());
+    assert_eq!(Err(CountError::TooManyTieBranches{max_branches:branches-1}),enumerate_tie_outcomes_of_election(&FederalRulesPost2021::default(),&prepared,branches-1));
+}
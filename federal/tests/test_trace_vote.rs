@@ -0,0 +1,101 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check following a ballot through a count.
+
+use std::collections::HashSet;
+use federal::FederalRulesUsed2016;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates, Party, PartyIndex};
+use stv::ballot_paper::{ATL, BTL, FormalityRules};
+use stv::ballot_pile::BallotPaperCount;
+use stv::count_observer::IgnoreCountProgress;
+use stv::distribution_of_preferences_transcript::CountIndex;
+use stv::election_data::ElectionData;
+use stv::random_util::Randomness;
+use stv::preference_distribution::CountOptions;
+use stv::trace_vote::{trace_vote, TracedVoteStatus, VoteToTrace, VoteTrace};
+use stv::transfer_value::TransferValue;
+
+/// 2 vacancies, quota 34. A is elected on first preferences, and A's surplus goes to C, who is later excluded.
+fn make_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C","D","E"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(2)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 30 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 10 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 12 },
+        BTL{ candidates: vec![CandidateIndex(4),CandidateIndex(3)], n: 8 },
+    ])
+}
+
+/// As [make_election], with A and C in party X, B and D in party Y, and 5 more votes above the line for X then Y.
+fn make_election_with_atl() -> ElectionData {
+    let mut data = make_election();
+    let party = |name:&str,candidates:Vec<CandidateIndex>| Party{ column_id: name.to_string(), name: name.to_string(), abbreviation: None, atl_allowed: true, candidates, tickets: vec![] };
+    data.metadata.parties = vec![party("X",vec![CandidateIndex(0),CandidateIndex(2)]),party("Y",vec![CandidateIndex(1),CandidateIndex(3)])];
+    data.atl.push(ATL{ parties: vec![PartyIndex(0),PartyIndex(1)], n: 5, ticket_index: None });
+    data
+}
+
+fn trace_in(data:&ElectionData,vote:&VoteToTrace,formality_rules:FormalityRules) -> Option<VoteTrace> {
+    trace_vote(&FederalRulesUsed2016::default(),data,vote,formality_rules,&CountOptions::new(NumberOfCandidates(2),HashSet::default(),&data.metadata.tie_resolutions),&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap()
+}
+
+fn trace(vote:&VoteToTrace) -> Option<VoteTrace> { trace_in(&make_election(),vote,FormalityRules::default()) }
+
+fn markings(atl:&str,btl:&str) -> VoteToTrace { VoteToTrace::Markings{ atl: atl.to_string(), btl: btl.to_string() } }
+
+#[test]
+fn test_trace_vote() {
+    let trace = trace(&VoteToTrace::Btl(vec![CandidateIndex(0),CandidateIndex(2)])).unwrap();
+    assert_eq!(trace.papers,BallotPaperCount(40));
+    let first = &trace.counts[0];
+    assert_eq!(first.count,CountIndex(0));
+    assert_eq!(first.locations.len(),1);
+    assert_eq!(first.locations[0].candidate,Some(CandidateIndex(0)));
+    assert_eq!(first.locations[0].status,TracedVoteStatus::AwaitingSurplusDistribution);
+    assert_eq!(first.locations[0].papers,BallotPaperCount(40));
+    assert_eq!(first.locations[0].transfer_value,TransferValue::one());
+    assert_eq!(first.locations[0].value.0.to_string(),"40");
+    let second = &trace.counts[1];
+    assert_eq!(second.locations.len(),1);
+    assert_eq!(second.locations[0].candidate,Some(CandidateIndex(2)));
+    assert_eq!(second.locations[0].status,TracedVoteStatus::Continuing);
+    // the 40 papers transferred at 6/40 contribute 6 votes to C.
+    assert_eq!(second.locations[0].transfer_value.to_string(),"3/20");
+    assert_eq!(second.locations[0].value.0.to_string(),"6");
+    let last = trace.counts.last().unwrap();
+    assert_eq!(last.locations.len(),1);
+    assert_eq!(last.locations[0].candidate,None);
+    assert_eq!(last.locations[0].status,TracedVoteStatus::Exhausted);
+    assert_eq!(last.locations[0].papers,BallotPaperCount(40));
+    assert_eq!(trace.counts.iter().map(|c|c.count).collect::<Vec<_>>(),(0..trace.counts.len()).map(CountIndex).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_trace_markings() {
+    let by_markings = trace(&markings("","1,,2,,")).unwrap();
+    assert_eq!(by_markings.vote,VoteToTrace::Btl(vec![CandidateIndex(0),CandidateIndex(2)]));
+    assert_eq!(by_markings.counts,trace(&VoteToTrace::Btl(vec![CandidateIndex(0),CandidateIndex(2)])).unwrap().counts);
+    assert!(trace(&markings("",",,,,")).is_none());
+    // Informal with the federal requirement of 6 preferences below the line.
+    assert!(trace_in(&make_election(),&markings("","1,,2,,"),FormalityRules{ min_atl_prefs_needed: 1, min_btl_prefs_needed: 6 }).is_none());
+    let absent = trace(&VoteToTrace::Btl(vec![CandidateIndex(3),CandidateIndex(2)])).unwrap();
+    assert_eq!(absent.papers,BallotPaperCount(0));
+    assert!(absent.counts.iter().all(|c|c.locations.is_empty()));
+}
+
+#[test]
+fn test_trace_atl_markings() {
+    let data = make_election_with_atl();
+    let trace = trace_in(&data,&markings("1,2",",,,,"),FormalityRules::default()).unwrap();
+    assert_eq!(trace.vote,VoteToTrace::Atl(vec![PartyIndex(0),PartyIndex(1)]));
+    assert_eq!(trace.papers,BallotPaperCount(5));
+    assert_eq!(trace.counts[0].locations.len(),1);
+    assert_eq!(trace.counts[0].locations[0].candidate,Some(CandidateIndex(0)));
+    assert_eq!(trace.counts[0].locations[0].value.0.to_string(),"5");
+    // below the line preferences take precedence if formal.
+    assert_eq!(trace_in(&data,&markings("1,2","1,,2,,"),FormalityRules::default()).unwrap().vote,VoteToTrace::Btl(vec![CandidateIndex(0),CandidateIndex(2)]));
+}
@@ -0,0 +1,66 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that verifiable draws are stored in the transcript and can be checked against the seed file.
+
+mod common;
+
+use common::make_tied_election;
+use federal::FederalRulesPost2021;
+use stv::ballot_metadata::CandidateIndex;
+use stv::election_data::ElectionData;
+use stv::random_util::{RandomDraw, Randomness};
+use stv::verifiable_draw::{verify_draws, DrawVerificationError, PublicSeed, VerifiableDraws};
+
+const SEED_FILE : &[u8] = b"Dice rolled at public ceremony: 3 1 4 1 5 9 2 6\n";
+
+#[test]
+fn test_verifiable_draws() {
+    let data = make_tied_election();
+    let seed = PublicSeed::from_contents("dice.txt",SEED_FILE);
+    let transcript = data.distribute_preferences::<FederalRulesPost2021>(&mut Randomness::Verifiable(VerifiableDraws::new(seed.clone()))).unwrap();
+    assert_eq!(transcript.draw_seed,Some(seed.clone()));
+    assert!(!transcript.draws.is_empty());
+    assert_eq!(Ok(transcript.draws.len()),verify_draws(transcript.draw_seed.as_ref(),&transcript.draws,SEED_FILE,recount(&data)));
+    // The same seed gives the same count.
+    let again = data.distribute_preferences::<FederalRulesPost2021>(&mut Randomness::Verifiable(VerifiableDraws::new(seed.clone()))).unwrap();
+    assert_eq!(serde_json::to_string(&again).unwrap(),serde_json::to_string(&transcript).unwrap());
+    // A different file is detected.
+    assert!(matches!(verify_draws(transcript.draw_seed.as_ref(),&transcript.draws,b"Other dice",recount(&data)),Err(DrawVerificationError::CommitmentMismatch{..})));
+    assert_eq!(Err(DrawVerificationError::NoSeedInTranscript),verify_draws(None,&transcript.draws,SEED_FILE,recount(&data)));
+    // A tampered result is detected.
+    let mut tampered = transcript.draws.clone();
+    if let RandomDraw::Tie{ order, .. } = &mut tampered[0] { order.reverse(); }
+    assert!(matches!(verify_draws(transcript.draw_seed.as_ref(),&tampered,SEED_FILE,recount(&data)),Err(DrawVerificationError::WrongResult{ draw: 0, .. })));
+    // A draw between candidates who were not tied is detected, even if it is consistent with the seed.
+    let mut tampered = transcript.draws.clone();
+    if let RandomDraw::Tie{ tied, order, .. } = &mut tampered[0] {
+        tied.retain(|&c|c!=CandidateIndex(3));
+        *order=tied.clone();
+        seed.draw(order,0);
+    }
+    assert!(matches!(verify_draws(transcript.draw_seed.as_ref(),&tampered,SEED_FILE,recount(&data)),Err(DrawVerificationError::WrongTiedCandidates{ draw: 0, .. })));
+    // A missing draw is detected.
+    assert!(matches!(verify_draws(transcript.draw_seed.as_ref(),&transcript.draws[..transcript.draws.len()-1],SEED_FILE,recount(&data)),Err(DrawVerificationError::WrongNumberOfDraws{..})));
+}
+
+/// Redo the count of `data` with the given randomness, getting the draws made.
+fn recount(data:&ElectionData) -> impl FnOnce(&mut Randomness) -> Result<Vec<RandomDraw>,DrawVerificationError> + '_ {
+    |randomness|Ok(data.distribute_preferences::<FederalRulesPost2021>(randomness)?.draws)
+}
+
+#[test]
+fn test_recorded_verifiable_draws_are_stored_once() {
+    let data = make_tied_election();
+    let seed = PublicSeed::from_contents("dice.txt",SEED_FILE);
+    let verifiable = data.distribute_preferences::<FederalRulesPost2021>(&mut Randomness::Verifiable(VerifiableDraws::new(seed.clone()))).unwrap();
+    let mut randomness = Randomness::Record(Box::new(Randomness::Verifiable(VerifiableDraws::new(seed.clone()))),vec![]);
+    let recorded = data.distribute_preferences::<FederalRulesPost2021>(&mut randomness).unwrap();
+    assert_eq!(verifiable.draws,recorded.draws);
+    assert!(randomness.take_recorded_draws().is_empty());
+    let Randomness::Record(inner,_) = &mut randomness else { panic!("randomness replaced") };
+    assert!(inner.take_recorded_draws().is_empty());
+}
@@ -0,0 +1,88 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rules for elections to Dáil Éireann. The Irish election data on [PrefLib](https://www.preflib.org/) can be
+//! converted with `preflib_to_stv` and counted with these rules.
+
+use stv::ballot_pile::{BallotPaperCount, DoNotSplitByCountNumber};
+use stv::preference_distribution::{BigRational, LastParcelUse, PreferenceDistributionRules, SurplusTransferMethod, TransferValueMethod, WhenToDoElectCandidateClauseChecking};
+use stv::tie_resolution::MethodOfTieResolution;
+use stv::transfer_value::{convert_usize_to_rational, round_rational_down_to_usize, TransferValue};
+
+/// My interpretation of the count described in section 121 of the Electoral Act 1992.
+///
+/// Votes are whole ballot papers. A surplus is distributed by examining just the last
+/// parcel of papers received by the elected candidate (all their papers if elected on first
+/// preferences), sorting the transferable ones by next available preference, and giving each
+/// continuing candidate the proportion of the surplus matching their share of the transferable papers,
+/// with remainders going to the largest fractions. The Act says which papers are
+/// transferred (the last filed in each sub-parcel); as ballot order is not recorded in the
+/// data this picks them at random, using the same mechanism as the Tasmanian and NSW random rules.
+///
+/// The largest surplus is distributed first. Several lowest candidates are excluded together
+/// when their combined votes plus undistributed surpluses are fewer than the next highest candidate's votes.
+///
+/// Not implemented : the Act allows a surplus to be left undistributed when it could not affect the
+/// order of exclusion, election, or the recovery of a deposit. Here surpluses are always distributed
+/// before any exclusion.
+#[derive(Default)]
+pub struct IrishDail {
+}
+
+impl PreferenceDistributionRules for IrishDail {
+    type Tally = usize;
+    type SplitByNumber = DoNotSplitByCountNumber;
+
+    /// Only the last parcel is examined for a surplus.
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::LiterallyLast }
+    /// The surplus is shared according to the transferable papers only.
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
+
+    fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { convert_usize_to_rational(tally)  }
+    fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { round_rational_down_to_usize(rational)  }
+
+    /// If the surplus is at least the number of transferable papers, they are all transferred.
+    fn make_transfer_value(&self,surplus: usize, ballots: BallotPaperCount) -> TransferValue {
+        if surplus>=ballots.0 { TransferValue::one() }
+        else { TransferValue::from_surplus(surplus,ballots) }
+    }
+
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
+        transfer_value.mul_rounding_down(ballots)
+    }
+
+    /// Whole papers are transferred, the rest staying with the elected candidate.
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::PickRandomlyAfterDistribution }
+    /// All papers have value 1, so this is not relevant.
+    fn sort_exclusions_by_transfer_value(&self) -> bool { false }
+    /// When there are several surpluses, the largest is distributed first.
+    fn distribute_largest_surplus_first(&self) -> bool { true }
+    /// Multiple lowest candidates are excluded together when they cannot catch up with the next highest.
+    fn should_eliminate_multiple_candidates_below_next_highest(&self) -> bool { true }
+
+    /// NA
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
+    /// The candidate with more votes at the first count at which they differed, failing which by lot.
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorEarliestCountFirst }
+    /// Order of election does not otherwise matter, so do the same as for quota.
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorEarliestCountFirst }
+    /// The candidate with fewer votes at the first count at which they differed, failing which by lot.
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorEarliestCountFirst }
+
+    /// NA as there is no middle
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { false }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { false }
+
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
+
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+    /// When the number of continuing candidates equals the number of unfilled vacancies, they are all elected.
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+
+    fn name(&self) -> String { "IrishDail".to_string() }
+}
@@ -0,0 +1,59 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Test the Irish rules on small examples.
+
+use std::collections::HashSet;
+use ireland::IrishDail;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::distribution_of_preferences_transcript::{CountIndex, ReasonForCount};
+use stv::election_data::ElectionData;
+use stv::preference_distribution::distribute_preferences;
+use stv::random_util::Randomness;
+use stv::count_observer::IgnoreCountProgress;
+
+#[test]
+fn test_multiple_exclusion() {
+    let data = ElectionData::from_btl(&["A","B","C","D","E"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(2)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 30 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(3),CandidateIndex(1)], n: 6 },
+        BTL{ candidates: vec![CandidateIndex(4),CandidateIndex(1)], n: 4 },
+    ]);
+    let transcript = distribute_preferences::<IrishDail>(&data,NumberOfCandidates(2),&HashSet::default(),&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!(34,transcript.quota.as_ref().unwrap().quota);
+    assert_eq!(vec![34,30,26,6,4],transcript.count(CountIndex(1)).status.tallies.candidate);
+    // E and D together have 10 votes, fewer than C's 26, so are excluded together. C is not, as 36 is more than B's 30.
+    let exclusion = transcript.count(CountIndex(2));
+    assert!(matches!(&exclusion.reason,ReasonForCount::Elimination(c) if c==&vec![CandidateIndex(4),CandidateIndex(3)]));
+    assert_eq!(3,transcript.counts.len());
+    assert_eq!(transcript.elected,vec![CandidateIndex(0),CandidateIndex(1)]);
+}
+
+#[test]
+fn test_tie_resolved_by_earliest_difference() {
+    let data = ElectionData::from_btl(&["A","B","C","D"],3,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(3)], n: 25 },
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(2)], n: 15 },
+        BTL{ candidates: vec![CandidateIndex(1),CandidateIndex(2)], n: 2 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 34 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 5 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 3 },
+    ]);
+    let transcript = distribute_preferences::<IrishDail>(&data,NumberOfCandidates(3),&HashSet::default(),&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!(22,transcript.quota.as_ref().unwrap().quota);
+    // A's surplus of 18 is larger than B's of 14, so goes first, giving D 11 and C 7.
+    assert!(matches!(transcript.count(CountIndex(1)).reason,ReasonForCount::ExcessDistribution(CandidateIndex(0))));
+    assert_eq!(vec![22,36,12,14],transcript.count(CountIndex(1)).status.tallies.candidate);
+    // B's surplus only has 2 transferable papers, which go to C.
+    assert_eq!(vec![22,22,14,14],transcript.count(CountIndex(2)).status.tallies.candidate);
+    // C and D are tied. D was lower at the first count, although higher at the second.
+    let exclusion = transcript.count(CountIndex(3));
+    assert!(matches!(&exclusion.reason,ReasonForCount::Elimination(c) if c==&vec![CandidateIndex(3)]));
+    assert_eq!(transcript.elected,vec![CandidateIndex(0),CandidateIndex(1),CandidateIndex(2)]);
+}
@@ -49,9 +49,9 @@ fn main() -> anyhow::Result <()> {
         let out = File::create(format!("nsw2021stv/{}.stv", electorate))?;
         serde_json::to_writer(out,&data)?;
 
-        let mut results = find_outcome_changes::<NSWECLocalGov2021>(&data,&options1,true,None);
-        results.merge_reevaluating::<NSWECLocalGov2021>(&old_changes,&data,&ballot_types_considered_unverifiable,true); // add in old data to make sure we don't do worse!
-        //let results2 = find_outcome_changes::<NSWECLocalGov2021>(&data,&options2);
+        let mut results = find_outcome_changes(&NSWECLocalGov2021::default(),&data,&options1,true,None,None)?;
+        results.merge_reevaluating(&NSWECLocalGov2021::default(),&old_changes,&data,&ballot_types_considered_unverifiable,true)?; // add in old data to make sure we don't do worse!
+        //let results2 = find_outcome_changes(&NSWECLocalGov2021::default(),&data,&options2);
         //results.merge(results2);
         results.sort();
 
@@ -0,0 +1,87 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+
+use clap::{Parser};
+use std::path::PathBuf;
+use std::fs::File;
+use anyhow::anyhow;
+use main_app::ModifyStvFileOptions;
+use main_app::rules::{PossibleTranscripts, Rules};
+use stv::ballot_metadata::CandidateIndex;
+use stv::casual_vacancy::count_casual_vacancy;
+use stv::count_error::CountError;
+use stv::count_observer::{IgnoreCountProgress, PrintProgressToStdout};
+use stv::election_data::ElectionData;
+use stv::extract_votes_in_pile::ExtractionRequest;
+use stv::preference_distribution::CountOptions;
+use stv::random_util::Randomness;
+
+#[derive(Parser)]
+#[clap(version = "0.3", author = "Andrew Conway", name="ConcreteSTV")]
+/// Fill a casual vacancy by a countback: count the original election, take the votes the rules say
+/// to recount (for the ACT, those used to elect the vacating member), and recount them amongst the candidates contesting the vacancy.
+/// The original count, the votes recounted and the countback are saved together in a .casual_vacancy file.
+struct Opts {
+    /// The counting rules to use for both the original count and the countback. See concrete_stv for options.
+    /// The rules also determine which votes are recounted; currently only the ACT rules say how.
+    rules : Rules,
+
+    /// The name of the .stv (or .vchange) file to get votes from
+    #[clap(value_parser)]
+    votes : PathBuf,
+
+    /// The candidate index (starting from 0) of the member whose seat became vacant.
+    #[clap(long)]
+    vacating : CandidateIndex,
+
+    /// The candidate indices (starting from 0) of the candidates contesting the vacancy, separated by commas.
+    /// All other candidates are excluded from the countback.
+    #[clap(long,value_delimiter=',',required=true)]
+    contesting : Vec<CandidateIndex>,
+
+    #[clap(flatten)]
+    input_options : ModifyStvFileOptions,
+
+    /// Whether the status of the counts should be printed out to stdout.
+    #[clap(long)]
+    verbose: bool,
+
+    /// How random ties are done. If specified, the seed for a pseudo random number generator.
+    /// If not specified, then reverse donkey vote is used.
+    #[clap(short, long,value_parser)]
+    seed : Option<u64>,
+
+    /// An optional output file. If not specified, the name is derived from the input file and rules.
+    #[clap(short, long,value_parser)]
+    out : Option<PathBuf>,
+}
+
+fn count(opt:&Opts,votes:&ElectionData,extractors:&[ExtractionRequest],randomness:&mut Randomness) -> Result<PossibleTranscripts,CountError> {
+    let options = CountOptions{ extractors, ..CountOptions::from_metadata(votes)? };
+    if opt.verbose {
+        opt.rules.count(votes,&options,&mut PrintProgressToStdout::default(),randomness)
+    } else {
+        opt.rules.count(votes,&options,&mut IgnoreCountProgress{},randomness)
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let opt : Opts = Opts::parse();
+
+    let votes = opt.input_options.get_data(&opt.votes,opt.verbose)?;
+    let method = opt.rules.casual_vacancy_method().ok_or_else(||anyhow!("The {} rules do not say how to fill a casual vacancy by a countback",opt.rules))?;
+    let mut randomness : Randomness = opt.seed.into();
+    let mut countback_randomness = randomness.clone();
+    let report = count_casual_vacancy(&votes,method,opt.vacating,&opt.contesting,|data,extractors|count(&opt,data,extractors,&mut randomness),|recounted|count(&opt,recounted,&[],&mut countback_randomness))?;
+    let winner = *report.countback.elected().first().ok_or_else(||anyhow!("No one was elected in the countback"))?;
+    println!("Vacancy left by {} filled by {}",votes.metadata.candidate(opt.vacating).name,votes.metadata.candidate(winner).name);
+
+    let report_file = opt.input_options.result_file_name(&opt.votes,opt.out.as_ref(),".casual_vacancy",&opt.rules.to_string());
+    if let Some(parent) = report_file.parent() { std::fs::create_dir_all(parent)? }
+    serde_json::to_writer(File::create(&report_file)?,&report)?;
+    Ok(())
+}
@@ -6,11 +6,12 @@
 
 use std::fs::File;
 use std::path::PathBuf;
-use anyhow::anyhow;
 use clap::{Parser};
 use main_app::{ChangeOptions, ModifyStvFileOptions};
 use main_app::rules::Rules;
+use stv::preference_distribution::CountOptions;
 use stv::random_util::Randomness;
+use stv::count_observer::IgnoreCountProgress;
 
 #[derive(Parser)]
 #[clap(version = "0.2", author = "Andrew Conway and Vanessa Teague", name="ConcreteSTV")]
@@ -19,6 +20,8 @@ use stv::random_util::Randomness;
 /// It will not necessarily find the smallest possible change, but can be
 /// used as an upper bound on the margin for the election.
 ///
+/// For single winner IRV elections, `--exact-irv` will instead compute the exact margin.
+///
 /// This program basically takes a .stv file as input, and produces a .vchange file
 /// as output. It is possible to use a .vchange file as input instead of a .stv file;
 /// this allows searching for manipulations on top of manipulations.
@@ -62,7 +65,8 @@ fn main() -> anyhow::Result<()> {
     let result_file = opt.input_options.result_file_name(&opt.votes,opt.out.as_ref(),".vchange",&opt.rules);
 
     // make sure the default elected people are correct.
-    let normal_elected_transcript = opt.rules.count(&votes,votes.metadata.vacancies.ok_or_else(||anyhow!("Need to specify number of vacancies"))?,&votes.metadata.excluded.iter().cloned().collect(),&votes.metadata.tie_resolutions,None,false,&mut Randomness::ReverseDonkeyVote,&[],false);
+    let options = CountOptions{ quota_method: opt.change_options.quota, ..CountOptions::from_metadata(&votes)? };
+    let normal_elected_transcript = opt.rules.count(&votes,&options,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote)?;
     votes.metadata.results=Some(normal_elected_transcript.elected().clone());
 
 
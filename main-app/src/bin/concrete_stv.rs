@@ -11,7 +11,11 @@ use std::fs::File;
 use main_app::ModifyStvFileOptions;
 use main_app::rules::Rules;
 use stv::extract_votes_in_pile::ExtractionRequest;
-use stv::random_util::Randomness;
+use stv::preference_distribution::CountOptions;
+use stv::quota::QuotaMethod;
+use stv::random_util::{read_draws_from_transcript, Randomness, ReplayDraws};
+use stv::tie_enumeration::enumerate_tie_outcomes;
+use stv::verifiable_draw::{PublicSeed, VerifiableDraws};
 
 #[derive(Parser)]
 #[clap(version = "0.3", author = "Andrew Conway", name="ConcreteSTV")]
@@ -43,15 +47,39 @@ struct Opts {
     #[clap(short, long,value_parser)]
     seed : Option<u64>,
 
+    /// Record every random draw (tie resolutions and NSW random ballot selections) in the transcript,
+    /// so that the count can be audited and reproduced with --replay-draws.
+    #[clap(long)]
+    record_draws : bool,
+
+    /// Instead of making random draws, use the draws recorded (with --record-draws) in the given transcript file.
+    /// This reproduces a randomised count exactly, and fails if the draws needed differ from those recorded.
+    #[clap(long,value_parser)]
+    replay_draws : Option<PathBuf>,
+
+    /// Draw lots for ties in a publicly verifiable way, using a seed derived from the given file, such as
+    /// a record of dice rolls made in public. The file's hash, the seed and the draws are stored in the
+    /// transcript, and can be checked with verify_draws.
+    #[clap(long,value_parser)]
+    draw_seed_file : Option<PathBuf>,
+
     /// It is possible to extract the particular votes at some point in the transcript. The
     /// general format for this is --extract what_to_extract;what_to_do_with_it, where
     ///
-    /// what_to_extract can currently only be UsedToElectACT:candidate_number where candidate_number
-    /// is an integer 0 to the number of candidates-1 and will extract the votes used to elect the
-    /// candidate according to the ACT casual vacancies legislation.
+    /// what_to_extract can be
+    ///  * UsedToElectACT:candidate_number to extract the votes used to elect the
+    ///    candidate according to the ACT casual vacancies legislation.
+    ///  * Pile:candidate_number@count_number to extract the votes in the candidate's pile at the end of the count.
+    ///  * Exhausted@count_number to extract the votes exhausted by the end of the count.
+    ///  * SetAside@count_number to extract the votes some of whose value was set aside by the end of the count
+    ///    due to a transfer value being limited to the incoming transfer value.
+    ///
+    /// where candidate_number is an integer 0 to the number of candidates-1, and count_number is an integer
+    /// 0 to the number of counts-1 (so the first preference count is 0).
     ///
-    /// what_to_do_with_it can currently only be file:file_name where file_name is the name of a .stv
-    /// file that you want to store the extracted votes in.
+    /// what_to_do_with_it can be file:file_name where file_name is the name of a .stv
+    /// file that you want to store the extracted votes (with their transfer values) in, or csv:file_name
+    /// to store them in a CSV file.
     #[clap(long)]
     extract : Vec<ExtractionRequest>,
 
@@ -60,15 +88,56 @@ struct Opts {
     /// not do this, flag makes it be done.
     #[clap(long)]
     include_list_of_votes_in_transcript:bool,
+
+    /// Compute the quota using a different formula to that specified by the rules, for comparative purposes.
+    /// Options are Droop, ExactDroop, HagenbachBischoff, Hare and Imperiali. Not supported for NZMeek.
+    #[clap(long)]
+    quota : Option<QuotaMethod>,
+
+    /// Count using exact fractions instead of the rounding specified by the rules, to see what difference rounding makes.
+    /// The default transcript file name has _Exact appended to the rules name. Not supported for NZMeek.
+    #[clap(long)]
+    exact : bool,
+
+    /// Instead of doing one count, try every possible result of each draw needed to resolve a tie, and
+    /// report each set of candidates who could be elected with its probability if the draws were uniform.
+    /// The results are stored in a .ties file instead of a .transcript file.
+    #[clap(long)]
+    enumerate_ties : bool,
+
+    /// The maximum number of counts to do when enumerating ties. If more would be needed, stop with an error.
+    #[clap(long,default_value_t=10000)]
+    max_branches : usize,
 }
 
 fn main() -> anyhow::Result<()> {
     let opt : Opts = Opts::parse();
 
     let votes = opt.input_options.get_data(&opt.votes,opt.verbose)?;
-    let transcript_file = opt.input_options.result_file_name(&opt.votes,opt.transcript.as_ref(),".transcript",&opt.rules);
-    let mut randomness : Randomness = opt.seed.into();
-    let transcript = opt.rules.count_simple(&votes,opt.verbose,&mut randomness,&opt.extract,opt.include_list_of_votes_in_transcript)?;
+    let rules_name = if opt.exact { format!("{}_Exact",opt.rules) } else { opt.rules.to_string() };
+    let options = CountOptions{ quota_method: opt.quota, ..CountOptions::from_metadata(&votes)? };
+    if opt.enumerate_ties {
+        let ties_file = opt.input_options.result_file_name(&opt.votes,opt.transcript.as_ref(),".ties",&rules_name);
+        let outcomes = enumerate_tie_outcomes(opt.max_branches,|randomness|opt.rules.count_simple(&votes,&options,opt.verbose,opt.exact,randomness).map(|transcript|transcript.elected().clone()))?;
+        println!("{} possible outcomes from {} branches with up to {} draws.",outcomes.outcomes.len(),outcomes.branches,outcomes.max_draws);
+        for outcome in &outcomes.outcomes {
+            println!("{:>9.6} {}",outcome.probability,outcome.elected.iter().map(|&c|votes.metadata.candidate(c).name.as_str()).collect::<Vec<_>>().join(", "));
+        }
+        if let Some(parent) = ties_file.parent() { std::fs::create_dir_all(parent)? }
+        serde_json::to_writer(File::create(&ties_file)?,&outcomes)?;
+        return Ok(());
+    }
+    let transcript_file = opt.input_options.result_file_name(&opt.votes,opt.transcript.as_ref(),".transcript",&rules_name);
+    let mut randomness : Randomness = match &opt.replay_draws {
+        Some(file) => Randomness::Replay(ReplayDraws::new(read_draws_from_transcript(File::open(file)?)?)),
+        None => match &opt.draw_seed_file {
+            Some(file) => Randomness::Verifiable(VerifiableDraws::new(PublicSeed::from_file(file)?)),
+            None => opt.seed.into(),
+        }
+    };
+    if opt.record_draws && opt.replay_draws.is_none() { randomness=Randomness::Record(Box::new(randomness),vec![]); }
+    let options = CountOptions{ extractors: &opt.extract, include_list_of_votes_in_transcript: opt.include_list_of_votes_in_transcript, ..options };
+    let transcript = opt.rules.count_simple(&votes,&options,opt.verbose,opt.exact,&mut randomness)?;
 
     if let Some(parent) = transcript_file.parent() { std::fs::create_dir_all(parent)? }
     serde_json::to_writer(File::create(&transcript_file)?,&transcript)?;
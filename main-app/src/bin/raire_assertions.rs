@@ -0,0 +1,52 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+
+use clap::{Parser};
+use std::path::PathBuf;
+use std::fs::File;
+use main_app::ModifyStvFileOptions;
+use main_app::rules::Rules;
+use margin::raire::generate_raire_assertions;
+use nsw::SimpleIRVAnyDifferenceBreaksTies;
+
+#[derive(Parser)]
+#[clap(version = "0.3", author = "Andrew Conway", name="ConcreteSTV")]
+/// Generate RAIRE assertions for a risk limiting audit of a single winner election counted with the IRV rules.
+/// If all the assertions are true, the reported winner won.
+/// The assertions, with their margins and diluted margins, are saved as JSON in a .raire file.
+struct Opts {
+    /// The name of the .stv (or .vchange) file to get votes from
+    #[clap(value_parser)]
+    votes : PathBuf,
+
+    #[clap(flatten)]
+    input_options : ModifyStvFileOptions,
+
+    /// Whether the assertions should be printed out to stdout.
+    #[clap(long)]
+    verbose: bool,
+
+    /// An optional output file. If not specified, the name is derived from the input file.
+    #[clap(short, long,value_parser)]
+    out : Option<PathBuf>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let opt : Opts = Opts::parse();
+
+    let votes = opt.input_options.get_data(&opt.votes,opt.verbose)?;
+    let assertions = generate_raire_assertions::<SimpleIRVAnyDifferenceBreaksTies>(&votes)?;
+    println!("{} assertions show that {} won, the hardest having diluted margin {:.4}",assertions.assertions.len(),votes.metadata.candidate(assertions.winner).name,1.0/assertions.difficulty);
+    if opt.verbose {
+        for a in &assertions.assertions { println!("{:?} margin {} diluted margin {:.4}",a.assertion,a.margin,a.diluted_margin); }
+    }
+
+    let report_file = opt.input_options.result_file_name(&opt.votes,opt.out.as_ref(),".raire",&Rules::IRV);
+    if let Some(parent) = report_file.parent() { std::fs::create_dir_all(parent)? }
+    serde_json::to_writer(File::create(&report_file)?,&assertions)?;
+    Ok(())
+}
@@ -0,0 +1,63 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+
+use clap::{Parser};
+use anyhow::anyhow;
+use main_app::rules::{Rules, RulesDetails};
+use stv::rule_card::RuleCard;
+
+#[derive(Parser)]
+#[clap(version = "0.3", author = "Andrew Conway", name="ConcreteSTV")]
+/// Describe all the options used by a set of counting rules, or the differences between two sets of rules.
+struct Opts {
+    /// The counting rules to describe. The names are the same as for concrete_stv, and may be a .toml or .json rule description file.
+    /// If two are given, just the differences between them are shown.
+    #[clap(value_parser)]
+    rules : Vec<Rules>,
+
+    /// Describe all supported rules (other than NZMeek, which uses a different counting engine).
+    #[clap(long)]
+    all: bool,
+
+    /// Output JSON rather than human readable text.
+    #[clap(long)]
+    json: bool,
+
+    /// Output a markdown table with a column for each set of rules, suitable for ElectionRules.md.
+    #[clap(long)]
+    markdown: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let opt : Opts = Opts::parse();
+    let mut rules = opt.rules.clone();
+    if opt.all {
+        for details in RulesDetails::list() {
+            if let Ok(r) = details.name.parse::<Rules>() {
+                if !matches!(r,Rules::NZMeek) { rules.push(r); }
+            }
+        }
+    }
+    let cards : Vec<RuleCard> = rules.iter().map(|r|r.rule_card()).collect::<anyhow::Result<_>>()?;
+    if opt.markdown {
+        print!("{}",RuleCard::markdown_table(&cards));
+    } else if cards.len()==2 && !opt.all {
+        let differences = cards[0].diff(&cards[1]);
+        if opt.json { println!("{}",serde_json::to_string_pretty(&differences)?) }
+        else {
+            println!("Differences between {} and {}",cards[0].name,cards[1].name);
+            for d in differences { println!("{}",d); }
+        }
+    } else if cards.is_empty() {
+        return Err(anyhow!("No rules specified"));
+    } else if opt.json {
+        println!("{}",serde_json::to_string_pretty(&cards)?)
+    } else {
+        for card in cards { println!("{}",card); }
+    }
+    Ok(())
+}
@@ -0,0 +1,75 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+
+use clap::{Parser};
+use std::path::PathBuf;
+use std::fs::File;
+use anyhow::anyhow;
+use federal::double_dissolution::{long_terms_by_order_elected, long_terms_by_recount};
+use main_app::ModifyStvFileOptions;
+use main_app::rules::{PossibleTranscripts, Rules};
+use stv::ballot_metadata::{CandidateIndex, ElectionMetadata, NumberOfCandidates};
+use stv::election_data::ElectionData;
+use stv::preference_distribution::CountOptions;
+use stv::random_util::Randomness;
+
+#[derive(Parser)]
+#[clap(version = "0.3", author = "Andrew Conway", name="ConcreteSTV")]
+/// Count a double dissolution Senate election, and the section 282 recount as if only half the
+/// vacancies existed, and show who would get long terms by the order elected and recount methods.
+struct Opts {
+    /// The counting rules to use. See concrete_stv for options.
+    rules : Rules,
+
+    /// The name of the .stv (or .vchange) file to get votes from. It should have secondary vacancies specified, as
+    /// the federal data loaders do for double dissolutions.
+    #[clap(value_parser)]
+    votes : PathBuf,
+
+    #[clap(flatten)]
+    input_options : ModifyStvFileOptions,
+
+    /// Whether the status of the counts should be printed out to stdout.
+    #[clap(long)]
+    verbose: bool,
+
+    /// How random ties are done. If specified, the seed for a pseudo random number generator.
+    /// If not specified, then reverse donkey vote is used.
+    #[clap(short, long,value_parser)]
+    seed : Option<u64>,
+}
+
+fn count(opt:&Opts,votes:&ElectionData,vacancies:NumberOfCandidates,randomness:&mut Randomness) -> anyhow::Result<PossibleTranscripts> {
+    let options = CountOptions::new(vacancies,votes.metadata.excluded.iter().cloned().collect(),&votes.metadata.tie_resolutions);
+    opt.rules.count_simple(votes,&options,opt.verbose,false,randomness)
+}
+
+fn names(metadata:&ElectionMetadata,candidates:&[CandidateIndex]) -> String {
+    candidates.iter().map(|&c|metadata.candidate(c).name.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+fn main() -> anyhow::Result<()> {
+    let opt : Opts = Opts::parse();
+
+    let votes = opt.input_options.get_data(&opt.votes,opt.verbose)?;
+    let vacancies = votes.metadata.vacancies.ok_or_else(||anyhow!("Need to specify number of vacancies"))?;
+    let long_terms = votes.metadata.secondary_vacancies.ok_or_else(||anyhow!("Need to specify number of secondary vacancies (long terms)"))?;
+    let mut randomness : Randomness = opt.seed.into();
+    let full_count = count(&opt,&votes,vacancies,&mut randomness)?;
+    let recount = count(&opt,&votes,long_terms,&mut randomness)?;
+    println!("Elected : {}",names(&votes.metadata,full_count.elected()));
+    println!("Elected in recount : {}",names(&votes.metadata,recount.elected()));
+    println!("Long terms by order elected : {}",names(&votes.metadata,&long_terms_by_order_elected(full_count.elected(),long_terms)));
+    println!("Long terms by section 282 recount : {}",names(&votes.metadata,&long_terms_by_recount(full_count.elected(),recount.elected(),long_terms)));
+
+    for (transcript,rules_name) in [(&full_count,opt.rules.to_string()),(&recount,format!("{}_Section282Recount",opt.rules))] {
+        let transcript_file = opt.input_options.result_file_name(&opt.votes,None,".transcript",&rules_name);
+        if let Some(parent) = transcript_file.parent() { std::fs::create_dir_all(parent)? }
+        serde_json::to_writer(File::create(&transcript_file)?,transcript)?;
+    }
+    Ok(())
+}
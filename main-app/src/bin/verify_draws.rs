@@ -0,0 +1,62 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+
+use clap::{Parser};
+use std::path::PathBuf;
+use std::fs::File;
+use main_app::ModifyStvFileOptions;
+use main_app::rules::Rules;
+use stv::preference_distribution::CountOptions;
+use stv::quota::QuotaMethod;
+use stv::random_util::DrawsInTranscript;
+use stv::verifiable_draw::verify_draws;
+
+#[derive(Parser)]
+#[clap(version = "0.3", author = "Andrew Conway", name="ConcreteSTV")]
+/// Check that the draws by lot in a transcript produced by concrete_stv --draw-seed-file
+/// really came from the committed seed file, and were the draws the count needed.
+/// The count is redone, so the rules, votes and options must be the same as given to concrete_stv.
+struct Opts {
+    /// The counting rules used.
+    rules : Rules,
+
+    /// The name of the .stv (or .vchange) file the votes came from.
+    #[clap(value_parser)]
+    votes : PathBuf,
+
+    /// The .transcript file to check.
+    #[clap(value_parser)]
+    transcript : PathBuf,
+
+    /// The seed file that was committed to before the count.
+    #[clap(value_parser)]
+    seed_file : PathBuf,
+
+    #[clap(flatten)]
+    input_options : ModifyStvFileOptions,
+
+    /// The quota method, if one was given to concrete_stv.
+    #[clap(long)]
+    quota : Option<QuotaMethod>,
+
+    /// Whether the count used exact fractions.
+    #[clap(long)]
+    exact : bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let opt : Opts = Opts::parse();
+    let votes = opt.input_options.get_data(&opt.votes,false)?;
+    let draws = DrawsInTranscript::read(File::open(&opt.transcript)?)?;
+    let options = CountOptions{ quota_method: opt.quota, ..CountOptions::from_metadata(&votes)? };
+    let num_draws = verify_draws(draws.draw_seed.as_ref(),&draws.draws,&std::fs::read(&opt.seed_file)?,|randomness|opt.rules.count_simple(&votes,&options,false,opt.exact,randomness).map(|transcript|transcript.draws().clone()))?;
+    let seed = draws.draw_seed.as_ref().unwrap();
+    println!("Commitment {} to {} matches seed file",seed.commitment,seed.file_name);
+    println!("Seed {}",seed.seed);
+    println!("Verified {} draws",num_draws);
+    Ok(())
+}
@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use stv::ballot_metadata::{CandidateIndex, ElectionMetadata};
 use stv::compare_transcripts::DifferenceBetweenTranscripts;
 use stv::election_data::ElectionData;
+use stv::preference_distribution::CountOptions;
 use stv::random_util::Randomness;
 use crate::rules::{PossibleTranscripts, Rules};
 
@@ -92,8 +93,9 @@ impl fmt::Display for RulesComparisonGroups {
 impl RulesComparisonGroups {
     pub fn create(data:&ElectionData,rules:&[Rules]) -> anyhow::Result<Self> {
         let mut res = RulesComparisonGroups { metadata: data.metadata.clone(), groups: vec![] };
+        let options = CountOptions::from_metadata(data)?;
         for rule in rules {
-            let transcript = rule.count_simple(data,false,&mut Randomness::ReverseDonkeyVote,&[],false)?;
+            let transcript = rule.count_simple(data,&options,false,false,&mut Randomness::ReverseDonkeyVote)?;
             let winners = transcript.elected();
             let mut ordered_winners = winners.clone();
             ordered_winners.sort_by_key(|c|c.0);
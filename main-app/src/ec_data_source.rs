@@ -13,6 +13,8 @@ use stv::parse_util::{RawDataSource, FileFinder};
 use act::parse::{get_act_data_loader_2008, get_act_data_loader_2012, get_act_data_loader_2016, get_act_data_loader_2020};
 use nsw::parse_lge::get_nsw_lge_data_loader_2021;
 use vic::parse_vic::{get_vic_data_loader_2014, get_vic_data_loader_2018, get_vic_data_loader_2022};
+use tas::parse::{get_tas_data_loader_2018, get_tas_data_loader_2021, get_tas_data_loader_2024};
+use scotland::parse::{get_scotland_data_loader_2012, get_scotland_data_loader_2017, get_scotland_data_loader_2022};
 
 #[derive(Copy, Clone)]
 pub enum ECDataSource {
@@ -28,6 +30,12 @@ pub enum ECDataSource {
     VIC2014,
     VIC2018,
     VIC2022,
+    TAS2018,
+    TAS2021,
+    TAS2024,
+    SCOT2012,
+    SCOT2017,
+    SCOT2022,
 }
 
 impl FromStr for ECDataSource {
@@ -47,7 +55,13 @@ impl FromStr for ECDataSource {
             "VIC2014" => Ok(ECDataSource::VIC2014),
             "VIC2018" => Ok(ECDataSource::VIC2018),
             "VIC2022" => Ok(ECDataSource::VIC2022),
-            _ => Err("No such source supported. Allowed sources are AEC2013, AEC2016, AEC2019, AEC2022, ACT2008, ACT2012, ACT2016, ACT2020, NSWLG2021, VIC2014, VIC2018, VIC2022")
+            "TAS2018" => Ok(ECDataSource::TAS2018),
+            "TAS2021" => Ok(ECDataSource::TAS2021),
+            "TAS2024" => Ok(ECDataSource::TAS2024),
+            "SCOT2012" => Ok(ECDataSource::SCOT2012),
+            "SCOT2017" => Ok(ECDataSource::SCOT2017),
+            "SCOT2022" => Ok(ECDataSource::SCOT2022),
+            _ => Err("No such source supported. Allowed sources are AEC2013, AEC2016, AEC2019, AEC2022, ACT2008, ACT2012, ACT2016, ACT2020, NSWLG2021, VIC2014, VIC2018, VIC2022, TAS2018, TAS2021, TAS2024, SCOT2012, SCOT2017, SCOT2022")
         }
     }
 }
@@ -67,6 +81,12 @@ impl Display for ECDataSource {
             ECDataSource::VIC2014 => "VIC2014",
             ECDataSource::VIC2018 => "VIC2018",
             ECDataSource::VIC2022 => "VIC2022",
+            ECDataSource::TAS2018 => "TAS2018",
+            ECDataSource::TAS2021 => "TAS2021",
+            ECDataSource::TAS2024 => "TAS2024",
+            ECDataSource::SCOT2012 => "SCOT2012",
+            ECDataSource::SCOT2017 => "SCOT2017",
+            ECDataSource::SCOT2022 => "SCOT2022",
         };
         f.write_str(s)
     }
@@ -88,6 +108,12 @@ impl ECDataSource {
             ECDataSource::VIC2014 => get_vic_data_loader_2014(finder)?.read_raw_data_checking_electorate_valid(electorate),
             ECDataSource::VIC2018 => get_vic_data_loader_2018(finder)?.read_raw_data_checking_electorate_valid(electorate),
             ECDataSource::VIC2022 => get_vic_data_loader_2022(finder)?.read_raw_data_checking_electorate_valid(electorate),
+            ECDataSource::TAS2018 => get_tas_data_loader_2018(finder)?.read_raw_data_checking_electorate_valid(electorate),
+            ECDataSource::TAS2021 => get_tas_data_loader_2021(finder)?.read_raw_data_checking_electorate_valid(electorate),
+            ECDataSource::TAS2024 => get_tas_data_loader_2024(finder)?.read_raw_data_checking_electorate_valid(electorate),
+            ECDataSource::SCOT2012 => get_scotland_data_loader_2012(finder)?.read_raw_data_checking_electorate_valid(electorate),
+            ECDataSource::SCOT2017 => get_scotland_data_loader_2017(finder)?.read_raw_data_checking_electorate_valid(electorate),
+            ECDataSource::SCOT2022 => get_scotland_data_loader_2022(finder)?.read_raw_data_checking_electorate_valid(electorate),
         }
     }
 }
@@ -12,10 +12,13 @@ use std::collections::HashSet;
 use std::fs::File;
 use std::num::ParseIntError;
 use std::path::PathBuf;
+use std::fmt::Display;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 use anyhow::anyhow;
 use margin::choose_votes::ChooseVotesOptions;
-use margin::find_outcome_changes::find_outcome_changes;
+use margin::find_outcome_changes::{find_composed_outcome_changes, find_outcome_changes_with_budget, SearchBudget, SearchProgress};
+use margin::irv_margin::find_exact_irv_margin;
 use margin::record_changes::ElectionChanges;
 use margin::vote_changes::{VoteChange, VoteChanges};
 use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
@@ -23,8 +26,8 @@ use stv::ballot_pile::BallotPaperCount;
 use stv::distribution_of_preferences_transcript::CountIndex;
 use stv::election_data::ElectionData;
 use stv::preference_distribution::PreferenceDistributionRules;
+use stv::quota::QuotaMethod;
 use stv::tie_resolution::{TieResolutionAtom, TieResolutionExplicitDecision, TieResolutionExplicitDecisionInCount, TieResolutionsMadeByEC, TieResolutionUsage};
-use crate::rules::Rules;
 
 pub mod rules;
 pub mod ec_data_source;
@@ -101,19 +104,60 @@ pub struct ChangeOptions {
     /// A "-" symbol may be used instead of "→".
     #[clap(long, value_delimiter=',')]
     just_try : Option<Vec<VoteChange<u64>>>,
+
+    /// Stop searching after approximately this many seconds, keeping the best changes found so far.
+    /// If several combinations of the allow options are searched, this is the total for all of them.
+    #[clap(long)]
+    time_limit : Option<f64>,
+
+    /// Stop searching after this many possible manipulations have been evaluated, keeping the best changes found so far.
+    /// If several combinations of the allow options are searched, this applies to each of them.
+    #[clap(long)]
+    max_evaluations : Option<usize>,
+
+    /// Periodically print out how far the search has got.
+    #[clap(long)]
+    progress : bool,
+
+    /// The number of different counts at which decisions may be changed by a single manipulation. Default 1.
+    /// Larger values may find smaller manipulations that combine changes at several exclusions, but are much slower.
+    #[clap(long,default_value_t=1)]
+    decision_points : usize,
+
+    /// Instead of searching heuristically, compute the exact margin of a single winner election using the branch and
+    /// bound algorithm of Magrino et al. Only valid with the IRV rules. Slow for more than about ten candidates.
+    #[clap(long)]
+    exact_irv : bool,
+
+    /// Compute the quota using a different formula to that specified by the rules when recounting.
+    /// Options are Droop, ExactDroop, HagenbachBischoff, Hare and Imperiali. Not supported with --exact-irv.
+    #[clap(long)]
+    pub quota : Option<QuotaMethod>,
 }
 
 
 impl ChangeOptions {
-    fn find_changes<Rules:PreferenceDistributionRules>(&self,data:&ElectionData,verbose:bool) -> anyhow::Result<ElectionChanges<Rules::Tally>> {
+    fn find_changes<Rules:PreferenceDistributionRules+Sync>(&self,rules:&Rules,data:&ElectionData,verbose:bool) -> anyhow::Result<ElectionChanges<Rules::Tally>> {
         let just_try = self.just_try.as_ref().map(|v|VoteChanges{changes:v.iter().map(|c|VoteChange{vote_value:Rules::Tally::from(BallotPaperCount(c.vote_value as usize)),from:c.from,to:c.to}).collect()});
         let ballot_types_considered_unverifiable = self.unverifiable.iter().cloned().collect::<HashSet<_>>();
+        if self.exact_irv {
+            if self.quota.is_some() { return Err(anyhow!("Exact IRV margins do not support a different quota.")); }
+            let margin = find_exact_irv_margin(rules,data,&ballot_types_considered_unverifiable,verbose)?;
+            println!("Exact margin is {} ballots, with elimination order {}",margin.margin,data.metadata.candidate_list_to_string(&margin.elimination_order));
+            return Ok(margin.changes);
+        }
         let mut res : Option<ElectionChanges<Rules::Tally>> = None;
+        let start = Instant::now();
+        let mut report_progress = |progress:&SearchProgress| {
+            if self.progress { println!("Searched {} of {} counts, evaluated {} manipulations in {:.1}s, smallest found {}",progress.counts_searched,progress.total_counts,progress.evaluations,progress.elapsed.as_secs_f64(),progress.smallest_manipulation_found.map(|n|n.to_string()).unwrap_or_else(||"none".to_string())); }
+            true
+        };
         for &allow_atl in  &self.allow_atl {
             for &allow_first_pref in &self.allow_first {
                 for &allow_verifiable in &self.allow_verifiable {
                     let options = ChooseVotesOptions{allow_atl,allow_first_pref,allow_verifiable,ballot_types_considered_unverifiable:ballot_types_considered_unverifiable.clone(), allow_additions: !self.disallow_additions, allow_from: self.allow_from.as_ref().map(|v|v.iter().copied().collect()), allow_to: self.allow_to.as_ref().map(|v|v.iter().copied().collect()) };
-                    let results = find_outcome_changes::<Rules>(&data,&options,verbose,just_try.as_ref());
+                    let budget = SearchBudget{ max_time: self.time_limit.map(|limit|Duration::from_secs_f64(limit).saturating_sub(start.elapsed())), max_evaluations: self.max_evaluations };
+                    let results = if self.decision_points>1 && just_try.is_none() { find_composed_outcome_changes(rules,data,&options,self.decision_points,verbose,self.quota,&budget)? } else { find_outcome_changes_with_budget(rules,data,&options,verbose,just_try.as_ref(),self.quota,&budget,&mut report_progress)? };
                     if res.is_none() { res=Some(results)} else { res.as_mut().unwrap().merge(results,false) }
                 }
             }
@@ -198,7 +242,7 @@ impl ModifyStvFileOptions {
         Ok(votes)
     }
 
-    pub fn result_file_name(&self,input_path:&PathBuf,explicit_out_path:Option<&PathBuf>,extension:&str,rules:&Rules) -> PathBuf {
+    pub fn result_file_name(&self,input_path:&PathBuf,explicit_out_path:Option<&PathBuf>,extension:&str,rules:&dyn Display) -> PathBuf {
         match explicit_out_path {
             None => {
                 let votename = input_path.file_name().map(|o|o.to_string_lossy()).unwrap_or_default();
@@ -7,13 +7,12 @@
 
 use std::str::FromStr;
 use stv::election_data::ElectionData;
-use stv::tie_resolution::TieResolutionsMadeByEC;
 use stv::distribution_of_preferences_transcript::TranscriptWithMetadata;
-use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
-use std::collections::HashSet;
+use stv::ballot_metadata::CandidateIndex;
 use federal::{FederalRulesUsed2013, FederalRulesUsed2019, FederalRulesUsed2016, FederalRulesPre2021, FederalRulesPost2021, FederalRulesPost2021Manual};
-use stv::preference_distribution::{distribute_preferences_with_extractors};
-use std::fmt::{Display, Formatter};
+use stv::preference_distribution::{distribute_preferences_with_extractors, CountOptions};
+use stv::count_error::CountError;
+use std::fmt::{Debug, Display, Formatter};
 use anyhow::anyhow;
 use act::{ACTPre2020, ACT2020, ACT2021};
 use stv::fixed_precision_decimal::FixedPrecisionDecimal;
@@ -22,14 +21,34 @@ use margin::record_changes::ElectionChanges;
 use nsw::{NSWECLocalGov2021, NSWECLocalGov2021Literal, NSWLocalCouncilLegislation2021MyGuessAtHighlyAmbiguousLegislation, SimpleIRVAnyDifferenceBreaksTies};
 use nsw::nsw_random_rules::{NSWECRandomLC2015, NSWECRandomLC2019, NSWECRandomLGE2012, NSWECRandomLGE2016, NSWECRandomLGE2017};
 use stv::compare_transcripts::{compare_transcripts, DifferenceBetweenTranscripts};
-use stv::extract_votes_in_pile::ExtractionRequest;
-use stv::random_util::Randomness;
+use stv::random_util::{RandomDraw, Randomness};
 use vic::Vic2018LegislativeCouncil;
 use minimal::Minimal;
+use meek::{distribute_preferences_meek_with_observer, MeekTally, NZMeek};
+use tas::TasHareClark;
+use scotland::ScottishSTV2007;
+use ireland::IrishDail;
 use wa::WALegislativeCouncil;
+use stv::count_observer::{CountObserver, IgnoreCountProgress, PrintProgressToStdout};
+use stv::dynamic_rules::{DynamicRules, DynamicSplitByNumber, DynamicTally, DynamicTallyType, RuleDescription};
+use stv::ballot_pile::{DoNotSplitByCountNumber, FullySplitByCountNumber, SplitByWhenTransferValueWasCreated, SplitFirstCount};
+use stv::distribution_of_preferences_transcript::Transcript;
+use stv::rule_card::RuleCard;
+use stv::ballot_paper::FormalityRules;
+use stv::casual_vacancy::{ACTCasualVacancy, CasualVacancyMethod};
+use stv::trace_vote::{trace_vote, VoteToTrace, VoteTrace};
+use stv::exact_arithmetic::{ExactArithmetic, ExactTally};
+use stv::official_dop_transcript::CanConvertToF64PossiblyLossily;
+use std::path::Path;
+use std::sync::Arc;
 use crate::ChangeOptions;
 
-#[derive(Copy, Clone,Serialize,Deserialize)]
+/// A [CountObserver] for all the tally types used by the supported rules, so it can be passed to [Rules::count].
+pub trait CountObserverForAnyRules : CountObserver<usize>+CountObserver<isize>+CountObserver<FixedPrecisionDecimal<6>>+CountObserver<FixedPrecisionDecimal<5>>+CountObserver<MeekTally> {}
+
+impl <T:CountObserver<usize>+CountObserver<isize>+CountObserver<FixedPrecisionDecimal<6>>+CountObserver<FixedPrecisionDecimal<5>>+CountObserver<MeekTally>> CountObserverForAnyRules for T {}
+
+#[derive(Clone,Serialize,Deserialize)]
 pub enum Rules {
     AEC2013,
     AEC2016,
@@ -50,12 +69,18 @@ pub enum Rules {
     NSWECRandomLC2019,
     Vic2018,
     WA2008,
+    TasHareClark,
+    ScottishSTV2007,
+    IrishDail,
     Minimal,
     IRV,
+    NZMeek,
+    /// Rules given by a [RuleDescription], usually loaded from a file, see [Rules::load_custom]. Serialized as the description itself.
+    Custom(Arc<RuleDescription>),
 }
 
 impl FromStr for Rules {
-    type Err = &'static str;
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -74,13 +99,19 @@ impl FromStr for Rules {
             "NSWECLocalGov2021Literal" => Ok(Rules::NSWECLocalGov2021Literal),
             "NSWECRandomLGE2012" => Ok(Rules::NSWECRandomLGE2012),
             "NSWECRandomLGE2016" => Ok(Rules::NSWECRandomLGE2016),
+            "NSWECRandomLGE2017" => Ok(Rules::NSWECRandomLGE2017),
             "NSWECRandomLC2015" => Ok(Rules::NSWECRandomLC2015),
             "NSWECRandomLC2019" => Ok(Rules::NSWECRandomLC2019),
             "Vic2018" => Ok(Rules::Vic2018),
             "WA2008" => Ok(Rules::WA2008),
+            "TasHareClark" => Ok(Rules::TasHareClark),
+            "ScottishSTV2007" => Ok(Rules::ScottishSTV2007),
+            "IrishDail" => Ok(Rules::IrishDail),
             "Minimal" => Ok(Rules::Minimal),
             "IRV" => Ok(Rules::IRV),
-            _ => Err("No such rule supported")
+            "NZMeek" => Ok(Rules::NZMeek),
+            _ if s.ends_with(".toml") || s.ends_with(".json") => Rules::load_custom(Path::new(s)).map_err(|e|e.to_string()),
+            _ => Err("No such rule supported".to_string())
         }
     }
 }
@@ -107,8 +138,13 @@ impl Display for Rules {
             Rules::NSWECRandomLC2019 => "NSWECRandomLC2019",
             Rules::Vic2018 => "Vic2018",
             Rules::WA2008 => "WA2008",
+            Rules::TasHareClark => "TasHareClark",
+            Rules::ScottishSTV2007 => "ScottishSTV2007",
+            Rules::IrishDail => "IrishDail",
             Rules::Minimal => "Minimal",
             Rules::IRV => "IRV",
+            Rules::NZMeek => "NZMeek",
+            Rules::Custom(description) => return f.write_str(&description.name),
         };
         f.write_str(s)
     }
@@ -116,71 +152,303 @@ impl Display for Rules {
 
 impl Rules {
 
-    pub fn count_simple(&self, data:&ElectionData, verbose:bool,randomness:&mut Randomness,extractors:&[ExtractionRequest],include_list_of_votes_in_transcript:bool) -> anyhow::Result<PossibleTranscripts> {
-        Ok(self.count(data,data.metadata.vacancies.ok_or_else(||anyhow!("Need to specify number of vacancies"))?,&data.metadata.excluded.iter().cloned().collect(),&data.metadata.tie_resolutions,None,verbose,randomness,extractors,include_list_of_votes_in_transcript))
+    /// Load a [RuleDescription] from a TOML or JSON file, and make a Rules that counts using it.
+    pub fn load_custom(path:&Path) -> anyhow::Result<Rules> {
+        Ok(Rules::Custom(Arc::new(RuleDescription::load(path)?)))
+    }
+
+    /// If these rules are given by a [RuleDescription], get it.
+    pub fn custom_description(&self) -> Option<&Arc<RuleDescription>> {
+        match self {
+            Rules::Custom(description) => Some(description),
+            _ => None,
+        }
+    }
+
+    /// Count, printing progress to stdout if verbose.
+    /// If exact, use exact rational arithmetic instead of the rounding specified by the rules, see [Rules::count_exact].
+    /// The options are usually made by [CountOptions::from_metadata] to use the vacancies, exclusions and tie resolutions in the metadata.
+    pub fn count_simple(&self, data:&ElectionData,options:&CountOptions,verbose:bool,exact:bool,randomness:&mut Randomness) -> anyhow::Result<PossibleTranscripts> {
+        if exact {
+            if verbose {
+                self.count_exact(data,options,&mut PrintProgressToStdout::default(),randomness)
+            } else {
+                self.count_exact(data,options,&mut IgnoreCountProgress{},randomness)
+            }
+        } else {
+            Ok(if verbose {
+                self.count(data,options,&mut PrintProgressToStdout::default(),randomness)?
+            } else {
+                self.count(data,options,&mut IgnoreCountProgress{},randomness)?
+            })
+        }
     }
 
-    pub fn count(&self,data: &ElectionData,candidates_to_be_elected : NumberOfCandidates,excluded_candidates:&HashSet<CandidateIndex>,ec_resolutions:& TieResolutionsMadeByEC,vote_types : Option<&[String]>,print_progress_to_stdout:bool,randomness:&mut Randomness,extractors:&[ExtractionRequest],include_list_of_votes_in_transcript:bool) -> PossibleTranscripts {
+    pub fn count(&self,data: &ElectionData,options:&CountOptions,observer:&mut impl CountObserverForAnyRules,randomness:&mut Randomness) -> Result<PossibleTranscripts,CountError> {
         let transcript = match self {
-            Rules::AEC2013 => distribute_preferences_with_extractors::<FederalRulesUsed2013>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::AEC2016 => distribute_preferences_with_extractors::<FederalRulesUsed2016>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::AEC2019 => distribute_preferences_with_extractors::<FederalRulesUsed2019>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::FederalPre2021 => distribute_preferences_with_extractors::<FederalRulesPre2021>(data, candidates_to_be_elected, excluded_candidates, ec_resolutions, vote_types, print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::FederalPost2021 => distribute_preferences_with_extractors::<FederalRulesPost2021>(data, candidates_to_be_elected, excluded_candidates, ec_resolutions, vote_types, print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::FederalPost2021Manual => distribute_preferences_with_extractors::<FederalRulesPost2021Manual>(data, candidates_to_be_elected, excluded_candidates, ec_resolutions, vote_types, print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::ACTPre2020 => distribute_preferences_with_extractors::<ACTPre2020>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::NSWLocalGov2021 => distribute_preferences_with_extractors::<NSWLocalCouncilLegislation2021MyGuessAtHighlyAmbiguousLegislation>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::NSWECLocalGov2021 => distribute_preferences_with_extractors::<NSWECLocalGov2021>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
+            Rules::AEC2013 => distribute_preferences_with_extractors(&FederalRulesUsed2013::default(),data,options,observer,randomness)?,
+            Rules::AEC2016 => distribute_preferences_with_extractors(&FederalRulesUsed2016::default(),data,options,observer,randomness)?,
+            Rules::AEC2019 => distribute_preferences_with_extractors(&FederalRulesUsed2019::default(),data,options,observer,randomness)?,
+            Rules::FederalPre2021 => distribute_preferences_with_extractors(&FederalRulesPre2021::default(),data,options,observer,randomness)?,
+            Rules::FederalPost2021 => distribute_preferences_with_extractors(&FederalRulesPost2021::default(),data,options,observer,randomness)?,
+            Rules::FederalPost2021Manual => distribute_preferences_with_extractors(&FederalRulesPost2021Manual::default(),data,options,observer,randomness)?,
+            Rules::ACTPre2020 => distribute_preferences_with_extractors(&ACTPre2020::default(),data,options,observer,randomness)?,
+            Rules::NSWLocalGov2021 => distribute_preferences_with_extractors(&NSWLocalCouncilLegislation2021MyGuessAtHighlyAmbiguousLegislation::default(),data,options,observer,randomness)?,
+            Rules::NSWECLocalGov2021 => distribute_preferences_with_extractors(&NSWECLocalGov2021::default(),data,options,observer,randomness)?,
             Rules::NSWECLocalGov2021Literal => {
-                let transcript = distribute_preferences_with_extractors::<NSWECLocalGov2021Literal>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript);
-                return PossibleTranscripts::SignedIntegers(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript })
+                let transcript = distribute_preferences_with_extractors(&NSWECLocalGov2021Literal::default(),data,options,observer,randomness)?;
+                return Ok(PossibleTranscripts::SignedIntegers(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript }))
+            },
+            Rules::NSWECRandomLGE2012 => distribute_preferences_with_extractors(&NSWECRandomLGE2012::default(),data,options,observer,randomness)?,
+            Rules::NSWECRandomLGE2016 => distribute_preferences_with_extractors(&NSWECRandomLGE2016::default(),data,options,observer,randomness)?,
+            Rules::NSWECRandomLGE2017 => distribute_preferences_with_extractors(&NSWECRandomLGE2017::default(),data,options,observer,randomness)?,
+            Rules::NSWECRandomLC2015 => distribute_preferences_with_extractors(&NSWECRandomLC2015::default(),data,options,observer,randomness)?,
+            Rules::NSWECRandomLC2019 => distribute_preferences_with_extractors(&NSWECRandomLC2019::default(),data,options,observer,randomness)?,
+            Rules::Vic2018 => distribute_preferences_with_extractors(&Vic2018LegislativeCouncil::default(),data,options,observer,randomness)?,
+            Rules::WA2008 => distribute_preferences_with_extractors(&WALegislativeCouncil::default(),data,options,observer,randomness)?,
+            Rules::TasHareClark => distribute_preferences_with_extractors(&TasHareClark::default(),data,options,observer,randomness)?,
+            Rules::IrishDail => distribute_preferences_with_extractors(&IrishDail::default(),data,options,observer,randomness)?,
+            Rules::IRV => distribute_preferences_with_extractors(&SimpleIRVAnyDifferenceBreaksTies::default(),data,options,observer,randomness)?,
+            Rules::Custom(description) => {
+                return match description.tally {
+                    DynamicTallyType::Integers => distribute_preferences_dynamic::<usize>(description,data,options,observer,randomness).map(|transcript|PossibleTranscripts::Integers(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript })),
+                    DynamicTallyType::FiveDigitDecimals => distribute_preferences_dynamic::<FixedPrecisionDecimal<5>>(description,data,options,observer,randomness).map(|transcript|PossibleTranscripts::FiveDigitDecimals(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript })),
+                    DynamicTallyType::SixDigitDecimals => distribute_preferences_dynamic::<FixedPrecisionDecimal<6>>(description,data,options,observer,randomness).map(|transcript|PossibleTranscripts::SixDigitDecimals(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript })),
+                }
+            },
+            Rules::NZMeek => { // Meek's method uses a different counting engine, which does not support extraction or a different quota.
+                if let Some(method) = options.quota_method { return Err(CountError::QuotaMethodNotSupported{method}); }
+                if !options.extractors.is_empty() { return Err(CountError::ExtractionNotSupported); }
+                let transcript = distribute_preferences_meek_with_observer::<NZMeek>(data,options,observer,randomness)?;
+                return Ok(PossibleTranscripts::NineDigitDecimals(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript }))
+            },
+            Rules::ScottishSTV2007 => {
+                let transcript = distribute_preferences_with_extractors(&ScottishSTV2007::default(),data,options,observer,randomness)?;
+                return Ok(PossibleTranscripts::FiveDigitDecimals(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript }))
             },
-            Rules::NSWECRandomLGE2012 => distribute_preferences_with_extractors::<NSWECRandomLGE2012>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::NSWECRandomLGE2016 => distribute_preferences_with_extractors::<NSWECRandomLGE2016>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::NSWECRandomLGE2017 => distribute_preferences_with_extractors::<NSWECRandomLGE2017>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::NSWECRandomLC2015 => distribute_preferences_with_extractors::<NSWECRandomLC2015>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::NSWECRandomLC2019 => distribute_preferences_with_extractors::<NSWECRandomLC2019>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::Vic2018 => distribute_preferences_with_extractors::<Vic2018LegislativeCouncil>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::WA2008 => distribute_preferences_with_extractors::<WALegislativeCouncil>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-            Rules::IRV => distribute_preferences_with_extractors::<SimpleIRVAnyDifferenceBreaksTies>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
             _ => { // handle 6 digit transcripts.
                 let transcript = match self {
-                    Rules::ACT2020 => distribute_preferences_with_extractors::<ACT2020>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-                    Rules::ACT2021 => distribute_preferences_with_extractors::<ACT2021>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
-                    Rules::Minimal => distribute_preferences_with_extractors::<Minimal>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,extractors,include_list_of_votes_in_transcript),
+                    Rules::ACT2020 => distribute_preferences_with_extractors(&ACT2020::default(),data,options,observer,randomness)?,
+                    Rules::ACT2021 => distribute_preferences_with_extractors(&ACT2021::default(),data,options,observer,randomness)?,
+                    Rules::Minimal => distribute_preferences_with_extractors(&Minimal::default(),data,options,observer,randomness)?,
                     _ => panic!("Case not handled.")
                 };
-                return PossibleTranscripts::SixDigitDecimals(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript })
+                return Ok(PossibleTranscripts::SixDigitDecimals(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript }))
             }
         };
-        PossibleTranscripts::Integers(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript })
+        Ok(PossibleTranscripts::Integers(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript }))
+    }
+
+    /// Like [Rules::count], but using exact rational arithmetic with no rounding, see [ExactArithmetic].
+    pub fn count_exact(&self,data: &ElectionData,options:&CountOptions,observer:&mut dyn CountObserver<ExactTally>,randomness:&mut Randomness) -> anyhow::Result<PossibleTranscripts> {
+        let transcript = match self {
+            Rules::AEC2013 => distribute_preferences_with_extractors(&ExactArithmetic::<FederalRulesUsed2013>::default(),data,options,observer,randomness)?,
+            Rules::AEC2016 => distribute_preferences_with_extractors(&ExactArithmetic::<FederalRulesUsed2016>::default(),data,options,observer,randomness)?,
+            Rules::AEC2019 => distribute_preferences_with_extractors(&ExactArithmetic::<FederalRulesUsed2019>::default(),data,options,observer,randomness)?,
+            Rules::FederalPre2021 => distribute_preferences_with_extractors(&ExactArithmetic::<FederalRulesPre2021>::default(),data,options,observer,randomness)?,
+            Rules::FederalPost2021 => distribute_preferences_with_extractors(&ExactArithmetic::<FederalRulesPost2021>::default(),data,options,observer,randomness)?,
+            Rules::FederalPost2021Manual => distribute_preferences_with_extractors(&ExactArithmetic::<FederalRulesPost2021Manual>::default(),data,options,observer,randomness)?,
+            Rules::ACTPre2020 => distribute_preferences_with_extractors(&ExactArithmetic::<ACTPre2020>::default(),data,options,observer,randomness)?,
+            Rules::ACT2020 => distribute_preferences_with_extractors(&ExactArithmetic::<ACT2020>::default(),data,options,observer,randomness)?,
+            Rules::ACT2021 => distribute_preferences_with_extractors(&ExactArithmetic::<ACT2021>::default(),data,options,observer,randomness)?,
+            Rules::NSWLocalGov2021 => distribute_preferences_with_extractors(&ExactArithmetic::<NSWLocalCouncilLegislation2021MyGuessAtHighlyAmbiguousLegislation>::default(),data,options,observer,randomness)?,
+            Rules::NSWECLocalGov2021 => distribute_preferences_with_extractors(&ExactArithmetic::<NSWECLocalGov2021>::default(),data,options,observer,randomness)?,
+            Rules::NSWECLocalGov2021Literal => distribute_preferences_with_extractors(&ExactArithmetic::<NSWECLocalGov2021Literal>::default(),data,options,observer,randomness)?,
+            Rules::NSWECRandomLGE2012 => distribute_preferences_with_extractors(&ExactArithmetic::<NSWECRandomLGE2012>::default(),data,options,observer,randomness)?,
+            Rules::NSWECRandomLGE2016 => distribute_preferences_with_extractors(&ExactArithmetic::<NSWECRandomLGE2016>::default(),data,options,observer,randomness)?,
+            Rules::NSWECRandomLGE2017 => distribute_preferences_with_extractors(&ExactArithmetic::<NSWECRandomLGE2017>::default(),data,options,observer,randomness)?,
+            Rules::NSWECRandomLC2015 => distribute_preferences_with_extractors(&ExactArithmetic::<NSWECRandomLC2015>::default(),data,options,observer,randomness)?,
+            Rules::NSWECRandomLC2019 => distribute_preferences_with_extractors(&ExactArithmetic::<NSWECRandomLC2019>::default(),data,options,observer,randomness)?,
+            Rules::Vic2018 => distribute_preferences_with_extractors(&ExactArithmetic::<Vic2018LegislativeCouncil>::default(),data,options,observer,randomness)?,
+            Rules::WA2008 => distribute_preferences_with_extractors(&ExactArithmetic::<WALegislativeCouncil>::default(),data,options,observer,randomness)?,
+            Rules::TasHareClark => distribute_preferences_with_extractors(&ExactArithmetic::<TasHareClark>::default(),data,options,observer,randomness)?,
+            Rules::ScottishSTV2007 => distribute_preferences_with_extractors(&ExactArithmetic::<ScottishSTV2007>::default(),data,options,observer,randomness)?,
+            Rules::IrishDail => distribute_preferences_with_extractors(&ExactArithmetic::<IrishDail>::default(),data,options,observer,randomness)?,
+            Rules::Minimal => distribute_preferences_with_extractors(&ExactArithmetic::<Minimal>::default(),data,options,observer,randomness)?,
+            Rules::IRV => distribute_preferences_with_extractors(&ExactArithmetic::<SimpleIRVAnyDifferenceBreaksTies>::default(),data,options,observer,randomness)?,
+            Rules::NZMeek => return Err(anyhow!("Meek's method uses a different counting engine, which does not support exact arithmetic.")),
+            Rules::Custom(description) => {
+                match description.tally {
+                    DynamicTallyType::Integers => distribute_preferences_dynamic_exact::<usize>(description,data,options,observer,randomness),
+                    DynamicTallyType::FiveDigitDecimals => distribute_preferences_dynamic_exact::<FixedPrecisionDecimal<5>>(description,data,options,observer,randomness),
+                    DynamicTallyType::SixDigitDecimals => distribute_preferences_dynamic_exact::<FixedPrecisionDecimal<6>>(description,data,options,observer,randomness),
+                }?
+            },
+        };
+        Ok(PossibleTranscripts::Exact(TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript }))
+    }
+
+    /// Count, following the ballots with the given preferences. See [trace_vote].
+    pub fn trace_vote(&self,data:&ElectionData,vote:&VoteToTrace,formality_rules:FormalityRules,options:&CountOptions,observer:&mut impl CountObserverForAnyRules,randomness:&mut Randomness) -> anyhow::Result<Option<VoteTrace>> {
+        Ok(match self {
+            Rules::AEC2013 => trace_vote(&FederalRulesUsed2013::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::AEC2016 => trace_vote(&FederalRulesUsed2016::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::AEC2019 => trace_vote(&FederalRulesUsed2019::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::FederalPre2021 => trace_vote(&FederalRulesPre2021::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::FederalPost2021 => trace_vote(&FederalRulesPost2021::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::FederalPost2021Manual => trace_vote(&FederalRulesPost2021Manual::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::ACTPre2020 => trace_vote(&ACTPre2020::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::ACT2020 => trace_vote(&ACT2020::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::ACT2021 => trace_vote(&ACT2021::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::NSWLocalGov2021 => trace_vote(&NSWLocalCouncilLegislation2021MyGuessAtHighlyAmbiguousLegislation::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::NSWECLocalGov2021 => trace_vote(&NSWECLocalGov2021::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::NSWECLocalGov2021Literal => trace_vote(&NSWECLocalGov2021Literal::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::NSWECRandomLGE2012 => trace_vote(&NSWECRandomLGE2012::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::NSWECRandomLGE2016 => trace_vote(&NSWECRandomLGE2016::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::NSWECRandomLGE2017 => trace_vote(&NSWECRandomLGE2017::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::NSWECRandomLC2015 => trace_vote(&NSWECRandomLC2015::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::NSWECRandomLC2019 => trace_vote(&NSWECRandomLC2019::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::Vic2018 => trace_vote(&Vic2018LegislativeCouncil::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::WA2008 => trace_vote(&WALegislativeCouncil::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::TasHareClark => trace_vote(&TasHareClark::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::ScottishSTV2007 => trace_vote(&ScottishSTV2007::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::IrishDail => trace_vote(&IrishDail::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::Minimal => trace_vote(&Minimal::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::IRV => trace_vote(&SimpleIRVAnyDifferenceBreaksTies::default(),data,vote,formality_rules,options,observer,randomness)?,
+            Rules::NZMeek => return Err(anyhow!("Meek's method uses a different counting engine, which does not support tracing votes.")),
+            Rules::Custom(description) => {
+                match description.tally {
+                    DynamicTallyType::Integers => trace_vote_dynamic::<usize>(description,data,vote,formality_rules,options,observer,randomness),
+                    DynamicTallyType::FiveDigitDecimals => trace_vote_dynamic::<FixedPrecisionDecimal<5>>(description,data,vote,formality_rules,options,observer,randomness),
+                    DynamicTallyType::SixDigitDecimals => trace_vote_dynamic::<FixedPrecisionDecimal<6>>(description,data,vote,formality_rules,options,observer,randomness),
+                }?
+            }
+        })
     }
 
     pub fn find_changes(&self,data:&ElectionData,options:&ChangeOptions,verbose:bool) -> anyhow::Result<PossibleChanges> {
+        if options.exact_irv && !matches!(self,Rules::IRV) { return Err(anyhow!("Exact margins are only supported for the IRV rules.")); }
+        Ok(match self {
+            Rules::AEC2013 => PossibleChanges::Integers(options.find_changes(&FederalRulesUsed2013::default(),data,verbose)?),
+            Rules::AEC2016 => PossibleChanges::Integers(options.find_changes(&FederalRulesUsed2016::default(),data,verbose)?),
+            Rules::AEC2019 => PossibleChanges::Integers(options.find_changes(&FederalRulesUsed2019::default(),data,verbose)?),
+            Rules::FederalPre2021 => PossibleChanges::Integers(options.find_changes(&FederalRulesPre2021::default(),data, verbose)?),
+            Rules::FederalPost2021 => PossibleChanges::Integers(options.find_changes(&FederalRulesPost2021::default(),data, verbose)?),
+            Rules::FederalPost2021Manual => PossibleChanges::Integers(options.find_changes(&FederalRulesPost2021Manual::default(),data, verbose)?),
+            Rules::ACTPre2020 => PossibleChanges::Integers(options.find_changes(&ACTPre2020::default(),data,verbose)?),
+            Rules::ACT2020 => PossibleChanges::SixDigitDecimals(options.find_changes(&ACT2020::default(),data,verbose)?),
+            Rules::ACT2021 => PossibleChanges::SixDigitDecimals(options.find_changes(&ACT2021::default(),data,verbose)?),
+            Rules::NSWLocalGov2021 => PossibleChanges::Integers(options.find_changes(&NSWLocalCouncilLegislation2021MyGuessAtHighlyAmbiguousLegislation::default(),data,verbose)?),
+            Rules::NSWECLocalGov2021 => PossibleChanges::Integers(options.find_changes(&NSWECLocalGov2021::default(),data,verbose)?),
+            Rules::NSWECLocalGov2021Literal => PossibleChanges::SignedIntegers(options.find_changes(&NSWECLocalGov2021Literal::default(),data,verbose)?),
+            Rules::Vic2018 => PossibleChanges::Integers(options.find_changes(&Vic2018LegislativeCouncil::default(),data,verbose)?),
+            Rules::WA2008 => PossibleChanges::Integers(options.find_changes(&WALegislativeCouncil::default(),data,verbose)?),
+            Rules::TasHareClark => PossibleChanges::Integers(options.find_changes(&TasHareClark::default(),data,verbose)?),
+            Rules::ScottishSTV2007 => PossibleChanges::FiveDigitDecimals(options.find_changes(&ScottishSTV2007::default(),data,verbose)?),
+            Rules::IrishDail => PossibleChanges::Integers(options.find_changes(&IrishDail::default(),data,verbose)?),
+            Rules::Minimal => PossibleChanges::SixDigitDecimals(options.find_changes(&Minimal::default(),data,verbose)?),
+            Rules::IRV => PossibleChanges::Integers(options.find_changes(&SimpleIRVAnyDifferenceBreaksTies::default(),data,verbose)?),
+            Rules::NSWECRandomLGE2012 => PossibleChanges::Integers(options.find_changes(&NSWECRandomLGE2012::default(),data, verbose)?),
+            Rules::NSWECRandomLGE2016 => PossibleChanges::Integers(options.find_changes(&NSWECRandomLGE2016::default(),data, verbose)?),
+            Rules::NSWECRandomLGE2017 => PossibleChanges::Integers(options.find_changes(&NSWECRandomLGE2017::default(),data, verbose)?),
+            Rules::NSWECRandomLC2015 => PossibleChanges::Integers(options.find_changes(&NSWECRandomLC2015::default(),data,verbose)?),
+            Rules::NSWECRandomLC2019 => PossibleChanges::Integers(options.find_changes(&NSWECRandomLC2019::default(),data,verbose)?),
+            Rules::NZMeek => return Err(anyhow!("Finding changes is not supported for Meek's method.")),
+            Rules::Custom(description) => {
+                match description.tally {
+                    DynamicTallyType::Integers => PossibleChanges::Integers(find_changes_dynamic::<usize>(description,options,data,verbose)?),
+                    DynamicTallyType::FiveDigitDecimals => PossibleChanges::FiveDigitDecimals(find_changes_dynamic::<FixedPrecisionDecimal<5>>(description,options,data,verbose)?),
+                    DynamicTallyType::SixDigitDecimals => PossibleChanges::SixDigitDecimals(find_changes_dynamic::<FixedPrecisionDecimal<6>>(description,options,data,verbose)?),
+                }
+            }
+        })
+    }
+
+    /// How these rules choose the votes to recount when filling a casual vacancy by a countback, if they say.
+    pub fn casual_vacancy_method(&self) -> Option<&'static dyn CasualVacancyMethod> {
+        match self {
+            Rules::ACTPre2020 | Rules::ACT2020 | Rules::ACT2021 => Some(&ACTCasualVacancy{}),
+            _ => None,
+        }
+    }
+
+    /// Get a structured description of the options used by these rules.
+    pub fn rule_card(&self) -> anyhow::Result<RuleCard> {
         Ok(match self {
-            Rules::AEC2013 => PossibleChanges::Integers(options.find_changes::<FederalRulesUsed2013>(data,verbose)?),
-            Rules::AEC2016 => PossibleChanges::Integers(options.find_changes::<FederalRulesUsed2016>(data,verbose)?),
-            Rules::AEC2019 => PossibleChanges::Integers(options.find_changes::<FederalRulesUsed2019>(data,verbose)?),
-            Rules::FederalPre2021 => PossibleChanges::Integers(options.find_changes::<FederalRulesPre2021>(data, verbose)?),
-            Rules::FederalPost2021 => PossibleChanges::Integers(options.find_changes::<FederalRulesPost2021>(data, verbose)?),
-            Rules::FederalPost2021Manual => PossibleChanges::Integers(options.find_changes::<FederalRulesPost2021Manual>(data, verbose)?),
-            Rules::ACTPre2020 => PossibleChanges::Integers(options.find_changes::<ACTPre2020>(data,verbose)?),
-            Rules::ACT2020 => PossibleChanges::SixDigitDecimals(options.find_changes::<ACT2020>(data,verbose)?),
-            Rules::ACT2021 => PossibleChanges::SixDigitDecimals(options.find_changes::<ACT2021>(data,verbose)?),
-            Rules::NSWLocalGov2021 => PossibleChanges::Integers(options.find_changes::<NSWLocalCouncilLegislation2021MyGuessAtHighlyAmbiguousLegislation>(data,verbose)?),
-            Rules::NSWECLocalGov2021 => PossibleChanges::Integers(options.find_changes::<NSWECLocalGov2021>(data,verbose)?),
-            Rules::NSWECLocalGov2021Literal => PossibleChanges::SignedIntegers(options.find_changes::<NSWECLocalGov2021Literal>(data,verbose)?),
-            Rules::Vic2018 => PossibleChanges::Integers(options.find_changes::<Vic2018LegislativeCouncil>(data,verbose)?),
-            Rules::WA2008 => PossibleChanges::Integers(options.find_changes::<WALegislativeCouncil>(data,verbose)?),
-            Rules::Minimal => PossibleChanges::SixDigitDecimals(options.find_changes::<Minimal>(data,verbose)?),
-            Rules::IRV => PossibleChanges::Integers(options.find_changes::<SimpleIRVAnyDifferenceBreaksTies>(data,verbose)?),
-            Rules::NSWECRandomLGE2012 => PossibleChanges::Integers(options.find_changes::<NSWECRandomLGE2012>(data, verbose)?),
-            Rules::NSWECRandomLGE2016 => PossibleChanges::Integers(options.find_changes::<NSWECRandomLGE2016>(data, verbose)?),
-            Rules::NSWECRandomLGE2017 => PossibleChanges::Integers(options.find_changes::<NSWECRandomLGE2017>(data, verbose)?),
-            Rules::NSWECRandomLC2015 => PossibleChanges::Integers(options.find_changes::<NSWECRandomLC2015>(data,verbose)?),
-            Rules::NSWECRandomLC2019 => PossibleChanges::Integers(options.find_changes::<NSWECRandomLC2019>(data,verbose)?),
+            Rules::AEC2013 => RuleCard::new(&FederalRulesUsed2013::default()),
+            Rules::AEC2016 => RuleCard::new(&FederalRulesUsed2016::default()),
+            Rules::AEC2019 => RuleCard::new(&FederalRulesUsed2019::default()),
+            Rules::FederalPre2021 => RuleCard::new(&FederalRulesPre2021::default()),
+            Rules::FederalPost2021 => RuleCard::new(&FederalRulesPost2021::default()),
+            Rules::FederalPost2021Manual => RuleCard::new(&FederalRulesPost2021Manual::default()),
+            Rules::ACTPre2020 => RuleCard::new(&ACTPre2020::default()),
+            Rules::ACT2020 => RuleCard::new(&ACT2020::default()),
+            Rules::ACT2021 => RuleCard::new(&ACT2021::default()),
+            Rules::NSWLocalGov2021 => RuleCard::new(&NSWLocalCouncilLegislation2021MyGuessAtHighlyAmbiguousLegislation::default()),
+            Rules::NSWECLocalGov2021 => RuleCard::new(&NSWECLocalGov2021::default()),
+            Rules::NSWECLocalGov2021Literal => RuleCard::new(&NSWECLocalGov2021Literal::default()),
+            Rules::NSWECRandomLGE2012 => RuleCard::new(&NSWECRandomLGE2012::default()),
+            Rules::NSWECRandomLGE2016 => RuleCard::new(&NSWECRandomLGE2016::default()),
+            Rules::NSWECRandomLGE2017 => RuleCard::new(&NSWECRandomLGE2017::default()),
+            Rules::NSWECRandomLC2015 => RuleCard::new(&NSWECRandomLC2015::default()),
+            Rules::NSWECRandomLC2019 => RuleCard::new(&NSWECRandomLC2019::default()),
+            Rules::Vic2018 => RuleCard::new(&Vic2018LegislativeCouncil::default()),
+            Rules::WA2008 => RuleCard::new(&WALegislativeCouncil::default()),
+            Rules::TasHareClark => RuleCard::new(&TasHareClark::default()),
+            Rules::ScottishSTV2007 => RuleCard::new(&ScottishSTV2007::default()),
+            Rules::IrishDail => RuleCard::new(&IrishDail::default()),
+            Rules::Minimal => RuleCard::new(&Minimal::default()),
+            Rules::IRV => RuleCard::new(&SimpleIRVAnyDifferenceBreaksTies::default()),
+            Rules::NZMeek => return Err(anyhow!("Meek's method uses a different counting engine, and does not have a rule card.")),
+            Rules::Custom(description) => {
+                match description.tally {
+                    DynamicTallyType::Integers => rule_card_dynamic::<usize>(description)?,
+                    DynamicTallyType::FiveDigitDecimals => rule_card_dynamic::<FixedPrecisionDecimal<5>>(description)?,
+                    DynamicTallyType::SixDigitDecimals => rule_card_dynamic::<FixedPrecisionDecimal<6>>(description)?,
+                }
+            }
         })
     }
+}
+
+/// Make the rule card for [DynamicRules] with the appropriate SplitByNumber type.
+fn rule_card_dynamic<Tally:DynamicTally>(description:&Arc<RuleDescription>) -> Result<RuleCard,CountError> {
+    match description.split_by_number {
+        DynamicSplitByNumber::DoNotSplitByCountNumber => Ok(RuleCard::new(&DynamicRules::<Tally,DoNotSplitByCountNumber>::new(description.clone())?)),
+        DynamicSplitByNumber::FullySplitByCountNumber => Ok(RuleCard::new(&DynamicRules::<Tally,FullySplitByCountNumber>::new(description.clone())?)),
+        DynamicSplitByNumber::SplitFirstCount => Ok(RuleCard::new(&DynamicRules::<Tally,SplitFirstCount>::new(description.clone())?)),
+        DynamicSplitByNumber::SplitByWhenTransferValueWasCreated => Ok(RuleCard::new(&DynamicRules::<Tally,SplitByWhenTransferValueWasCreated>::new(description.clone())?)),
+    }
+}
+
+/// Count using [DynamicRules] with the appropriate SplitByNumber type.
+fn distribute_preferences_dynamic<Tally:DynamicTally>(description:&Arc<RuleDescription>,data:&ElectionData,options:&CountOptions,observer:&mut dyn CountObserver<Tally>,randomness:&mut Randomness) -> Result<Transcript<Tally>,CountError> {
+    match description.split_by_number {
+        DynamicSplitByNumber::DoNotSplitByCountNumber => distribute_preferences_with_extractors(&DynamicRules::<Tally,DoNotSplitByCountNumber>::new(description.clone())?,data,options,observer,randomness),
+        DynamicSplitByNumber::FullySplitByCountNumber => distribute_preferences_with_extractors(&DynamicRules::<Tally,FullySplitByCountNumber>::new(description.clone())?,data,options,observer,randomness),
+        DynamicSplitByNumber::SplitFirstCount => distribute_preferences_with_extractors(&DynamicRules::<Tally,SplitFirstCount>::new(description.clone())?,data,options,observer,randomness),
+        DynamicSplitByNumber::SplitByWhenTransferValueWasCreated => distribute_preferences_with_extractors(&DynamicRules::<Tally,SplitByWhenTransferValueWasCreated>::new(description.clone())?,data,options,observer,randomness),
+    }
+}
+
+/// Count using [DynamicRules] with exact arithmetic and the appropriate SplitByNumber type.
+fn distribute_preferences_dynamic_exact<Tally:DynamicTally>(description:&Arc<RuleDescription>,data:&ElectionData,options:&CountOptions,observer:&mut dyn CountObserver<ExactTally>,randomness:&mut Randomness) -> Result<Transcript<ExactTally>,CountError> {
+    match description.split_by_number {
+        DynamicSplitByNumber::DoNotSplitByCountNumber => distribute_preferences_with_extractors(&ExactArithmetic::new(DynamicRules::<Tally,DoNotSplitByCountNumber>::new(description.clone())?),data,options,observer,randomness),
+        DynamicSplitByNumber::FullySplitByCountNumber => distribute_preferences_with_extractors(&ExactArithmetic::new(DynamicRules::<Tally,FullySplitByCountNumber>::new(description.clone())?),data,options,observer,randomness),
+        DynamicSplitByNumber::SplitFirstCount => distribute_preferences_with_extractors(&ExactArithmetic::new(DynamicRules::<Tally,SplitFirstCount>::new(description.clone())?),data,options,observer,randomness),
+        DynamicSplitByNumber::SplitByWhenTransferValueWasCreated => distribute_preferences_with_extractors(&ExactArithmetic::new(DynamicRules::<Tally,SplitByWhenTransferValueWasCreated>::new(description.clone())?),data,options,observer,randomness),
+    }
+}
+
+/// Trace a vote using [DynamicRules] with the appropriate SplitByNumber type.
+fn trace_vote_dynamic<Tally:DynamicTally>(description:&Arc<RuleDescription>,data:&ElectionData,vote:&VoteToTrace,formality_rules:FormalityRules,options:&CountOptions,observer:&mut dyn CountObserver<Tally>,randomness:&mut Randomness) -> Result<Option<VoteTrace>,CountError> {
+    match description.split_by_number {
+        DynamicSplitByNumber::DoNotSplitByCountNumber => trace_vote(&DynamicRules::<Tally,DoNotSplitByCountNumber>::new(description.clone())?,data,vote,formality_rules,options,observer,randomness),
+        DynamicSplitByNumber::FullySplitByCountNumber => trace_vote(&DynamicRules::<Tally,FullySplitByCountNumber>::new(description.clone())?,data,vote,formality_rules,options,observer,randomness),
+        DynamicSplitByNumber::SplitFirstCount => trace_vote(&DynamicRules::<Tally,SplitFirstCount>::new(description.clone())?,data,vote,formality_rules,options,observer,randomness),
+        DynamicSplitByNumber::SplitByWhenTransferValueWasCreated => trace_vote(&DynamicRules::<Tally,SplitByWhenTransferValueWasCreated>::new(description.clone())?,data,vote,formality_rules,options,observer,randomness),
+    }
+}
+
+/// Find changes using [DynamicRules] with the appropriate SplitByNumber type.
+fn find_changes_dynamic<Tally:DynamicTally>(description:&Arc<RuleDescription>,options:&ChangeOptions,data:&ElectionData,verbose:bool) -> anyhow::Result<ElectionChanges<Tally>> {
+    match description.split_by_number {
+        DynamicSplitByNumber::DoNotSplitByCountNumber => options.find_changes(&DynamicRules::<Tally,DoNotSplitByCountNumber>::new(description.clone())?,data,verbose),
+        DynamicSplitByNumber::FullySplitByCountNumber => options.find_changes(&DynamicRules::<Tally,FullySplitByCountNumber>::new(description.clone())?,data,verbose),
+        DynamicSplitByNumber::SplitFirstCount => options.find_changes(&DynamicRules::<Tally,SplitFirstCount>::new(description.clone())?,data,verbose),
+        DynamicSplitByNumber::SplitByWhenTransferValueWasCreated => options.find_changes(&DynamicRules::<Tally,SplitByWhenTransferValueWasCreated>::new(description.clone())?,data,verbose),
+    }
 
 }
 
@@ -211,9 +479,13 @@ impl RulesDetails {
             RulesDetails{ name: "NSWECRandomLC2015".to_string(), description: "My interpretation of the rules actually used by the NSW electoral commission for the NSW 2015 legislative council elections. Note that there is considerable randomness so recounting with a different random choices will probably produce different results. Same as NSWECRandomLC2019 except with the same last parcel error as NSWECRandomLGE2012 (which didn't come up so may or may not be present).".to_string() },
             RulesDetails{ name: "NSWECRandomLC2019".to_string(), description: "My interpretation of the rules actually used by the NSW electoral commission for the NSW 2019 and 2023 legislative council elections. Note that there is considerable randomness so recounting with a different random choices will probably produce different results. ".to_string() },
             RulesDetails{ name: "Vic2018".to_string(), description: "My interpretation of the rules that should have been used by the VEC since the 2018 modification to 114A(28)(c) of the Electoral Act 2002, and a plausible if not literal interpretation of the rules prior to that.".to_string() },
-            RulesDetails{ name: "WA2018".to_string(), description: "My interpretation of the Western Australian Legislative Council rules consistent with the 2008 published official distribution of preferences.".to_string() },
+            RulesDetails{ name: "WA2008".to_string(), description: "My interpretation of the Western Australian Legislative Council rules consistent with the 2008 published official distribution of preferences.".to_string() },
+            RulesDetails{ name: "TasHareClark".to_string(), description: "My interpretation of the Hare-Clark rules used for the Tasmanian House of Assembly. Note that there is considerable randomness so recounting with a different random choices will probably produce different results.".to_string() },
+            RulesDetails{ name: "ScottishSTV2007".to_string(), description: "My interpretation of the Weighted Inclusive Gregory Method used for Scottish local council elections under the Scottish Local Government Elections Order 2007, with transfer values and votes truncated to 5 decimal places.".to_string() },
+            RulesDetails{ name: "IrishDail".to_string(), description: "My interpretation of section 121 of the Electoral Act 1992 used for Dáil Éireann elections, suitable for the Irish data on PrefLib. Note that there is considerable randomness so recounting with a different random choices will probably produce different results.".to_string() },
             RulesDetails{ name: "Minimal".to_string(), description: "Minimal rules; according to the STV Margin Paper.".to_string() },
             RulesDetails{ name: "IRV".to_string(), description: "IRV with tie resolution by count backs with any non-equality breaking ties where possible.".to_string() },
+            RulesDetails{ name: "NZMeek".to_string(), description: "Meek's method, as in Algorithm 123 and the New Zealand Local Electoral Regulations 2001 Schedule 1A. Keep values are iterated to 9 decimal places.".to_string() },
         ]
     }
 }
@@ -225,6 +497,7 @@ pub enum PossibleChanges {
     Integers(ElectionChanges<usize>),
     SignedIntegers(ElectionChanges<isize>),
     SixDigitDecimals(ElectionChanges<FixedPrecisionDecimal<6>>),
+    FiveDigitDecimals(ElectionChanges<FixedPrecisionDecimal<5>>),
 }
 
 
@@ -235,6 +508,10 @@ pub enum PossibleTranscripts {
     Integers(TranscriptWithMetadata<usize>),
     SignedIntegers(TranscriptWithMetadata<isize>),
     SixDigitDecimals(TranscriptWithMetadata<FixedPrecisionDecimal<6>>),
+    NineDigitDecimals(TranscriptWithMetadata<MeekTally>),
+    FiveDigitDecimals(TranscriptWithMetadata<FixedPrecisionDecimal<5>>),
+    /// Counted with [ExactArithmetic]. This is last so an untagged transcript whose values are all decimals is read as one of the above.
+    Exact(TranscriptWithMetadata<ExactTally>),
 }
 
 impl PossibleTranscripts {
@@ -243,20 +520,43 @@ impl PossibleTranscripts {
             PossibleTranscripts::Integers(t) => {&t.transcript.elected}
             PossibleTranscripts::SignedIntegers(t) => {&t.transcript.elected}
             PossibleTranscripts::SixDigitDecimals(t) => {&t.transcript.elected}
+            PossibleTranscripts::NineDigitDecimals(t) => {&t.transcript.elected}
+            PossibleTranscripts::FiveDigitDecimals(t) => {&t.transcript.elected}
+            PossibleTranscripts::Exact(t) => {&t.transcript.elected}
+        }
+    }
+
+    /// The random draws recorded in the transcript.
+    pub fn draws(&self) -> &Vec<RandomDraw> {
+        match self {
+            PossibleTranscripts::Integers(t) => {&t.transcript.draws}
+            PossibleTranscripts::SignedIntegers(t) => {&t.transcript.draws}
+            PossibleTranscripts::SixDigitDecimals(t) => {&t.transcript.draws}
+            PossibleTranscripts::NineDigitDecimals(t) => {&t.transcript.draws}
+            PossibleTranscripts::FiveDigitDecimals(t) => {&t.transcript.draws}
+            PossibleTranscripts::Exact(t) => {&t.transcript.draws}
         }
     }
 
     pub fn compare_transcripts(&self, other:&PossibleTranscripts) -> DifferenceBetweenTranscripts {
-        match (self,other) {
-            (PossibleTranscripts::Integers(t1), PossibleTranscripts::Integers(t2)) => compare_transcripts(&t1.transcript,&t2.transcript),
-            (PossibleTranscripts::Integers(t1), PossibleTranscripts::SignedIntegers(t2)) => compare_transcripts(&t1.transcript,&t2.transcript),
-            (PossibleTranscripts::Integers(t1), PossibleTranscripts::SixDigitDecimals(t2)) => compare_transcripts(&t1.transcript,&t2.transcript),
-            (PossibleTranscripts::SignedIntegers(t1), PossibleTranscripts::Integers(t2)) => compare_transcripts(&t1.transcript,&t2.transcript),
-            (PossibleTranscripts::SignedIntegers(t1), PossibleTranscripts::SignedIntegers(t2)) => compare_transcripts(&t1.transcript,&t2.transcript),
-            (PossibleTranscripts::SignedIntegers(t1), PossibleTranscripts::SixDigitDecimals(t2)) => compare_transcripts(&t1.transcript,&t2.transcript),
-            (PossibleTranscripts::SixDigitDecimals(t1), PossibleTranscripts::Integers(t2)) => compare_transcripts(&t1.transcript,&t2.transcript),
-            (PossibleTranscripts::SixDigitDecimals(t1), PossibleTranscripts::SignedIntegers(t2)) => compare_transcripts(&t1.transcript,&t2.transcript),
-            (PossibleTranscripts::SixDigitDecimals(t1), PossibleTranscripts::SixDigitDecimals(t2)) => compare_transcripts(&t1.transcript,&t2.transcript),
+        match other {
+            PossibleTranscripts::Integers(t2) => self.compare_with_transcript(&t2.transcript),
+            PossibleTranscripts::SignedIntegers(t2) => self.compare_with_transcript(&t2.transcript),
+            PossibleTranscripts::SixDigitDecimals(t2) => self.compare_with_transcript(&t2.transcript),
+            PossibleTranscripts::NineDigitDecimals(t2) => self.compare_with_transcript(&t2.transcript),
+            PossibleTranscripts::FiveDigitDecimals(t2) => self.compare_with_transcript(&t2.transcript),
+            PossibleTranscripts::Exact(t2) => self.compare_with_transcript(&t2.transcript),
+        }
+    }
+
+    fn compare_with_transcript<Tally2:PartialEq+Clone+Display+FromStr+Debug+CanConvertToF64PossiblyLossily>(&self, other:&Transcript<Tally2>) -> DifferenceBetweenTranscripts {
+        match self {
+            PossibleTranscripts::Integers(t1) => compare_transcripts(&t1.transcript,other),
+            PossibleTranscripts::SignedIntegers(t1) => compare_transcripts(&t1.transcript,other),
+            PossibleTranscripts::SixDigitDecimals(t1) => compare_transcripts(&t1.transcript,other),
+            PossibleTranscripts::NineDigitDecimals(t1) => compare_transcripts(&t1.transcript,other),
+            PossibleTranscripts::FiveDigitDecimals(t1) => compare_transcripts(&t1.transcript,other),
+            PossibleTranscripts::Exact(t1) => compare_transcripts(&t1.transcript,other),
         }
     }
 }
\ No newline at end of file
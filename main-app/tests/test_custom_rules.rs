@@ -0,0 +1,103 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check that rules given by a description can be serialized, e.g. in a web request, and counted with.
+
+use std::sync::Arc;
+use clap::Parser;
+use main_app::ChangeOptions;
+use main_app::rules::{PossibleChanges, Rules};
+use stv::ballot_metadata::CandidateIndex;
+use stv::ballot_paper::BTL;
+use stv::dynamic_rules::RuleDescription;
+use stv::election_data::ElectionData;
+use stv::count_observer::IgnoreCountProgress;
+use stv::preference_distribution::CountOptions;
+use stv::random_util::Randomness;
+
+const FEDERAL_POST_2021 : &str = r#"{
+"name" : "MyFederalPost2021",
+"tally" : "Integers",
+"split_by_number" : "DoNotSplitByCountNumber",
+"last_parcel" : "No",
+"transfer_value_method" : "SurplusOverBallots",
+"surplus_transfer_method" : "JustOneTransferValue",
+"sort_exclusions_by_transfer_value" : true,
+"resolve_ties_elected_one_of_last_two" : "None",
+"resolve_ties_elected_by_quota" : "RequireHistoricalCountsToBeAllDifferent",
+"resolve_ties_elected_all_remaining" : "RequireHistoricalCountsToBeAllDifferent",
+"resolve_ties_choose_lowest_candidate_for_exclusion" : "AnyDifferenceIsADiscriminator",
+"check_elected_if_in_middle_of_surplus_distribution" : true,
+"check_elected_if_in_middle_of_exclusion" : true,
+"finish_all_counts_in_elimination_when_all_elected" : false,
+"finish_all_surplus_distributions_when_all_elected" : false,
+"when_to_check_if_just_two_standing_for_shortcut_election" : "AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing",
+"when_to_check_if_all_remaining_should_get_elected" : "AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing",
+"when_to_check_if_top_few_have_overwhelming_votes" : "Never"
+}"#;
+
+#[test]
+fn test_custom_rules_serialize_as_description() {
+    let description : RuleDescription = serde_json::from_str(FEDERAL_POST_2021).unwrap();
+    let rules = Rules::Custom(Arc::new(description));
+    assert_eq!("MyFederalPost2021",rules.to_string());
+    let serialized = serde_json::to_string(&rules).unwrap();
+    assert!(serialized.contains("\"MyFederalPost2021\""));
+    let deserialized : Rules = serde_json::from_str(&serialized).unwrap();
+    assert_eq!("MyFederalPost2021",deserialized.to_string());
+    assert_eq!("\"AEC2013\"",serde_json::to_string(&Rules::AEC2013).unwrap());
+}
+
+#[test]
+fn test_custom_rules_count_like_compiled_rules() {
+    let description : RuleDescription = serde_json::from_str(FEDERAL_POST_2021).unwrap();
+    let data = ElectionData::from_btl(&["A","B","C","D","E"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(3),CandidateIndex(1)], n: 43 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(2),CandidateIndex(4)], n: 21 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 9 },
+        BTL{ candidates: vec![CandidateIndex(4),CandidateIndex(1)], n: 7 },
+    ]);
+    let options = CountOptions::from_metadata(&data).unwrap();
+    let custom = Rules::Custom(Arc::new(description)).count_simple(&data,&options,false,false,&mut Randomness::ReverseDonkeyVote).unwrap();
+    let compiled = Rules::FederalPost2021.count_simple(&data,&options,false,false,&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!(compiled.elected(),custom.elected());
+}
+
+#[derive(Parser)]
+struct ChangeOpts {
+    #[clap(flatten)]
+    change_options : ChangeOptions,
+}
+
+/// The sizes of the changes found, which must be in integer tallies.
+fn change_sizes(changes:PossibleChanges) -> Vec<usize> {
+    match changes {
+        PossibleChanges::Integers(changes) => changes.changes.iter().map(|c|c.ballots.n.0).collect(),
+        _ => panic!("Expecting integer tallies"),
+    }
+}
+
+#[test]
+fn test_custom_rules_find_changes_like_compiled_rules() {
+    let custom = Rules::Custom(Arc::new(serde_json::from_str(FEDERAL_POST_2021).unwrap()));
+    for args in [vec!["test"],vec!["test","--quota","Hare"]] {
+        let options = ChangeOpts::parse_from(args).change_options;
+        let mut data = ElectionData::from_btl(&["A","B","C"],2,vec![
+            BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(1)], n: 45 },
+            BTL{ candidates: vec![CandidateIndex(1)], n: 25 },
+            BTL{ candidates: vec![CandidateIndex(2)], n: 30 },
+        ]);
+        let count_options = CountOptions{ quota_method: options.quota, ..CountOptions::from_metadata(&data).unwrap() };
+        let transcript = Rules::FederalPost2021.count(&data,&count_options,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+        data.metadata.results=Some(transcript.elected().clone());
+        // the search is done on several threads, each of which needs the rule description.
+        let custom_changes = change_sizes(custom.find_changes(&data,&options,false).unwrap());
+        let compiled_changes = change_sizes(Rules::FederalPost2021.find_changes(&data,&options,false).unwrap());
+        assert!(!compiled_changes.is_empty());
+        assert_eq!(compiled_changes,custom_changes);
+    }
+}
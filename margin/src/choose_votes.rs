@@ -120,8 +120,8 @@ impl ChooseVotesIndistinguishableSet {
 
 }
 impl <'a> ChooseVotesUpTo<'a> {
-    fn votes_available_total<R:PreferenceDistributionRules>(&self) -> R::Tally { R::use_transfer_value(self.current_transfer_value,self.atl.ballots_remaining+self.btl.ballots_remaining) }
-    fn votes_available_btl<R:PreferenceDistributionRules>(&self) -> R::Tally { R::use_transfer_value(self.current_transfer_value,self.btl.ballots_remaining) }
+    fn votes_available_total<R:PreferenceDistributionRules>(&self,rules:&R) -> R::Tally { rules.use_transfer_value(self.current_transfer_value,self.atl.ballots_remaining+self.btl.ballots_remaining) }
+    fn votes_available_btl<R:PreferenceDistributionRules>(&self,rules:&R) -> R::Tally { rules.use_transfer_value(self.current_transfer_value,self.btl.ballots_remaining) }
     /*
     fn votes_available<R:PreferenceDistributionRules>(&self) -> VotesAvailable<R::Tally> {
         VotesAvailable{
@@ -194,8 +194,8 @@ impl <'a> ChooseVotesUpTo<'a> {
         if !take_atl.is_zero() { self.atl.take_votes(election_data,take_atl,&mut res); }
         res
     }
-    fn get_votes<R:PreferenceDistributionRules>(&mut self,election_data:&'_ ElectionData,wanted:R::Tally,allow_atl:bool) -> BallotsWithGivenTransferValue<R::Tally> {
-        let max_available = if allow_atl { self.votes_available_total::<R>() } else { self.votes_available_btl::<R>() };
+    fn get_votes<R:PreferenceDistributionRules>(&mut self,rules:&R,election_data:&'_ ElectionData,wanted:R::Tally,allow_atl:bool) -> BallotsWithGivenTransferValue<R::Tally> {
+        let max_available = if allow_atl { self.votes_available_total(rules) } else { self.votes_available_btl(rules) };
         let to_take = wanted.min(max_available);
         let ballots_needed = self.current_transfer_value.num_ballot_papers_to_get_this_tv(R::convert_tally_to_rational(to_take.clone()));
         BallotsWithGivenTransferValue{
@@ -228,18 +228,18 @@ impl <'a> ChooseVotes<'a> {
         ChooseVotes{ election_data, sources:vec![]}
     }
     /// Total available votes, both below and above the line, taking rounding into account.
-    pub fn votes_available_total<R:PreferenceDistributionRules>(&self) -> R::Tally { self.sources.iter().map(|s|s.votes_available_total::<R>()).sum() }
+    pub fn votes_available_total<R:PreferenceDistributionRules>(&self,rules:&R) -> R::Tally { self.sources.iter().map(|s|s.votes_available_total(rules)).sum() }
     /// available votes below the line, taking rounding into account.
-    pub fn votes_available_btl<R:PreferenceDistributionRules>(&self) -> R::Tally { self.sources.iter().map(|s|s.votes_available_btl::<R>()).sum() }
+    pub fn votes_available_btl<R:PreferenceDistributionRules>(&self,rules:&R) -> R::Tally { self.sources.iter().map(|s|s.votes_available_btl(rules)).sum() }
 
     /// If possible, get a set of ballots that will provide the wanted number of votes.
     /// This will preferentially use votes with a large transfer value.
     /// BTL votes will be used preferentially, but atl votes will be used if allow_atl is true.
-    pub fn get_votes<R:PreferenceDistributionRules>(&mut self,wanted:R::Tally,allow_atl:bool) -> Option<Vec<BallotsWithGivenTransferValue<R::Tally>>> {
+    pub fn get_votes<R:PreferenceDistributionRules>(&mut self,rules:&R,wanted:R::Tally,allow_atl:bool) -> Option<Vec<BallotsWithGivenTransferValue<R::Tally>>> {
         let mut res = vec![];
         let mut sofar = R::Tally::zero();
         for i in (0..self.sources.len()).rev() {
-            let parcel = self.sources[i].get_votes::<R>(self.election_data,wanted.clone()-sofar.clone(),allow_atl);
+            let parcel = self.sources[i].get_votes(rules,self.election_data,wanted.clone()-sofar.clone(),allow_atl);
             if parcel.n.is_zero() {
                 if i+1==self.sources.len() && (allow_atl||self.sources[i].atl.ballots_remaining.is_zero()) {
                     self.sources.pop(); // remove empty source from future considerations.
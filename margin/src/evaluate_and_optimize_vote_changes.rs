@@ -14,7 +14,8 @@
 use num_traits::Zero;
 use stv::ballot_pile::BallotPaperCount;
 use stv::compare_transcripts::{DeltasInCandidateLists};
-use stv::election_data::ElectionData;
+use stv::count_error::CountError;
+use stv::incremental_recount::RecordedCount;
 use stv::preference_distribution::{PreferenceDistributionRules, RoundUpToUsize};
 use crate::choose_votes::ChooseVotesOptions;
 use crate::retroscope::Retroscope;
@@ -24,14 +25,21 @@ use crate::vote_changes::{BallotChanges, VoteChanges};
 pub enum ChangeResult<Tally> {
     NotEnoughVotesAvailable,
     NoChange,
-    Change(DeltasInCandidateLists,BallotChanges<Tally>)
+    Change(DeltasInCandidateLists,BallotChanges<Tally>),
+    /// The election could not be recounted with the changes.
+    CountFailed(CountError),
 }
 
 /// Test the effect of the provided changes on the election.
-/// ElectionData must contain vacancy information and results (official winners).
-pub fn simple_test<R:PreferenceDistributionRules>(vote_changes:&VoteChanges<R::Tally>,election_data:&ElectionData,retroscope:&Retroscope,options:&ChooseVotesOptions) -> ChangeResult<R::Tally> {
-    if let Some(ballot_changes) = vote_changes.make_concrete::<R>(retroscope,election_data,options) {
-        let diffs  : DeltasInCandidateLists = ballot_changes.see_effect::<R>(election_data);
+/// The election data must contain vacancy information and results (official winners).
+/// The election is counted once by the caller as this is typically called many times on the same data, and
+/// each test only recounts from the first count affected by the changes, see [RecordedCount].
+pub fn simple_test<R:PreferenceDistributionRules>(recorded:&RecordedCount<R>,vote_changes:&VoteChanges<R::Tally>,retroscope:&Retroscope,options:&ChooseVotesOptions) -> ChangeResult<R::Tally> {
+    if let Some(ballot_changes) = vote_changes.make_concrete(recorded.rules(),retroscope,recorded.election().data,options) {
+        let diffs  : DeltasInCandidateLists = match ballot_changes.see_effect_incremental(recorded) {
+            Ok(diffs) => diffs,
+            Err(error) => return ChangeResult::CountFailed(error),
+        };
         if diffs.is_empty() { ChangeResult::NoChange } else { ChangeResult::Change(diffs,ballot_changes)}
     } else {ChangeResult::NotEnoughVotesAvailable}
 }
@@ -41,21 +49,25 @@ pub struct FoundChange<Tally> {
     pub deltas : DeltasInCandidateLists,
     pub changes : BallotChanges<Tally>
 }
-pub fn optimise<R:PreferenceDistributionRules>(vote_changes:&VoteChanges<R::Tally>,election_data:&ElectionData,retroscope:&Retroscope,options:&ChooseVotesOptions,verbose:bool) -> Option<FoundChange<R::Tally>> {
-    optimise_work::<R>(vote_changes,election_data,retroscope,options,verbose,0)
+pub fn optimise<R:PreferenceDistributionRules>(recorded:&RecordedCount<R>,vote_changes:&VoteChanges<R::Tally>,retroscope:&Retroscope,options:&ChooseVotesOptions,verbose:bool) -> Option<FoundChange<R::Tally>> {
+    optimise_work(recorded,vote_changes,retroscope,options,verbose,0)
 }
-pub fn optimise_work<R:PreferenceDistributionRules>(vote_changes:&VoteChanges<R::Tally>,election_data:&ElectionData,retroscope:&Retroscope,options:&ChooseVotesOptions,verbose:bool,tried_already:usize) -> Option<FoundChange<R::Tally>> {
-    match simple_test::<R>(vote_changes,election_data,retroscope,options) {
+pub fn optimise_work<R:PreferenceDistributionRules>(recorded:&RecordedCount<R>,vote_changes:&VoteChanges<R::Tally>,retroscope:&Retroscope,options:&ChooseVotesOptions,verbose:bool,tried_already:usize) -> Option<FoundChange<R::Tally>> {
+    match simple_test(recorded,vote_changes,retroscope,options) {
         ChangeResult::NotEnoughVotesAvailable => { // could try reducing.
-            if verbose { println!("Not enough votes available - looking for {} from {}",vote_changes.changes.iter().map(|c|c.vote_value.clone()).sum::<R::Tally>(),vote_changes.changes.first().and_then(|c|c.from).map(|c|election_data.metadata.candidate(c).name.as_str()).unwrap_or(""));}
+            if verbose { println!("Not enough votes available - looking for {} from {}",vote_changes.changes.iter().map(|c|c.vote_value.clone()).sum::<R::Tally>(),vote_changes.changes.first().and_then(|c|c.from).map(|c|recorded.election().data.metadata.candidate(c).name.as_str()).unwrap_or(""));}
             None // TODO try reducing
         }
+        ChangeResult::CountFailed(error) => {
+            if verbose { println!("Could not recount with change : {}",error); }
+            None
+        }
         ChangeResult::NoChange => { // could try increasing
             if tried_already==0 {
                 if verbose { println!("No change - trying doubling everything"); }
                 let mut new_changes = vote_changes.clone();
                 for c in &mut new_changes.changes { c.vote_value+=c.vote_value.clone(); }
-                optimise_work::<R>(&new_changes,election_data,retroscope,options,verbose,tried_already+1)
+                optimise_work(recorded,&new_changes,retroscope,options,verbose,tried_already+1)
             } else {
                 if verbose { println!("No change - giving up"); }
                 None
@@ -72,7 +84,7 @@ pub fn optimise_work<R:PreferenceDistributionRules>(vote_changes:&VoteChanges<R:
                     let current_tally = opt_vote_changes.changes[i].vote_value.ceil();
                     let try_value = |new_count:usize| {
                         if verbose { println!("Trying change to {}",new_count); }
-                        simple_test::<R>(&opt_vote_changes.change_single_value(i,BallotPaperCount(new_count)),election_data,retroscope,options)
+                        simple_test(recorded,&opt_vote_changes.change_single_value(i,BallotPaperCount(new_count)),retroscope,options)
                     };
                     if let Some(search_res) = binary_search(try_value,0,current_tally) {
                         if search_res.n<current_tally { // had an improvement!
@@ -108,6 +120,7 @@ fn binary_search<F,Tally>(f:F,mut low:usize,mut high:usize) -> Option<BinarySear
         match f(mid) {
             ChangeResult::NotEnoughVotesAvailable => { if mid==0 { return None; } else { high=mid-1;} } // has to be smaller.
             ChangeResult::NoChange => { low=mid+1 } // has to be bigger
+            ChangeResult::CountFailed(_) => { return last_good; }
             ChangeResult::Change(deltas, changes) => { high=mid; last_good=Some(BinarySearchSuccess{n:mid,deltas,changes}) }
         }
     }
@@ -4,101 +4,178 @@
 // ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
 // You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use rayon::prelude::*;
 use stv::preference_distribution::RoundUpToUsize;
 use num_traits::Zero;
 use stv::ballot_metadata::{CandidateIndex};
 use stv::ballot_pile::BallotPaperCount;
-use stv::distribution_of_preferences_transcript::{CountIndex, ReasonForCount, SingleCount};
+use stv::distribution_of_preferences_transcript::{CountIndex, ReasonForCount, SingleCount, Transcript};
 use stv::election_data::ElectionData;
 use stv::preference_distribution::PreferenceDistributionRules;
+use stv::prepared_election::{PreparedElection, VoteDeltas};
+use stv::count_error::CountError;
+use stv::quota::QuotaMethod;
+use stv::count_observer::IgnoreCountProgress;
+use stv::incremental_recount::RecordedCount;
 use stv::random_util::Randomness;
 use crate::choose_votes::{ChooseVotes, ChooseVotesOptions};
-use crate::evaluate_and_optimize_vote_changes::optimise;
-use crate::record_changes::ElectionChanges;
+use crate::evaluate_and_optimize_vote_changes::{optimise, FoundChange};
+use crate::record_changes::{ElectionChange, ElectionChanges};
 use crate::retroscope::Retroscope;
-use crate::vote_changes::{VoteChange, VoteChanges};
+use crate::vote_changes::{BallotChanges, VoteChange, VoteChanges};
 
-pub fn find_outcome_changes <Rules:PreferenceDistributionRules>(original_data:&ElectionData, vote_choice_options:&ChooseVotesOptions,verbose:bool,just_try : Option<&VoteChanges<Rules::Tally>>) -> ElectionChanges<Rules::Tally> {
+/// Limits on how much work [find_outcome_changes_with_budget] may do. If a limit is reached, the search stops early,
+/// keeping the best changes found so far.
+#[derive(Clone,Debug,Default)]
+pub struct SearchBudget {
+    /// Stop after this much wall clock time.
+    pub max_time : Option<Duration>,
+    /// Stop after this many possible manipulations have been evaluated.
+    pub max_evaluations : Option<usize>,
+}
 
-    let transcript = original_data.distribute_preferences::<Rules>(&mut Randomness::ReverseDonkeyVote);
-    let mut not_continuing = HashSet::new();
+/// How far [find_outcome_changes_with_budget] has got, passed periodically to a progress callback.
+#[derive(Clone,Debug)]
+pub struct SearchProgress {
+    /// The number of counts in the original transcript that have been searched.
+    pub counts_searched : usize,
+    pub total_counts : usize,
+    /// The number of possible manipulations evaluated so far.
+    pub evaluations : usize,
+    pub elapsed : Duration,
+    pub smallest_manipulation_found : Option<BallotPaperCount>,
+    /// Whether the search has run out of budget, so will stop before searching all counts.
+    pub budget_exhausted : bool,
+}
 
-    let mut retroscope = Retroscope::new(&original_data, &original_data.metadata.excluded);
-    let mut change_recorder = ElectionChanges::new(original_data,&vote_choice_options.ballot_types_considered_unverifiable);
-    for countnumber in 0 .. transcript.counts.len() {
-        let count = &transcript.counts[countnumber];
-        retroscope.apply(CountIndex(countnumber),count );
-        // In NSW there are multiple counts for one 'action', so most counts do not have a decision
-        // We only need to try changing the decision on those counts for which some decision (e.g.
-        // eliminate or seat someone) can be made. This is superfluous for non-NSW elections.
-        if !count.reason_completed {
-            continue;
-        }
-        if let Some(vote_changes) = just_try { // see if we just want to use the provided hints.
-            if let Some(possible_manipulation) = optimise::<Rules>(&vote_changes, &original_data, &retroscope, vote_choice_options,verbose) {
-                change_recorder.add(possible_manipulation,verbose);
+/// Keep track of how much of a [SearchBudget] has been used, shared between threads.
+struct BudgetTracker<'a> {
+    budget : &'a SearchBudget,
+    start : Instant,
+    evaluations : AtomicUsize,
+}
+
+impl <'a> BudgetTracker<'a> {
+    fn exhausted(&self) -> bool {
+        self.budget.max_evaluations.map(|max|self.evaluations.load(Ordering::Relaxed)>=max).unwrap_or(false) ||
+            self.budget.max_time.map(|max|self.start.elapsed()>=max).unwrap_or(false)
+    }
+    /// Record the start of an evaluation, returning false if it should not be done as the budget is exhausted.
+    fn start_evaluation(&self) -> bool {
+        if self.exhausted() { false } else { self.evaluations.fetch_add(1,Ordering::Relaxed); true }
+    }
+}
+
+/// Something to try at a count.
+enum SearchTask {
+    /// Try getting the candidate at the given index in the sorted continuing candidates excluded instead, by a simple transfer and by levelling.
+    ExcludeInstead(usize),
+    /// Try adding votes to the candidate who was excluded.
+    AddToExcluded,
+    /// Try getting someone else elected instead of the lowest candidate just elected.
+    ElectInstead,
+    /// Try the changes given to [find_outcome_changes].
+    JustTry,
+}
+
+/// The state of the original count at a count where a decision was made.
+struct CountSnapshot {
+    countnumber : usize,
+    retroscope : Retroscope,
+    sorted_continuing_candidates : Vec<CandidateIndex>,
+}
+
+/// Things shared by all search tasks.
+struct SearchContext<'a,Rules:PreferenceDistributionRules> {
+    rules : &'a Rules,
+    original_data : &'a ElectionData,
+    recorded : &'a RecordedCount<'a,Rules>,
+    transcript : &'a Transcript<Rules::Tally>,
+    vote_choice_options : &'a ChooseVotesOptions,
+    just_try : Option<&'a VoteChanges<Rules::Tally>>,
+    budget : BudgetTracker<'a>,
+    verbose : bool,
+}
+
+impl <'a,Rules:PreferenceDistributionRules> SearchContext<'a,Rules> {
+    fn evaluate(&self,vote_changes:&VoteChanges<Rules::Tally>,retroscope:&Retroscope) -> Option<FoundChange<Rules::Tally>> {
+        if !self.budget.start_evaluation() { return None; }
+        optimise(self.recorded,vote_changes, retroscope, self.vote_choice_options,self.verbose)
+    }
+
+    /// The tasks worth trying at a given count.
+    fn tasks(&self,snapshot:&CountSnapshot) -> Vec<SearchTask> {
+        if self.just_try.is_some() { return vec![SearchTask::JustTry]; }
+        match self.transcript.counts.get(snapshot.countnumber+1).map(|c|&c.reason) {
+            Some(ReasonForCount::Elimination(eliminated_candidates)) if eliminated_candidates.len()==1 => {
+                let mut tasks : Vec<SearchTask> = snapshot.sorted_continuing_candidates.iter().enumerate().filter(|(_,&c)|c!=eliminated_candidates[0]).map(|(index,_)|SearchTask::ExcludeInstead(index)).collect();
+                if self.vote_choice_options.allow_additions { tasks.push(SearchTask::AddToExcluded); }
+                tasks
             }
-            continue;
+            _ => vec![SearchTask::ElectInstead],
         }
-        let mut sorted_continuing_candidates:Vec<CandidateIndex> = retroscope.continuing.iter().cloned().collect();
-        sorted_continuing_candidates.sort_by_key(| c | count.status.tallies.candidate[c.0].clone());
-        match if countnumber < transcript.counts.len()-1 { Some(&transcript.counts[countnumber+1].reason) } else { None } {
+    }
+
+    /// Try a task, returning the changes found.
+    fn run(&self,snapshot:&CountSnapshot,task:&SearchTask) -> Vec<FoundChange<Rules::Tally>> {
+        let mut found = vec![];
+        let count = &self.transcript.counts[snapshot.countnumber];
+        let retroscope = &snapshot.retroscope;
+        let sorted_continuing_candidates = &snapshot.sorted_continuing_candidates;
+        let verbose = self.verbose;
+        let vote_choice_options = self.vote_choice_options;
+        let original_data = self.original_data;
+        let eliminated_candidate = || match self.transcript.counts.get(snapshot.countnumber+1).map(|c|&c.reason) {
+            Some(ReasonForCount::Elimination(eliminated_candidates)) => eliminated_candidates[0],
+            _ => panic!("Search task only makes sense for an exclusion"),
+        };
+        match task {
+            SearchTask::JustTry => {
+                if let Some(possible_manipulation) = self.evaluate(self.just_try.unwrap(),retroscope) { found.push(possible_manipulation); }
+            }
             // If this is a count that redistributes the votes of a candidate eliminated in the previous
             // count, see if we can get someone else eliminated instead, first by iterating through
             // all other continuing candidates to see if we can shift votes from them to the otherwise-eliminated
             // candidate.
-            // Then try an addition-only option, where we simply try adding enough votes to the would-be
-            // eliminated candidate in order to raise it above the next-lowest.
-            Some(ReasonForCount::Elimination(eliminated_candidates)) if eliminated_candidates.len()==1 => {
-                let eliminated_candidate = eliminated_candidates[0];
-                for (index_of_target_in_sorted_list,&candidate) in sorted_continuing_candidates.iter().enumerate() {
-                    if candidate!=eliminated_candidate {
-                        if vote_choice_options.allow_to_candidate(eliminated_candidate)&&vote_choice_options.allow_from_candidate(candidate) { // try very simple transfer from candidate to eliminated_candidate.
-                            let vote_change = compute_vote_change::<Rules>(eliminated_candidate, candidate, count,verbose);
-                            let vote_changes = VoteChanges{ changes: vec![vote_change] };
-                            if let Some(possible_manipulation) = optimise::<Rules>(&vote_changes, &original_data, &retroscope, vote_choice_options,verbose) {
-                                change_recorder.add(possible_manipulation,verbose);
-                            }
-                        }
-                        // try leveling
-                        if let Some(leveling) = compute_vote_change_leveling::<Rules>(index_of_target_in_sorted_list,true,count,&sorted_continuing_candidates,&original_data, &retroscope, vote_choice_options,true,verbose) {
-                            if verbose { println!("Found a levelling to try {}",leveling); }
-                            if let Some(possible_manipulation) = optimise::<Rules>(&leveling, &original_data, &retroscope, vote_choice_options,verbose) {
-                                change_recorder.add(possible_manipulation,verbose);
-                                // that worked! Try related things.
-                                if let Some(leveling) = compute_vote_change_leveling::<Rules>(index_of_target_in_sorted_list,true,count,&sorted_continuing_candidates,&original_data, &retroscope, vote_choice_options,false,verbose) {
-                                    if verbose { println!("Found a related levelling to try {}",leveling); }
-                                    if let Some(possible_manipulation) = optimise::<Rules>(&leveling, &original_data, &retroscope, vote_choice_options,verbose) {
-                                        change_recorder.add(possible_manipulation,verbose);
-                                    }
-                                }
-                                if let Some(leveling) = compute_vote_change_leveling::<Rules>(index_of_target_in_sorted_list,false,count,&sorted_continuing_candidates,&original_data, &retroscope, vote_choice_options,false,verbose) {
-                                    if verbose { println!("Found a related levelling to try {}",leveling); }
-                                    if let Some(possible_manipulation) = optimise::<Rules>(&leveling, &original_data, &retroscope, vote_choice_options,verbose) {
-                                        change_recorder.add(possible_manipulation,verbose);
-                                    }
-                                }
-                                if let Some(leveling) = compute_vote_change_leveling::<Rules>(index_of_target_in_sorted_list,false,count,&sorted_continuing_candidates,&original_data, &retroscope, vote_choice_options,true,verbose) {
-                                    if verbose { println!("Found a related levelling to try {}",leveling); }
-                                    if let Some(possible_manipulation) = optimise::<Rules>(&leveling, &original_data, &retroscope, vote_choice_options,verbose) {
-                                        change_recorder.add(possible_manipulation,verbose);
-                                    }
+            SearchTask::ExcludeInstead(index_of_target_in_sorted_list) => {
+                let index_of_target_in_sorted_list = *index_of_target_in_sorted_list;
+                let eliminated_candidate = eliminated_candidate();
+                let candidate = sorted_continuing_candidates[index_of_target_in_sorted_list];
+                if vote_choice_options.allow_to_candidate(eliminated_candidate)&&vote_choice_options.allow_from_candidate(candidate) { // try very simple transfer from candidate to eliminated_candidate.
+                    let vote_change = compute_vote_change::<Rules>(eliminated_candidate, candidate, count,verbose);
+                    let vote_changes = VoteChanges{ changes: vec![vote_change] };
+                    if let Some(possible_manipulation) = self.evaluate(&vote_changes, retroscope) {
+                        found.push(possible_manipulation);
+                    }
+                }
+                // try leveling
+                if let Some(leveling) = compute_vote_change_leveling(self.rules,index_of_target_in_sorted_list,true,count,sorted_continuing_candidates,original_data, retroscope, vote_choice_options,true,verbose) {
+                    if verbose { println!("Found a levelling to try {}",leveling); }
+                    if let Some(possible_manipulation) = self.evaluate(&leveling, retroscope) {
+                        found.push(possible_manipulation);
+                        // that worked! Try related things.
+                        for (may_take_votes_from_target,reverse_secondary_targets) in [(true,false),(false,false),(false,true)] {
+                            if let Some(leveling) = compute_vote_change_leveling(self.rules,index_of_target_in_sorted_list,may_take_votes_from_target,count,sorted_continuing_candidates,original_data, retroscope, vote_choice_options,reverse_secondary_targets,verbose) {
+                                if verbose { println!("Found a related levelling to try {}",leveling); }
+                                if let Some(possible_manipulation) = self.evaluate(&leveling, retroscope) {
+                                    found.push(possible_manipulation);
                                 }
                             }
                         }
                     }
                 }
-                if vote_choice_options.allow_additions {
-                    // Addition-only option.
-                    let vote_addition = compute_vote_addition::<Rules>(eliminated_candidate, sorted_continuing_candidates[1], count,verbose);
-                    let vote_additions = VoteChanges{ changes: vec![vote_addition] };
-                    if let Some(possible_addition) = optimise::<Rules>(&vote_additions, &original_data, &retroscope, vote_choice_options,verbose) {
-                        change_recorder.add(possible_addition,verbose);
-                    }
+            }
+            // Try an addition-only option, where we simply try adding enough votes to the would-be
+            // eliminated candidate in order to raise it above the next-lowest.
+            SearchTask::AddToExcluded => {
+                let vote_addition = compute_vote_addition::<Rules>(eliminated_candidate(), sorted_continuing_candidates[1], count,verbose);
+                let vote_additions = VoteChanges{ changes: vec![vote_addition] };
+                if let Some(possible_addition) = self.evaluate(&vote_additions, retroscope) {
+                    found.push(possible_addition);
                 }
             }
-
             // If this is not an elimination count, probably someone got elected in this count,
             // either because they got a quota or because the
             // number of continuing candidates was just enough to fill the seats. See if we can get
@@ -107,41 +184,196 @@ pub fn find_outcome_changes <Rules:PreferenceDistributionRules>(original_data:&E
             // is not an official winner.
             // It then tries the addition-only option, in which we try adding votes to the highest
             // non-winner until it exceeds the official winner.
-
-            _ => {
-                let sorted_continuing_non_winners = sorted_continuing_candidates.iter().filter(| c | !transcript.elected.contains(c)).cloned().collect::<Vec<_>>();
+            SearchTask::ElectInstead => {
+                let sorted_continuing_non_winners = sorted_continuing_candidates.iter().filter(| c | !self.transcript.elected.contains(c)).cloned().collect::<Vec<_>>();
                 let just_elected_candidates = &count.elected;
-                if just_elected_candidates.len() > 0  && sorted_continuing_non_winners.len() > 0 {
+                if !just_elected_candidates.is_empty() && !sorted_continuing_non_winners.is_empty() {
                     let highest_non_winner= sorted_continuing_non_winners[sorted_continuing_non_winners.len()-1];
                     let elected_candidate_tallies = just_elected_candidates.iter().map(|c| count.status.tallies.candidate[c.who.0].clone()).collect::<Vec<_>>();
                     let lowest_winner_tally = elected_candidate_tallies.iter().cloned().min().unwrap();
                     let lowest_winner_index = elected_candidate_tallies.iter().position(|t| lowest_winner_tally == *t).unwrap();
                     let lowest_winner = just_elected_candidates[lowest_winner_index].who;
-                    let vote_change = compute_vote_change::<Rules>(highest_non_winner, lowest_winner, &count,verbose);
+                    let vote_change = compute_vote_change::<Rules>(highest_non_winner, lowest_winner, count,verbose);
 
                     // Try shifting votes from the lowest winner to the highest non-winner
                     let vote_changes = VoteChanges{ changes: vec![vote_change] };
-                    if let Some(possible_manipulation) = optimise::<Rules>(&vote_changes, &original_data, &retroscope, vote_choice_options,verbose) {
-                        change_recorder.add(possible_manipulation,verbose);
+                    if let Some(possible_manipulation) = self.evaluate(&vote_changes, retroscope) {
+                        found.push(possible_manipulation);
                     }
 
                     if vote_choice_options.allow_additions {
                         // Addition-only
-                        let vote_addition = compute_vote_addition::<Rules>(highest_non_winner, lowest_winner, &count,verbose);
+                        let vote_addition = compute_vote_addition::<Rules>(highest_non_winner, lowest_winner, count,verbose);
                         let vote_additions = VoteChanges{ changes: vec![vote_addition] };
-                        if let Some(possible_addition) = optimise::<Rules>(&vote_additions, &original_data, &retroscope, vote_choice_options,verbose) {
-                            change_recorder.add(possible_addition,verbose);
+                        if let Some(possible_addition) = self.evaluate(&vote_additions, retroscope) {
+                            found.push(possible_addition);
                         }
                     }
                 }
             }
         }
-        for c in &count.not_continuing { not_continuing.insert(*c); }
+        found
+    }
+}
+
+/// If quota_method is given, use it instead of the quota method specified by the rules.
+pub fn find_outcome_changes <Rules:PreferenceDistributionRules+Sync>(rules:&Rules,original_data:&ElectionData, vote_choice_options:&ChooseVotesOptions,verbose:bool,just_try : Option<&VoteChanges<Rules::Tally>>,quota_method:Option<QuotaMethod>) -> Result<ElectionChanges<Rules::Tally>,CountError> {
+    find_outcome_changes_with_budget(rules,original_data,vote_choice_options,verbose,just_try,quota_method,&SearchBudget::default(),&mut |_|true)
+}
 
+/// Like [find_outcome_changes], but stop early if the budget is exhausted or the progress callback returns false.
+/// The best changes found so far are returned.
+///
+/// Counts are searched in parallel, in batches of about as many counts as there are threads.
+/// The progress callback is called after each batch.
+pub fn find_outcome_changes_with_budget <Rules:PreferenceDistributionRules+Sync>(rules:&Rules,original_data:&ElectionData, vote_choice_options:&ChooseVotesOptions,verbose:bool,just_try : Option<&VoteChanges<Rules::Tally>>,quota_method:Option<QuotaMethod>,budget:&SearchBudget,progress:&mut dyn FnMut(&SearchProgress)->bool) -> Result<ElectionChanges<Rules::Tally>,CountError> {
+    let prepared = PreparedElection::new(original_data,None).with_quota_method(quota_method); // resolve tickets just once, as there will be many recounts.
+    let no_changes = VoteDeltas::default();
+    let votes = prepared.votes(&no_changes);
+    let mut observer = IgnoreCountProgress{};
+    let mut randomness = Randomness::ReverseDonkeyVote;
+    let recorded = RecordedCount::new(rules,&prepared,&votes,&mut observer,&mut randomness)?; // so each recount can start from the first count affected.
+    let transcript = &recorded.transcript;
+    let context = SearchContext{ rules, original_data, recorded: &recorded, transcript, vote_choice_options, just_try, budget: BudgetTracker{ budget, start: Instant::now(), evaluations: AtomicUsize::new(0) }, verbose };
+
+    let mut retroscope = Retroscope::new(original_data, &original_data.metadata.excluded);
+    let mut change_recorder = ElectionChanges::new(original_data,&vote_choice_options.ballot_types_considered_unverifiable);
+    let batch_size = rayon::current_num_threads().max(1);
+    let mut batch : Vec<CountSnapshot> = vec![];
+    for countnumber in 0 .. transcript.counts.len() {
+        let count = &transcript.counts[countnumber];
+        retroscope.apply(CountIndex(countnumber),count )?;
+        // In NSW there are multiple counts for one 'action', so most counts do not have a decision
+        // We only need to try changing the decision on those counts for which some decision (e.g.
+        // eliminate or seat someone) can be made. This is superfluous for non-NSW elections.
+        if count.reason_completed {
+            let mut sorted_continuing_candidates:Vec<CandidateIndex> = retroscope.continuing.iter().cloned().collect();
+            sorted_continuing_candidates.sort_by_key(| c | count.status.tallies.candidate[c.0].clone());
+            batch.push(CountSnapshot{ countnumber, retroscope: retroscope.clone(), sorted_continuing_candidates });
+        }
+        if batch.len()>=batch_size || (countnumber+1==transcript.counts.len() && !batch.is_empty()) {
+            let tasks : Vec<(&CountSnapshot,SearchTask)> = batch.iter().flat_map(|snapshot|context.tasks(snapshot).into_iter().map(move |task|(snapshot,task))).collect();
+            let found : Vec<FoundChange<Rules::Tally>> = tasks.par_iter().flat_map_iter(|(snapshot,task)|context.run(snapshot,task)).collect();
+            for possible_manipulation in found { change_recorder.add(possible_manipulation,verbose); }
+            batch.clear();
+            let budget_exhausted = context.budget.exhausted();
+            let keep_going = progress(&SearchProgress{
+                counts_searched: countnumber+1,
+                total_counts: transcript.counts.len(),
+                evaluations: context.budget.evaluations.load(Ordering::Relaxed),
+                elapsed: context.budget.start.elapsed(),
+                smallest_manipulation_found: change_recorder.smallest_manipulation_found(),
+                budget_exhausted,
+            });
+            if budget_exhausted || !keep_going {
+                if verbose { println!("Stopping search after {} of {} counts",countnumber+1,transcript.counts.len()); }
+                break;
+            }
+        }
     }
     change_recorder.sort();
     if verbose { println!("Electorate: {}. {} total votes. Min manipulations: size {:?}", original_data.metadata.name.electorate, original_data.num_votes(),  change_recorder.changes.iter().map(| c | c.ballots.n).collect::<Vec<_>>()); }
-    change_recorder
+    Ok(change_recorder)
+}
+
+/// The number of candidates, closest in tally to the candidate excluded, that are tried as alternatives at each exclusion in [find_composed_outcome_changes].
+const COMPOSED_ALTERNATIVES_PER_EXCLUSION : usize = 3;
+
+/// Like [find_outcome_changes], but also look for changes that combine manipulations at up to `max_decision_points` different counts.
+///
+/// [find_outcome_changes] changes one decision in the original count. Sometimes it is cheaper to change who is excluded at
+/// one count, and then, in the resulting modified count, change a later decision as well. This does a depth first search over
+/// such sequences, each step being a simple transfer that changes who is excluded, applied to the election as modified
+/// by the previous steps. The last step is optimised as in [find_outcome_changes].
+///
+/// A step is not tried if the [Retroscope] shows that the candidate losing votes does not have enough votes on their pile.
+/// Steps are not pruned by size, as the outcome they lead to is not known until they are tried, and a larger change with a different
+/// outcome is still worth keeping. Results are kept Pareto-minimal by [ElectionChanges::add_change]. This is slow, and gets much slower as `max_decision_points` increases, so the `budget`
+/// applies to both the initial and the composed search. If quota_method is given, use it instead of the quota method specified by the rules.
+pub fn find_composed_outcome_changes<Rules:PreferenceDistributionRules+Sync>(rules:&Rules,original_data:&ElectionData, vote_choice_options:&ChooseVotesOptions,max_decision_points:usize,verbose:bool,quota_method:Option<QuotaMethod>,budget:&SearchBudget) -> Result<ElectionChanges<Rules::Tally>,CountError> {
+    let start = Instant::now();
+    let mut change_recorder = find_outcome_changes_with_budget(rules,original_data,vote_choice_options,verbose,None,quota_method,budget,&mut |_|true)?;
+    if max_decision_points>1 {
+        let remaining = SearchBudget{ max_time: budget.max_time.map(|max|max.saturating_sub(start.elapsed())), max_evaluations: budget.max_evaluations };
+        let search = ComposedSearch{ original: PreparedElection::new(original_data,None).with_quota_method(quota_method), quota_method, options: vote_choice_options, max_decision_points, verbose, budget: BudgetTracker{ budget: &remaining, start: Instant::now(), evaluations: AtomicUsize::new(0) } };
+        search.search(rules,original_data,&BallotChanges{ changes: vec![], n: BallotPaperCount(0) },0,0,&mut change_recorder);
+        change_recorder.sort();
+        if verbose { println!("After composing changes at up to {} counts, min manipulations: size {:?}",max_decision_points,change_recorder.changes.iter().map(| c | c.ballots.n).collect::<Vec<_>>()); }
+    }
+    Ok(change_recorder)
+}
+
+struct ComposedSearch<'a> {
+    original : PreparedElection<'a>,
+    quota_method : Option<QuotaMethod>,
+    options : &'a ChooseVotesOptions,
+    max_decision_points : usize,
+    verbose : bool,
+    budget : BudgetTracker<'a>,
+}
+
+impl <'a> ComposedSearch<'a> {
+    /// `data` is the original data with `so_far` applied, which changed `depth` decisions, the last of which was before count `first_count`.
+    fn search<Rules:PreferenceDistributionRules>(&self,rules:&Rules,data:&ElectionData,so_far:&BallotChanges<Rules::Tally>,depth:usize,first_count:usize,change_recorder:&mut ElectionChanges<Rules::Tally>) {
+        let prepared = PreparedElection::new(data,None).with_quota_method(self.quota_method);
+        let no_changes = VoteDeltas::default();
+        let votes = prepared.votes(&no_changes);
+        let mut observer = IgnoreCountProgress{};
+        let mut randomness = Randomness::ReverseDonkeyVote;
+        let recorded = match RecordedCount::new(rules,&prepared,&votes,&mut observer,&mut randomness) {
+            Ok(recorded) => recorded,
+            Err(error) => { if self.verbose { println!("Could not recount with change : {}",error); } return; }
+        };
+        let transcript = &recorded.transcript;
+        let mut retroscope = Retroscope::new(data, &data.metadata.excluded);
+        for countnumber in 0 .. transcript.counts.len() {
+            let count = &transcript.counts[countnumber];
+            if let Err(error) = retroscope.apply(CountIndex(countnumber),count ) { if self.verbose { println!("Could not follow votes in recount with change : {}",error); } return; }
+            if countnumber<first_count || !count.reason_completed { continue; }
+            let eliminated_candidate = match transcript.counts.get(countnumber+1).map(|c|&c.reason) {
+                Some(ReasonForCount::Elimination(eliminated_candidates)) if eliminated_candidates.len()==1 => eliminated_candidates[0],
+                _ => continue,
+            };
+            if !self.options.allow_to_candidate(eliminated_candidate) { continue; }
+            let mut alternatives : Vec<CandidateIndex> = retroscope.continuing.iter().cloned().filter(|&c|c!=eliminated_candidate && self.options.allow_from_candidate(c)).collect();
+            alternatives.sort_by_key(| c | (count.status.tallies.candidate[c.0].clone(),c.0));
+            for &candidate in alternatives.iter().take(COMPOSED_ALTERNATIVES_PER_EXCLUSION) {
+                if !self.budget.start_evaluation() { return; }
+                let vote_change = compute_vote_change::<Rules>(eliminated_candidate, candidate, count,self.verbose);
+                if retroscope.get_chooser(candidate,data,self.options).votes_available_total(rules)<vote_change.vote_value { continue; }
+                let vote_changes = VoteChanges{ changes: vec![vote_change] };
+                if depth+1==self.max_decision_points {
+                    if let Some(found) = optimise(&recorded,&vote_changes, &retroscope, self.options,self.verbose) {
+                        self.record(rules,so_far,&found.changes,data,change_recorder);
+                    }
+                } else if let Some(step) = vote_changes.make_concrete(rules,&retroscope,data,self.options) {
+                    match step.see_effect_incremental(&recorded) {
+                        Ok(deltas) if deltas.is_empty() => {
+                            if let Some(combined) = so_far.then(&step,self.original.data,data) {
+                                if self.verbose { println!("Composing change of {} ballots at count {} with later changes",step.n,countnumber+1); }
+                                let next_data = step.apply_to_votes(data,false);
+                                self.search(rules,&next_data,&combined,depth+1,countnumber+1,change_recorder);
+                            }
+                        }
+                        Ok(_) => self.record(rules,so_far,&step,data,change_recorder),
+                        Err(error) => { if self.verbose { println!("Could not recount with change : {}",error); } }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record the combination of `so_far` and `step` (relative to `data`) if it changes the outcome of the original election.
+    fn record<Rules:PreferenceDistributionRules>(&self,rules:&Rules,so_far:&BallotChanges<Rules::Tally>,step:&BallotChanges<Rules::Tally>,data:&ElectionData,change_recorder:&mut ElectionChanges<Rules::Tally>) {
+        if let Some(combined) = so_far.then(step,self.original.data,data) {
+            if let Ok(deltas) = combined.see_effect(rules,&self.original) {
+                if !deltas.is_empty() {
+                    let change = ElectionChange::new(deltas,combined,self.original.data,&change_recorder.ballot_types_considered_unverifiable);
+                    change_recorder.add_change(change,self.verbose);
+                }
+            }
+        }
+    }
 }
 
 
@@ -180,12 +412,12 @@ fn compute_vote_change<Rules:PreferenceDistributionRules>(to_candidate: Candidat
 ///
 /// Will aim for a level base_level for the target. Everyone else should be > base_level.
 ///
-fn compute_vote_change_leveling<Rules:PreferenceDistributionRules>(index_of_target_in_sorted_list:usize,may_take_votes_from_target:bool, count: &SingleCount<Rules::Tally>,sorted_continuing_candidates:&[CandidateIndex],election_data:&ElectionData,retroscope:&Retroscope,options:&ChooseVotesOptions,reverse_secondary_targets:bool,verbose:bool) -> Option<VoteChanges<Rules::Tally>> {
+fn compute_vote_change_leveling<Rules:PreferenceDistributionRules>(rules:&Rules,index_of_target_in_sorted_list:usize,may_take_votes_from_target:bool, count: &SingleCount<Rules::Tally>,sorted_continuing_candidates:&[CandidateIndex],election_data:&ElectionData,retroscope:&Retroscope,options:&ChooseVotesOptions,reverse_secondary_targets:bool,verbose:bool) -> Option<VoteChanges<Rules::Tally>> {
     let can_use_atl = |c:CandidateIndex|retroscope.is_highest_continuing_member_party_ticket(c,&election_data.metadata);
     let target: CandidateIndex=sorted_continuing_candidates[index_of_target_in_sorted_list];
     let current_target_tally = count.status.tallies.candidate[target.0].clone();
     let target_chooser = if may_take_votes_from_target { retroscope.get_chooser(target,election_data,options) } else { ChooseVotes::zero(election_data) };
-    let max_can_take_from_target : Rules::Tally = target_chooser.votes_available_total::<Rules>();
+    let max_can_take_from_target : Rules::Tally = target_chooser.votes_available_total(rules);
     let base_level = if may_take_votes_from_target { // level that the target is aimed at.
         let mut sub_targets_to_raise = 0;
         let mut sum_of_target_plus_subtargets_plus_one = current_target_tally.clone()+Rules::Tally::from(BallotPaperCount(1));
@@ -233,13 +465,13 @@ fn compute_vote_change_leveling<Rules:PreferenceDistributionRules>(index_of_targ
                 let tally = count.status.tallies.candidate[c.0].clone();
                 if tally<= base_level {
                     let increment = base_level.clone()-tally.clone()+margin_above_base.clone();
-                    if !source.give_to_candidate::<Rules>(increment,c,&mut res,atl_ok) { return None; }
+                    if !source.give_to_candidate(rules,increment,c,&mut res,atl_ok) { return None; }
                 }
             }
         }
     }
     // it is possible that not enough votes have been taken from target, if the votes available were largely ATL and the people being given to were largely BTL.
-    let after_mods_tally_for_target = current_target_tally+source.available_to_take_from_sources[0].votes_available_total::<Rules>()-max_can_take_from_target;
+    let after_mods_tally_for_target = current_target_tally+source.available_to_take_from_sources[0].votes_available_total(rules)-max_can_take_from_target;
     if after_mods_tally_for_target>base_level {
         if verbose { println!("Could not take enough from target. Taking more."); }
         if let Some(&recipient) = sorted_continuing_candidates[index_of_target_in_sorted_list+1..].iter().rev().find(|&&c|can_use_atl(c)) {
@@ -259,31 +491,31 @@ struct PolyFromSource<'a> {
 }
 
 impl <'a> PolyFromSource<'a> {
-    fn available_to_take_atl_ok<Rules:PreferenceDistributionRules>(&mut self) -> Option<Rules::Tally> {
+    fn available_to_take_atl_ok<Rules:PreferenceDistributionRules>(&mut self,rules:&Rules) -> Option<Rules::Tally> {
         while self.index_currently_taking_from_atl_ok<self.from_sources.len() {
-            let available : Rules::Tally = self.available_to_take_from_sources[self.index_currently_taking_from_atl_ok].votes_available_total::<Rules>();
+            let available : Rules::Tally = self.available_to_take_from_sources[self.index_currently_taking_from_atl_ok].votes_available_total(rules);
             if available == Rules::Tally::zero() { self.index_currently_taking_from_atl_ok+=1; }
             else { return Some(available); }
         }
         None
     }
-    fn available_to_take_btl_only<Rules:PreferenceDistributionRules>(&mut self) -> Option<Rules::Tally> {
+    fn available_to_take_btl_only<Rules:PreferenceDistributionRules>(&mut self,rules:&Rules) -> Option<Rules::Tally> {
         while self.index_currently_taking_from_btl_only<self.from_sources.len() {
-            let available : Rules::Tally = self.available_to_take_from_sources[self.index_currently_taking_from_btl_only].votes_available_btl::<Rules>();
+            let available : Rules::Tally = self.available_to_take_from_sources[self.index_currently_taking_from_btl_only].votes_available_btl(rules);
             if available == Rules::Tally::zero() { self.index_currently_taking_from_btl_only+=1; }
             else { return Some(available); }
         }
         None
     }
     /// Try to give a certain amount of votes to a given recipient, return true iff success.
-    fn give_to_candidate<Rules:PreferenceDistributionRules>(&mut self, amount:Rules::Tally, recipient:CandidateIndex, changes:&mut VoteChanges<Rules::Tally>,may_be_atl:bool) -> bool {
+    fn give_to_candidate<Rules:PreferenceDistributionRules>(&mut self, rules:&Rules, amount:Rules::Tally, recipient:CandidateIndex, changes:&mut VoteChanges<Rules::Tally>,may_be_atl:bool) -> bool {
         let mut togo = amount;
         while togo>Rules::Tally::zero() {
-            if let Some(available) = if may_be_atl { self.available_to_take_atl_ok::<Rules>() } else { self.available_to_take_btl_only::<Rules>() } {
+            if let Some(available) = if may_be_atl { self.available_to_take_atl_ok(rules) } else { self.available_to_take_btl_only(rules) } {
                 let parcel = togo.clone().min(available);
                 let index_currently_taking_from = if may_be_atl {self.index_currently_taking_from_atl_ok} else { self.index_currently_taking_from_btl_only};
                 changes.transfer(parcel.clone(),self.from_sources[index_currently_taking_from],recipient);
-                if self.available_to_take_from_sources[index_currently_taking_from].get_votes::<Rules>(parcel.clone(),may_be_atl).is_none() { return false; } // unlikely
+                if self.available_to_take_from_sources[index_currently_taking_from].get_votes(rules,parcel.clone(),may_be_atl).is_none() { return false; } // unlikely
                 togo-=parcel.clone();
             } else { return false; }
         }
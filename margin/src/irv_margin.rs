@@ -0,0 +1,338 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Exact margins for single winner instant runoff voting (IRV) elections.
+//!
+//! The heuristics in [crate::find_outcome_changes] only give upper bounds on the margin. For IRV the margin can
+//! be computed exactly with the branch and bound algorithm of Magrino, Rivest, Shen and Wagner, "Computing the
+//! Margin of Victory in IRV Elections" (2011). The search is over elimination orders, built up from the end.
+//! The root of each tree is an alternative winner, and each child puts one more candidate just before the
+//! others. For each node, the linear programming relaxation of the minimum number of ballots that need to be changed
+//! for the last candidates to be eliminated in that order is a lower bound for all its descendants. A complete
+//! elimination order is solved exactly as an integer program.
+//!
+//! A ballot change replaces a ballot by one with any preferences. The manipulated count is required to have no
+//! ties at any exclusion, so the result does not rely on how ties are resolved.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use microlp::{ComparisonOp, OptimizationDirection, Problem, Variable};
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_pile::BallotPaperCount;
+use stv::count_error::CountError;
+use stv::election_data::ElectionData;
+use stv::preference_distribution::PreferenceDistributionRules;
+use stv::prepared_election::{PreparedElection, VoteDeltas};
+use stv::random_util::Randomness;
+use stv::transfer_value::TransferValue;
+use crate::choose_votes::TakeVotes;
+use crate::record_changes::{ElectionChange, ElectionChanges};
+use crate::retroscope::RetroscopeVoteIndex;
+use crate::vote_changes::{BallotChanges, BallotChangeSimilar, BallotsFromCandidateWithGivenTransferValue};
+
+#[derive(thiserror::Error, Debug)]
+pub enum IRVMarginError {
+    #[error("Exact IRV margins need exactly one vacancy, not {0}")]
+    NotSingleWinner(NumberOfCandidates),
+    #[error("There is no other candidate who could win")]
+    NoAlternativeWinner,
+    #[error("Exact IRV margins can only be computed for up to {max} candidates, not {candidates}")]
+    TooManyCandidates{candidates:usize,max:usize},
+    #[error("{0} ballots is too many for the integer program solver")]
+    TooManyBallots(usize),
+    #[error("No change to the ballots can alter the winner without ties")]
+    NoManipulationPossible,
+    #[error("Linear program solver failed : {0}")]
+    Solver(#[from] microlp::Error),
+    #[error("The integer program solution removed {removed} ballots but added {added}")]
+    InconsistentSolution{removed:usize,added:usize},
+    #[error("The manipulation found did not change the winner when recounted")]
+    WitnessDidNotChangeOutcome,
+    #[error("{0}")]
+    Count(#[from] CountError),
+}
+
+/// The maximum number of continuing candidates [find_exact_irv_margin] will consider.
+pub const MAX_IRV_MARGIN_CANDIDATES : usize = 20;
+
+/// The result of [find_exact_irv_margin].
+#[derive(Clone,Debug)]
+pub struct IRVMargin<Tally:Clone> {
+    /// The minimum number of ballots that need to be changed to alter the winner.
+    pub margin : BallotPaperCount,
+    /// The order in which candidates are excluded in the manipulated election, the new winner last.
+    pub elimination_order : Vec<CandidateIndex>,
+    /// A manipulation changing `margin` ballots, which has been checked by recounting.
+    pub changes : ElectionChanges<Tally>,
+    /// The number of nodes in the search tree that were expanded.
+    pub nodes_expanded : usize,
+}
+
+/// Some identical ballots that could be changed.
+struct IRVBallot {
+    /// The index of the vote, ATLs first then BTLs, as in [RetroscopeVoteIndex].
+    index : usize,
+    n : usize,
+    /// The preferences, ignoring excluded candidates. Never empty.
+    preferences : Vec<CandidateIndex>,
+}
+
+/// Ballots that count for the same candidates in every round of some elimination order.
+struct Signature {
+    /// Indices into the order of the candidates the ballots count for, in increasing order.
+    counts_for : Vec<usize>,
+    /// Indices into [IRVMarginSearch::ballots].
+    ballots : Vec<usize>,
+    n : usize,
+}
+
+impl Signature {
+    /// The index into the order of the candidate the ballots count for when order[round..] are the remaining candidates.
+    fn counts_for_in_round(counts_for:&[usize],round:usize) -> Option<usize> { counts_for.iter().copied().find(|&c|c>=round) }
+
+    /// The signatures worth considering for a replacement ballot when there are `len` candidates in the order.
+    ///
+    /// A replacement ballot may as well finish with the winner, as extra votes for the winner never hurt. A preference for
+    /// the candidate excluded in the round straight after the previous preference (or in the first round) only ever counts
+    /// for that candidate in the round they are excluded, so dropping it is never worse. This leaves a Fibonacci number
+    /// of signatures rather than `2^(len-1)`.
+    fn replacement_types(len:usize) -> Vec<Vec<usize>> {
+        let last = len-1;
+        let mut types = vec![];
+        let mut stack : Vec<Vec<usize>> = vec![vec![]];
+        while let Some(prefix) = stack.pop() {
+            let next = prefix.last().map(|&p|p+2).unwrap_or(1);
+            for p in next..last { let mut longer = prefix.clone(); longer.push(p); stack.push(longer); }
+            let mut finished = prefix;
+            finished.push(last);
+            types.push(finished);
+        }
+        types
+    }
+}
+
+/// The minimum number of ballot changes needed for an elimination order.
+struct Distance {
+    /// The number of ballots changed; a lower bound if the relaxation was solved.
+    changes : usize,
+    /// The number of ballots of each signature to remove, and the preferences and number of ballots to replace them by. Empty for the relaxation.
+    removed : Vec<(Vec<usize>,usize)>,
+    added : Vec<(Vec<CandidateIndex>,usize)>,
+}
+
+struct IRVMarginSearch {
+    ballots : Vec<IRVBallot>,
+}
+
+/// The last candidates eliminated, in order, as indices into the continuing candidates, waiting to be expanded by the search in [find_exact_irv_margin].
+#[derive(PartialEq,Eq)]
+struct SearchNode {
+    /// A lower bound on the number of ballots that need to be changed for any elimination order ending in [Self::order].
+    lower_bound : usize,
+    order : Vec<usize>,
+}
+
+impl Ord for SearchNode {
+    /// The node to expand first is the greatest, as [BinaryHeap] pops the greatest. This is the one with the lowest bound,
+    /// preferring deeper nodes amongst those with the same bound.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.lower_bound.cmp(&self.lower_bound).then_with(||self.order.len().cmp(&other.order.len())).then_with(||other.order.cmp(&self.order))
+    }
+}
+
+impl PartialOrd for SearchNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl IRVMarginSearch {
+    fn new(data:&ElectionData) -> Self {
+        let excluded : HashSet<CandidateIndex> = data.metadata.excluded.iter().cloned().collect();
+        let mut ballots = vec![];
+        let mut add = |index:usize,n:usize,preferences:Vec<CandidateIndex>| {
+            let preferences : Vec<CandidateIndex> = preferences.into_iter().filter(|c|!excluded.contains(c)).collect();
+            if n>0 && !preferences.is_empty() { ballots.push(IRVBallot{ index, n, preferences }); }
+        };
+        for (i,atl) in data.atl.iter().enumerate() { add(i,atl.n,atl.resolve_to_candidates(&data.metadata)); }
+        for (i,btl) in data.btl.iter().enumerate() { add(data.atl.len()+i,btl.n,btl.candidates.clone()); }
+        IRVMarginSearch{ ballots }
+    }
+
+    fn signatures(&self,order:&[CandidateIndex]) -> Vec<Signature> {
+        let position : HashMap<CandidateIndex,usize> = order.iter().enumerate().map(|(i,&c)|(c,i)).collect();
+        let mut signatures : Vec<Signature> = vec![];
+        let mut signature_index : HashMap<Vec<usize>,usize> = HashMap::new();
+        for (ballot_index,ballot) in self.ballots.iter().enumerate() {
+            let mut counts_for = vec![];
+            for c in &ballot.preferences {
+                if let Some(&p) = position.get(c) {
+                    if counts_for.last().map(|&last|p>last).unwrap_or(true) { counts_for.push(p); }
+                    if p+1==order.len() { break; }
+                }
+            }
+            let index = *signature_index.entry(counts_for.clone()).or_insert_with(||{ signatures.push(Signature{ counts_for, ballots: vec![], n: 0 }); signatures.len()-1 });
+            signatures[index].ballots.push(ballot_index);
+            signatures[index].n+=ballot.n;
+        }
+        signatures
+    }
+
+    /// The minimum number of ballots to change such that, after all candidates not in `order` are excluded,
+    /// the candidates in `order` are excluded in that order, the last one winning. None if impossible.
+    /// If `exact` is false, only the linear programming relaxation is solved, giving a lower bound.
+    fn distance_to(&self,order:&[CandidateIndex],exact:bool) -> Result<Option<Distance>,IRVMarginError> {
+        let signatures = self.signatures(order);
+        let total : usize = signatures.iter().map(|s|s.n).sum();
+        let mut problem = Problem::new(OptimizationDirection::Minimize);
+        // every signature has at most the total number of ballots, so this bounds all the variables.
+        let max_ballots = i32::try_from(total).map_err(|_|IRVMarginError::TooManyBallots(total))?;
+        let mut new_var = |objective:f64,max:usize| {
+            let max = i32::try_from(max).unwrap_or(max_ballots).min(max_ballots);
+            if exact { problem.add_integer_var(objective,(0,max)) } else { problem.add_var(objective,(0.0,f64::from(max))) }
+        };
+        let removed : Vec<Variable> = signatures.iter().map(|s|new_var(1.0,s.n)).collect();
+        let last = order.len()-1;
+        let added_types = Signature::replacement_types(order.len());
+        let added : Vec<Variable> = added_types.iter().map(|_|new_var(0.0,total)).collect();
+        problem.add_constraint(removed.iter().map(|&v|(v,1.0)).chain(added.iter().map(|&v|(v,-1.0))).collect::<Vec<_>>(),ComparisonOp::Eq,0.0);
+        for round in 0..last {
+            for other in round+1..order.len() {
+                // tally(order[round]) - tally(order[other]) <= -1, where a tally is the original ballots less the removed plus the added.
+                let mut lhs : Vec<(Variable,f64)> = vec![];
+                let mut rhs : f64 = -1.0;
+                for (s,&v) in signatures.iter().zip(removed.iter()) {
+                    match Signature::counts_for_in_round(&s.counts_for,round) {
+                        Some(c) if c==round => { lhs.push((v,-1.0)); rhs-=s.n as f64; }
+                        Some(c) if c==other => { lhs.push((v,1.0)); rhs+=s.n as f64; }
+                        _ => {}
+                    }
+                }
+                for (t,&v) in added_types.iter().zip(added.iter()) {
+                    match Signature::counts_for_in_round(t,round) {
+                        Some(c) if c==round => { lhs.push((v,1.0)); }
+                        Some(c) if c==other => { lhs.push((v,-1.0)); }
+                        _ => {}
+                    }
+                }
+                problem.add_constraint(lhs,ComparisonOp::Le,rhs);
+            }
+        }
+        let solution = match problem.solve() {
+            Ok(solution) => solution,
+            Err(microlp::Error::Infeasible) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let changes = (solution.objective()-1e-6).ceil().max(0.0) as usize;
+        if !exact { return Ok(Some(Distance{ changes, removed: vec![], added: vec![] })); }
+        let value = |v:Variable| solution.var_value_rounded(v).max(0.0) as usize;
+        Ok(Some(Distance{
+            changes,
+            removed: signatures.into_iter().zip(removed).map(|(s,v)|(s.ballots,value(v))).filter(|(_,n)|*n>0).collect(),
+            added: added_types.iter().zip(added).map(|(t,v)|(t.iter().map(|&i|order[i]).collect(),value(v))).filter(|(_,n)|*n>0).collect(),
+        }))
+    }
+
+    /// Turn the solution of the integer program into concrete changes to ballots.
+    fn make_ballot_changes<Tally:From<BallotPaperCount>>(&self,distance:&Distance) -> Result<BallotChanges<Tally>,IRVMarginError> {
+        let total_removed : usize = distance.removed.iter().map(|(_,n)|*n).sum();
+        let total_added : usize = distance.added.iter().map(|(_,n)|*n).sum();
+        if total_removed!=total_added { return Err(IRVMarginError::InconsistentSolution{ removed: total_removed, added: total_added }); }
+        let mut removed = distance.removed.iter().flat_map(|(ballots,n)|{
+            let mut remaining = *n;
+            ballots.iter().filter_map(move |&b|{
+                let take = remaining.min(self.ballots[b].n);
+                remaining-=take;
+                if take>0 { Some((b,take)) } else { None }
+            })
+        });
+        let mut changes : Vec<(CandidateIndex,Vec<CandidateIndex>,Vec<TakeVotes>,usize)> = vec![];
+        let mut current_removed : Option<(usize,usize)> = None;
+        for (preferences,n) in &distance.added {
+            let mut wanted = *n;
+            while wanted>0 {
+                let (ballot,available) = match current_removed.take() { Some(r) => r, None => removed.next().ok_or(IRVMarginError::InconsistentSolution{ removed: total_removed, added: total_added })? };
+                let take = wanted.min(available);
+                if available>take { current_removed=Some((ballot,available-take)); }
+                wanted-=take;
+                let from_candidate = self.ballots[ballot].preferences[0];
+                let take_votes = TakeVotes{ from: RetroscopeVoteIndex(self.ballots[ballot].index), n: take };
+                match changes.iter_mut().find(|(c,p,_,_)|*c==from_candidate && p==preferences) {
+                    Some((_,_,ballots,total)) => { ballots.push(take_votes); *total+=take; }
+                    None => changes.push((from_candidate,preferences.clone(),vec![take_votes],take)),
+                }
+            }
+        }
+        let changes : Vec<BallotChangeSimilar<Tally>> = changes.into_iter().map(|(candidate,preferences,ballots,n)|BallotChangeSimilar{
+            n: BallotPaperCount(n),
+            tally: Tally::from(BallotPaperCount(n)),
+            from: Some(BallotsFromCandidateWithGivenTransferValue{ candidate, ballots, tv: TransferValue::one() }),
+            candidate_to: Some(preferences[0]),
+            preferences_to: Some(preferences),
+        }).collect();
+        let n = changes.iter().map(|c|c.n).sum();
+        Ok(BallotChanges{ changes, n })
+    }
+}
+
+/// Find the minimum number of ballots that need to be changed to alter the winner of a single winner election
+/// counted with IRV rules, and a manipulation achieving it.
+///
+/// The search is exponential in the number of candidates, but is usually fast for up to about ten candidates.
+/// Elections with more than [MAX_IRV_MARGIN_CANDIDATES] continuing candidates are rejected.
+pub fn find_exact_irv_margin<Rules:PreferenceDistributionRules>(rules:&Rules,data:&ElectionData,ballot_types_considered_unverifiable:&HashSet<String>,verbose:bool) -> Result<IRVMargin<Rules::Tally>,IRVMarginError>
+    where Rules::Tally : From<BallotPaperCount>
+{
+    let vacancies = data.metadata.vacancies.ok_or(CountError::MissingVacancies)?;
+    if vacancies!=NumberOfCandidates(1) { return Err(IRVMarginError::NotSingleWinner(vacancies)); }
+    let mut data = data.clone();
+    let transcript = PreparedElection::new(&data,None).distribute_preferences(rules,&VoteDeltas::default(),&mut Randomness::ReverseDonkeyVote)?;
+    data.metadata.results=Some(transcript.elected.clone());
+    let original_winner = *transcript.elected.first().ok_or(IRVMarginError::NoAlternativeWinner)?;
+    let candidates : Vec<CandidateIndex> = (0..data.metadata.candidates.len()).map(CandidateIndex).filter(|c|!data.metadata.excluded.contains(c)).collect();
+    if candidates.len()<2 { return Err(IRVMarginError::NoAlternativeWinner); }
+    if candidates.len()>MAX_IRV_MARGIN_CANDIDATES { return Err(IRVMarginError::TooManyCandidates{ candidates: candidates.len(), max: MAX_IRV_MARGIN_CANDIDATES }); }
+    let search = IRVMarginSearch::new(&data);
+    // best first search, preferring deeper nodes amongst those with the same lower bound.
+    let mut queue : BinaryHeap<SearchNode> = BinaryHeap::new();
+    for (i,&c) in candidates.iter().enumerate() {
+        if c!=original_winner { queue.push(SearchNode{ lower_bound: 0, order: vec![i] }); }
+    }
+    let mut best : Option<(Vec<CandidateIndex>,Distance)> = None;
+    let mut nodes_expanded = 0;
+    while let Some(SearchNode{ lower_bound, order }) = queue.pop() {
+        if let Some((_,best_distance)) = &best { if lower_bound>=best_distance.changes { break; } }
+        nodes_expanded+=1;
+        let order_candidates : Vec<CandidateIndex> = order.iter().map(|&i|candidates[i]).collect();
+        if order.len()==candidates.len() {
+            if let Some(distance) = search.distance_to(&order_candidates,true)? {
+                if best.as_ref().map(|(_,b)|distance.changes<b.changes).unwrap_or(true) {
+                    if verbose { println!("Found manipulation of {} ballots with elimination order {}",distance.changes,data.metadata.candidate_list_to_string(&order_candidates)); }
+                    best=Some((order_candidates,distance));
+                }
+            }
+        } else {
+            for i in 0..candidates.len() {
+                if order.contains(&i) { continue; }
+                let mut child = vec![i];
+                child.extend_from_slice(&order);
+                let child_candidates : Vec<CandidateIndex> = child.iter().map(|&i|candidates[i]).collect();
+                if let Some(distance) = search.distance_to(&child_candidates,false)? {
+                    let child_bound = distance.changes.max(lower_bound);
+                    if best.as_ref().map(|(_,b)|child_bound<b.changes).unwrap_or(true) {
+                        queue.push(SearchNode{ lower_bound: child_bound, order: child });
+                    }
+                }
+            }
+        }
+    }
+    let (elimination_order,distance) = best.ok_or(IRVMarginError::NoManipulationPossible)?;
+    if verbose { println!("Exact margin {} found after expanding {} nodes",distance.changes,nodes_expanded); }
+    let ballots : BallotChanges<Rules::Tally> = search.make_ballot_changes(&distance)?;
+    let deltas = ballots.see_effect(rules,&PreparedElection::new(&data,None))?;
+    if deltas.is_empty() { return Err(IRVMarginError::WitnessDidNotChangeOutcome); }
+    let mut changes = ElectionChanges::new(&data,ballot_types_considered_unverifiable);
+    changes.add_change(ElectionChange::new(deltas,ballots,&data,ballot_types_considered_unverifiable),verbose);
+    Ok(IRVMargin{ margin: BallotPaperCount(distance.changes), elimination_order, changes, nodes_expanded })
+}
@@ -0,0 +1,247 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Generate assertions for risk limiting audits of single winner instant runoff voting (IRV) elections,
+//! following Blom, Stuckey and Teague, "RAIRE: Risk-Limiting Audits for IRV Elections" (2019).
+//!
+//! If every assertion is true, then the reported winner won. Each assertion compares two tallies,
+//! so can be checked by a ballot level comparison or ballot polling audit. There are two types:
+//! * NEB (not eliminated before) : the winner of the assertion has more first preferences than the
+//!   loser can ever have while the winner is continuing, so the winner cannot be excluded before the loser.
+//! * NEN (not eliminated next) : when exactly the given candidates are continuing, the winner of the
+//!   assertion has more votes than the loser, so is not the next excluded.
+//!
+//! The difficulty of an assertion is the reciprocal of its diluted margin, roughly proportional to the number
+//! of ballots an audit needs to sample. Alternative outcomes are ruled out by searching over elimination orders
+//! built up from the end, as in [crate::irv_margin], choosing assertions that make the most difficult
+//! assertion as easy as possible.
+
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use stv::ballot_metadata::{CandidateIndex, ElectionMetadata, NumberOfCandidates};
+use stv::count_error::CountError;
+use stv::election_data::ElectionData;
+use stv::preference_distribution::PreferenceDistributionRules;
+use stv::random_util::Randomness;
+
+#[derive(thiserror::Error, Debug)]
+pub enum RaireError {
+    #[error("RAIRE assertions need exactly one vacancy, not {0}")]
+    NotSingleWinner(NumberOfCandidates),
+    #[error("No candidate was elected")]
+    NoWinner,
+    #[error("There are no ballots to audit")]
+    NoBallots,
+    #[error("The election is too close to audit : the elimination order {0:?} cannot be ruled out by any assertion")]
+    CannotRuleOut(Vec<CandidateIndex>),
+    #[error("{0}")]
+    Count(#[from] CountError),
+}
+
+/// A claim about the tallies in an IRV count.
+#[derive(Clone,Debug,Serialize,Deserialize,PartialEq,Eq,Hash)]
+#[serde(tag = "type")]
+pub enum Assertion {
+    /// `winner` cannot be excluded before `loser`, as `winner`'s first preferences exceed every tally `loser` could have while `winner` is continuing.
+    NEB{ winner:CandidateIndex, loser:CandidateIndex },
+    /// When exactly the candidates in `continuing` are continuing, `winner` has more votes than `loser`, so is not excluded next.
+    NEN{ winner:CandidateIndex, loser:CandidateIndex, continuing:Vec<CandidateIndex> },
+}
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct AssertionWithMargin {
+    pub assertion : Assertion,
+    /// The winner's tally less the loser's tally, in ballots.
+    pub margin : usize,
+    /// The margin divided by the number of ballots in the audit universe.
+    pub diluted_margin : f64,
+}
+
+/// The assertions for an audit, as written to a .raire file.
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct RaireAssertions {
+    pub metadata : ElectionMetadata,
+    /// The reported winner that the assertions imply.
+    pub winner : CandidateIndex,
+    /// The number of ballots the audit samples from, used to compute diluted margins.
+    pub total_auditable_ballots : usize,
+    /// The reciprocal of the smallest diluted margin.
+    pub difficulty : f64,
+    pub assertions : Vec<AssertionWithMargin>,
+}
+
+/// The ballots, reduced to the information needed to compute tallies.
+struct RaireBallots {
+    num_candidates : usize,
+    /// Sets of identical preferences, ignoring excluded candidates, with the number of them.
+    ballots : Vec<(Vec<CandidateIndex>,usize)>,
+    total : usize,
+}
+
+impl RaireBallots {
+    fn new(data:&ElectionData) -> Self {
+        let excluded : HashSet<CandidateIndex> = data.metadata.excluded.iter().cloned().collect();
+        let mut ballots = vec![];
+        let mut total = 0;
+        let mut add = |preferences:Vec<CandidateIndex>,n:usize| {
+            total+=n;
+            let preferences : Vec<CandidateIndex> = preferences.into_iter().filter(|c|!excluded.contains(c)).collect();
+            if n>0 && !preferences.is_empty() { ballots.push((preferences,n)); }
+        };
+        for atl in &data.atl { add(atl.resolve_to_candidates(&data.metadata),atl.n); }
+        for btl in &data.btl { add(btl.candidates.clone(),btl.n); }
+        RaireBallots{ num_candidates: data.metadata.candidates.len(), ballots, total }
+    }
+
+    /// The tally of each candidate when exactly the candidates in `continuing` are continuing.
+    fn tallies(&self,continuing:&[CandidateIndex]) -> Vec<usize> {
+        let mut tallies = vec![0;self.num_candidates];
+        for (preferences,n) in &self.ballots {
+            if let Some(c) = preferences.iter().find(|c|continuing.contains(c)) { tallies[c.0]+=n; }
+        }
+        tallies
+    }
+
+    /// The margin of NEB(winner,loser), if positive.
+    fn neb_margin(&self,winner:CandidateIndex,loser:CandidateIndex) -> Option<usize> {
+        let mut winner_tally = 0;
+        let mut loser_tally = 0;
+        for (preferences,n) in &self.ballots {
+            if preferences[0]==winner { winner_tally+=n; }
+            // the loser gets the ballot if it comes before the winner, when all other candidates before the loser are excluded.
+            if let Some(&c) = preferences.iter().find(|&&c|c==winner || c==loser) { if c==loser { loser_tally+=n; } }
+        }
+        winner_tally.checked_sub(loser_tally).filter(|&m|m>0)
+    }
+}
+
+/// An assertion that rules out an elimination order, with its difficulty.
+#[derive(Clone)]
+struct Attack {
+    assertion : Assertion,
+    margin : usize,
+}
+
+struct RaireSearch<'a> {
+    ballots : &'a RaireBallots,
+    candidates : Vec<CandidateIndex>,
+    /// All NEB assertions with positive margin.
+    neb : Vec<Attack>,
+    /// Assertions already chosen.
+    chosen : Vec<Attack>,
+}
+
+impl <'a> RaireSearch<'a> {
+    fn difficulty(&self,margin:usize) -> f64 { self.ballots.total as f64/margin as f64 }
+
+    /// Whether the assertion is inconsistent with `order` being the last candidates to be excluded, in order, with the winner last.
+    fn contradicts(assertion:&Assertion,order:&[CandidateIndex]) -> bool {
+        match assertion {
+            Assertion::NEB { winner, loser } => {
+                match (order.iter().position(|c|c==winner),order.iter().position(|c|c==loser)) {
+                    (Some(w),Some(l)) => w<l,
+                    (None,Some(_)) => true,
+                    _ => false,
+                }
+            }
+            Assertion::NEN { winner, continuing, .. } => order.len()>=continuing.len() && {
+                let tail = &order[order.len()-continuing.len()..];
+                tail[0]==*winner && tail.iter().all(|c|continuing.contains(c))
+            }
+        }
+    }
+
+    /// The easiest assertion contradicting `order`, if any.
+    fn best_attack(&self,order:&[CandidateIndex]) -> Option<Attack> {
+        let mut best : Option<Attack> = self.neb.iter().filter(|a|Self::contradicts(&a.assertion,order)).max_by_key(|a|a.margin).cloned();
+        if order.len()>=2 {
+            let tallies = self.ballots.tallies(order);
+            let eliminated = order[0];
+            if let Some(&loser) = order[1..].iter().min_by_key(|c|tallies[c.0]) {
+                if tallies[eliminated.0]>tallies[loser.0] {
+                    let margin = tallies[eliminated.0]-tallies[loser.0];
+                    if best.as_ref().map(|b|margin>b.margin).unwrap_or(true) {
+                        let mut continuing = order.to_vec();
+                        continuing.sort_by_key(|c|c.0);
+                        best=Some(Attack{ assertion: Assertion::NEN { winner: eliminated, loser, continuing }, margin });
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Find assertions ruling out every elimination order ending in `order`, with difficulty no more than necessary,
+    /// stopping early if an assertion with difficulty at most `bound` is found. Returns the maximum difficulty of the assertions used.
+    fn rule_out(&self,order:&[CandidateIndex],bound:f64,used:&mut Vec<Attack>) -> Result<f64,RaireError> {
+        if let Some(existing) = self.chosen.iter().chain(used.iter()).find(|a|Self::contradicts(&a.assertion,order)) {
+            let difficulty = self.difficulty(existing.margin);
+            if difficulty<=bound { return Ok(difficulty); }
+        }
+        let attack = self.best_attack(order);
+        let difficulty = attack.as_ref().map(|a|self.difficulty(a.margin)).unwrap_or(f64::INFINITY);
+        if difficulty>bound && order.len()<self.candidates.len() {
+            let mut children_used = vec![];
+            let mut children_difficulty : f64 = 0.0;
+            for &c in &self.candidates {
+                if order.contains(&c) { continue; }
+                let mut child = vec![c];
+                child.extend_from_slice(order);
+                match self.rule_out(&child,bound.max(children_difficulty),&mut children_used) {
+                    Ok(child_difficulty) => children_difficulty=children_difficulty.max(child_difficulty),
+                    Err(_) if attack.is_some() => children_difficulty=f64::INFINITY,
+                    Err(e) => return Err(e),
+                }
+                if children_difficulty>=difficulty { break; }
+            }
+            if children_difficulty<difficulty {
+                used.extend(children_used);
+                return Ok(children_difficulty);
+            }
+        }
+        match attack {
+            Some(attack) => { used.push(attack); Ok(difficulty) }
+            None => Err(RaireError::CannotRuleOut(order.to_vec())),
+        }
+    }
+}
+
+/// Generate RAIRE assertions showing that the winner of a single winner election counted by IRV rules won.
+///
+/// The audit universe is taken to be all formal ballots in `data`, including those with no preferences for eligible candidates.
+/// The search is exponential in the number of candidates in the worst case, but is usually fast.
+pub fn generate_raire_assertions<Rules:PreferenceDistributionRules+Default>(data:&ElectionData) -> Result<RaireAssertions,RaireError> {
+    let vacancies = data.metadata.vacancies.ok_or(CountError::MissingVacancies)?;
+    if vacancies!=NumberOfCandidates(1) { return Err(RaireError::NotSingleWinner(vacancies)); }
+    let transcript = data.distribute_preferences::<Rules>(&mut Randomness::ReverseDonkeyVote)?;
+    let winner = *transcript.elected.first().ok_or(RaireError::NoWinner)?;
+    let ballots = RaireBallots::new(data);
+    if ballots.total==0 { return Err(RaireError::NoBallots); }
+    let candidates : Vec<CandidateIndex> = (0..data.metadata.candidates.len()).map(CandidateIndex).filter(|c|!data.metadata.excluded.contains(c)).collect();
+    let mut neb = vec![];
+    for &w in &candidates {
+        for &l in &candidates {
+            if w!=l { if let Some(margin) = ballots.neb_margin(w,l) { neb.push(Attack{ assertion: Assertion::NEB { winner: w, loser: l }, margin }); } }
+        }
+    }
+    let mut search = RaireSearch{ ballots: &ballots, candidates: candidates.clone(), neb, chosen: vec![] };
+    let alternatives : Vec<CandidateIndex> = candidates.iter().copied().filter(|&c|c!=winner).collect();
+    // First find the overall difficulty, then redo the search allowing any assertion up to that difficulty, which usually needs fewer assertions.
+    let mut difficulty : f64 = 0.0;
+    for &c in &alternatives {
+        difficulty=difficulty.max(search.rule_out(&[c],difficulty,&mut vec![])?);
+    }
+    for &c in &alternatives {
+        let mut used = vec![];
+        search.rule_out(&[c],difficulty,&mut used)?;
+        for attack in used {
+            if !search.chosen.iter().any(|a|a.assertion==attack.assertion) { search.chosen.push(attack); }
+        }
+    }
+    let total = ballots.total;
+    let assertions = search.chosen.into_iter().map(|a|AssertionWithMargin{ assertion: a.assertion, margin: a.margin, diluted_margin: a.margin as f64/total as f64 }).collect();
+    Ok(RaireAssertions{ metadata: data.metadata.clone(), winner, total_auditable_ballots: total, difficulty, assertions })
+}
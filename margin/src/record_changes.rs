@@ -12,7 +12,9 @@ use std::collections::HashSet;
 use std::fmt::{Debug, Display};
 use std::str::FromStr;
 use stv::compare_transcripts::DeltasInCandidateLists;
+use stv::count_error::CountError;
 use stv::election_data::ElectionData;
+use stv::prepared_election::PreparedElection;
 use crate::vote_changes::BallotChanges;
 use serde::Serialize;
 use serde::Deserialize;
@@ -175,12 +177,15 @@ impl <Tally:Clone> ElectionChanges<Tally> {
 
 impl <Tally:PartialEq+Clone+Display+FromStr+Debug> ElectionChanges<Tally> {
     /// Add in a (suspicious, possible old) extra data structure, reevaluating everything
-    pub fn merge_reevaluating<R:PreferenceDistributionRules<Tally=Tally>>(&mut self,other:&[ElectionChange<Tally>],election_data:&ElectionData,ballot_types_considered_unverifiable:&HashSet<String>,verbose:bool) {
+    pub fn merge_reevaluating<R:PreferenceDistributionRules<Tally=Tally>>(&mut self,rules:&R,other:&[ElectionChange<Tally>],election_data:&ElectionData,ballot_types_considered_unverifiable:&HashSet<String>,verbose:bool) -> Result<(),CountError> {
+        let prepared = PreparedElection::new(election_data,None);
         for v in other {
-            let deltas  : DeltasInCandidateLists = v.ballots.see_effect::<R>(election_data);
+            let deltas  : DeltasInCandidateLists = v.ballots.see_effect(rules,&prepared)?;
             if !deltas.is_empty() {
                 self.add_change(ElectionChange::new(deltas,v.ballots.clone(),election_data,ballot_types_considered_unverifiable),verbose);
             }
         }
+
+        Ok(())
     }
 }
\ No newline at end of file
@@ -15,6 +15,7 @@ use stv::ballot_metadata::{CandidateIndex, ElectionMetadata, NumberOfCandidates}
 use stv::distribution_of_preferences_transcript::{CountIndex, ReasonForCount, SingleCount};
 use stv::election_data::ElectionData;
 use stv::transfer_value::TransferValue;
+use stv::count_error::CountError;
 use crate::choose_votes::{ChooseVotes, ChooseVotesOptions};
 use serde::Serialize;
 use serde::Deserialize;
@@ -22,6 +23,7 @@ use serde::Deserialize;
 /// A tool to help rerun an election given a transcript.
 /// It lets you tell which candidate a particular person's votes were with, and with what transfer value.
 /// You go continuously through the transcript, adding counts, and the retroscope will update the vote's data structures.
+#[derive(Clone)]
 pub struct Retroscope {
     /// the count we have processed. The last count sent to "apply()". Fields below are as at the end of this count
     pub count : CountIndex,
@@ -38,7 +40,7 @@ pub struct Retroscope {
     pub piles_by_candidate: Vec<RetroscopeVotePileForCandidate>,
 }
 
-#[derive(Default)]
+#[derive(Default,Clone)]
 pub struct RetroscopeVotes {
     pub atl : Vec<RetroscopeVoteStatus>,
     pub btl : Vec<RetroscopeVoteStatus>,
@@ -51,6 +53,7 @@ impl RetroscopeVotes {
     }
 }
 /// Information corresponding to a particular ATL or BTL structure.
+#[derive(Clone)]
 pub struct RetroscopeVoteStatus {
     /// Whether this vote is currently in a pile or not.
     pub pile_status : PileStatus,
@@ -86,7 +89,7 @@ impl fmt::Debug for RetroscopeVoteIndex {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "#{}", self.0) }
 }
 
-#[derive(Default,Debug)]
+#[derive(Default,Debug,Clone)]
 pub struct RetroscopeVotePileForCandidate {
     pub by_count : HashMap<CountIndex,Vec<RetroscopeVoteIndex>>,
 }
@@ -144,7 +147,8 @@ impl Retroscope {
         }
     }
     /// Apply the given count to the Retroscope. Update all the internal fields.
-    pub fn apply<Tally:PartialEq+Clone+Display+FromStr>(&mut self,count:CountIndex,transcript:&SingleCount<Tally>) {
+    /// Fails for keep value based methods like Meek's, where a vote does not sit in a single candidate's pile.
+    pub fn apply<Tally:PartialEq+Clone+Display+FromStr>(&mut self,count:CountIndex,transcript:&SingleCount<Tally>) -> Result<(),CountError> {
         if count.0>0 && self.count.0+1!=count.0 { panic!("Counts must be processed in order without skipping any.")}
         self.count=count;
         for c in &transcript.not_continuing {
@@ -155,12 +159,14 @@ impl Retroscope {
             ReasonForCount::FirstPreferenceCount => { self.first_preferences(count); }
             ReasonForCount::ExcessDistribution(c) => { self.update(count,transcript.reason_completed,&[*c],&transcript.portion.papers_came_from_counts); }
             ReasonForCount::Elimination(c) => { self.update(count,transcript.reason_completed,c,&transcript.portion.papers_came_from_counts); }
+            ReasonForCount::ExcessDistributionByKeepValues(_) => { return Err(CountError::KeepValuesNotSupported{ count }); }
         }
         // elections occur after distribution.
         for c in &transcript.elected {
             self.continuing.remove(&c.who);
             self.elected.push(c.who);
         }
+        Ok(())
     }
 
     /// Get the transfer value that votes arrived at in a given count.
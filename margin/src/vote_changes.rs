@@ -22,12 +22,15 @@ use serde::Deserialize;
 use stv::ballot_paper::{ATL, BTL};
 use stv::ballot_pile::BallotPaperCount;
 use stv::compare_transcripts::{DeltasInCandidateLists, DifferentCandidateLists};
+use stv::count_error::CountError;
 use stv::election_data::ElectionData;
+use stv::incremental_recount::RecordedCount;
 use stv::preference_distribution::{PreferenceDistributionRules, RoundUpToUsize};
+use stv::prepared_election::{PreparedElection, VoteDeltas};
 use stv::random_util::Randomness;
 use stv::transfer_value::TransferValue;
 use crate::choose_votes::{BallotsWithGivenTransferValue, ChooseVotes, ChooseVotesOptions, TakeVotes};
-use crate::retroscope::{Retroscope};
+use crate::retroscope::{Retroscope, RetroscopeVoteIndex};
 
 /// A list of vote changes that may change the outcome of the election
 /// These are conceptual, measured in votes. There may be a larger number of ballot papers involved.
@@ -75,7 +78,7 @@ impl <Tally:Clone+RoundUpToUsize> VoteChanges<Tally> {
 }
 
 impl <Tally:Clone+AddAssign+SubAssign+From<BallotPaperCount>+Display+PartialEq+Serialize+FromStr+Ord+Sub<Output=Tally>+Zero+Hash+Sum<Tally>+RoundUpToUsize> VoteChanges<Tally> {
-    pub fn make_concrete<R:PreferenceDistributionRules<Tally=Tally>>(&self,retroscope:&Retroscope,election_data:&ElectionData,options:&ChooseVotesOptions) -> Option<BallotChanges<Tally>> {
+    pub fn make_concrete<R:PreferenceDistributionRules<Tally=Tally>>(&self,rules:&R,retroscope:&Retroscope,election_data:&ElectionData,options:&ChooseVotesOptions) -> Option<BallotChanges<Tally>> {
         let mut builder = BallotChangesBuilder{ map: HashMap::new() };
         let mut choosers : HashMap<CandidateIndex,ChooseVotes> = HashMap::new();
         let (atl_ok_changes,btl_only_changes):(Vec<_>,Vec<_>) = self.changes.iter().partition(|vc|vc.to.map(|c|retroscope.is_highest_continuing_member_party_ticket(c,&election_data.metadata)).unwrap_or(true));
@@ -83,8 +86,8 @@ impl <Tally:Clone+AddAssign+SubAssign+From<BallotPaperCount>+Display+PartialEq+S
             if change.vote_value==Tally::zero() { continue; }
             if let Some(from) = change.from {
                 let chooser = choosers.entry(from).or_insert_with(||retroscope.get_chooser(from,election_data,options));
-                // println!("Trying to find {} votes from {} allowing ATL: {} Total available : {}   BTL available : {}",change.vote_value,from,allow_atl,chooser.votes_available_total::<R>(),chooser.votes_available_btl::<R>());
-                if let Some(ballots) = chooser.get_votes::<R>(change.vote_value.clone(),allow_atl) {
+                // println!("Trying to find {} votes from {} allowing ATL: {} Total available : {}   BTL available : {}",change.vote_value,from,allow_atl,chooser.votes_available_total(rules),chooser.votes_available_btl(rules));
+                if let Some(ballots) = chooser.get_votes(rules,change.vote_value.clone(),allow_atl) {
                     for b in ballots {
                         builder.add(change.from,change.to,b);
                     }
@@ -130,7 +133,8 @@ impl <Tally:AddAssign> BallotChangesBuilder<Tally> {
                 n: value.n,
                 tally: value.tally,
                 from: if let Some((tv,candidate)) = key.from { Some(BallotsFromCandidateWithGivenTransferValue{ candidate,ballots:value.ballots,tv})} else {None},
-                candidate_to: key.to
+                candidate_to: key.to,
+                preferences_to: None,
             }
         }).collect();
         // do a series of stable sorts to sort by first who from, then who to, then TV.
@@ -195,6 +199,10 @@ pub struct BallotChangeSimilar<Tally> {
     pub tally: Tally,
     pub from : Option<BallotsFromCandidateWithGivenTransferValue>,
     pub candidate_to : Option<CandidateIndex>,
+    /// If present, the changed (or added) ballots have exactly these preferences, which start with candidate_to,
+    /// rather than being the original ballots with candidate_to substituted for the from candidate.
+    #[serde(default,skip_serializing_if = "Option::is_none")]
+    pub preferences_to : Option<Vec<CandidateIndex>>,
 }
 
 /// A concrete set of ballot level changes.
@@ -214,28 +222,31 @@ impl <Tally> BallotChanges<Tally> {
                 for wv in &from.ballots {
                     if wv.from.0 < num_atl { // It is an ATL vote
                         data.atl[wv.from.0].n -= wv.n;
-                        if let Some(to) = change.candidate_to {
-                            let from_party = election_data.metadata.candidate(from.candidate).party.unwrap(); // must have a party or couldn't be in an ATL vote.
-                            if let Some(to_party) = election_data.metadata.candidate(to).party {
-                                let new_parties: Vec<PartyIndex> = data.atl[wv.from.0].parties.iter().filter(|&&c| c != to_party).map(|&c| if c == from_party { to_party } else { c }).collect();
-                                if verbose {
-                                    println!("Changed {} ATL from [{}] to [{}]", wv.n, data.metadata.party_list_to_string(&data.atl[wv.from.0].parties), data.metadata.party_list_to_string(&new_parties));
-                                }
-                                data.atl.push(ATL { parties: new_parties, n: wv.n, ticket_index: if data.atl[wv.from.0].ticket_index.is_some() { Some(0)} else {None} }) // the ticket index is a hack, and is not accurate. The margin computation is not designed for ticket ATL modifications.
-                            } else {
-                                panic!("Candidate {} got ATL vote but doesn't have a party.", election_data.metadata.candidate(from.candidate).name);
+                        if let Some(preferences) = change.preferences_to.as_ref() {
+                            if verbose {
+                                println!("Changed {} ATL from [{}] to BTL [{}]", wv.n, data.metadata.party_list_to_string(&data.atl[wv.from.0].parties), data.metadata.candidate_list_to_string(preferences));
+                            }
+                            data.btl.push(BTL { candidates: preferences.clone(), n: wv.n })
+                        } else if let Some(to) = change.candidate_to {
+                            let new_atl = Self::changed_atl(election_data,&election_data.atl[wv.from.0],from.candidate,to,wv.n);
+                            if verbose {
+                                println!("Changed {} ATL from [{}] to [{}]", wv.n, data.metadata.party_list_to_string(&data.atl[wv.from.0].parties), data.metadata.party_list_to_string(&new_atl.parties));
                             }
+                            data.atl.push(new_atl)
                         } else if verbose {
                             println!("Removed {} ATL votes [{}]", wv.n, data.metadata.party_list_to_string(&data.atl[wv.from.0].parties));
                         }
                     } else { // It is a BTL vote.
                         data.btl[wv.from.0 - num_atl].n -= wv.n;
                         if let Some(to) = change.candidate_to {
-                            let new_candidates: Vec<CandidateIndex> = data.btl[wv.from.0 - num_atl].candidates.iter().filter(|&&c| c != to).map(|&c| if c == from.candidate { to } else { c }).collect();
+                            let new_btl = match change.preferences_to.as_ref() {
+                                Some(preferences) => BTL { candidates: preferences.clone(), n: wv.n },
+                                None => Self::changed_btl(&election_data.btl[wv.from.0 - num_atl],from.candidate,to,wv.n),
+                            };
                             if verbose {
-                                println!("Changed {} BTL from [{}] to [{}]", wv.n, data.metadata.candidate_list_to_string(&data.btl[wv.from.0 - num_atl].candidates), data.metadata.candidate_list_to_string(&new_candidates));
+                                println!("Changed {} BTL from [{}] to [{}]", wv.n, data.metadata.candidate_list_to_string(&data.btl[wv.from.0 - num_atl].candidates), data.metadata.candidate_list_to_string(&new_btl.candidates));
                             }
-                            data.btl.push(BTL { candidates: new_candidates, n: wv.n })
+                            data.btl.push(new_btl)
                         } else if verbose {
                             println!("Removed {} BTL votes [{}]", wv.n, data.metadata.candidate_list_to_string(&data.btl[wv.from.0 - num_atl].candidates));
                         }
@@ -243,19 +254,98 @@ impl <Tally> BallotChanges<Tally> {
                 }
             } else {
                 if let Some(to) = change.candidate_to { // insert votes
-                    data.btl.push(BTL { candidates: vec![to], n: change.n.0 });
+                    data.btl.push(BTL { candidates: change.preferences_to.clone().unwrap_or_else(||vec![to]), n: change.n.0 });
                     if verbose {}
                 } else { eprintln!("Trying to do a vote change that does nothing."); } // don't actually do anything...
             }
         }
         data
     }
+
+    /// The same changes as [`Self::apply_to_votes`], as lightweight deltas to a prepared election rather than a modified copy of the data.
+    pub fn vote_deltas(&self, election: &PreparedElection) -> VoteDeltas {
+        let election_data = election.data;
+        let mut deltas = VoteDeltas::default();
+        let num_atl = election_data.atl.len();
+        for change in &self.changes {
+            if let Some(from) = change.from.as_ref() {
+                for wv in &from.ballots {
+                    if let Some(preferences) = change.preferences_to.as_ref() {
+                        deltas.change_btl(wv.from.0,BTL { candidates: preferences.clone(), n: wv.n });
+                    } else if let Some(to) = change.candidate_to {
+                        if wv.from.0 < num_atl { // It is an ATL vote
+                            deltas.change_atl(wv.from.0,Self::changed_atl(election_data,&election_data.atl[wv.from.0],from.candidate,to,wv.n),&election_data.metadata);
+                        } else {
+                            deltas.change_btl(wv.from.0,Self::changed_btl(&election_data.btl[wv.from.0 - num_atl],from.candidate,to,wv.n));
+                        }
+                    } else { deltas.remove(wv.from.0,wv.n); }
+                }
+            } else if let Some(to) = change.candidate_to { // insert votes
+                deltas.add_btl(BTL { candidates: change.preferences_to.clone().unwrap_or_else(||vec![to]), n: change.n.0 });
+            }
+        }
+        deltas
+    }
+
+    /// n votes like `original`, but with the party of candidate `from` replaced by the party of candidate `to`.
+    fn changed_atl(election_data: &ElectionData,original:&ATL,from:CandidateIndex,to:CandidateIndex,n:usize) -> ATL {
+        let from_party = election_data.metadata.candidate(from).party.unwrap(); // must have a party or couldn't be in an ATL vote.
+        if let Some(to_party) = election_data.metadata.candidate(to).party {
+            let new_parties: Vec<PartyIndex> = original.parties.iter().filter(|&&c| c != to_party).map(|&c| if c == from_party { to_party } else { c }).collect();
+            ATL { parties: new_parties, n, ticket_index: if original.ticket_index.is_some() { Some(0)} else {None} } // the ticket index is a hack, and is not accurate. The margin computation is not designed for ticket ATL modifications.
+        } else {
+            panic!("Candidate {} got ATL vote but doesn't have a party.", election_data.metadata.candidate(from).name);
+        }
+    }
+
+    /// n votes like `original`, but with candidate `from` replaced by candidate `to`.
+    fn changed_btl(original:&BTL,from:CandidateIndex,to:CandidateIndex,n:usize) -> BTL {
+        let new_candidates: Vec<CandidateIndex> = original.candidates.iter().filter(|&&c| c != to).map(|&c| if c == from { to } else { c }).collect();
+        BTL { candidates: new_candidates, n }
+    }
 }
+
+impl <Tally:Clone> BallotChanges<Tally> {
+    /// Combine these changes with `later` changes, which are relative to `intermediate`, the result of applying these changes to `original`
+    /// with [`Self::apply_to_votes`]. The result is relative to `original`.
+    ///
+    /// Returns None if `later` changes a vote that was created by these changes, as that cannot be expressed as a change to an original vote.
+    pub fn then(&self,later:&BallotChanges<Tally>,original:&ElectionData,intermediate:&ElectionData) -> Option<Self> {
+        let num_atl_original = original.atl.len();
+        let num_atl_intermediate = intermediate.atl.len();
+        let num_btl_original = original.btl.len();
+        let map_index = |index:usize| -> Option<usize> {
+            if index<num_atl_original { Some(index) }
+            else if index<num_atl_intermediate { None }
+            else if index-num_atl_intermediate<num_btl_original { Some(index-num_atl_intermediate+num_atl_original) }
+            else { None }
+        };
+        let mut changes = self.changes.clone();
+        for change in &later.changes {
+            let mut change = change.clone();
+            if let Some(from) = change.from.as_mut() {
+                for b in &mut from.ballots {
+                    b.from = RetroscopeVoteIndex(map_index(b.from.0)?);
+                }
+            }
+            changes.push(change);
+        }
+        Some(BallotChanges{ changes, n: self.n+later.n })
+    }
+}
+
 impl <Tally:PartialEq+Clone+Display+FromStr+Debug> BallotChanges<Tally> {
-    pub fn see_effect<R:PreferenceDistributionRules<Tally=Tally>>(&self, election_data:&ElectionData) -> DeltasInCandidateLists {
-        let changed_data = self.apply_to_votes(election_data,false);
-        let transcript = changed_data.distribute_preferences::<R>(&mut Randomness::ReverseDonkeyVote);
-        let diffs  : DeltasInCandidateLists = DifferentCandidateLists{ list1: transcript.elected.clone(), list2: election_data.metadata.results.as_ref().unwrap().clone() }.into();
-        diffs
+    /// Recount the election with these changes, and see how the elected candidates differ from the official results.
+    pub fn see_effect<R:PreferenceDistributionRules<Tally=Tally>>(&self, rules:&R, election:&PreparedElection) -> Result<DeltasInCandidateLists,CountError> {
+        let transcript = election.distribute_preferences(rules,&self.vote_deltas(election),&mut Randomness::ReverseDonkeyVote)?;
+        let diffs  : DeltasInCandidateLists = DifferentCandidateLists{ list1: transcript.elected.clone(), list2: election.data.metadata.results.as_ref().unwrap().clone() }.into();
+        Ok(diffs)
+    }
+    /// Like [`Self::see_effect`], but only recount from the first count affected by the changes, using a recorded count of the original election.
+    /// The recorded count should use [Randomness::ReverseDonkeyVote].
+    pub fn see_effect_incremental<R:PreferenceDistributionRules<Tally=Tally>>(&self, recorded:&RecordedCount<R>) -> Result<DeltasInCandidateLists,CountError> {
+        let transcript = recorded.recount(&self.vote_deltas(recorded.election()))?;
+        let diffs  : DeltasInCandidateLists = DifferentCandidateLists{ list1: transcript.elected.clone(), list2: recorded.election().data.metadata.results.as_ref().unwrap().clone() }.into();
+        Ok(diffs)
     }
 }
\ No newline at end of file
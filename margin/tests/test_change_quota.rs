@@ -0,0 +1,50 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Test searching for changes with a quota other than the one specified by the rules.
+
+use std::collections::HashSet;
+use federal::FederalRulesPost2021;
+use margin::choose_votes::ChooseVotesOptions;
+use margin::find_outcome_changes::{find_composed_outcome_changes, find_outcome_changes, SearchBudget};
+use stv::ballot_metadata::CandidateIndex;
+use stv::ballot_paper::BTL;
+use stv::election_data::ElectionData;
+use stv::prepared_election::{PreparedElection, VoteDeltas};
+use stv::quota::QuotaMethod;
+use stv::random_util::Randomness;
+
+/// A Droop quota elects A then B on A's surplus, but with a Hare quota no one gets a quota, B is excluded, and A and C are elected.
+fn make_election(quota_method:Option<QuotaMethod>) -> ElectionData {
+    let mut data = ElectionData::from_btl(&["A","B","C"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(1)], n: 45 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 25 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 30 },
+    ]);
+    let transcript = PreparedElection::new(&data,None).with_quota_method(quota_method).distribute_preferences(&FederalRulesPost2021::default(),&VoteDeltas::default(),&mut Randomness::ReverseDonkeyVote).unwrap();
+    data.metadata.results=Some(transcript.elected);
+    data
+}
+
+fn options() -> ChooseVotesOptions {
+    ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: HashSet::new(), allow_additions: true, allow_from: None, allow_to: None }
+}
+
+#[test]
+fn test_find_changes_with_quota_override() {
+    assert_eq!(make_election(None).metadata.results,Some(vec![CandidateIndex(0),CandidateIndex(1)]));
+    let data = make_election(Some(QuotaMethod::Hare));
+    let mut elected = data.metadata.results.clone().unwrap();
+    elected.sort_by_key(|c|c.0);
+    assert_eq!(elected,vec![CandidateIndex(0),CandidateIndex(2)]);
+    let hare = PreparedElection::new(&data,None).with_quota_method(Some(QuotaMethod::Hare));
+    let single = find_outcome_changes(&FederalRulesPost2021::default(),&data,&options(),false,None,Some(QuotaMethod::Hare)).unwrap();
+    let composed = find_composed_outcome_changes(&FederalRulesPost2021::default(),&data,&options(),2,false,Some(QuotaMethod::Hare),&SearchBudget::default()).unwrap();
+    assert!(!single.changes.is_empty());
+    for change in single.changes.iter().chain(composed.changes.iter()) {
+        assert_eq!(change.ballots.see_effect(&FederalRulesPost2021::default(),&hare).unwrap(),change.outcome);
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Test finding changes that combine manipulations at more than one count.
+
+use std::collections::HashSet;
+use margin::choose_votes::{ChooseVotesOptions, TakeVotes};
+use margin::find_outcome_changes::{find_composed_outcome_changes, find_outcome_changes, SearchBudget};
+use margin::irv_margin::find_exact_irv_margin;
+use margin::retroscope::RetroscopeVoteIndex;
+use margin::vote_changes::{BallotChangeSimilar, BallotChanges, BallotsFromCandidateWithGivenTransferValue};
+use nsw::SimpleIRVAnyDifferenceBreaksTies;
+use stv::ballot_metadata::CandidateIndex;
+use stv::ballot_paper::BTL;
+use stv::ballot_pile::BallotPaperCount;
+use stv::election_data::ElectionData;
+use stv::prepared_election::PreparedElection;
+use stv::random_util::Randomness;
+use stv::transfer_value::TransferValue;
+
+fn make_election() -> ElectionData {
+    let btl = |candidates:&[usize],n:usize| BTL{ candidates: candidates.iter().map(|&c|CandidateIndex(c)).collect(), n };
+    let mut data = ElectionData::from_btl(&["A","B","C","D","E"],1,vec![
+        btl(&[0],42),
+        btl(&[0],44),
+        btl(&[0,1,2],34),
+        btl(&[4,1],33),
+        btl(&[2],31),
+        btl(&[2,1,0],29),
+        btl(&[3],19),
+        btl(&[1],34),
+        btl(&[4,1],23),
+    ]);
+    let transcript = data.distribute_preferences::<SimpleIRVAnyDifferenceBreaksTies>(&mut Randomness::ReverseDonkeyVote).unwrap();
+    data.metadata.results=Some(transcript.elected);
+    data
+}
+
+fn options() -> ChooseVotesOptions {
+    ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: HashSet::new(), allow_additions: false, allow_from: None, allow_to: None }
+}
+
+#[test]
+fn test_composed_changes_are_smaller() {
+    let data = make_election();
+    let single = find_outcome_changes(&SimpleIRVAnyDifferenceBreaksTies::default(),&data,&options(),false,None,None).unwrap();
+    let composed = find_composed_outcome_changes(&SimpleIRVAnyDifferenceBreaksTies::default(),&data,&options(),2,false,None,&SearchBudget::default()).unwrap();
+    let single_size = single.smallest_manipulation_found().unwrap();
+    let composed_size = composed.smallest_manipulation_found().unwrap();
+    assert!(composed_size<single_size,"composed {} should be smaller than single count {}",composed_size,single_size);
+    // can't beat the exact margin.
+    let exact = find_exact_irv_margin(&SimpleIRVAnyDifferenceBreaksTies::default(),&data,&HashSet::new(),false).unwrap();
+    assert!(composed_size>=exact.margin);
+    // every change found really does change the outcome, and none is dominated by another.
+    let prepared = PreparedElection::new(&data,None);
+    for change in &composed.changes {
+        assert_eq!(change.ballots.see_effect(&SimpleIRVAnyDifferenceBreaksTies::default(),&prepared).unwrap(),change.outcome);
+        for other in &composed.changes {
+            if !std::ptr::eq(change,other) { assert!(!change.is_dominated_by_or_equivalent_to(other)); }
+        }
+    }
+}
+
+#[test]
+fn test_then_maps_ballot_indices() {
+    let data = make_election();
+    let take = |from:usize,candidate:usize,n:usize| BallotsFromCandidateWithGivenTransferValue{ candidate: CandidateIndex(candidate), ballots: vec![TakeVotes{ from: RetroscopeVoteIndex(from), n }], tv: TransferValue::one() };
+    let change = |from:BallotsFromCandidateWithGivenTransferValue,to:usize| BallotChanges{ changes: vec![BallotChangeSimilar{ n: BallotPaperCount(from.ballots[0].n), tally: from.ballots[0].n, from: Some(from), candidate_to: Some(CandidateIndex(to)), preferences_to: None }], n: BallotPaperCount(1) };
+    let first : BallotChanges<usize> = change(take(0,0,1),3);
+    let intermediate = first.apply_to_votes(&data,false);
+    assert_eq!(intermediate.btl.len(),data.btl.len()+1);
+    // a later change to an original vote is fine.
+    let combined = first.then(&change(take(4,2,1),3),&data,&intermediate).unwrap();
+    assert_eq!(combined.n,BallotPaperCount(2));
+    assert_eq!(combined.changes[1].from.as_ref().unwrap().ballots[0].from.0,4);
+    assert_eq!(combined.apply_to_votes(&data,false).btl.iter().filter(|b|b.candidates==vec![CandidateIndex(3)]).map(|b|b.n).sum::<usize>(),19+2);
+    // but a later change to the vote created by the first change can't be expressed relative to the original.
+    assert!(first.then(&change(take(data.btl.len(),3,1),2),&data,&intermediate).is_none());
+}
@@ -0,0 +1,72 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Test exact IRV margin computation.
+
+use std::collections::HashSet;
+use margin::irv_margin::{find_exact_irv_margin, IRVMarginError, MAX_IRV_MARGIN_CANDIDATES};
+use nsw::SimpleIRVAnyDifferenceBreaksTies;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::count_error::CountError;
+use stv::ballot_pile::BallotPaperCount;
+use stv::election_data::ElectionData;
+use stv::prepared_election::PreparedElection;
+
+fn make_election() -> ElectionData {
+    ElectionData::from_btl(&["A","B","C"],1,vec![
+        BTL{ candidates: vec![CandidateIndex(0)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 35 },
+        BTL{ candidates: vec![CandidateIndex(2),CandidateIndex(1)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 5 },
+    ])
+}
+
+#[test]
+fn test_exact_irv_margin() {
+    let data = make_election();
+    // C is excluded first, and B beats A 55 to 40. Making A win by changing votes from B to A needs 8 changes,
+    // but changing 6 votes from B to C gets B excluded first (29 to C's 31), after which A beats C 40 to 31.
+    let margin = find_exact_irv_margin(&SimpleIRVAnyDifferenceBreaksTies::default(),&data,&HashSet::new(),false).unwrap();
+    assert_eq!(margin.margin,BallotPaperCount(6));
+    assert_eq!(margin.elimination_order,vec![CandidateIndex(1),CandidateIndex(2),CandidateIndex(0)]);
+    assert_eq!(margin.changes.changes.len(),1);
+    let change = &margin.changes.changes[0];
+    assert_eq!(change.ballots.n,BallotPaperCount(6));
+    assert_eq!(change.outcome.list1only,vec![CandidateIndex(0)]);
+    assert_eq!(change.outcome.list2only,vec![CandidateIndex(1)]);
+    // check the witness independently.
+    let mut data_with_results = data.clone();
+    data_with_results.metadata.results=Some(vec![CandidateIndex(1)]);
+    let recount = change.ballots.see_effect(&SimpleIRVAnyDifferenceBreaksTies::default(),&PreparedElection::new(&data_with_results,None)).unwrap();
+    assert_eq!(recount,change.outcome);
+}
+
+#[test]
+fn test_exact_irv_margin_needs_single_winner() {
+    let mut data = make_election();
+    data.metadata.vacancies=Some(NumberOfCandidates(2));
+    assert!(find_exact_irv_margin(&SimpleIRVAnyDifferenceBreaksTies::default(),&data,&HashSet::new(),false).is_err());
+}
+
+#[test]
+fn test_exact_irv_margin_needs_vacancies() {
+    let mut data = make_election();
+    data.metadata.vacancies=None;
+    assert!(matches!(find_exact_irv_margin(&SimpleIRVAnyDifferenceBreaksTies::default(),&data,&HashSet::new(),false),Err(IRVMarginError::Count(CountError::MissingVacancies))));
+}
+
+#[test]
+fn test_exact_irv_margin_too_many_candidates() {
+    let names : Vec<String> = (0..=MAX_IRV_MARGIN_CANDIDATES).map(|i|format!("C{}",i)).collect();
+    let names : Vec<&str> = names.iter().map(|s|s.as_str()).collect();
+    let btl = (0..names.len()).map(|i|BTL{ candidates: vec![CandidateIndex(i)], n: 10+i }).collect();
+    let data = ElectionData::from_btl(&names,1,btl);
+    match find_exact_irv_margin(&SimpleIRVAnyDifferenceBreaksTies::default(),&data,&HashSet::new(),false) {
+        Err(IRVMarginError::TooManyCandidates{candidates,max}) => { assert_eq!(candidates,MAX_IRV_MARGIN_CANDIDATES+1); assert_eq!(max,MAX_IRV_MARGIN_CANDIDATES); }
+        other => panic!("Expected too many candidates, got {:?}",other.map(|m|m.margin)),
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Test RAIRE assertion generation.
+
+use margin::raire::{generate_raire_assertions, Assertion, RaireAssertions, RaireError};
+use nsw::SimpleIRVAnyDifferenceBreaksTies;
+use stv::ballot_metadata::CandidateIndex;
+use stv::ballot_paper::BTL;
+use stv::count_error::CountError;
+use stv::election_data::ElectionData;
+
+fn make_election(btl:Vec<BTL>) -> ElectionData {
+    ElectionData::from_btl(&["A","B","C"],1,btl)
+}
+
+#[test]
+fn test_raire_assertions() {
+    // C is excluded first, and B beats A 55 to 40.
+    let data = make_election(vec![
+        BTL{ candidates: vec![CandidateIndex(0)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 35 },
+        BTL{ candidates: vec![CandidateIndex(2),CandidateIndex(1)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 5 },
+    ]);
+    let assertions = generate_raire_assertions::<SimpleIRVAnyDifferenceBreaksTies>(&data).unwrap();
+    assert_eq!(assertions.winner,CandidateIndex(1));
+    assert_eq!(assertions.total_auditable_ballots,100);
+    // B has 35 first preferences, C can get at most 25 votes while B is continuing. This also shows C cannot win.
+    // If C is excluded first, B beats A 55 to 40.
+    let found : Vec<(Assertion,usize)> = assertions.assertions.iter().map(|a|(a.assertion.clone(),a.margin)).collect();
+    assert_eq!(found.len(),2);
+    assert!(found.contains(&(Assertion::NEB { winner: CandidateIndex(1), loser: CandidateIndex(2) },10)));
+    assert!(found.contains(&(Assertion::NEN { winner: CandidateIndex(1), loser: CandidateIndex(0), continuing: vec![CandidateIndex(0),CandidateIndex(1)] },15)));
+    assert!((assertions.difficulty-10.0).abs()<1e-9);
+    // check it survives serialization
+    let json = serde_json::to_string(&assertions).unwrap();
+    let read_back : RaireAssertions = serde_json::from_str(&json).unwrap();
+    assert_eq!(read_back.assertions.len(),2);
+    assert_eq!(read_back.assertions[0].assertion,assertions.assertions[0].assertion);
+}
+
+#[test]
+fn test_raire_tie_cannot_be_audited() {
+    let data = make_election(vec![
+        BTL{ candidates: vec![CandidateIndex(0)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 20 },
+    ]);
+    assert!(matches!(generate_raire_assertions::<SimpleIRVAnyDifferenceBreaksTies>(&data),Err(RaireError::CannotRuleOut(_))));
+}
+
+#[test]
+fn test_raire_needs_vacancies() {
+    let mut data = make_election(vec![BTL{ candidates: vec![CandidateIndex(0)], n: 40 }]);
+    data.metadata.vacancies=None;
+    assert!(matches!(generate_raire_assertions::<SimpleIRVAnyDifferenceBreaksTies>(&data),Err(RaireError::Count(CountError::MissingVacancies))));
+}
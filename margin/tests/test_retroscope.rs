@@ -13,8 +13,14 @@ use margin::choose_votes::ChooseVotesOptions;
 use margin::evaluate_and_optimize_vote_changes::{ChangeResult, optimise, simple_test};
 use stv::ballot_metadata::{Candidate, CandidateIndex, ElectionMetadata, ElectionName, NumberOfCandidates, Party, PartyIndex};
 use stv::ballot_paper::{ATL, BTL};
-use stv::distribution_of_preferences_transcript::CountIndex;
+use stv::distribution_of_preferences_transcript::{CountIndex, ReasonForCount};
+use stv::count_error::CountError;
+use meek::{distribute_preferences_meek, NZMeek};
+use stv::compare_transcripts::{compare_transcripts, DifferenceBetweenTranscripts};
 use stv::election_data::ElectionData;
+use stv::prepared_election::{PreparedElection, VoteDeltas};
+use stv::incremental_recount::RecordedCount;
+use stv::count_observer::IgnoreCountProgress;
 use margin::retroscope::{PileStatus, Retroscope, RetroscopeVoteIndex, RetroscopeVoteStatus};
 use margin::vote_changes::{VoteChange, VoteChanges};
 use stv::ballot_pile::BallotPaperCount;
@@ -69,14 +75,14 @@ fn test_retroscope() {
         btl_transfer_values: vec![],
         informal: 0
     };
-    let transcript = vote_data.distribute_preferences::<FederalRulesPre2021>(&mut Randomness::ReverseDonkeyVote);
+    let transcript = vote_data.distribute_preferences::<FederalRulesPre2021>(&mut Randomness::ReverseDonkeyVote).unwrap();
     println!("{}",serde_json::to_string_pretty(&transcript).unwrap());
     let mut retroscope = Retroscope::new(&vote_data,&[]);
     assert_eq!(false,retroscope.is_highest_continuing_member_party_ticket(CandidateIndex(1),&vote_data.metadata)); // 0 is above
     assert_eq!(false,retroscope.is_highest_continuing_member_party_ticket(CandidateIndex(3),&vote_data.metadata)); // 2 is above
     assert_eq!(false,retroscope.is_highest_continuing_member_party_ticket(CandidateIndex(4),&vote_data.metadata)); // not on a ticket
 
-    retroscope.apply(CountIndex(0),transcript.count(CountIndex(0)));
+    retroscope.apply(CountIndex(0),transcript.count(CountIndex(0))).unwrap();
     // First preferences - should get candidates 0 and 2 elected.
     assert_eq!(retroscope.count,CountIndex(0));
     assert_eq!(retroscope.continuing,[CandidateIndex(1),CandidateIndex(3),CandidateIndex(4)].into_iter().collect::<HashSet<CandidateIndex>>());
@@ -97,7 +103,7 @@ fn test_retroscope() {
     assert_eq!(retroscope.piles_by_candidate[1].by_count.get(&CountIndex(0)).unwrap(),&vec![RetroscopeVoteIndex(3)]);
     assert_eq!(retroscope.transfer_value(CountIndex(0)),&TransferValue::one());
 
-    retroscope.apply(CountIndex(1),transcript.count(CountIndex(1)));
+    retroscope.apply(CountIndex(1),transcript.count(CountIndex(1))).unwrap();
     // Second count - distribute candidate 0.
     assert_eq!(retroscope.continuing,[CandidateIndex(1),CandidateIndex(3),CandidateIndex(4)].into_iter().collect::<HashSet<CandidateIndex>>());
     assert_pile(&retroscope.votes.atl[0],CandidateIndex(1),CountIndex(1));
@@ -111,7 +117,7 @@ fn test_retroscope() {
     assert_eq!(retroscope.piles_by_candidate[1].by_count.get(&CountIndex(1)).unwrap(),&vec![RetroscopeVoteIndex(0)]);
     assert_eq!(retroscope.transfer_value(CountIndex(1)),&TransferValue::from_str("79/180").unwrap());
 
-    retroscope.apply(CountIndex(2),transcript.count(CountIndex(2)));
+    retroscope.apply(CountIndex(2),transcript.count(CountIndex(2))).unwrap();
     // Third count - distribute candidate 2. atl[1] goes to 3, btl[2] goes to 4.
     assert_eq!(retroscope.continuing,[CandidateIndex(1),CandidateIndex(3),CandidateIndex(4)].into_iter().collect::<HashSet<CandidateIndex>>());
     assert_pile(&retroscope.votes.atl[0],CandidateIndex(1),CountIndex(1));
@@ -133,13 +139,13 @@ fn test_retroscope() {
     assert_eq!(false,retroscope.is_highest_continuing_member_party_ticket(CandidateIndex(4),&vote_data.metadata)); // not on a ticket
     // Test ChooseVotes
     let mut chooser1 = retroscope.get_chooser(CandidateIndex(1),&vote_data,&ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: Default::default(), allow_additions: false, allow_from: None, allow_to: None });
-    assert!(chooser1.get_votes::<FederalRulesPre2021>(1000, true).is_none());
+    assert!(chooser1.get_votes(&FederalRulesPre2021::default(),1000, true).is_none());
     let mut chooser1 = retroscope.get_chooser(CandidateIndex(1),&vote_data,&ChooseVotesOptions{ allow_atl: false, allow_first_pref: false, allow_verifiable: true, ballot_types_considered_unverifiable: Default::default(), allow_additions: false, allow_from: None, allow_to: None });
-    assert!(chooser1.get_votes::<FederalRulesPre2021>(1, true).is_none());
+    assert!(chooser1.get_votes(&FederalRulesPre2021::default(),1, true).is_none());
     let mut chooser1 = retroscope.get_chooser(CandidateIndex(1),&vote_data,&ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: Default::default(), allow_additions: false, allow_from: None, allow_to: None });
-    assert_eq!(10,chooser1.votes_available_btl::<FederalRulesPre2021>());
-    assert_eq!(53,chooser1.votes_available_total::<FederalRulesPre2021>());
-    let found1 = chooser1.get_votes::<FederalRulesPre2021>(4, true).unwrap(); // there are 10 BTL TV 1, and 100 ATL TV 79/180
+    assert_eq!(10,chooser1.votes_available_btl(&FederalRulesPre2021::default()));
+    assert_eq!(53,chooser1.votes_available_total(&FederalRulesPre2021::default()));
+    let found1 = chooser1.get_votes(&FederalRulesPre2021::default(),4, true).unwrap(); // there are 10 BTL TV 1, and 100 ATL TV 79/180
     assert_eq!(found1.len(),1);
     assert_eq!(found1[0].n,BallotPaperCount(4));
     assert_eq!(found1[0].tally,4);
@@ -147,7 +153,7 @@ fn test_retroscope() {
     assert_eq!(found1[0].ballots.len(),1);
     assert_eq!(found1[0].ballots[0].n,4);
     assert_eq!(found1[0].ballots[0].from,RetroscopeVoteIndex(3));
-    let found1 = chooser1.get_votes::<FederalRulesPre2021>(1, true).unwrap(); // there are 6 BTL TV 1, and 100 ATL TV 79/180 left
+    let found1 = chooser1.get_votes(&FederalRulesPre2021::default(),1, true).unwrap(); // there are 6 BTL TV 1, and 100 ATL TV 79/180 left
     assert_eq!(found1.len(),1);
     assert_eq!(found1[0].n,BallotPaperCount(1));
     assert_eq!(found1[0].tally,1);
@@ -155,7 +161,7 @@ fn test_retroscope() {
     assert_eq!(found1[0].ballots.len(),1);
     assert_eq!(found1[0].ballots[0].n,1);
     assert_eq!(found1[0].ballots[0].from,RetroscopeVoteIndex(3));
-    let found1 = chooser1.get_votes::<FederalRulesPre2021>(25, true).unwrap(); // there are 5 BTL TV 1, and 100 ATL TV 79/180 left
+    let found1 = chooser1.get_votes(&FederalRulesPre2021::default(),25, true).unwrap(); // there are 5 BTL TV 1, and 100 ATL TV 79/180 left
     assert_eq!(found1.len(),2);
     assert_eq!(found1[0].n,BallotPaperCount(5));
     assert_eq!(found1[1].n,BallotPaperCount(46));
@@ -169,13 +175,13 @@ fn test_retroscope() {
     assert_eq!(found1[1].ballots.len(),1);
     assert_eq!(found1[1].ballots[0].n,46);
     assert_eq!(found1[1].ballots[0].from,RetroscopeVoteIndex(0));
-    assert!(chooser1.get_votes::<FederalRulesPre2021>(30, true).is_none());
+    assert!(chooser1.get_votes(&FederalRulesPre2021::default(),30, true).is_none());
 
     let attempted_changes = VoteChanges{ changes: vec![VoteChange{ vote_value: 30, from: Some(CandidateIndex(1)), to: Some(CandidateIndex(4)) }] };
-    let concrete = attempted_changes.make_concrete::<FederalRulesPre2021>(&retroscope, &vote_data, &ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: Default::default(), allow_additions: false, allow_from: None, allow_to: None });
+    let concrete = attempted_changes.make_concrete(&FederalRulesPre2021::default(),&retroscope, &vote_data, &ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: Default::default(), allow_additions: false, allow_from: None, allow_to: None });
     assert!(concrete.is_none());
     let attempted_changes = VoteChanges{ changes: vec![VoteChange{ vote_value: 30, from: Some(CandidateIndex(1)), to: Some(CandidateIndex(3)) }] };
-    let concrete = attempted_changes.make_concrete::<FederalRulesPre2021>(&retroscope, &vote_data, &ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: Default::default(), allow_additions: false, allow_from: None, allow_to: None }).unwrap();
+    let concrete = attempted_changes.make_concrete(&FederalRulesPre2021::default(),&retroscope, &vote_data, &ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: Default::default(), allow_additions: false, allow_from: None, allow_to: None }).unwrap();
     assert_eq!(2,concrete.changes.len());
     assert_eq!(BallotPaperCount(10),concrete.changes[0].n);
     assert_eq!(10,concrete.changes[0].tally);
@@ -196,22 +202,36 @@ fn test_retroscope() {
 
     // consider changing the outcome of the election at this point. Candidate 1 has 53 votes, 3 has 86, 4 has 23. Normally 4 would be excluded, giving 1 vote to candidate 1, and then candidate 3 gets elected 86 to 54. This could be changed by moving 17 votes from candidate 3 to candidate 1.
     let vote_changes = VoteChanges{ changes: vec![VoteChange{ vote_value: 20, from: Some(CandidateIndex(3)), to: Some(CandidateIndex(1)) }] };
-    match simple_test::<FederalRulesPre2021>(&vote_changes, &vote_data, &retroscope, &ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: Default::default(), allow_additions: false, allow_from: None, allow_to: None }) {
+    let prepared = PreparedElection::new(&vote_data,None);
+    let no_changes = VoteDeltas::default();
+    let votes = prepared.votes(&no_changes);
+    let mut observer = IgnoreCountProgress{};
+    let mut randomness = Randomness::ReverseDonkeyVote;
+    let rules = FederalRulesPre2021::default();
+    let recorded = RecordedCount::new(&rules,&prepared,&votes,&mut observer,&mut randomness).unwrap();
+    match simple_test(&recorded,&vote_changes, &retroscope, &ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: Default::default(), allow_additions: false, allow_from: None, allow_to: None }) {
         ChangeResult::NoChange => panic!("No change!"),
         ChangeResult::NotEnoughVotesAvailable => panic!("Not enough votes available!"),
+        ChangeResult::CountFailed(error) => panic!("Count failed : {}",error),
         ChangeResult::Change(deltas,ballot_changes) => {
             assert_eq!(deltas.list2only,vec![CandidateIndex(3)]);
             assert_eq!(deltas.list1only,vec![CandidateIndex(1)]);
             assert_eq!(ballot_changes.n,BallotPaperCount(20)); // first prefs.
+            // recounting the prepared election with deltas should be identical to recounting modified data.
+            let recount_modified_data = ballot_changes.apply_to_votes(&vote_data,false).distribute_preferences::<FederalRulesPre2021>(&mut Randomness::ReverseDonkeyVote).unwrap();
+            let recount_with_deltas = prepared.distribute_preferences(&FederalRulesPre2021::default(),&ballot_changes.vote_deltas(&prepared),&mut Randomness::ReverseDonkeyVote).unwrap();
+            assert_eq!(DifferenceBetweenTranscripts::Same,compare_transcripts(&recount_modified_data,&recount_with_deltas));
+            // and the incremental recount used by simple_test should agree with a full recount.
+            assert_eq!(deltas,ballot_changes.see_effect(&rules,&prepared).unwrap());
         }
     }
 
-    let optimize_result = optimise::<FederalRulesPre2021>(&vote_changes, &vote_data, &retroscope, &ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: Default::default(), allow_additions: false, allow_from: None, allow_to: None }, true).unwrap();
+    let optimize_result = optimise(&recorded,&vote_changes, &retroscope, &ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: Default::default(), allow_additions: false, allow_from: None, allow_to: None }, true).unwrap();
     assert_eq!(optimize_result.deltas.list2only,vec![CandidateIndex(3)]);
     assert_eq!(optimize_result.deltas.list1only,vec![CandidateIndex(1)]);
     assert_eq!(optimize_result.changes.n,BallotPaperCount(17)); // optimized it down to 17.
 
-    retroscope.apply(CountIndex(3),transcript.count(CountIndex(3)));
+    retroscope.apply(CountIndex(3),transcript.count(CountIndex(3))).unwrap();
     // Fourth count - eliminate candidate 4, TV 1 btl[4] goes to 1.
     assert_eq!(retroscope.continuing,[CandidateIndex(1),CandidateIndex(3)].into_iter().collect::<HashSet<CandidateIndex>>());
     assert_eq!(retroscope.elected,vec![CandidateIndex(0),CandidateIndex(2)]);
@@ -227,7 +247,7 @@ fn test_retroscope() {
     assert_eq!(retroscope.piles_by_candidate[1].by_count.get(&CountIndex(3)).unwrap(),&vec![RetroscopeVoteIndex(6)]);
     assert_eq!(retroscope.transfer_value(CountIndex(3)),&TransferValue::from_str("1/1").unwrap());
 
-    retroscope.apply(CountIndex(4),transcript.count(CountIndex(4)));
+    retroscope.apply(CountIndex(4),transcript.count(CountIndex(4))).unwrap();
     // Fourth count - eliminate candidate 4, TV 59/160 btl[2] goes to exhausted. Candidate 3 gets elected
     assert_eq!(retroscope.continuing,[CandidateIndex(1)].into_iter().collect::<HashSet<CandidateIndex>>());
     assert_eq!(retroscope.elected,vec![CandidateIndex(0),CandidateIndex(2),CandidateIndex(3)]);
@@ -239,4 +259,18 @@ fn test_retroscope() {
     assert_pile(&retroscope.votes.btl[3],CandidateIndex(3),CountIndex(0));
     assert_pile(&retroscope.votes.btl[4],CandidateIndex(1),CountIndex(3));
     assert_eq!(retroscope.transfer_value(CountIndex(4)),&TransferValue::from_str("59/160").unwrap());
-}
\ No newline at end of file
+}
+#[test]
+fn test_retroscope_rejects_keep_values() {
+    let vote_data = ElectionData::from_btl(&["A","B","C"],1,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(1)], n: 60 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 25 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 15 },
+    ]);
+    let transcript = distribute_preferences_meek::<NZMeek>(&vote_data,NumberOfCandidates(2),&HashSet::default(),&vote_data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    let mut retroscope = Retroscope::new(&vote_data,&[]);
+    retroscope.apply(CountIndex(0),transcript.count(CountIndex(0))).unwrap();
+    let keep_values_count = transcript.counts.iter().position(|c|matches!(c.reason,ReasonForCount::ExcessDistributionByKeepValues(_))).unwrap();
+    for count in 1..keep_values_count { retroscope.apply(CountIndex(count),transcript.count(CountIndex(count))).unwrap(); }
+    assert_eq!(retroscope.apply(CountIndex(keep_values_count),transcript.count(CountIndex(keep_values_count))),Err(CountError::KeepValuesNotSupported{ count: CountIndex(keep_values_count) }));
+}
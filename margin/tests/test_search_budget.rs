@@ -0,0 +1,83 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Test stopping a search for changes early, and progress reporting.
+
+use std::collections::HashSet;
+use margin::choose_votes::ChooseVotesOptions;
+use margin::find_outcome_changes::{find_outcome_changes_with_budget, SearchBudget, SearchProgress};
+use nsw::SimpleIRVAnyDifferenceBreaksTies;
+use stv::ballot_metadata::CandidateIndex;
+use stv::ballot_paper::BTL;
+use stv::election_data::ElectionData;
+use stv::prepared_election::PreparedElection;
+use stv::random_util::Randomness;
+
+fn make_election() -> ElectionData {
+    let btl = |candidates:&[usize],n:usize| BTL{ candidates: candidates.iter().map(|&c|CandidateIndex(c)).collect(), n };
+    let mut data = ElectionData::from_btl(&["A","B","C","D","E"],1,vec![
+        btl(&[0],86),
+        btl(&[0,1,2],34),
+        btl(&[4,1],56),
+        btl(&[2],31),
+        btl(&[2,1,0],29),
+        btl(&[3],19),
+        btl(&[1],34),
+    ]);
+    let transcript = data.distribute_preferences::<SimpleIRVAnyDifferenceBreaksTies>(&mut Randomness::ReverseDonkeyVote).unwrap();
+    data.metadata.results=Some(transcript.elected);
+    data
+}
+
+fn options() -> ChooseVotesOptions {
+    ChooseVotesOptions{ allow_atl: true, allow_first_pref: true, allow_verifiable: true, ballot_types_considered_unverifiable: HashSet::new(), allow_additions: true, allow_from: None, allow_to: None }
+}
+
+/// Run the search with a single thread, so each batch is one count, recording the progress reports.
+fn search(budget:&SearchBudget,stop_after_reports:Option<usize>) -> (usize,Vec<SearchProgress>) {
+    let data = make_election();
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+    let mut reports = vec![];
+    let changes = pool.install(||find_outcome_changes_with_budget(&SimpleIRVAnyDifferenceBreaksTies::default(),&data,&options(),false,None,None,budget,&mut |progress|{
+        reports.push(progress.clone());
+        stop_after_reports.map(|n|reports.len()<n).unwrap_or(true)
+    })).unwrap();
+    let prepared = PreparedElection::new(&data,None);
+    for change in &changes.changes {
+        assert_eq!(change.ballots.see_effect(&SimpleIRVAnyDifferenceBreaksTies::default(),&prepared).unwrap(),change.outcome);
+    }
+    (changes.changes.len(),reports)
+}
+
+#[test]
+fn test_unlimited_budget() {
+    let (found,reports) = search(&SearchBudget::default(),None);
+    assert!(found>0);
+    let last = reports.last().unwrap();
+    assert_eq!(last.counts_searched,last.total_counts);
+    assert!(!last.budget_exhausted);
+    assert!(last.smallest_manipulation_found.is_some());
+    assert!(reports.windows(2).all(|w|w[0].counts_searched<w[1].counts_searched && w[0].evaluations<=w[1].evaluations));
+}
+
+#[test]
+fn test_no_evaluations_allowed() {
+    let (found,reports) = search(&SearchBudget{ max_time: None, max_evaluations: Some(0) },None);
+    assert_eq!(found,0);
+    assert_eq!(reports.len(),1);
+    assert!(reports[0].budget_exhausted);
+    assert_eq!(reports[0].evaluations,0);
+    assert!(reports[0].counts_searched<reports[0].total_counts);
+}
+
+#[test]
+fn test_progress_callback_can_stop_search() {
+    let (_,all_reports) = search(&SearchBudget::default(),None);
+    let (_,reports) = search(&SearchBudget::default(),Some(1));
+    assert_eq!(reports.len(),1);
+    assert!(!reports[0].budget_exhausted);
+    assert!(reports[0].counts_searched<all_reports.last().unwrap().counts_searched);
+}
@@ -0,0 +1,424 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Meek's method of counting STV.
+//!
+//! This is quite different from the Gregory style methods handled by [stv::preference_distribution::PreferenceDistributor].
+//! Votes are never moved between piles. Instead each candidate has a "keep value", the proportion
+//! of the value of each vote reaching them that they keep, passing the rest on to the next
+//! preference. Hopeful candidates have a keep value of 1, excluded candidates 0, and elected candidates
+//! have their keep value iteratively reduced until their tally is (almost) exactly a quota. The quota
+//! itself shrinks as votes exhaust.
+//!
+//! This follows "Algorithm 123 - Single Transferable Vote by Meek's Method", I.D. Hill, B.A. Wichmann and D.R. Woodall,
+//! The Computer Journal 30 (1987) 277-281, which is the basis for the method prescribed
+//! by Schedule 1A of the New Zealand Local Electoral Regulations 2001.
+//!
+//! Each count in the transcript is one "stage" of the algorithm: the first preferences,
+//! the adjustment of keep values after someone is elected, or an exclusion. The keep values
+//! for each iteration within a stage are stored in [SingleCount::keep_values].
+
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use stv::ballot_metadata::{CandidateIndex, ElectionMetadata, NumberOfCandidates};
+use stv::count_error::CountError;
+use stv::count_observer::CountObserver;
+use stv::ballot_pile::BallotPaperCount;
+use stv::distribution_of_preferences_transcript::{CandidateElected, CountIndex, ElectionReason, EndCountStatus, KeepValueIteration, PerCandidate, PortionOfReasonBeingDoneThisCount, QuotaInfo, ReasonForCount, SingleCount, Transcript};
+use stv::quota::QuotaMethod;
+use stv::election_data::ElectionData;
+use stv::preference_distribution::CountOptions;
+use stv::fixed_precision_decimal::FixedPrecisionDecimal;
+use stv::random_util::Randomness;
+use stv::signed_version::SignedVersion;
+use stv::tie_resolution::{MethodOfTieResolution, TieResolutionExplicitDecision, TieResolutionGranularityNeeded, TieResolutionsMadeByEC, TieResolutionUsage};
+
+/// Meek counts are done to 9 decimal places.
+pub type MeekTally = FixedPrecisionDecimal<9>;
+
+/// The choices that distinguish different variants of Meek's method.
+pub trait MeekRules {
+    /// Stop iterating keep values when the sum of the surpluses of all elected candidates is less than this.
+    fn surplus_tolerance() -> MeekTally;
+    /// How to order candidates who reach the quota in the same iteration with the same tally.
+    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution;
+    /// How to choose between candidates with equal lowest tallies for exclusion.
+    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution;
+    fn name() -> String;
+}
+
+/// My interpretation of Schedule 1A of the New Zealand Local Electoral Regulations 2001,
+/// using the parameters of Algorithm 123.
+pub struct NZMeek {
+}
+
+impl MeekRules for NZMeek {
+    /// 0.00001
+    fn surplus_tolerance() -> MeekTally { MeekTally::from_scaled_value(10000) }
+    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn name() -> String { "NZMeek".to_string() }
+}
+
+/// A set of identical votes, with preferences resolved to candidates.
+struct MeekBallot<'a> {
+    prefs : &'a [CandidateIndex],
+    n : usize,
+    /// The value of each vote, scaled by [MeekTally::SCALE]. Usually exactly 1.
+    weight : u64,
+}
+
+#[derive(Copy,Clone,Eq,PartialEq,Debug)]
+enum MeekCandidateStatus {
+    Hopeful,
+    Elected,
+    Excluded,
+}
+
+/// Why the iterations in a stage stopped.
+#[derive(Copy,Clone,Eq,PartialEq,Debug)]
+enum EndOfStage {
+    SomeoneElected,
+    /// total surplus is below the tolerance, or iterating is no longer reducing it.
+    Converged,
+}
+
+pub struct MeekDistributor<'a,Rules:MeekRules> {
+    ballots : Vec<MeekBallot<'a>>,
+    candidates_to_be_elected : NumberOfCandidates,
+    ec_resolutions : &'a TieResolutionsMadeByEC,
+    randomness : &'a mut Randomness,
+    observer : &'a mut dyn CountObserver<MeekTally>,
+    metadata : &'a ElectionMetadata,
+    status : Vec<MeekCandidateStatus>,
+    keep_values : Vec<MeekTally>,
+    tallies : Vec<MeekTally>,
+    exhausted : MeekTally,
+    /// papers whose first non-excluded preference is the given candidate.
+    papers : Vec<BallotPaperCount>,
+    exhausted_papers : BallotPaperCount,
+    total_papers : BallotPaperCount,
+    quota : MeekTally,
+    elected_candidates : Vec<CandidateIndex>,
+    current_count : CountIndex,
+    elected_this_count : Vec<CandidateElected>,
+    not_continuing_this_count : Vec<CandidateIndex>,
+    decisions_this_count : Vec<TieResolutionExplicitDecision>,
+    pub transcript : Transcript<MeekTally>,
+    /// A problem found that means the count cannot continue.
+    failure : Option<CountError>,
+    phantom_data: PhantomData<Rules>,
+}
+
+impl <'a,Rules:MeekRules> MeekDistributor<'a,Rules> {
+    /// Make a distributor for the votes in `data`. The quota method and extractors in `options` are not supported, and are ignored.
+    pub fn new(data:&'a ElectionData,arena:&'a typed_arena::Arena<CandidateIndex>,options:&CountOptions<'a>,observer:&'a mut dyn CountObserver<MeekTally>,randomness:&'a mut Randomness) -> Self {
+        let num_candidates = data.metadata.candidates.len();
+        let draw_seed = randomness.public_seed().cloned();
+        let mut ballots = vec![];
+        for (transfer_value,votes) in data.resolve_atl_including_weights(arena,options.vote_types) {
+            let weight = transfer_value.mul_rounding_down(BallotPaperCount(MeekTally::SCALE as usize)) as u64;
+            for vote in votes {
+                ballots.push(MeekBallot{ prefs: vote.prefs, n: vote.n.0, weight });
+            }
+        }
+        let total_papers = BallotPaperCount(ballots.iter().map(|b|b.n).sum());
+        let mut status = vec![MeekCandidateStatus::Hopeful;num_candidates];
+        let mut keep_values = vec![MeekTally::from_scaled_value(MeekTally::SCALE);num_candidates];
+        let mut not_continuing_this_count = vec![];
+        let mut failure = None;
+        for &c in &options.excluded_candidates {
+            if c.0>=num_candidates { failure=Some(CountError::InvalidCandidate{candidate:c,num_candidates}); continue; }
+            status[c.0]=MeekCandidateStatus::Excluded;
+            keep_values[c.0]=MeekTally::from_scaled_value(0);
+            not_continuing_this_count.push(c);
+        }
+        not_continuing_this_count.sort_by_key(|c|c.0);
+        MeekDistributor {
+            ballots,
+            candidates_to_be_elected: options.candidates_to_be_elected,
+            ec_resolutions: options.ec_resolutions,
+            randomness,
+            observer,
+            metadata: &data.metadata,
+            status,
+            keep_values,
+            tallies: vec![MeekTally::from_scaled_value(0);num_candidates],
+            exhausted: MeekTally::from_scaled_value(0),
+            papers: vec![BallotPaperCount(0);num_candidates],
+            exhausted_papers: BallotPaperCount(0),
+            total_papers,
+            quota: MeekTally::from_scaled_value(0),
+            elected_candidates: vec![],
+            current_count: CountIndex(0),
+            elected_this_count: vec![],
+            not_continuing_this_count,
+            decisions_this_count: vec![],
+            transcript: Transcript{
+                rules: Rules::name(),
+                quota: None,
+                counts: vec![],
+                elected: vec![],
+                draws: vec![],
+                draw_seed,
+            },
+            failure,
+            phantom_data: Default::default(),
+        }
+    }
+
+    fn hopeful_candidates(&self) -> Vec<CandidateIndex> {
+        (0..self.status.len()).filter(|&i|self.status[i]==MeekCandidateStatus::Hopeful).map(CandidateIndex).collect()
+    }
+
+    pub fn remaining_to_elect(&self) -> usize {
+        self.candidates_to_be_elected.0.saturating_sub(self.elected_candidates.len())
+    }
+
+    /// Pass each vote down its preferences, each candidate keeping their keep value's share of what reaches them.
+    /// The amount kept is rounded up to 9 decimal places, so nothing is lost to rounding.
+    fn distribute_votes(&mut self) {
+        let scale = MeekTally::SCALE as u128;
+        let mut tallies = vec![0u64;self.tallies.len()];
+        let mut exhausted : u64 = 0;
+        let mut papers = vec![BallotPaperCount(0);self.papers.len()];
+        let mut exhausted_papers = BallotPaperCount(0);
+        for ballot in &self.ballots {
+            let mut remaining = ballot.weight;
+            let mut holder : Option<CandidateIndex> = None;
+            for &c in ballot.prefs {
+                let keep = self.keep_values[c.0].get_scaled_value() as u128;
+                if keep==0 { continue; }
+                if holder.is_none() { holder=Some(c); }
+                let kept = (remaining as u128*keep).div_ceil(scale) as u64;
+                tallies[c.0]+=kept*ballot.n as u64;
+                remaining-=kept;
+                if remaining==0 { break; }
+            }
+            exhausted+=remaining*ballot.n as u64;
+            match holder {
+                Some(c) => papers[c.0]+=BallotPaperCount(ballot.n),
+                None => exhausted_papers+=BallotPaperCount(ballot.n),
+            }
+        }
+        self.tallies=tallies.into_iter().map(MeekTally::from_scaled_value).collect();
+        self.exhausted=MeekTally::from_scaled_value(exhausted);
+        self.papers=papers;
+        self.exhausted_papers=exhausted_papers;
+    }
+
+    /// The quota is the total votes held by candidates divided by one more than the number of vacancies,
+    /// truncated to 9 decimal places, plus 0.000000001.
+    fn compute_quota(&mut self) {
+        let total : u64 = self.tallies.iter().map(|t|t.get_scaled_value()).sum();
+        self.quota = MeekTally::from_scaled_value(total/(self.candidates_to_be_elected.0 as u64+1)+1);
+        if self.transcript.quota.is_none() {
+            let quota = QuotaInfo{ papers: self.total_papers, vacancies: self.candidates_to_be_elected, quota: self.quota, method: QuotaMethod::ExactDroop };
+            self.observer.quota_computed(&quota);
+            self.transcript.quota=Some(quota);
+        }
+    }
+
+    fn total_surplus(&self) -> MeekTally {
+        self.elected_candidates.iter().map(|c|self.tallies[c.0]).filter(|&t|t>self.quota).map(|t|t-self.quota).sum()
+    }
+
+    /// Reduce the keep value of each elected candidate in proportion to how much they are over the quota, rounding up.
+    fn update_keep_values(&mut self) {
+        let scale = MeekTally::SCALE as u128;
+        for &c in &self.elected_candidates {
+            let tally = self.tallies[c.0].get_scaled_value() as u128;
+            if tally>0 {
+                let keep = self.keep_values[c.0].get_scaled_value() as u128;
+                let new_keep = (keep*self.quota.get_scaled_value() as u128).div_ceil(tally).min(scale);
+                self.keep_values[c.0]=MeekTally::from_scaled_value(new_keep as u64);
+            }
+        }
+    }
+
+    /// Sort candidates low to high by tally, resolving ties with the given method, the EC's decisions, or randomness.
+    fn sort_resolving_ties(&mut self,candidates:&mut [CandidateIndex],how:MethodOfTieResolution,granularity:TieResolutionGranularityNeeded,usage:TieResolutionUsage) {
+        candidates.sort_by_key(|c|self.tallies[c.0]);
+        let mut i = 0;
+        while i<candidates.len() {
+            let mut differs = i+1;
+            while differs<candidates.len() && self.tallies[candidates[i].0]==self.tallies[candidates[differs].0] { differs+=1; }
+            if differs!=i+1 {
+                let sub_granularity = match granularity {
+                    TieResolutionGranularityNeeded::Total => Some(TieResolutionGranularityNeeded::Total),
+                    TieResolutionGranularityNeeded::LowestSeparated(n) if n<differs && n>i => Some(TieResolutionGranularityNeeded::LowestSeparated(n-i)),
+                    _ => None, // no resolution needed as all in or all not in.
+                };
+                if let Some(sub_granularity) = sub_granularity {
+                    let mut needed_ec_decision = false;
+                    for (still_tied,remaining_granularity) in how.resolve(&mut candidates[i..differs],&self.transcript,sub_granularity) {
+                        needed_ec_decision = true;
+                        let decision = self.ec_resolutions.resolve(still_tied,remaining_granularity,usage,self.current_count,self.randomness);
+                        self.decisions_this_count.push(decision);
+                        if let Some(failure) = self.randomness.take_draw_failure(self.current_count) {
+                            self.failure.get_or_insert(failure);
+                        }
+                    }
+                    self.observer.tie_resolved(usage,&candidates[i..differs],needed_ec_decision);
+                }
+            }
+            i=differs;
+        }
+    }
+
+    fn declare_elected(&mut self,who:CandidateIndex,why:ElectionReason) {
+        self.observer.candidate_elected(who,why);
+        self.status[who.0]=MeekCandidateStatus::Elected;
+        self.elected_candidates.push(who);
+        self.elected_this_count.push(CandidateElected{who,why});
+    }
+
+    /// Elect all hopeful candidates with at least a quota. Returns true if anyone was elected.
+    fn check_elected_by_quota(&mut self) -> bool {
+        let mut reached_quota : Vec<CandidateIndex> = self.hopeful_candidates().into_iter().filter(|c|self.tallies[c.0]>=self.quota).collect();
+        if reached_quota.is_empty() { return false; }
+        self.sort_resolving_ties(&mut reached_quota,Rules::resolve_ties_elected_by_quota(),TieResolutionGranularityNeeded::Total,TieResolutionUsage::OrderElected);
+        for &c in reached_quota.iter().rev() {
+            if self.remaining_to_elect()>0 { self.declare_elected(c,ElectionReason::ReachedQuota); }
+        }
+        true
+    }
+
+    /// If there are no more hopeful candidates than remaining vacancies, elect them all, highest tally first.
+    fn check_elected_all_remaining(&mut self) {
+        let mut hopeful = self.hopeful_candidates();
+        if !hopeful.is_empty() && hopeful.len()<=self.remaining_to_elect() {
+            self.sort_resolving_ties(&mut hopeful,MethodOfTieResolution::None,TieResolutionGranularityNeeded::Total,TieResolutionUsage::OrderElected);
+            for &c in hopeful.iter().rev() {
+                self.declare_elected(c,ElectionReason::AllRemainingMustBeElected);
+            }
+        }
+    }
+
+    /// Iterate keep values until someone new reaches the quota, or the surplus is small enough.
+    fn do_stage(&mut self,reason:ReasonForCount) -> EndOfStage {
+        let mut iterations = vec![];
+        let mut previous_surplus : Option<MeekTally> = None;
+        let end = loop {
+            self.distribute_votes();
+            self.compute_quota();
+            let total_surplus = self.total_surplus();
+            iterations.push(KeepValueIteration{ keep_values: self.keep_values.clone(), quota: self.quota, total_surplus });
+            if self.check_elected_by_quota() { break EndOfStage::SomeoneElected; }
+            if total_surplus<Rules::surplus_tolerance() { break EndOfStage::Converged; }
+            if let Some(previous_surplus) = previous_surplus {
+                if total_surplus>=previous_surplus { break EndOfStage::Converged; }
+            }
+            previous_surplus=Some(total_surplus);
+            self.update_keep_values();
+        };
+        if self.remaining_to_elect()>0 { self.check_elected_all_remaining(); }
+        self.end_of_count(reason,iterations);
+        end
+    }
+
+    fn end_of_count(&mut self,reason:ReasonForCount,iterations:Vec<KeepValueIteration<MeekTally>>) {
+        self.transcript.counts.push(SingleCount{
+            reason,
+            portion: PortionOfReasonBeingDoneThisCount { transfer_value: None, when_tv_created: None, papers_came_from_counts: vec![] },
+            reason_completed: true,
+            elected: self.elected_this_count.clone(),
+            not_continuing: std::mem::take(&mut self.not_continuing_this_count),
+            created_transfer_value: None,
+            decisions: std::mem::take(&mut self.decisions_this_count),
+            set_aside_for_quota: None,
+            status: EndCountStatus {
+                tallies: PerCandidate {
+                    candidate: self.tallies.clone(),
+                    exhausted: self.exhausted,
+                    rounding: SignedVersion{ negative: false, value: MeekTally::from_scaled_value(0) },
+                    set_aside: None,
+                },
+                papers: PerCandidate {
+                    candidate: self.papers.clone(),
+                    exhausted: self.exhausted_papers,
+                    rounding: SignedVersion{ negative: false, value: BallotPaperCount(0) },
+                    set_aside: None,
+                },
+                atl_papers: None,
+                list_of_votes: None,
+            },
+            count_name: None,
+            keep_values: Some(iterations),
+        });
+        if let Some(count) = self.transcript.counts.last() { self.observer.count_finished(count); }
+        self.current_count=CountIndex(self.current_count.0+1);
+        self.not_continuing_this_count=self.elected_this_count.drain(..).map(|e|e.who).collect();
+    }
+
+    fn exclude_lowest(&mut self) -> CandidateIndex {
+        let mut hopeful = self.hopeful_candidates();
+        self.sort_resolving_ties(&mut hopeful,Rules::resolve_ties_choose_lowest_candidate_for_exclusion(),TieResolutionGranularityNeeded::LowestSeparated(1),TieResolutionUsage::Exclusion);
+        let lowest = hopeful[0];
+        self.observer.candidates_excluded(&[lowest]);
+        self.status[lowest.0]=MeekCandidateStatus::Excluded;
+        self.keep_values[lowest.0]=MeekTally::from_scaled_value(0);
+        self.not_continuing_this_count.push(lowest);
+        lowest
+    }
+
+    /// Check that the votes and EC decisions only mention valid candidates.
+    fn check_input(&self) -> Result<(),CountError> {
+        let num_candidates = self.status.len();
+        let mentioned = self.ballots.iter().flat_map(|b|b.prefs.iter().cloned()).chain(self.ec_resolutions.candidates_mentioned());
+        for candidate in mentioned {
+            if candidate.0>=num_candidates { return Err(CountError::InvalidCandidate{candidate,num_candidates}); }
+        }
+        Ok(())
+    }
+
+    /// Return any problem found during the last stage.
+    fn check_failure(&mut self) -> Result<(),CountError> {
+        match self.failure.take() {
+            Some(failure) => Err(failure),
+            None => Ok(()),
+        }
+    }
+
+    pub fn go(&mut self) -> Result<(),CountError> {
+        self.check_failure()?;
+        self.check_input()?;
+        self.observer.count_started(self.metadata);
+        let mut end = self.do_stage(ReasonForCount::FirstPreferenceCount);
+        self.check_failure()?;
+        while self.remaining_to_elect()>0 && !self.hopeful_candidates().is_empty() {
+            let reason = if end==EndOfStage::SomeoneElected && self.total_surplus()>=Rules::surplus_tolerance() {
+                let with_surplus : Vec<CandidateIndex> = self.elected_candidates.iter().cloned().filter(|c|self.tallies[c.0]>self.quota).collect();
+                for &c in &with_surplus { self.observer.surplus_distribution_started(c,&(self.tallies[c.0]-self.quota)); }
+                ReasonForCount::ExcessDistributionByKeepValues(with_surplus)
+            } else {
+                ReasonForCount::Elimination(vec![self.exclude_lowest()])
+            };
+            end = self.do_stage(reason);
+            self.check_failure()?;
+        }
+        self.randomness.check_all_replayed_draws_used()?;
+        self.transcript.elected=self.elected_candidates.clone();
+        self.transcript.draws=self.randomness.take_recorded_draws();
+        Ok(())
+    }
+}
+
+/// Count the election using Meek's method, telling the observer what is happening.
+/// The quota method and extractors in `options` are not supported, and are ignored.
+pub fn distribute_preferences_meek_with_observer<Rules:MeekRules>(data:&ElectionData,options:&CountOptions,observer:&mut dyn CountObserver<MeekTally>,randomness:&mut Randomness) -> Result<Transcript<MeekTally>,CountError> {
+    let arena = typed_arena::Arena::<CandidateIndex>::new();
+    let mut work : MeekDistributor<'_,Rules> = MeekDistributor::new(data,&arena,options,observer,randomness);
+    work.go()?;
+    Ok(work.transcript)
+}
+
+/// Count the election using Meek's method, telling `observer` what happens as it goes.
+pub fn distribute_preferences_meek<Rules:MeekRules>(data:&ElectionData,candidates_to_be_elected : NumberOfCandidates,excluded_candidates:&HashSet<CandidateIndex>,ec_resolutions:& TieResolutionsMadeByEC,vote_types : Option<&[String]>,observer:&mut impl CountObserver<MeekTally>,randomness:&mut Randomness) -> Result<Transcript<MeekTally>,CountError> {
+    let options = CountOptions{ vote_types, ..CountOptions::new(candidates_to_be_elected,excluded_candidates.clone(),ec_resolutions) };
+    distribute_preferences_meek_with_observer::<Rules>(data,&options,observer,randomness)
+}
@@ -0,0 +1,73 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Test Meek's method on small elections that can be checked by hand.
+
+use std::collections::HashSet;
+use meek::{distribute_preferences_meek, MeekTally, NZMeek};
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::distribution_of_preferences_transcript::{CountIndex, ReasonForCount};
+use stv::election_data::ElectionData;
+use stv::random_util::Randomness;
+use stv::count_observer::IgnoreCountProgress;
+
+fn make_election(btl:Vec<BTL>) -> ElectionData {
+    ElectionData::from_btl(&["A","B","C","D"],2,btl)
+}
+
+#[test]
+fn test_meek_surplus_then_exclusion() {
+    let data = make_election(vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(1)], n: 60 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 25 },
+        BTL{ candidates: vec![CandidateIndex(3),CandidateIndex(2)], n: 15 },
+    ]);
+    let transcript = distribute_preferences_meek::<NZMeek>(&data,NumberOfCandidates(2),&HashSet::default(),&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!(transcript.elected,vec![CandidateIndex(0),CandidateIndex(2)]);
+    assert_eq!("33.333333334",transcript.quota.as_ref().unwrap().quota.to_string());
+    assert_eq!(3,transcript.counts.len());
+    // A elected on first preferences
+    let first = transcript.count(CountIndex(0));
+    assert!(matches!(first.reason,ReasonForCount::FirstPreferenceCount));
+    assert_eq!(1,first.elected.len());
+    assert_eq!(1,first.keep_values.as_ref().unwrap().len());
+    // A's surplus goes to B by reducing A's keep value to about 5/9.
+    let second = transcript.count(CountIndex(1));
+    assert!(matches!(&second.reason,ReasonForCount::ExcessDistributionByKeepValues(c) if c==&vec![CandidateIndex(0)]));
+    assert!(second.elected.is_empty());
+    let iterations = second.keep_values.as_ref().unwrap();
+    assert!(iterations.len()>1);
+    let last = iterations.last().unwrap();
+    assert!(last.total_surplus<MeekTally::from_scaled_value(10000));
+    assert_eq!("0.555555556",last.keep_values[0].to_string());
+    assert_eq!("1",last.keep_values[1].to_string());
+    // D excluded, and their votes elect C.
+    let third = transcript.count(CountIndex(2));
+    assert!(matches!(&third.reason,ReasonForCount::Elimination(c) if c==&vec![CandidateIndex(3)]));
+    assert_eq!(CandidateIndex(2),third.elected[0].who);
+    assert_eq!("0",third.keep_values.as_ref().unwrap()[0].keep_values[3].to_string());
+    // Nothing is lost in Meek's method; every count accounts for all 100 votes.
+    for count in &transcript.counts {
+        let total : MeekTally = count.status.tallies.candidate.iter().sum::<MeekTally>()+count.status.tallies.exhausted;
+        assert_eq!("100",total.to_string());
+    }
+}
+
+#[test]
+fn test_meek_elects_all_remaining() {
+    let data = make_election(vec![
+        BTL{ candidates: vec![CandidateIndex(0)], n: 50 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 19 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 11 },
+    ]);
+    let mut excluded = HashSet::new();
+    excluded.insert(CandidateIndex(3));
+    let transcript = distribute_preferences_meek::<NZMeek>(&data,NumberOfCandidates(2),&excluded,&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!(vec![CandidateIndex(3)],transcript.count(CountIndex(0)).not_continuing);
+    assert_eq!(transcript.elected,vec![CandidateIndex(0),CandidateIndex(1)]);
+}
@@ -20,6 +20,7 @@ use stv::tie_resolution::TieResolutionsMadeByEC;
 use calamine::{DataType, open_workbook_auto};
 use stv::ballot_pile::BallotPaperCount;
 use stv::distribution_of_preferences_transcript::{CountIndex, PerCandidate, QuotaInfo};
+use stv::quota::QuotaMethod;
 use stv::download::{CacheDir, DownloadWithReqwest};
 use stv::signed_version::SignedVersion;
 use scraper::{ElementRef, Html, Selector};
@@ -64,11 +65,11 @@ impl RawDataSource for WADataLoader {
         }
     }
 
-    fn candidates_to_be_elected(&self, _region: &str) -> NumberOfCandidates {
-        match self.year.as_str() {
+    fn candidates_to_be_elected(&self, _region: &str) -> anyhow::Result<NumberOfCandidates> {
+        Ok(match self.year.as_str() {
             "2005" => NumberOfCandidates(5),
             _ => NumberOfCandidates(6),
-        }
+        })
     }
 
     /// These are deduced by looking at the actual transcript of results.
@@ -78,8 +79,8 @@ impl RawDataSource for WADataLoader {
     }
 
     /// These are due to a variety of events.
-    fn excluded_candidates(&self, _electorate: &str) -> Vec<CandidateIndex> {
-        Default::default()
+    fn excluded_candidates(&self, _electorate: &str) -> anyhow::Result<Vec<CandidateIndex>> {
+        Ok(Default::default())
     }
 
     fn find_raw_data_file(&self, filename: &str) -> Result<PathBuf, MissingFile> {
@@ -121,7 +122,7 @@ impl RawDataSource for WADataLoader {
             source: vec![DataSource::new(&url_candidates_and_parties,&path_candidates_and_parties),
                          DataSource::new(&url_winners,&path_winners),],
             results: None,
-            vacancies: Some(self.candidates_to_be_elected(electorate)),
+            vacancies: Some(self.candidates_to_be_elected(electorate)?),
             enrolment: None,
             secondary_vacancies: None,
             excluded: vec![],
@@ -227,6 +228,7 @@ fn parse_dop_excel_file(sheet:&calamine::Range<DataType>,metadata:&ElectionMetad
             papers: BallotPaperCount(captures[1].parse::<usize>()?),
             vacancies: NumberOfCandidates(captures[2].parse::<usize>()?),
             quota: captures[3].parse::<f64>()?,
+            method: QuotaMethod::Droop,
         })
     } else { None };
     let col_who_caused_count : u32 = 0;
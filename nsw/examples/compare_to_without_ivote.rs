@@ -25,8 +25,8 @@ fn main() -> anyhow::Result<()> {
         let total_num_ivotes = total_num_votes-data_without_ivotes.num_votes();
         let turnout = if let Some(enrolment) = data_with_ivotes.metadata.enrolment { format!(" enrolment {} informal {} turnout {:.1}%",enrolment.0,data_with_ivotes.informal,100.0*(total_num_votes+data_with_ivotes.informal) as f64/enrolment.0 as f64) } else { "".to_string() };
         println!("Electorate {} {} formal votes including {} formal iVotes ({:.1}%){}",&electorate,total_num_votes,total_num_ivotes,100.0*total_num_ivotes as f64/total_num_votes as f64,turnout);
-        let transcript_with_ivotes = data_with_ivotes.distribute_preferences::<NSWECLocalGov2021>(&mut Randomness::ReverseDonkeyVote);
-        let transcript_without_ivotes = data_without_ivotes.distribute_preferences::<NSWECLocalGov2021>(&mut Randomness::ReverseDonkeyVote);
+        let transcript_with_ivotes = data_with_ivotes.distribute_preferences::<NSWECLocalGov2021>(&mut Randomness::ReverseDonkeyVote)?;
+        let transcript_without_ivotes = data_without_ivotes.distribute_preferences::<NSWECLocalGov2021>(&mut Randomness::ReverseDonkeyVote)?;
         let compare_official : DeltasInCandidateLists = DifferentCandidateLists{ list1: data_with_ivotes.metadata.results.as_ref().unwrap().clone(), list2: transcript_with_ivotes.elected.clone() }.into();
         if !compare_official.is_empty() {
             println!("  Different to official results for {} : {}",&electorate,compare_official.pretty_print(&data_with_ivotes.metadata));
@@ -93,7 +93,7 @@ fn run_elections(data:&ElectionData, sampler:&SampleWithReplacement<usize>, num_
             undos.push(index);
             if index<num_atl { data.atl[index].n+=1; } else { data.btl[index-num_atl].n+=1; }
         }
-        let result = data.distribute_preferences::<NSWECLocalGov2021>(&mut Randomness::ReverseDonkeyVote).elected;
+        let result = data.distribute_preferences::<NSWECLocalGov2021>(&mut Randomness::ReverseDonkeyVote).unwrap().elected;
         let diff : DeltasInCandidateLists = DifferentCandidateLists{ list1: data.metadata.results.as_ref().unwrap().clone(), list2: result }.into();
         if WRITE_CHARACTER_PER_RUN {
             if diff.is_empty() { print!("."); } else { print!("*"); }
@@ -16,7 +16,7 @@ fn main() -> anyhow::Result<()> {
     let loader = get_nsw_lc_data_loader_2023(&finder)?;
     let data = loader.read_raw_data("")?;
     data.print_summary();
-    let results = PossibleResults::new_from_runs_multithreaded::<NSWECRandomLC2019>(&data,10000,32);
+    let results = PossibleResults::new_from_runs_multithreaded(&NSWECRandomLC2019::default(),&data,10000,32)?;
     results.print_table_results(&data.metadata);
     let official = loader.read_official_dop_transcript(&data.metadata).unwrap();
     for elected in official.all_elected() {
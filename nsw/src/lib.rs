@@ -31,6 +31,7 @@ use stv::tie_resolution::MethodOfTieResolution;
 /// for reasons behind things. I am not claiming these are right;
 /// just that they generally seem no more wrong than any other interpretation.
 #[doc = include_str!("../NSWLocalCouncilLegislation2021.md")]
+#[derive(Default)]
 pub struct NSWLocalCouncilLegislation2021MyGuessAtHighlyAmbiguousLegislation {
 }
 
@@ -38,76 +39,77 @@ impl PreferenceDistributionRules for NSWLocalCouncilLegislation2021MyGuessAtHigh
     type Tally = usize;
     type SplitByNumber = FullySplitByCountNumber;
 
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { LastParcelUse::No }
-    fn transfer_value_method() -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::No }
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
 
     fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { convert_usize_to_rational(tally)  }
     fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { round_rational_down_to_usize(rational)  }
 
-    fn make_transfer_value(surplus: usize, ballots: BallotPaperCount) -> TransferValue { // NA
+    fn make_transfer_value(&self,surplus: usize, ballots: BallotPaperCount) -> TransferValue { // NA
         TransferValue::from_surplus(surplus,ballots)
     }
 
-    fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
         transfer_value.mul_rounding_down(ballots)
     }
 
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod { SurplusTransferMethod::MergeSameTransferValuesAndScale }
-    fn sort_exclusions_by_transfer_value() -> bool { false }
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::MergeSameTransferValuesAndScale }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { false }
 
     /// In general, Highly ambiguous, although OK for 2 person case.
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
     /// same as [resolve_ties_elected_one_of_last_two]
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
     /// same as [resolve_ties_elected_one_of_last_two]
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
 
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
 
     /// see discussion
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool { false }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { false }
     /// see discussion
-    fn check_elected_if_in_middle_of_exclusion() -> bool { true }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { true }
 
     /// 9(7) says
     /// ```text
     /// (7)  This clause is subject to clause 11 of this Schedule, and if at any time there is one remaining vacancy which can be filled under that clause, no further exclusion under this clause can be made.
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool { false }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
     /// 7(5) and 6(2) says
     /// ```text
     /// (5)  However, this clause is subject to clause 11 of this Schedule, and if at any time there is one remaining vacancy which can be filled under that clause, no further transfer under this clause can be made.
     /// ```
-    fn finish_all_surplus_distributions_when_all_elected() -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
 
 
     /// See discussion
     /// ```text
     /// 11 (4)  When only one vacancy remains unfilled, and there are only 2 continuing candidates, and those 2 candidates each have the same number of votes, and no surplus votes remain capable of transfer, one candidate is excluded in accordance with clause 9(5) and (6) of this Schedule and the other is elected.
     /// ```
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
 
     /// See discussion.
     /// ```text
     /// 11 (1)  When the number of continuing candidates is reduced to the number of vacancies remaining unfilled the continuing candidates are elected, even if they have not reached the quota.
     /// ```
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing }
 
     /// See discussion.
     /// ```text
     /// 11 (2)  When only one vacancy remains unfilled and the votes of one continuing candidate exceed the total of all the votes of the other continuing candidates, together with any surplus not transferred, that candidate is elected.
     /// 11 (3)  When more than one vacancy remains unfilled and the votes of the candidate who (if all the vacancies were filled by the successive election of the continuing candidates with the largest number of votes) would be the last to be elected exceed the total of any surplus not transferred plus the votes of all the continuing candidates with fewer votes than that candidate, that candidate and all the other continuing candidates who do not have fewer votes than that candidate are elected.
     /// ```
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing}
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing}
     /// The legislation may be bad, but at least it doesn't have this buggy mess!
-    fn should_eliminate_multiple_candidates_federal_rule_13a() -> bool { false }
+    fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { false }
 
-    fn name() -> String { "NSWLocalGov2021".to_string() }
-    fn how_to_name_counts() -> CountNamingMethod { CountNamingMethod::BasedOnSourceName }
+    fn name(&self) -> String { "NSWLocalGov2021".to_string() }
+    fn how_to_name_counts(&self) -> CountNamingMethod { CountNamingMethod::BasedOnSourceName }
 }
 
 
 /// My guess at what the algorithm used by NSWEC in the 2021 Local Government Elections.
 /// The legislation is ambiguous so this is inspired by the legislation and looking at the detailed distribution of preferences transcript.
+#[derive(Default)]
 pub struct NSWECLocalGov2021 {
 }
 
@@ -115,23 +117,23 @@ impl PreferenceDistributionRules for NSWECLocalGov2021 {
     type Tally = usize;
     type SplitByNumber = FullySplitByCountNumber;
 
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { LastParcelUse::No }
-    fn transfer_value_method() -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::No }
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
 
     fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { convert_usize_to_rational(tally)  }
     fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { round_rational_down_to_usize(rational)  }
 
-    fn make_transfer_value(surplus: usize, ballots: BallotPaperCount) -> TransferValue { // NA
+    fn make_transfer_value(&self,surplus: usize, ballots: BallotPaperCount) -> TransferValue { // NA
         TransferValue::from_surplus(surplus,ballots)
     }
 
-    fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
         transfer_value.mul_rounding_down(ballots)
     }
 
     /// NSWEC split surplus distribution into separate transfers for each source transfer in. E.g. City of Albury, count 47
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod { SurplusTransferMethod::ScaleTransferValues }
-    fn sort_exclusions_by_transfer_value() -> bool { false }
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::ScaleTransferValues }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { false }
 
     /// Evidence that only when an action is finished is relevant : City of Albury, count 39,
     /// TIERNAN Jodie was eliminated. At the end of count 38, TIERNAN Jodie was tied on 94 with DOCKSEY Graham.
@@ -158,26 +160,26 @@ impl PreferenceDistributionRules for NSWECLocalGov2021 {
     /// It is not clear whether if a countback resolves some but not all candidates, then is there a lot amongst all or just the unresolved?
     /// I am giving the NSWEC the benefit of the doubt and taking the more powerful answer as there is convincing
     /// evidence that this was done in the prior system (e.g. Port Stephens - Central Ward, count 2, 2017)
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
 
     /// NSWEC didn't check in the middle of surplus. E.g. City of Albury, count 47
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool { false }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { false }
     /// NSWEC didn't check in the middle of exclusion. E.g. City of Albury, count 46
-    fn check_elected_if_in_middle_of_exclusion() -> bool { false }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { false }
 
 
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool { false }
-    fn finish_all_surplus_distributions_when_all_elected() -> bool { false }
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing }
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing}
-    fn should_eliminate_multiple_candidates_federal_rule_13a() -> bool { false }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing }
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing}
+    fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { false }
 
-    fn name() -> String { "NSWECLocalGov2021".to_string() }
-    fn how_to_name_counts() -> CountNamingMethod { CountNamingMethod::BasedOnSourceName }
+    fn name(&self) -> String { "NSWECLocalGov2021".to_string() }
+    fn how_to_name_counts(&self) -> CountNamingMethod { CountNamingMethod::BasedOnSourceName }
 
     /// There is some bizarre ordering thing in the City of Albury. Count 42.41.20.5.1 is listed after counts 42.41.20.14.1 and 42.41.20.14.5.1.
     /// count 42 is the exclusion of COHN Amanda
@@ -192,7 +194,7 @@ impl PreferenceDistributionRules for NSWECLocalGov2021 {
     /// as the order of subcounts doesn't matter in their interpretation of the legislation. Nevertheless,
     /// the legislation is specific in 9(2)(b) about the order it should be done.
     /// A very similar thing happens for surplus distributions - compare 47.45.40.4.1 and 47.45.40.37.6.2.1
-    fn sort_subcounts_by_count() -> Option<Box<dyn FnMut(&Transcript<Self::Tally>,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide) -> Ordering>> {
+    fn sort_subcounts_by_count(&self) -> Option<Box<dyn FnMut(&Transcript<Self::Tally>,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide) -> Ordering>> {
         Some(Box::new(Self::sort_counts_by_name_as_dotted_number_sequences_numerically_first_3_fields_lexicographically_afterwards))
     }
 
@@ -221,7 +223,7 @@ impl PreferenceDistributionRules for NSWECLocalGov2021 {
     /// number (which is not used in the counting legislation) would be better off to be
     /// replaced by a "First Preferences" 10389 row (which is used in the dop counting legislation
     /// but is not listed in the `dopfulldetails.xls` file.
-    fn should_exhausted_votes_count_for_quota_computation() -> bool { false }
+    fn should_exhausted_votes_count_for_quota_computation(&self) -> bool { false }
 
 }
 
@@ -269,54 +271,56 @@ impl NSWECLocalGov2021 {
 ///
 /// Note that most things are the same as NSWECLocalGov2021 except the tally is now signed and
 /// prohibit_negative_surplus_fraction() is set.
+#[derive(Default)]
 pub struct NSWECLocalGov2021Literal {
 }
 
 impl PreferenceDistributionRules for NSWECLocalGov2021Literal {
-    fn prohibit_negative_surplus_fraction() -> bool { false }
+    fn prohibit_negative_surplus_fraction(&self) -> bool { false }
     type Tally = isize;
     type SplitByNumber = FullySplitByCountNumber;
 
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { LastParcelUse::No }
-    fn transfer_value_method() -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::No }
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
 
     fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { convert_usize_to_rational(tally)  }
     fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { round_rational_down_to_isize(rational)  }
 
-    fn make_transfer_value(surplus: isize, ballots: BallotPaperCount) -> TransferValue { // NA
+    fn make_transfer_value(&self,surplus: isize, ballots: BallotPaperCount) -> TransferValue { // NA
         TransferValue::from_surplus(surplus as usize,ballots) // surplus can't actually get negative even if tallies can be negative.
     }
 
-    fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> isize {
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> isize {
         transfer_value.mul_rounding_down_isize(ballots)
     }
 
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod { SurplusTransferMethod::ScaleTransferValues }
-    fn sort_exclusions_by_transfer_value() -> bool { false }
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool { false }
-    fn check_elected_if_in_middle_of_exclusion() -> bool { false }
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool { false }
-    fn finish_all_surplus_distributions_when_all_elected() -> bool { false }
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing }
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing}
-    fn should_eliminate_multiple_candidates_federal_rule_13a() -> bool { false }
-
-    fn name() -> String { "NSWECLocalGov2021Literal".to_string() }
-    fn how_to_name_counts() -> CountNamingMethod { CountNamingMethod::BasedOnSourceName }
-    fn sort_subcounts_by_count() -> Option<Box<dyn FnMut(&Transcript<Self::Tally>,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide) -> Ordering>> {
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::ScaleTransferValues }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { false }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { false }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { false }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing }
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing}
+    fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { false }
+
+    fn name(&self) -> String { "NSWECLocalGov2021Literal".to_string() }
+    fn how_to_name_counts(&self) -> CountNamingMethod { CountNamingMethod::BasedOnSourceName }
+    fn sort_subcounts_by_count(&self) -> Option<Box<dyn FnMut(&Transcript<Self::Tally>,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide) -> Ordering>> {
         Some(Box::new(NSWECLocalGov2021::sort_counts_by_name_as_dotted_number_sequences_numerically_first_3_fields_lexicographically_afterwards))
     }
-    fn should_exhausted_votes_count_for_quota_computation() -> bool { false }
+    fn should_exhausted_votes_count_for_quota_computation(&self) -> bool { false }
 }
 
 
 
 /// A simple IRV computation.
+#[derive(Default)]
 pub struct SimpleIRVAnyDifferenceBreaksTies {
 }
 
@@ -325,45 +329,45 @@ impl PreferenceDistributionRules for SimpleIRVAnyDifferenceBreaksTies {
     type SplitByNumber = DoNotSplitByCountNumber;
 
     /// MAKE IT IRV!
-    fn has_quota() -> bool { false }
+    fn has_quota(&self) -> bool { false }
     // a bunch of not applicable functions.
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { LastParcelUse::No }
-    fn transfer_value_method() -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::No }
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
     fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { convert_usize_to_rational(tally)  }
     fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { round_rational_down_to_usize(rational)  }
-    fn make_transfer_value(surplus: usize, ballots: BallotPaperCount) -> TransferValue { // NA
+    fn make_transfer_value(&self,surplus: usize, ballots: BallotPaperCount) -> TransferValue { // NA
         TransferValue::from_surplus(surplus,ballots)
     }
-    fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
         transfer_value.mul_rounding_down(ballots)
     }
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod { SurplusTransferMethod::ScaleTransferValues }
-    fn sort_exclusions_by_transfer_value() -> bool { false }
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::ScaleTransferValues }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { false }
 
     /// Legislation is ambiguous - use method there was evidence EC used for similar legislation for STV..
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished }
 
     // more not applicable functions.
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool { false }
-    fn check_elected_if_in_middle_of_exclusion() -> bool { false }
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool { false }
-    fn finish_all_surplus_distributions_when_all_elected() -> bool { false }
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { false }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { false }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
 
     // termination condition in case of tie for top.
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfExclusionNotOngoing }
     // normal termination condition
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuota}
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuota}
 
-    fn should_eliminate_multiple_candidates_federal_rule_13a() -> bool { false }
-    fn name() -> String { "IRV".to_string() }
-    fn how_to_name_counts() -> CountNamingMethod { CountNamingMethod::SimpleNumber }
+    fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { false }
+    fn name(&self) -> String { "IRV".to_string() }
+    fn how_to_name_counts(&self) -> CountNamingMethod { CountNamingMethod::SimpleNumber }
 
-    fn sort_subcounts_by_count() -> Option<Box<dyn FnMut(&Transcript<Self::Tally>,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide) -> Ordering>> {
+    fn sort_subcounts_by_count(&self) -> Option<Box<dyn FnMut(&Transcript<Self::Tally>,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide) -> Ordering>> {
         None
     }
-    fn should_exhausted_votes_count_for_quota_computation() -> bool { false }
+    fn should_exhausted_votes_count_for_quota_computation(&self) -> bool { false }
 }
\ No newline at end of file
@@ -10,7 +10,7 @@ use std::marker::PhantomData;
 use stv::ballot_pile::{BallotPaperCount, DoNotSplitByCountNumber};
 use stv::preference_distribution::{BigRational, DeferSurplusDistribution, LastParcelUse, PreferenceDistributionRules, SurplusTransferMethod, TransferValueMethod, WhenToDoElectCandidateClauseChecking};
 use stv::tie_resolution::MethodOfTieResolution;
-use stv::transfer_value::{convert_usize_to_rational, TransferValue};
+use stv::transfer_value::{convert_usize_to_rational, round_rational_down_to_usize, TransferValue};
 
 /// Many variants on the NSW randomized rules are used, partly due to them
 /// fixing various bugs we pointed out, and partly due to the differences between
@@ -191,64 +191,69 @@ pub struct NSWRandomSamplingVariant<V:NSWRandomVariations> {
     phantom : PhantomData<V>
 }
 
+// Not derived, as that would require V:Default.
+impl <V:NSWRandomVariations> Default for NSWRandomSamplingVariant<V> {
+    fn default() -> Self { NSWRandomSamplingVariant{ phantom: PhantomData } }
+}
+
 impl <V:NSWRandomVariations> PreferenceDistributionRules for NSWRandomSamplingVariant<V> {
     type Tally = usize;
     type SplitByNumber = DoNotSplitByCountNumber;
 
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { V::use_last_parcel_for_surplus_distribution() }
-    fn transfer_value_method() -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { V::use_last_parcel_for_surplus_distribution() }
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
 
     fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { convert_usize_to_rational(tally)  }
-    fn convert_rational_to_tally_after_applying_transfer_value(_rational: BigRational) -> Self::Tally { panic!("NSW Random sampling never does conversion to rational")  }
+    fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { round_rational_down_to_usize(rational) } // Not used in the count itself, but may be used for computing an alternative quota.
 
-    fn make_transfer_value(surplus: usize, ballots: BallotPaperCount) -> TransferValue {
+    fn make_transfer_value(&self,surplus: usize, ballots: BallotPaperCount) -> TransferValue {
         if surplus>=ballots.0 { TransferValue::one() }
         else { TransferValue::from_surplus(surplus,ballots) }
     }
 
-    fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
         transfer_value.mul_rounding_down(ballots)
     }
 
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod { SurplusTransferMethod::PickRandomlyAfterDistribution }
-    fn sort_exclusions_by_transfer_value() -> bool { false }
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::PickRandomlyAfterDistribution }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { false }
 
     // NA
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { MethodOfTieResolution::None }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
     /// From the "Functional Requirements for Count Module, 1.4.8 Step 8:
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { V::resolve_ties_elected_by_quota() }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { V::resolve_ties_elected_by_quota() }
     /// There doesn't seem to be mention of this in the functional requirements document, either in section 1.4.10 or 1.4.30.
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { MethodOfTieResolution::None }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
 
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { V::resolve_ties_choose_lowest_candidate_for_exclusion() }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { V::resolve_ties_choose_lowest_candidate_for_exclusion() }
 
     /// NA as there is no middle
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool { false }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { false }
     /// A count can be interrupted in before papers are distributed if the rest are elected.
-    fn check_elected_if_in_middle_of_exclusion() -> bool { true }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { true }
 
     /// NA as there is no middle
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool { false }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
     /// The functional requirements are pretty clear on this.
-    fn finish_all_surplus_distributions_when_all_elected() -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
 
 
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
 
     /// Section 1.4.11 (after quota) and 1.4.27 (after exclusion, before doing distribution or checking quota)
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapersOrQuotaButOnlyIfContinuingCandidatesEqualsUnfilledVacanciesAndNotAfterSurplusIfMoreSurplusesAvailable }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapersOrQuotaButOnlyIfContinuingCandidatesEqualsUnfilledVacanciesAndNotAfterSurplusIfMoreSurplusesAvailable }
 
     /// Section 14.4.11 says do this for LC for 1 vote, and LGE for any number
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapersOrQuotaButOnlyIfContinuingCandidatesEqualsUnfilledVacanciesAndNotAfterSurplusIfMoreSurplusesAvailable}
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapersOrQuotaButOnlyIfContinuingCandidatesEqualsUnfilledVacanciesAndNotAfterSurplusIfMoreSurplusesAvailable}
     /// See comment for [when_to_check_if_top_few_have_overwhelming_votes()]
-    fn when_checking_if_top_few_have_overwhelming_votes_require_exactly_one() -> bool { V::when_checking_if_top_few_have_overwhelming_votes_require_exactly_one() }
+    fn when_checking_if_top_few_have_overwhelming_votes_require_exactly_one(&self) -> bool { V::when_checking_if_top_few_have_overwhelming_votes_require_exactly_one() }
 
     /// Taken from "Functional Requirements for Count Module", 1.4.12
-    fn when_should_surplus_distribution_be_deferred() -> DeferSurplusDistribution { V::when_should_surplus_distribution_be_deferred() }
+    fn when_should_surplus_distribution_be_deferred(&self) -> DeferSurplusDistribution { V::when_should_surplus_distribution_be_deferred() }
 
     /// 2016 LGE Ballina Shire Council - C Ward there is one vote that is exhausted on round 1, and if it were used the quota would be 1 higher.
-    fn should_exhausted_votes_count_for_quota_computation() -> bool { false }
-    fn use_f32_arithmetic_when_applying_transfer_values_instead_of_exact() -> bool { V::use_f32_arithmetic_when_applying_transfer_values_instead_of_exact() }
+    fn should_exhausted_votes_count_for_quota_computation(&self) -> bool { false }
+    fn use_f32_arithmetic_when_applying_transfer_values_instead_of_exact(&self) -> bool { V::use_f32_arithmetic_when_applying_transfer_values_instead_of_exact() }
 
-    fn name() -> String { V::name() }
+    fn name(&self) -> String { V::name() }
 }
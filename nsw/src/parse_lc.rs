@@ -15,14 +15,16 @@ use stv::election_data::ElectionData;
 use anyhow::{anyhow, Context};
 use scraper::{ElementRef, Html, Selector};
 use url::Url;
-use stv::parse_util::{FileFinder, KnowsAboutRawMarkings, MissingFile, RawDataSource};
+use stv::parse_util::{CanReadRawMarkings, FileFinder, MissingFile, RawBallotPaperMetadata, RawDataSource};
+use stv::ballot_paper::RawBallotMarkings;
 use stv::tie_resolution::{TieResolutionAtom, TieResolutionExplicitDecision, TieResolutionExplicitDecisionInCount, TieResolutionsMadeByEC};
 use stv::ballot_pile::BallotPaperCount;
 use stv::datasource_description::{AssociatedRules, Copyright, ElectionDataSource};
 use stv::distribution_of_preferences_transcript::{CountIndex, PerCandidate, QuotaInfo};
+use stv::quota::QuotaMethod;
 use stv::download::CacheDir;
 use stv::official_dop_transcript::{OfficialDistributionOfPreferencesTranscript, OfficialDOPForOneCount};
-use crate::parse_lge::parse_zip_election_file;
+use crate::parse_lge::{iterate_over_raw_markings_in_zip_election_file, parse_zip_election_file};
 
 
 // 2011 data files are at http://www.pastvtr.elections.nsw.gov.au/SGE2011/lc_prefdata.htm
@@ -70,7 +72,22 @@ pub struct NSWLCDataLoader {
 
 
 
-impl KnowsAboutRawMarkings for NSWLCDataLoader {}
+impl CanReadRawMarkings for NSWLCDataLoader {
+    fn iterate_over_raw_markings<F>(&self,electorate:&str,callback:F)  -> anyhow::Result<ElectionMetadata>
+        where F:FnMut(&RawBallotMarkings,RawBallotPaperMetadata)
+    {
+        let mut metadata = self.read_raw_metadata(electorate)?;
+        let zip_preferences_list = self.find_raw_data_file_from_cache(self.pref_url.join(&self.pref_data_filename())?.as_str())?;
+        metadata.source.push(DataSource{
+            url: self.pref_url.to_string(),
+            files: vec![self.pref_data_filename()],
+            comments: None,
+        });
+        iterate_over_raw_markings_in_zip_election_file(File::open(zip_preferences_list)?,&metadata,false,callback)?;
+        Ok(metadata)
+    }
+}
+
 /// Get all the text in an element as a string
 fn text_content(e:&ElementRef<'_>) -> String {
     e.text().collect::<Vec<_>>().join("")
@@ -96,8 +113,8 @@ impl RawDataSource for NSWLCDataLoader {
         }
     }
 
-    fn candidates_to_be_elected(&self, _region: &str) -> NumberOfCandidates {
-        NumberOfCandidates(21)
+    fn candidates_to_be_elected(&self, _region: &str) -> anyhow::Result<NumberOfCandidates> {
+        Ok(NumberOfCandidates(21))
     }
 
     /// There is not much point including these as there is so much other randomness
@@ -106,8 +123,8 @@ impl RawDataSource for NSWLCDataLoader {
     }
 
     /// These are due to a variety of events.
-    fn excluded_candidates(&self, _electorate: &str) -> Vec<CandidateIndex> {
-        Default::default()
+    fn excluded_candidates(&self, _electorate: &str) -> anyhow::Result<Vec<CandidateIndex>> {
+        Ok(Default::default())
     }
 
     fn find_raw_data_file(&self, filename: &str) -> Result<PathBuf, MissingFile> {
@@ -367,6 +384,7 @@ pub fn read_official_dop_transcript_html_index_page_then_one_html_page_per_count
                     papers : BallotPaperCount(total_formal_votes),
                     vacancies,
                     quota,
+                    method: QuotaMethod::Droop,
                 })
             } else { None }
         } else { None }
@@ -516,7 +534,7 @@ impl NSWLCDataLoader {
                 comments: None,
             }],
             results: None,
-            vacancies: Some(self.candidates_to_be_elected(electorate)),
+            vacancies: Some(self.candidates_to_be_elected(electorate)?),
             enrolment: None,
             secondary_vacancies: None,
             excluded: vec![],
@@ -14,14 +14,15 @@ use stv::ballot_metadata::{Candidate, CandidateIndex, DataSource, ElectionMetada
 use stv::election_data::ElectionData;
 use anyhow::{anyhow, Context};
 use scraper::{ElementRef, Html, Selector};
-use stv::ballot_paper::PreferencesComingOutOfOrderHelper;
-use stv::parse_util::{file_to_string, file_to_string_windows_1252, FileFinder, KnowsAboutRawMarkings, MissingAlternateNamedFiles, MissingFile, RawDataSource, read_raw_data_checking_against_official_transcript_to_deduce_ec_resolutions};
+use stv::ballot_paper::{parse_marking, PreferencesComingOutOfOrderHelper, RawBallotMarking, RawBallotMarkings, RawMarkingsComingOutOfOrderHelper};
+use stv::parse_util::{file_to_string, file_to_string_windows_1252, CanReadRawMarkings, FileFinder, MissingAlternateNamedFiles, RawBallotPaperMetadata, MissingFile, RawDataSource, read_raw_data_checking_against_official_transcript_to_deduce_ec_resolutions};
 use stv::tie_resolution::{TieResolutionAtom, TieResolutionExplicitDecision, TieResolutionExplicitDecisionInCount, TieResolutionsMadeByEC};
 use serde::{Serialize,Deserialize};
 use url::Url;
 use stv::ballot_pile::BallotPaperCount;
 use stv::datasource_description::{AssociatedRules, Copyright, ElectionDataSource};
 use stv::distribution_of_preferences_transcript::{CountIndex, PerCandidate, QuotaInfo};
+use stv::quota::QuotaMethod;
 use stv::download::CacheDir;
 use stv::official_dop_transcript::{OfficialDistributionOfPreferencesTranscript, OfficialDOPForOneCount};
 use stv::parse_util::parse_xlsx_by_converting_to_csv_using_openoffice;
@@ -79,7 +80,24 @@ pub struct NSWLGEContest {
     pub vacancies : NumberOfCandidates,
 }
 
-impl KnowsAboutRawMarkings for NSWLGEDataLoader {}
+impl CanReadRawMarkings for NSWLGEDataLoader {
+    fn iterate_over_raw_markings<F>(&self,electorate:&str,callback:F)  -> anyhow::Result<ElectionMetadata>
+        where F:FnMut(&RawBallotMarkings,RawBallotPaperMetadata)
+    {
+        let contest = &self.find_contest(electorate)?.url;
+        let mayoral = electorate.ends_with(" Mayoral");
+        let metadata = self.read_raw_metadata(electorate)?;
+        match self.year.as_str() {
+            "2016" | "2017" | "2021" => {
+                let zip_name = if mayoral {"mayoral-finalpreferencedatafile.zip"} else {"finalpreferencedatafile.zip"};
+                let zip_preferences_list = self.find_raw_data_file_relative(contest,if self.year=="2021"{"download"} else {""},zip_name,&((if self.year=="2021"{"download/"} else {""}).to_string()+zip_name))?;
+                iterate_over_raw_markings_in_zip_election_file(File::open(zip_preferences_list)?,&metadata,mayoral,callback)?;
+                Ok(metadata)
+            }
+            _ => Err(anyhow!("Raw markings not available for NSW LGE year {} as the published preferences are already converted to below the line equivalents",&self.year)),
+        }
+    }
+}
 
 impl RawDataSource for NSWLGEDataLoader {
     fn name(&self, electorate: &str) -> ElectionName {
@@ -93,8 +111,8 @@ impl RawDataSource for NSWLGEDataLoader {
         }
     }
 
-    fn candidates_to_be_elected(&self, region: &str) -> NumberOfCandidates {
-        self.find_contest(region).unwrap().vacancies
+    fn candidates_to_be_elected(&self, region: &str) -> anyhow::Result<NumberOfCandidates> {
+        Ok(self.find_contest(region)?.vacancies)
     }
 
     /// These are deduced by looking at the actual transcript of results.
@@ -104,8 +122,8 @@ impl RawDataSource for NSWLGEDataLoader {
     }
 
     /// These are due to a variety of events.
-    fn excluded_candidates(&self, _electorate: &str) -> Vec<CandidateIndex> {
-        Default::default()
+    fn excluded_candidates(&self, _electorate: &str) -> anyhow::Result<Vec<CandidateIndex>> {
+        Ok(Default::default())
     }
 
     fn find_raw_data_file(&self, filename: &str) -> Result<PathBuf, MissingFile> {
@@ -437,7 +455,8 @@ impl NSWLGEDataLoader {
         let quota = Some(QuotaInfo{
             papers: BallotPaperCount(remove_comma(&table[1][2]).parse::<usize>()?-remove_comma(&table[7][col_exhausted_bps]).parse::<usize>()?),
             vacancies: NumberOfCandidates(remove_comma(&table[2][2]).parse::<usize>()?),
-            quota: remove_comma(&table[3][2]).parse::<f64>()?
+            quota: remove_comma(&table[3][2]).parse::<f64>()?,
+            method: QuotaMethod::Droop,
         });
         assert_eq!(col_result+1,expected_number_columns);
         let mut counts = vec![];
@@ -771,6 +790,57 @@ pub(crate) fn parse_zip_election_file(zipfile : File, metadata:ElectionMetadata,
     Ok(helper.done(metadata))
 }
 
+/// Iterate over the raw markings in the zipped election file, as read by [parse_zip_election_file].
+/// Each paper's metadata includes its vote type, and venue if present in the file.
+/// If the file includes the actual mark made (not just the preference number it was interpreted as), that is used.
+pub(crate) fn iterate_over_raw_markings_in_zip_election_file<F>(zipfile : File, metadata:&ElectionMetadata, mayoral: bool, mut callback:F) -> anyhow::Result<()>
+    where F:FnMut(&RawBallotMarkings,RawBallotPaperMetadata)
+{
+    let mut zipfile = zip::ZipArchive::new(zipfile)?;
+    let zip_contents = zipfile.by_index(0)?;
+    let mut reader = csv::ReaderBuilder::new().delimiter(b'\t').from_reader(zip_contents);
+    let headings = reader.headers()?;
+    let find_optional_col = |heading_name:&str|headings.iter().position(|s|s==heading_name);
+    let find_col = |heading_name:&str|find_optional_col(heading_name).ok_or_else(||anyhow!("Could not find {} column",heading_name));
+    let vote_type_column = find_col("VoteType")?;
+    let paper_id_column = find_col("VCBallotPaperID")?;
+    let preference_number_column = find_col("PreferenceNumber")?;
+    let preference_mark_column = find_optional_col("PreferenceMark");
+    let venue_column = find_optional_col("VenueName").or_else(||find_optional_col("VCVenueName"));
+    let candidate_name_column = find_col("CandidateName")?;
+    let group_column = if mayoral {None} else {Some(find_col("GroupCode")?)};
+    let mut helper = RawMarkingsComingOutOfOrderHelper::new(metadata);
+    let candidate_name_lookup = metadata.get_candidate_name_lookup();
+    let group_id_lookup = metadata.get_party_id_lookup();
+    for record in reader.records() {
+        let record = record?;
+        let mut paper_metadata = vec![("Vote Type",&record[vote_type_column])];
+        if let Some(venue_column) = venue_column { paper_metadata.push(("Venue",&record[venue_column])); }
+        paper_metadata.push(("Ballot Paper ID",&record[paper_id_column]));
+        helper.set_current_paper(&record[paper_id_column],&paper_metadata,&mut callback);
+        let marking = match preference_mark_column {
+            Some(column) => parse_marking(record[column].trim()),
+            None if record[preference_number_column].is_empty() => RawBallotMarking::Other, // something was there, but it was not counted as a preference.
+            None => parse_marking(&record[preference_number_column]),
+        };
+        if marking==RawBallotMarking::Blank { continue; }
+        let candidate_name = &record[candidate_name_column];
+        if candidate_name.is_empty() { // ATL vote
+            if let Some(group_column) = group_column {
+                let group_id = &record[group_column];
+                if group_id.is_empty() { continue; } // no square, e.g. a paper with no markings at all.
+                let party = *group_id_lookup.get(group_id).ok_or_else(||anyhow!("Unknown group id {}",group_id))?;
+                helper.set_atl_marking(party,marking)?;
+            }
+        } else { // BTL vote
+            let candidate = *candidate_name_lookup.get(candidate_name).ok_or_else(||anyhow!("Unknown candidate name {}",candidate_name))?;
+            helper.set_btl_marking(candidate,marking)?;
+        }
+    }
+    helper.done(&mut callback);
+    Ok(())
+}
+
 /// Parse the zipped election file, 2012 LGE format.
 /// This is already decoded into BTL equivalents.
 /// There is a column, TYPE, that could be used to decode back into ATL votes, but this is not currently done.
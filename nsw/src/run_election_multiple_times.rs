@@ -10,14 +10,15 @@
 
 use std::fmt::{Debug, Display};
 use std::str::FromStr;
-use std::sync::Arc;
 use std::thread;
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 use stv::ballot_metadata::{CandidateIndex, ElectionMetadata};
 use stv::distribution_of_preferences_transcript::Transcript;
 use stv::election_data::ElectionData;
+use stv::count_error::CountError;
 use stv::preference_distribution::PreferenceDistributionRules;
+use stv::prepared_election::{PreparedElection, VoteDeltas};
 use stv::random_util::Randomness;
 
 pub struct PossibleResults {
@@ -42,17 +43,20 @@ impl PossibleResults {
         }
     }
     /// Run the election some number of times, and add each
-    pub fn add_run_times<R: PreferenceDistributionRules>(&mut self, data: &ElectionData, times: usize,randomness:&mut Randomness) {
+    pub fn add_run_times<R: PreferenceDistributionRules>(&mut self, rules: &R, data: &ElectionData, times: usize,randomness:&mut Randomness) -> Result<(),CountError> {
+        let prepared = PreparedElection::new(data,None); // resolve tickets once rather than for every run.
+        let no_changes = VoteDeltas::default();
         for _ in 0..times {
-            let result = data.distribute_preferences::<R>(randomness);
+            let result = prepared.distribute_preferences(rules,&no_changes,randomness)?;
             self.add_run(&result);
         }
+        Ok(())
     }
     /// Create a new PossibleResults structure from running the rules a given number of times.
-    pub fn new_from_runs<R: PreferenceDistributionRules>(data: &ElectionData, times: usize,randomness:&mut Randomness) -> Self {
+    pub fn new_from_runs<R: PreferenceDistributionRules>(rules: &R, data: &ElectionData, times: usize,randomness:&mut Randomness) -> Result<Self,CountError> {
         let mut res = PossibleResults::new(data.metadata.candidates.len());
-        res.add_run_times::<R>(data, times,randomness);
-        res
+        res.add_run_times(rules, data, times,randomness)?;
+        Ok(res)
     }
     /// add in other to the cumulative sum of self.
     pub fn merge(&mut self, other: &PossibleResults) {
@@ -62,24 +66,24 @@ impl PossibleResults {
         }
     }
     /// Create a new PossibleResults structure from running the rules a given number of times, split amongst num_threads threads.
-    pub fn new_from_runs_multithreaded<R: PreferenceDistributionRules>(data: &ElectionData, times: usize, num_threads: usize) -> Self {
-        let mut handles = vec![];
-        let data = Arc::new(data.clone());
-        for thread_no in 0..num_threads {
-            let num_to_do = times / num_threads + (if times % num_threads > thread_no { 1 } else { 0 });
-            let data = data.clone();
-            let handle = thread::spawn(move || {
-                let mut rng = Randomness::PRNG(ChaCha20Rng::seed_from_u64(thread_no as u64));
-                Self::new_from_runs::<R>(&data, num_to_do,&mut rng)
-            });
-            handles.push(handle);
-        }
-        let mut res = PossibleResults::new(data.metadata.candidates.len());
-        for handle in handles {
-            let partial = handle.join().unwrap();
-            res.merge(&partial);
-        }
-        res
+    pub fn new_from_runs_multithreaded<R: PreferenceDistributionRules+Sync>(rules: &R, data: &ElectionData, times: usize, num_threads: usize) -> Result<Self,CountError> {
+        thread::scope(|scope|{
+            let mut handles = vec![];
+            for thread_no in 0..num_threads {
+                let num_to_do = times / num_threads + (if times % num_threads > thread_no { 1 } else { 0 });
+                let handle = scope.spawn(move || {
+                    let mut rng = Randomness::PRNG(ChaCha20Rng::seed_from_u64(thread_no as u64));
+                    Self::new_from_runs(rules, data, num_to_do,&mut rng)
+                });
+                handles.push(handle);
+            }
+            let mut res = PossibleResults::new(data.metadata.candidates.len());
+            for handle in handles {
+                let partial = handle.join().unwrap()?;
+                res.merge(&partial);
+            }
+            Ok(res)
+        })
     }
 
     pub fn possible_winners(&self) -> Vec<&ResultForACandidate> {
@@ -21,6 +21,7 @@ mod tests {
     use stv::preference_distribution::{distribute_preferences, PreferenceDistributionRules};
     use stv::random_util::Randomness;
     use stv::tie_resolution::{TieResolutionAtom, TieResolutionExplicitDecisionInCount};
+    use stv::count_observer::IgnoreCountProgress;
 
 
     #[test]
@@ -93,18 +94,18 @@ mod tests {
     /// Test a particular year & electorate against a particular set of rules.
     /// Outermost error is IO type errors.
     /// Innermost error is discrepancies with the official DoP.
-    fn test_internally_consistent<Rules:PreferenceDistributionRules>(year:&str) -> anyhow::Result<Result<Option<TieResolutionExplicitDecisionInCount>, DifferenceBetweenOfficialDoPAndComputed<Rules::Tally>>> where <Rules as PreferenceDistributionRules>::Tally: Send+Sync+'static {
+    fn test_internally_consistent<Rules:PreferenceDistributionRules+Default>(year:&str) -> anyhow::Result<Result<Option<TieResolutionExplicitDecisionInCount>, DifferenceBetweenOfficialDoPAndComputed<Rules::Tally>>> where <Rules as PreferenceDistributionRules>::Tally: Send+Sync+'static {
         test_official_dop_without_actual_votes::<Rules,_>(&NSWLCDataSource{},year,"",true)
     }
 
-    fn test<Rules:PreferenceDistributionRules>(electorate:&str,loader:&NSWLCDataLoader) {
+    fn test<Rules:PreferenceDistributionRules+Default>(electorate:&str,loader:&NSWLCDataLoader) {
         let data = loader.read_raw_data(electorate).unwrap();
         data.print_summary();
         let mut tie_resolutions = data.metadata.tie_resolutions.clone();
         let official_transcript = loader.read_official_dop_transcript(&data.metadata).unwrap();
         let mut randomness = Randomness::PRNG(ChaCha20Rng::seed_from_u64(1));
         loop {
-            let transcript = distribute_preferences::<Rules>(&data, loader.candidates_to_be_elected(electorate), &data.metadata.excluded.iter().cloned().collect(), &tie_resolutions,None,false,&mut randomness);
+            let transcript = distribute_preferences::<Rules>(&data, loader.candidates_to_be_elected(electorate).unwrap(), &data.metadata.excluded.iter().cloned().collect(), &tie_resolutions,None,&mut IgnoreCountProgress{},&mut randomness).unwrap();
             let transcript = TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript };
             std::fs::create_dir_all("test_transcripts").unwrap();
             {
@@ -75,7 +75,7 @@ mod tests {
             btl_transfer_values: vec![],
             informal: 0
         };
-        let transcript = data.distribute_preferences::<NSWLocalCouncilLegislation2021MyGuessAtHighlyAmbiguousLegislation>(&mut Randomness::ReverseDonkeyVote);
+        let transcript = data.distribute_preferences::<NSWLocalCouncilLegislation2021MyGuessAtHighlyAmbiguousLegislation>(&mut Randomness::ReverseDonkeyVote).unwrap();
         assert_eq!(transcript.quota.as_ref().unwrap().papers, BallotPaperCount(40000));
         assert_eq!(transcript.quota.as_ref().unwrap().quota, 10001);
         assert_eq!(transcript.elected, vec![CandidateIndex(2), CandidateIndex(1), CandidateIndex(0)]);
@@ -52,7 +52,7 @@ fn test_boorowa_run_10000_times_and_check_probabilistic_winners_reasonably_close
     let loader = get_nsw_lge_data_loader_2012(&finder).unwrap();
     let data = loader.read_raw_data("Boorowa Council").unwrap();
     let mut prng = Randomness::PRNG(ChaCha20Rng::seed_from_u64(1));
-    let results = PossibleResults::new_from_runs::<NSWECRandomLGE2017>(&data, 10000, &mut prng);
+    let results = PossibleResults::new_from_runs(&NSWECRandomLGE2017::default(),&data, 10000, &mut prng).unwrap();
     results.print_table_results(&data.metadata);
     assert!(results.is_close_to_expected_prob_winning(CandidateIndex(12),1.0));
     assert!(results.is_close_to_expected_prob_winning(CandidateIndex(11),1.0));
@@ -98,7 +98,7 @@ fn test_griffith_run_10000_times_and_check_probabilistic_winners_reasonably_clos
     let loader = get_nsw_lge_data_loader_2012(&finder).unwrap();
     let data = loader.read_raw_data("Griffith City Council").unwrap();
     let mut prng = Randomness::PRNG(ChaCha20Rng::seed_from_u64(1));
-    let results = PossibleResults::new_from_runs::<NSWECRandomLGE2017>(&data, 10000, &mut prng);
+    let results = PossibleResults::new_from_runs(&NSWECRandomLGE2017::default(),&data, 10000, &mut prng).unwrap();
     results.print_table_results(&data.metadata);
     assert!(results.is_close_to_expected_prob_winning(CandidateIndex(8),1.0));
     assert!(results.is_close_to_expected_prob_winning(CandidateIndex(6),1.0));
@@ -130,7 +130,7 @@ fn test_griffith_run_100_times_and_check_probabilistic_winners_reasonably_close_
     let loader = get_nsw_lge_data_loader_2012(&finder).unwrap();
     let data = loader.read_raw_data("Griffith City Council").unwrap();
     let mut prng = Randomness::PRNG(ChaCha20Rng::seed_from_u64(1));
-    let results = PossibleResults::new_from_runs::<NSWECRandomLGE2012>(&data, 100, &mut prng);
+    let results = PossibleResults::new_from_runs(&NSWECRandomLGE2012::default(),&data, 100, &mut prng).unwrap();
     results.print_table_results(&data.metadata);
     assert!(results.is_close_to_expected_prob_winning(CandidateIndex(8),1.0));
     assert!(results.is_close_to_expected_prob_winning(CandidateIndex(6),1.0));
@@ -18,16 +18,17 @@ use stv::parse_util::{FileFinder, RawDataSource};
 use stv::preference_distribution::{distribute_preferences, PreferenceDistributionRules};
 use stv::random_util::Randomness;
 use stv::tie_resolution::{TieResolutionAtom, TieResolutionExplicitDecisionInCount};
+use stv::count_observer::IgnoreCountProgress;
 
 
-fn test<Rules:PreferenceDistributionRules>(electorate:&str,loader:&NSWLGEDataLoader) {
+fn test<Rules:PreferenceDistributionRules+Default>(electorate:&str,loader:&NSWLGEDataLoader) {
     let data = loader.read_raw_data(electorate).unwrap();
     data.print_summary();
     let mut tie_resolutions = data.metadata.tie_resolutions.clone();
     let official_transcript = loader.read_official_dop_transcript(&data.metadata).unwrap();
     let mut randomness = Randomness::PRNG(ChaCha20Rng::seed_from_u64(1));
     loop {
-        let transcript = distribute_preferences::<Rules>(&data, loader.candidates_to_be_elected(electorate), &data.metadata.excluded.iter().cloned().collect(), &tie_resolutions,None,false,&mut randomness);
+        let transcript = distribute_preferences::<Rules>(&data, loader.candidates_to_be_elected(electorate).unwrap(), &data.metadata.excluded.iter().cloned().collect(), &tie_resolutions,None,&mut IgnoreCountProgress{},&mut randomness).unwrap();
         let transcript = TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript };
         std::fs::create_dir_all("test_transcripts").unwrap();
         {
@@ -101,7 +102,7 @@ fn test_wollstonecraft_run_10000_times_and_check_probabilistic_winners_reasonabl
     let loader = get_nsw_lge_data_loader_2017(&finder).unwrap();
     let data = loader.read_raw_data("North Sydney - Wollstonecraft Ward").unwrap();
     let mut randomness = Randomness::PRNG(ChaCha20Rng::seed_from_u64(1));
-    let results = PossibleResults::new_from_runs::<NSWECRandomLGE2017>(&data, 10000, &mut randomness);
+    let results = PossibleResults::new_from_runs(&NSWECRandomLGE2017::default(),&data, 10000, &mut randomness).unwrap();
     results.print_table_results(&data.metadata);
     assert_eq!(10000,results.candidates[9].num_times_elected);
     assert!(results.is_close_to_expected_prob_winning(CandidateIndex(9),1.0));
@@ -136,7 +137,7 @@ fn test_2017_internally_consistent() {
 /// Test a particular year & electorate against a particular set of rules.
 /// Outermost error is IO type errors.
 /// Innermost error is discrepancies with the official DoP.
-fn test_internally_consistent<Rules:PreferenceDistributionRules>(year:&str,state:&str) -> anyhow::Result<Result<Option<TieResolutionExplicitDecisionInCount>, DifferenceBetweenOfficialDoPAndComputed<Rules::Tally>>> where <Rules as PreferenceDistributionRules>::Tally: Send+Sync+'static {
+fn test_internally_consistent<Rules:PreferenceDistributionRules+Default>(year:&str,state:&str) -> anyhow::Result<Result<Option<TieResolutionExplicitDecisionInCount>, DifferenceBetweenOfficialDoPAndComputed<Rules::Tally>>> where <Rules as PreferenceDistributionRules>::Tally: Send+Sync+'static {
     test_official_dop_without_actual_votes::<Rules,_>(&NSWLGEDataSource{},year,state,false)
 }
 
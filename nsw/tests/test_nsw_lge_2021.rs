@@ -14,17 +14,18 @@ use stv::parse_util::{FileFinder, RawDataSource};
 use stv::preference_distribution::{distribute_preferences, PreferenceDistributionRules};
 use stv::random_util::Randomness;
 use stv::tie_resolution::{TieResolutionAtom, TieResolutionsMadeByEC};
+use stv::count_observer::IgnoreCountProgress;
 
 mod test_nsw_lge;
 
 
-fn test<Rules:PreferenceDistributionRules>(electorate:&str,loader:&NSWLGEDataLoader) {
+fn test<Rules:PreferenceDistributionRules+Default>(electorate:&str,loader:&NSWLGEDataLoader) {
     let data = loader.read_raw_data(electorate).unwrap();
     data.print_summary();
     let mut tie_resolutions = TieResolutionsMadeByEC::default();
     let official_transcript = loader.read_official_dop_transcript(&data.metadata).unwrap();
     loop {
-        let transcript = distribute_preferences::<Rules>(&data, loader.candidates_to_be_elected(electorate), &data.metadata.excluded.iter().cloned().collect(), &tie_resolutions,None,false,&mut Randomness::ReverseDonkeyVote);
+        let transcript = distribute_preferences::<Rules>(&data, loader.candidates_to_be_elected(electorate).unwrap(), &data.metadata.excluded.iter().cloned().collect(), &tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
         let transcript = TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript };
         std::fs::create_dir_all("test_transcripts").unwrap();
         {
@@ -0,0 +1,48 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Check assembling raw markings from files with one row per marking, as used by the NSWEC.
+
+use stv::ballot_metadata::{Candidate, CandidateIndex, ElectionMetadata, NumberOfCandidates, Party, PartyIndex};
+use stv::ballot_paper::{RawBallotMarking, RawBallotMarkings, RawMarkingsComingOutOfOrderHelper};
+
+/// Two groups, only the first of which has a square above the line, and an ungrouped candidate.
+fn make_metadata() -> ElectionMetadata {
+    let party = |name:&str,atl_allowed:bool,candidates:Vec<CandidateIndex>| Party{ column_id: name.to_string(), name: name.to_string(), abbreviation: None, atl_allowed, candidates, tickets: vec![] };
+    let candidate = |name:&str,party:Option<PartyIndex>| Candidate{ name: name.to_string(), party, position: None, ec_id: None };
+    let mut metadata = ElectionMetadata::from_candidates(vec![candidate("A1",Some(PartyIndex(0))),candidate("A2",Some(PartyIndex(0))),candidate("B1",Some(PartyIndex(1))),candidate("C",None)],NumberOfCandidates(1));
+    metadata.parties = vec![party("A",true,vec![CandidateIndex(0),CandidateIndex(1)]),party("B",false,vec![CandidateIndex(2)])];
+    metadata
+}
+
+/// The ATL markings, BTL markings and metadata for a paper.
+type FoundPaper = (Vec<RawBallotMarking>,Vec<RawBallotMarking>,Vec<(String,String)>);
+
+#[test]
+fn test_raw_markings_helper() {
+    let metadata = make_metadata();
+    let mut found : Vec<FoundPaper> = vec![];
+    let mut callback = |markings:&RawBallotMarkings,meta:&[(&str,&str)]| {
+        found.push((markings.atl.to_vec(),markings.btl.to_vec(),meta.iter().map(|(k,v)|(k.to_string(),v.to_string())).collect()));
+    };
+    let mut helper = RawMarkingsComingOutOfOrderHelper::new(&metadata);
+    helper.set_current_paper("1",&[("Vote Type","Ordinary")],&mut callback);
+    helper.set_atl_marking(PartyIndex(0),RawBallotMarking::OneEquivalent).unwrap();
+    helper.set_current_paper("2",&[("Vote Type","Postal")],&mut callback);
+    helper.set_btl_marking(CandidateIndex(3),RawBallotMarking::Number(1)).unwrap();
+    helper.set_current_paper("2",&[("Vote Type","Postal")],&mut callback);
+    helper.set_btl_marking(CandidateIndex(0),RawBallotMarking::Number(2)).unwrap();
+    assert!(helper.set_atl_marking(PartyIndex(1),RawBallotMarking::Number(1)).is_err());
+    assert!(helper.set_btl_marking(CandidateIndex(4),RawBallotMarking::Number(1)).is_err());
+    helper.done(&mut callback);
+    assert_eq!(found.len(),2);
+    assert_eq!(found[0].0,vec![RawBallotMarking::OneEquivalent]);
+    assert_eq!(found[0].1,vec![RawBallotMarking::Blank;4]);
+    assert_eq!(found[0].2,vec![("Vote Type".to_string(),"Ordinary".to_string())]);
+    assert_eq!(found[1].0,vec![RawBallotMarking::Blank]);
+    assert_eq!(found[1].1,vec![RawBallotMarking::Number(2),RawBallotMarking::Blank,RawBallotMarking::Blank,RawBallotMarking::Number(1)]);
+    assert_eq!(found[1].2,vec![("Vote Type".to_string(),"Postal".to_string())]);
+}
@@ -10,7 +10,7 @@
 //! The first line contains two integers separated by whitespace; the first is the number of candidates, the second the number of seats.
 //! The next many lines represent votes, as a list of candidate indices (starting at 1) separated by whitespace and ending with 0 and starting with the number of people who voted that way.
 //! The last of these lines just contains "0"
-//! Subsequent lines list the "candidate name" "party", one per line.
+//! Subsequent lines list the "candidate name" "party", one per line. The party may be omitted, as in files published by Scottish councils.
 
 
 use std::fs::File;
@@ -28,6 +28,32 @@ fn parse_as_ints(s:String) -> Result<Vec<i64>,ParseIntError> {
     fields
 }
 
+/// The first lines of a .blt file.
+pub struct BltHeader {
+    pub num_candidates : NumberOfCandidates,
+    pub vacancies : NumberOfCandidates,
+    /// Withdrawn candidates, listed as negative numbers after the first line.
+    pub excluded : Vec<CandidateIndex>,
+}
+
+/// Read just the number of candidates, seats, and withdrawn candidates from a .blt file, without reading the votes.
+pub fn read_header<P:AsRef<Path>>(path:P) -> anyhow::Result<BltHeader> {
+    let file = File::open(path.as_ref())?;
+    let mut lines =  std::io::BufReader::new(file).lines();
+    let firstline = parse_as_ints(get_line(lines.next())?)?;
+    if firstline.len()!=2 { return Err(anyhow!("Expecting the first line to have two fields")); }
+    let mut excluded : Vec<CandidateIndex> = vec![];
+    loop {
+        let line = parse_as_ints(get_line(lines.next())?)?;
+        if line.is_empty() { return Err(anyhow!("Found blank line")); }
+        if line[0]>=0 { break; }
+        for c in line {
+            excluded.push(CandidateIndex((-c-1) as usize))
+        }
+    }
+    Ok(BltHeader{ num_candidates: NumberOfCandidates(firstline[0] as usize), vacancies: NumberOfCandidates(firstline[1] as usize), excluded })
+}
+
 pub fn parse<P:AsRef<Path>>(path:P) -> anyhow::Result<ElectionData> {
     let file = File::open(path.as_ref())?;
     let filename = path.as_ref().file_name().and_then(|s|s.to_str()).unwrap_or("");
@@ -66,22 +92,25 @@ pub fn parse<P:AsRef<Path>>(path:P) -> anyhow::Result<ElectionData> {
     for _ in 0..num_candidates.0 {
         let candidate_name_line = get_line(lines.next())?;
         let fields : Vec<&str> = candidate_name_line.split('"').collect();
-        if fields.len()!=5 { return Err(anyhow!("Expecting candidate name and party in quotes")); }
+        if fields.len()!=5 && fields.len()!=3 { return Err(anyhow!("Expecting candidate name and optionally party in quotes")); }
         let candidate_name = fields[1];
-        let party_name = fields[3];
-        let party = parties.iter().position(|p|party_name==&p.name);
-        let party = party.unwrap_or_else(||{parties.push(Party{
-            column_id: "".to_string(),
-            name: party_name.to_string(),
-            abbreviation: None,
-            atl_allowed: false,
-            candidates: vec![],
-            tickets: vec![],
-        }); parties.len()-1});
-        parties[party].candidates.push(CandidateIndex(candidates.len()));
+        let party = if fields.len()==5 {
+            let party_name = fields[3];
+            let party = parties.iter().position(|p|party_name==p.name);
+            let party = party.unwrap_or_else(||{parties.push(Party{
+                column_id: "".to_string(),
+                name: party_name.to_string(),
+                abbreviation: None,
+                atl_allowed: false,
+                candidates: vec![],
+                tickets: vec![],
+            }); parties.len()-1});
+            parties[party].candidates.push(CandidateIndex(candidates.len()));
+            Some(PartyIndex(party))
+        } else { None };
         candidates.push(Candidate{
             name: candidate_name.to_string(),
-            party: Some(PartyIndex(party)),
+            party,
             position: None,
             ec_id: None,
         })
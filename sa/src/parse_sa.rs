@@ -60,19 +60,19 @@ impl RawDataSource for SADataLoader {
         }
     }
 
-    fn candidates_to_be_elected(&self, _region: &str) -> NumberOfCandidates {
-        match self.year.as_str() {
+    fn candidates_to_be_elected(&self, _region: &str) -> anyhow::Result<NumberOfCandidates> {
+        Ok(match self.year.as_str() {
             "2022" => NumberOfCandidates(11),
             _ => NumberOfCandidates(11), // assumed to not change
-        }
+        })
     }
 
     fn ec_decisions(&self, _electorate: &str) -> TieResolutionsMadeByEC {
         Default::default()
     }
 
-    fn excluded_candidates(&self, _electorate: &str) -> Vec<CandidateIndex> {
-        Default::default()
+    fn excluded_candidates(&self, _electorate: &str) -> anyhow::Result<Vec<CandidateIndex>> {
+        Ok(Default::default())
     }
 
     fn find_raw_data_file(&self, filename: &str) -> Result<PathBuf, MissingFile> {
@@ -124,7 +124,7 @@ impl RawDataSource for SADataLoader {
             parties: builder.parties,
             source: builder.source,
             results: builder.results,
-            vacancies: Some(self.candidates_to_be_elected(_electorate)),
+            vacancies: Some(self.candidates_to_be_elected(_electorate)?),
             enrolment: None,
             secondary_vacancies: None,
             excluded: vec![],
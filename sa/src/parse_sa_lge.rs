@@ -83,16 +83,17 @@ impl RawDataSource for SALGEDataLoader {
         }
     }
 
-    fn candidates_to_be_elected(&self, region: &str) -> NumberOfCandidates {
-        if let Some(&contest_index) = self.contest_index_of_name.get(region) { self.contests[contest_index].pre_election_info.vacancies } else {NumberOfCandidates(0)}
+    fn candidates_to_be_elected(&self, region: &str) -> anyhow::Result<NumberOfCandidates> {
+        let contest_index = self.contest_index_of_name.get(region).ok_or_else(||self.bad_electorate(region))?;
+        Ok(self.contests[*contest_index].pre_election_info.vacancies)
     }
 
     fn ec_decisions(&self, _electorate: &str) -> TieResolutionsMadeByEC {
         Default::default()
     }
 
-    fn excluded_candidates(&self, _electorate: &str) -> Vec<CandidateIndex> {
-        Default::default()
+    fn excluded_candidates(&self, _electorate: &str) -> anyhow::Result<Vec<CandidateIndex>> {
+        Ok(Default::default())
     }
 
     fn find_raw_data_file(&self, filename: &str) -> Result<PathBuf, MissingFile> {
@@ -0,0 +1,81 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rules for Scottish local council elections, which use the Weighted Inclusive Gregory Method.
+
+use stv::ballot_pile::{BallotPaperCount, DoNotSplitByCountNumber};
+use stv::preference_distribution::{BigRational, LastParcelUse, PreferenceDistributionRules, SurplusTransferMethod, TransferValueMethod, WhenToDoElectCandidateClauseChecking};
+use stv::tie_resolution::MethodOfTieResolution;
+use stv::transfer_value::TransferValue;
+use stv::fixed_precision_decimal::FixedPrecisionDecimal;
+
+pub mod parse;
+
+/// My interpretation of the counting rules in Schedule 1 of the Scottish Local Government Elections Order 2007.
+///
+/// All the papers of an elected candidate are transferred, each at its current value multiplied by
+/// surplus/(total value of the candidate's votes), with the resulting transfer value truncated to
+/// 5 decimal places (the Weighted Inclusive Gregory Method). Vote totals are also kept to 5 decimal places.
+/// Each surplus or exclusion is a single stage, even if papers have different values, and
+/// if multiple surpluses are pending the largest is transferred first.
+#[derive(Default)]
+pub struct ScottishSTV2007 {
+}
+
+impl PreferenceDistributionRules for ScottishSTV2007 {
+    type Tally = FixedPrecisionDecimal<5>;
+    type SplitByNumber = DoNotSplitByCountNumber;
+
+    /// All papers are transferred, not just the last parcel.
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::No }
+    /// The denominator is the total value of all the candidate's votes, including ones that will be non-transferable.
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverBallots }
+
+    fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { tally.to_rational() }
+    fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { Self::Tally::from_rational_rounding_down(rational) }
+
+    fn make_transfer_value(&self,surplus: Self::Tally, ballots: BallotPaperCount) -> TransferValue {
+        TransferValue::from_surplus(surplus.get_scaled_value() as usize,BallotPaperCount(ballots.0*(Self::Tally::SCALE as usize)))
+    }
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> Self::Tally {
+        Self::Tally::from_scaled_value(transfer_value.mul_rounding_down(BallotPaperCount(ballots.0*(Self::Tally::SCALE as usize))) as u64)
+    }
+
+    /// Each paper's existing value is multiplied by the surplus fraction.
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::ScaleTransferValues }
+    /// The transfer value of each paper is calculated to 5 decimal places, ignoring the remainder.
+    fn munge_scaled_transfer_value(&self,original: TransferValue) -> TransferValue { original.round_down_to_decimal_digits(5) }
+    /// A surplus or exclusion is a single stage.
+    fn do_all_transfer_values_in_one_count(&self) -> bool { true }
+    /// The largest surplus is transferred first.
+    fn distribute_largest_surplus_first(&self) -> bool { true }
+
+    /// All papers are transferred in one stage, so order doesn't matter.
+    fn sort_exclusions_by_transfer_value(&self) -> bool { false }
+
+    /// NA
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
+    /// Use the most recent stage at which the tied candidates had different totals, failing which by lot.
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    /// Order of election does not otherwise matter, so do the same as for quota.
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    /// The most recent stage at which the tied candidates had different totals, failing which by lot.
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+
+    /// Each transfer is one stage, so there is no middle.
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { false }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { false }
+
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
+
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+    /// When the number of continuing candidates equals the number of unfilled vacancies, they are all elected.
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+
+    fn name(&self) -> String { "ScottishSTV2007".to_string() }
+}
@@ -0,0 +1,222 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Parse the ballot data published by Scottish councils for local government elections.
+//!
+//! Each council publishes the preferences for each ward as a `.blt` file. These are expected in the
+//! folder `Scotland/<year>/<Council>/<Ward>.blt` of the data repository, and the electorate name is `<Council>/<Ward>`.
+//! The number of vacancies and any withdrawn candidates are taken from the `.blt` file.
+//!
+//! The official results for a ward, if present, are expected in `Scotland/<year>/<Council>/<Ward> Results.csv`.
+//! This has columns `Stage`, one column per candidate (headed by name), `Non-transferable` and `Remarks`.
+//! Each row is the progressive total after that stage, and the remarks column contains things like `X elected.` or `X excluded.`
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use anyhow::anyhow;
+use preflib::parse_blt::{read_header, BltHeader};
+use stv::ballot_metadata::{CandidateIndex, DataSource, ElectionMetadata, ElectionName, NumberOfCandidates};
+use stv::datasource_description::{AssociatedRules, Copyright, ElectionDataSource};
+use stv::distribution_of_preferences_transcript::PerCandidate;
+use stv::election_data::ElectionData;
+use stv::official_dop_transcript::{OfficialDistributionOfPreferencesTranscript, OfficialDOPForOneCount};
+use stv::parse_util::{FileFinder, KnowsAboutRawMarkings, MissingFile, RawDataSource};
+use stv::tie_resolution::TieResolutionsMadeByEC;
+
+pub fn get_scotland_data_loader_2012(finder:&FileFinder) -> anyhow::Result<ScottishCouncilDataLoader> {
+    ScottishCouncilDataLoader::new(finder,"2012","https://www.electionsscotland.info/")
+}
+pub fn get_scotland_data_loader_2017(finder:&FileFinder) -> anyhow::Result<ScottishCouncilDataLoader> {
+    ScottishCouncilDataLoader::new(finder,"2017","https://www.electionsscotland.info/")
+}
+pub fn get_scotland_data_loader_2022(finder:&FileFinder) -> anyhow::Result<ScottishCouncilDataLoader> {
+    ScottishCouncilDataLoader::new(finder,"2022","https://www.electionsscotland.info/")
+}
+
+pub struct ScottishCouncilDataSource {}
+
+impl ElectionDataSource for ScottishCouncilDataSource {
+    fn name(&self) -> Cow<'static, str> { "Scottish Local Government".into() }
+    fn ec_name(&self) -> Cow<'static, str> { "Electoral Management Board for Scotland".into() }
+    fn ec_url(&self) -> Cow<'static, str> { "https://www.electionsscotland.info/".into() }
+    fn years(&self) -> Vec<String> { vec!["2012".to_string(),"2017".to_string(),"2022".to_string()] }
+    fn get_loader_for_year(&self,year: &str,finder:&FileFinder) -> anyhow::Result<Box<dyn RawDataSource+Send+Sync>> {
+        match year {
+            "2012" => Ok(Box::new(get_scotland_data_loader_2012(finder)?)),
+            "2017" => Ok(Box::new(get_scotland_data_loader_2017(finder)?)),
+            "2022" => Ok(Box::new(get_scotland_data_loader_2022(finder)?)),
+            _ => Err(anyhow!("Not a valid year")),
+        }
+    }
+}
+
+pub struct ScottishCouncilDataLoader {
+    finder : FileFinder,
+    archive_location : String,
+    year : String,
+    page_url : String,
+}
+
+impl KnowsAboutRawMarkings for ScottishCouncilDataLoader {}
+
+impl RawDataSource for ScottishCouncilDataLoader {
+    fn name(&self,electorate:&str) -> ElectionName {
+        ElectionName{
+            year: self.year.clone(),
+            authority: "Scottish Councils".to_string(),
+            name: "Scottish Local Government".to_string(),
+            electorate: electorate.to_string(),
+            modifications: vec![],
+            comment: None,
+        }
+    }
+
+    /// Wards elect 3 or 4 councillors; the number is in the .blt file.
+    fn candidates_to_be_elected(&self,electorate:&str) -> anyhow::Result<NumberOfCandidates> {
+        Ok(self.read_blt_header(electorate)?.vacancies)
+    }
+
+    fn ec_decisions(&self,_electorate:&str) -> TieResolutionsMadeByEC {
+        TieResolutionsMadeByEC::default()
+    }
+
+    /// Withdrawn candidates are listed in the .blt file.
+    fn excluded_candidates(&self,electorate:&str) -> anyhow::Result<Vec<CandidateIndex>> {
+        Ok(self.read_blt_header(electorate)?.excluded)
+    }
+
+    fn read_raw_data(&self,electorate:&str) -> anyhow::Result<ElectionData> {
+        let filename = electorate.to_string()+".blt";
+        let path = self.find_raw_data_file(&filename)?;
+        println!("Parsing {}",&path.to_string_lossy());
+        let mut data = preflib::parse_blt::parse(&path)?;
+        data.metadata.name = self.name(electorate);
+        data.metadata.source = vec![DataSource{
+            url: self.page_url.clone(),
+            files: vec![filename],
+            comments: None
+        }];
+        Ok(data)
+    }
+
+    fn find_raw_data_file(&self,filename:&str) -> Result<PathBuf,MissingFile> {
+        self.finder.find_raw_data_file(filename,&self.archive_location,&self.page_url)
+    }
+
+    /// Every `<Council>/<Ward>.blt` file found in the archive.
+    fn all_electorates(&self) -> Vec<String> {
+        let archive = self.finder.path.join(&self.archive_location);
+        let mut res : Vec<String> = vec![];
+        if let Ok(councils) = std::fs::read_dir(archive) {
+            for council in councils.flatten() {
+                if let (Ok(wards),Some(council_name)) = (std::fs::read_dir(council.path()),council.file_name().to_str()) {
+                    for ward in wards.flatten() {
+                        let path = ward.path();
+                        if path.extension().and_then(|e|e.to_str())==Some("blt") {
+                            if let Some(ward_name) = path.file_stem().and_then(|s|s.to_str()) {
+                                res.push(council_name.to_string()+"/"+ward_name);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        res.sort();
+        res
+    }
+
+    fn read_raw_metadata(&self,electorate:&str) -> anyhow::Result<ElectionMetadata> {
+        Ok(self.read_raw_data(electorate)?.metadata)
+    }
+
+    fn copyright(&self) -> Copyright {
+        Copyright{
+            statement: Some("© The relevant Scottish council".into()),
+            url: None,
+            license_name: Some("Open Government Licence".into()),
+            license_url: Some("https://www.nationalarchives.gov.uk/doc/open-government-licence/version/3/".into()),
+        }
+    }
+
+    fn rules(&self, _electorate: &str) -> AssociatedRules {
+        AssociatedRules{
+            rules_used: Some("ScottishSTV2007".into()),
+            rules_recommended: Some("ScottishSTV2007".into()),
+            comment: None,
+            reports: vec![]
+        }
+    }
+
+    fn read_official_dop_transcript(&self, metadata: &ElectionMetadata) -> anyhow::Result<OfficialDistributionOfPreferencesTranscript> {
+        let path = self.find_raw_data_file(&(metadata.name.electorate.clone()+" Results.csv"))?;
+        parse_scottish_official_results(&path,metadata)
+    }
+}
+
+impl ScottishCouncilDataLoader {
+
+    pub fn new(finder:&FileFinder,year:&'static str,page_url:&'static str) -> anyhow::Result<Self> {
+        Ok(ScottishCouncilDataLoader {
+            finder : finder.clone(),
+            archive_location: "Scotland/".to_string()+year,
+            year: year.to_string(),
+            page_url: page_url.to_string(),
+        })
+    }
+
+    /// Read just the start of the .blt file for a ward, which is much faster than reading the votes.
+    fn read_blt_header(&self,electorate:&str) -> anyhow::Result<BltHeader> {
+        let path = self.find_raw_data_file(&(electorate.to_string()+".blt"))?;
+        read_header(&path)
+    }
+}
+
+/// Parse the stage by stage results for a ward, as described in the module documentation.
+pub fn parse_scottish_official_results(path:&PathBuf,metadata:&ElectionMetadata) -> anyhow::Result<OfficialDistributionOfPreferencesTranscript> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let headers = rdr.headers()?.clone();
+    let find_column = |name:&str| headers.iter().position(|h|h.trim()==name).ok_or_else(||anyhow!("Could not find column {} in official results",name));
+    let stage_col = find_column("Stage")?;
+    let exhausted_col = find_column("Non-transferable")?;
+    let remarks_col = find_column("Remarks")?;
+    let candidate_of_name = metadata.get_candidate_name_lookup_multiple_ways();
+    let mut candidate_cols = vec![usize::MAX;metadata.candidates.len()];
+    for (col,heading) in headers.iter().enumerate() {
+        if let Some(candidate) = candidate_of_name.get(heading.trim()) { candidate_cols[candidate.0]=col; }
+    }
+    if candidate_cols.contains(&usize::MAX) { return Err(anyhow!("Could not find all candidates in official results")); }
+    fn parse_num(s:&str) -> anyhow::Result<f64> {
+        let s = s.trim().replace(',',"");
+        if s.is_empty() { Ok(0.0) } else { Ok(s.parse::<f64>()?) }
+    }
+    let mut counts : Vec<OfficialDOPForOneCount> = vec![];
+    let mut not_continuing : HashSet<CandidateIndex> = HashSet::default();
+    for record in rdr.records() {
+        let record = record?;
+        let stage = record[stage_col].trim().to_string();
+        if stage.is_empty() { continue; }
+        let mut count = OfficialDOPForOneCount{ count_name:Some(stage.clone()), ..Default::default() };
+        count.vote_total = Some(PerCandidate{
+            candidate: candidate_cols.iter().map(|&c|parse_num(&record[c])).collect::<anyhow::Result<Vec<f64>>>()?,
+            exhausted: parse_num(&record[exhausted_col])?,
+            rounding: 0.0.into(),
+            set_aside: None,
+        });
+        for remark in record[remarks_col].split('.') {
+            let remark = remark.trim();
+            if let Some(name) = remark.strip_suffix(" elected") {
+                let candidate = *candidate_of_name.get(name.trim()).ok_or_else(|| anyhow!("Can't find elected candidate name {} in stage {}",name,stage))?;
+                if not_continuing.insert(candidate) { count.elected.push(candidate); }
+            } else if let Some(name) = remark.strip_suffix(" excluded") {
+                let candidate = *candidate_of_name.get(name.trim()).ok_or_else(|| anyhow!("Can't find excluded candidate name {} in stage {}",name,stage))?;
+                if not_continuing.insert(candidate) { count.excluded.push(candidate); }
+            }
+        }
+        counts.push(count);
+    }
+    Ok(OfficialDistributionOfPreferencesTranscript{ quota:None, counts, missing_negatives_in_papers_delta: false, elected_candidates_are_in_order: true, all_exhausted_go_to_rounding: false, negative_values_in_surplus_distributions_and_rounding_may_be_off: false })
+}
@@ -0,0 +1,110 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Test the Scottish STV rules and the council .blt file loading on small examples.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use scotland::parse::get_scotland_data_loader_2022;
+use scotland::ScottishSTV2007;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::distribution_of_preferences_transcript::{CountIndex, ReasonForCount};
+use stv::election_data::ElectionData;
+use stv::parse_util::{FileFinder, RawDataSource};
+use stv::preference_distribution::distribute_preferences;
+use stv::random_util::Randomness;
+use stv::count_observer::IgnoreCountProgress;
+
+#[test]
+fn test_wigm_truncation_and_single_stage_transfers() {
+    let data = ElectionData::from_btl(&["A","B","C","D","E"],3,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(1)], n: 30 },
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(2)], n: 10 },
+        BTL{ candidates: vec![CandidateIndex(1),CandidateIndex(3)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 15 },
+        BTL{ candidates: vec![CandidateIndex(3),CandidateIndex(2)], n: 14 },
+        BTL{ candidates: vec![CandidateIndex(4)], n: 11 },
+    ]);
+    let transcript = distribute_preferences::<ScottishSTV2007>(&data,NumberOfCandidates(3),&HashSet::default(),&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!("26",transcript.quota.as_ref().unwrap().quota.to_string());
+    // A's surplus of 14 out of 40 votes gives a transfer value of 0.35, electing B.
+    let a_surplus = transcript.count(CountIndex(1));
+    assert!(matches!(a_surplus.reason,ReasonForCount::ExcessDistribution(CandidateIndex(0))));
+    assert_eq!("30.5",a_surplus.status.tallies.candidate[1].to_string());
+    assert_eq!("18.5",a_surplus.status.tallies.candidate[2].to_string());
+    // B's surplus of 4.5 out of 30.5 is 0.14754 after truncation. The 30 papers received from A, worth 0.35 each,
+    // are transferred at 0.35*0.14754... = 0.05163 truncated, all in the same stage.
+    let b_surplus = transcript.count(CountIndex(2));
+    assert!(matches!(b_surplus.reason,ReasonForCount::ExcessDistribution(CandidateIndex(1))));
+    assert!(b_surplus.reason_completed);
+    assert_eq!("7377/50000",b_surplus.created_transfer_value.as_ref().unwrap().transfer_value.to_string());
+    assert_eq!("16.9508",b_surplus.status.tallies.candidate[3].to_string());
+    assert_eq!("1.5489",b_surplus.status.tallies.exhausted.to_string());
+    // E then D are excluded. D has papers at two different values, but is excluded in one stage.
+    assert!(matches!(&transcript.count(CountIndex(3)).reason,ReasonForCount::Elimination(c) if c==&vec![CandidateIndex(4)]));
+    let d_exclusion = transcript.count(CountIndex(4));
+    assert!(matches!(&d_exclusion.reason,ReasonForCount::Elimination(c) if c==&vec![CandidateIndex(3)]));
+    assert!(d_exclusion.reason_completed);
+    assert_eq!(5,transcript.counts.len());
+    assert_eq!(transcript.elected,vec![CandidateIndex(0),CandidateIndex(1),CandidateIndex(2)]);
+}
+
+#[test]
+fn test_largest_surplus_distributed_first() {
+    let data = ElectionData::from_btl(&["A","Y","Z","E","F"],4,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(2)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(1),CandidateIndex(3)], n: 27 },
+        BTL{ candidates: vec![CandidateIndex(2),CandidateIndex(3)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 8 },
+        BTL{ candidates: vec![CandidateIndex(4)], n: 5 },
+    ]);
+    let transcript = distribute_preferences::<ScottishSTV2007>(&data,NumberOfCandidates(4),&HashSet::default(),&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!("21",transcript.quota.as_ref().unwrap().quota.to_string());
+    assert!(matches!(transcript.count(CountIndex(1)).reason,ReasonForCount::ExcessDistribution(CandidateIndex(0))));
+    // Z now has a surplus of 18, larger than Y's surplus of 6 from the first stage, so is distributed first.
+    assert!(matches!(transcript.count(CountIndex(2)).reason,ReasonForCount::ExcessDistribution(CandidateIndex(2))));
+    assert!(matches!(transcript.count(CountIndex(3)).reason,ReasonForCount::ExcessDistribution(CandidateIndex(1))));
+    assert_eq!(transcript.elected,vec![CandidateIndex(0),CandidateIndex(1),CandidateIndex(2),CandidateIndex(3)]);
+}
+
+/// Make a small data repository in a temporary directory.
+fn make_test_files() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("concrete_stv_scotland_test_{}",std::process::id()));
+    let council_dir = dir.join("Scotland").join("2022").join("Glenfinnan");
+    std::fs::create_dir_all(&council_dir).unwrap();
+    std::fs::write(council_dir.join("Ward 1 North.blt"),"4 3\n-2\n5 1 3 0\n3 3 4 1 0\n2 4 0\n0\n\"Ann\"\n\"Bob\"\n\"Cat\"\n\"Dan\"\n\"Ward 1 North\"\n").unwrap();
+    std::fs::write(council_dir.join("Ward 2 South.blt"),"2 1\n1 1 0\n0\n\"Eve\" \"Party\"\n\"Fay\" \"Party\"\n").unwrap();
+    std::fs::write(council_dir.join("Ward 1 North Results.csv"),"Stage,Ann,Bob,Cat,Dan,Non-transferable,Remarks\n1,5,0,3,2,0,Ann elected. Bob excluded.\n2,2.5,0,5.5,2,0,Cat elected\n").unwrap();
+    dir
+}
+
+#[test]
+fn test_parse_council_files() {
+    let dir = make_test_files();
+    let loader = get_scotland_data_loader_2022(&FileFinder{path:dir.clone()}).unwrap();
+    assert_eq!(vec!["Glenfinnan/Ward 1 North".to_string(),"Glenfinnan/Ward 2 South".to_string()],loader.all_electorates());
+    let data = loader.read_raw_data("Glenfinnan/Ward 1 North").unwrap();
+    assert_eq!(4,data.metadata.candidates.len());
+    assert_eq!(None,data.metadata.candidates[0].party);
+    assert_eq!(Some(NumberOfCandidates(3)),data.metadata.vacancies);
+    assert_eq!(vec![CandidateIndex(1)],data.metadata.excluded);
+    assert_eq!("Glenfinnan/Ward 1 North",data.metadata.name.electorate);
+    assert_eq!(10,data.num_votes());
+    assert_eq!(NumberOfCandidates(3),loader.candidates_to_be_elected("Glenfinnan/Ward 1 North").unwrap());
+    assert_eq!(vec![CandidateIndex(1)],loader.excluded_candidates("Glenfinnan/Ward 1 North").unwrap());
+    assert!(loader.candidates_to_be_elected("Glenfinnan/Ward 3 Missing").is_err());
+    assert!(loader.excluded_candidates("Glenfinnan/Ward 3 Missing").is_err());
+    let official = loader.read_official_dop_transcript(&data.metadata).unwrap();
+    assert_eq!(2,official.counts.len());
+    assert_eq!(vec![CandidateIndex(0)],official.counts[0].elected);
+    assert_eq!(vec![CandidateIndex(1)],official.counts[0].excluded);
+    assert_eq!(vec![CandidateIndex(2)],official.counts[1].elected);
+    assert_eq!(Some(5.5),official.counts[1].vote_total.as_ref().map(|t|t.candidate[2]));
+    let other = loader.read_raw_data("Glenfinnan/Ward 2 South").unwrap();
+    assert_eq!(1,other.metadata.parties.len());
+    std::fs::remove_dir_all(dir).unwrap();
+}
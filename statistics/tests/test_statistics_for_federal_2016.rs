@@ -23,7 +23,7 @@ fn test_tasmanian_statistics_using_just_formal_votes() {
 
     let loader = get_federal_data_loader_2016(&FileFinder::find_ec_data_repository());
     let data = loader.load_cached_data("TAS").unwrap();
-    let _transcript = data.distribute_preferences::<FederalRulesUsed2016>(&mut Randomness::ReverseDonkeyVote);
+    let _transcript = data.distribute_preferences::<FederalRulesUsed2016>(&mut Randomness::ReverseDonkeyVote).unwrap();
 
     // Test simple statistics.
     let simple_statistics = SimpleStatistics::new(&data);
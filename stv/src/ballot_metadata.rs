@@ -107,6 +107,25 @@ pub struct ElectionMetadata {
     pub tie_resolutions : TieResolutionsMadeByEC,
 }
 
+impl ElectionMetadata {
+    /// Metadata for a synthetic election, such as those made up for tests, with a blank name,
+    /// the given candidates and number of vacancies, and nothing else.
+    pub fn from_candidates(candidates:Vec<Candidate>,vacancies:NumberOfCandidates) -> Self {
+        ElectionMetadata{
+            name: ElectionName::blank(),
+            candidates,
+            parties: vec![],
+            source: vec![],
+            results: None,
+            vacancies: Some(vacancies),
+            enrolment: None,
+            secondary_vacancies: None,
+            excluded: vec![],
+            tie_resolutions: Default::default()
+        }
+    }
+}
+
 /// Documentation on where the data files used for this data came from.
 #[derive(Debug,Serialize,Deserialize,Clone)]
 pub struct DataSource {
@@ -213,6 +232,11 @@ pub struct ElectionName {
 }
 
 impl ElectionName {
+    /// A name with every field empty, for synthetic elections such as those made up for tests.
+    pub fn blank() -> Self {
+        ElectionName{ year: "".to_string(), authority: "".to_string(), name: "".to_string(), electorate: "".to_string(), modifications: vec![], comment: None }
+    }
+
     pub fn human_readable_name(&self) -> String {
         format!("{} {} election for {}.{}",self.year,self.name,self.electorate,self.modifications.join(" & "))
     }
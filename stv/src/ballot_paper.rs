@@ -14,6 +14,7 @@ use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use anyhow::anyhow;
 use crate::election_data::{ElectionData, VoteTypeSpecification};
+use crate::parse_util::RawBallotPaperMetadata;
 
 /// A marking on a particular square in a ballot. This may or may not be a number.
 #[derive(Copy,Clone,Debug,Eq, PartialEq)]
@@ -75,6 +76,17 @@ pub enum FormalVote {
     Atl(ATL)
 }
 
+/// The minimum number of preferences needed above or below the line for markings to be a formal vote.
+#[derive(Clone,Copy,Debug,Serialize,Deserialize,PartialEq,Eq)]
+pub struct FormalityRules {
+    pub min_atl_prefs_needed : usize,
+    pub min_btl_prefs_needed : usize,
+}
+
+impl Default for FormalityRules {
+    fn default() -> Self { FormalityRules{ min_atl_prefs_needed: 1, min_btl_prefs_needed: 1 } }
+}
+
 /// Where a vote came from.
 #[derive(Clone, Copy,Debug)]
 pub enum VoteSource<'a> {
@@ -136,6 +148,11 @@ impl<'a> RawBallotMarkings<'a> {
         } else {None}
     }
 
+    /// Like [RawBallotMarkings::interpret_vote], with the given formality rules.
+    pub fn interpret_vote_with_rules(&self,rules:FormalityRules) -> Option<FormalVote> {
+        self.interpret_vote(rules.min_atl_prefs_needed,rules.min_btl_prefs_needed)
+    }
+
     /// Interpret a list of markings as preferences.
     /// * Ignore all repeated numbers. E.g. 1 2 2 ignore the 2s.
     /// * Ignore all numbers after a gap. E.g. 1 3 4 ignore the 3 and 4 (unless allow_gaps) is true
@@ -143,6 +160,7 @@ impl<'a> RawBallotMarkings<'a> {
     /// Otherwise take the longest list of preferences starting at 1.
     /// The return type is given by a (provided) function
     fn look_for_continuous_streams<T:Copy,F : Fn(usize)->T>(markings:&[RawBallotMarking],result_generator:F,consider_cross_as_one:bool,allow_gaps:bool) -> Vec<T> {
+        if markings.is_empty() { return vec![]; } // result_generator(0) may not be valid.
         let mut times_seen = vec![0 as usize;markings.len()];
         let mut prefs = vec![result_generator(0);markings.len()];
         for i in 0..markings.len() {
@@ -431,4 +449,68 @@ impl PreferencesComingOutOfOrderHelper {
             informal: self.informal,
         }
     }
-}
\ No newline at end of file
+}
+
+/// A helper structure for iterating over raw markings coming in the form "paper X had marking Y next to Z",
+/// one row per marking with all the rows for a paper adjacent. A callback is called with the
+/// markings and metadata for each paper once all its rows have been read.
+pub struct RawMarkingsComingOutOfOrderHelper {
+    parties_that_can_get_atls : Vec<PartyIndex>,
+    /// atl_position_of_party[i] is the index into markings of the ATL square for PartyIndex(i), if it has one.
+    atl_position_of_party : Vec<Option<usize>>,
+    /// ATL markings first, then BTL markings, as expected by [RawBallotMarkings::new].
+    markings : Vec<RawBallotMarking>,
+    current_paper_id : Option<String>,
+    current_metadata : Vec<(&'static str,String)>,
+}
+
+impl RawMarkingsComingOutOfOrderHelper {
+    pub fn new(metadata:&ElectionMetadata) -> Self {
+        let parties_that_can_get_atls : Vec<PartyIndex> = (0..metadata.parties.len()).filter(|&i|metadata.parties[i].atl_allowed).map(PartyIndex).collect();
+        let mut atl_position_of_party = vec![None;metadata.parties.len()];
+        for (position,party) in parties_that_can_get_atls.iter().enumerate() { atl_position_of_party[party.0]=Some(position); }
+        let markings = vec![RawBallotMarking::Blank;parties_that_can_get_atls.len()+metadata.candidates.len()];
+        RawMarkingsComingOutOfOrderHelper{ parties_that_can_get_atls, atl_position_of_party, markings, current_paper_id: None, current_metadata: vec![] }
+    }
+
+    /// Call for every row before setting markings. If the paper is different from the current paper,
+    /// the current paper is passed to the callback and a new paper started with the given metadata.
+    pub fn set_current_paper<F>(&mut self,paper_id:&str,paper_metadata:&[(&'static str,&str)],callback:&mut F)
+        where F:FnMut(&RawBallotMarkings,RawBallotPaperMetadata)
+    {
+        if self.current_paper_id.as_deref()!=Some(paper_id) {
+            self.done_current_paper(callback);
+            self.current_paper_id=Some(paper_id.to_string());
+            self.current_metadata=paper_metadata.iter().map(|&(name,value)|(name,value.to_string())).collect();
+        }
+    }
+
+    pub fn set_atl_marking(&mut self,party:PartyIndex,marking:RawBallotMarking) -> anyhow::Result<()> {
+        let position = self.atl_position_of_party.get(party.0).cloned().flatten().ok_or_else(||anyhow!("Party {} can not receive above the line votes",party))?;
+        self.markings[position]=marking;
+        Ok(())
+    }
+
+    pub fn set_btl_marking(&mut self,candidate:CandidateIndex,marking:RawBallotMarking) -> anyhow::Result<()> {
+        let position = self.parties_that_can_get_atls.len()+candidate.0;
+        *self.markings.get_mut(position).ok_or_else(||anyhow!("Candidate {} out of range",candidate))? = marking;
+        Ok(())
+    }
+
+    fn done_current_paper<F>(&mut self,callback:&mut F)
+        where F:FnMut(&RawBallotMarkings,RawBallotPaperMetadata)
+    {
+        if self.current_paper_id.take().is_some() {
+            let metadata : Vec<(&str,&str)> = self.current_metadata.iter().map(|(name,value)|(*name,value.as_str())).collect();
+            callback(&RawBallotMarkings::new(&self.parties_that_can_get_atls,&self.markings),&metadata);
+            for m in &mut self.markings { *m=RawBallotMarking::Blank; }
+        }
+    }
+
+    /// Pass the last paper, if any, to the callback.
+    pub fn done<F>(mut self,callback:&mut F)
+        where F:FnMut(&RawBallotMarkings,RawBallotPaperMetadata)
+    {
+        self.done_current_paper(callback);
+    }
+}
@@ -14,7 +14,7 @@ use crate::ballot_metadata::CandidateIndex;
 use crate::ballot_paper::VoteSource;
 use std::collections::{HashSet, HashMap};
 use crate::transfer_value::TransferValue;
-use num::{Zero};
+use num::{BigInt, BigRational, Zero};
 use std::ops::{AddAssign, Sub, Add, SubAssign};
 use serde::Deserialize;
 use serde::Serialize;
@@ -225,11 +225,12 @@ impl <'a> VotesWithSameTransferValue<'a> {
     }
 
     /// Set aside randomly some number of ballots, and return (the chosen ones,the ones set aside).
-    pub fn set_aside(&self,num_to_set_aside:BallotPaperCount,randomness:&mut Randomness) -> (VotesWithSameTransferValue<'a>,VotesWithSameTransferValue<'a>) {
+    /// The count and candidate whose pile this is are used for recording the draw.
+    pub fn set_aside(&self,num_to_set_aside:BallotPaperCount,randomness:&mut Randomness,count:CountIndex,candidate:CandidateIndex) -> (VotesWithSameTransferValue<'a>,VotesWithSameTransferValue<'a>) {
         assert!(num_to_set_aside<=self.num_ballots);
         let mut the_chosen_ones = VotesWithSameTransferValue::default();
         let mut the_unchosen_ones = VotesWithSameTransferValue::default();
-        let chosen = randomness.make_array_with_some_randomly_true(self.num_ballots.0,self.num_ballots.0-num_to_set_aside.0);
+        let chosen = randomness.make_array_with_some_randomly_true(self.num_ballots.0,self.num_ballots.0-num_to_set_aside.0,count,candidate);
         let mut ballots_considered = 0;
         for v in &self.votes {
             let kept = chosen[ballots_considered..][..v.n.0].iter().filter(|v|**v).count();
@@ -386,7 +387,7 @@ impl <'a> DistributedVotes<'a> {
 
 /// Different jurisdictions split up parcels of shares by their provenence in different ways. This abstracts that.
 pub trait HowSplitByCountNumber {
-    type KeyToDivide : Eq+Hash+Clone+Ord+Debug;
+    type KeyToDivide : Eq+Hash+Clone+Ord+Debug+Send+Sync;
     fn key(count_index:CountIndex,when_tv_created:Option<CountIndex>) -> Self::KeyToDivide;
 }
 
@@ -417,6 +418,7 @@ impl HowSplitByCountNumber for SplitByWhenTransferValueWasCreated {
 
 
 
+#[derive(Clone)]
 struct LastParcelInfo<Tally> {
     tally : Tally,
     prior_state : StateBeforeAddition,
@@ -431,6 +433,12 @@ pub struct VotesWithMultipleTransferValues<'a,S:HowSplitByCountNumber,Tally> {
     by_provenance : HashMap<(S::KeyToDivide,TransferValue),(PileProvenance<Tally>,VotesWithSameTransferValue<'a>)>
 }
 
+impl <'a,S:HowSplitByCountNumber,Tally:Clone> Clone for VotesWithMultipleTransferValues<'a,S,Tally> {
+    fn clone(&self) -> Self {
+        VotesWithMultipleTransferValues{ last_parcel: self.last_parcel.clone(), by_provenance: self.by_provenance.clone() }
+    }
+}
+
 impl <'a,S:HowSplitByCountNumber,Tally> Default for VotesWithMultipleTransferValues<'a,S,Tally> {
     fn default() -> Self {
         VotesWithMultipleTransferValues{ last_parcel: None, by_provenance: HashMap::default() }
@@ -493,6 +501,47 @@ impl <'a,S:HowSplitByCountNumber,Tally:AddAssign+Zero+Clone+Display+FromStr+Part
         res
     }
 
+    /// All the votes, in no particular order.
+    pub fn votes(&self) -> impl Iterator<Item=&PartiallyDistributedVote<'a>> {
+        self.by_provenance.values().flat_map(|(_,votes)|votes.votes.iter())
+    }
+
+    /// The number of ballot papers matching some criterion, for each transfer value, with the value they contributed to the tally.
+    /// This is their share of the tally from the papers they arrived with, so includes any rounding. `to_rational` converts a tally to a number.
+    pub fn count_matching_votes(&self,matches:impl Fn(&PartiallyDistributedVote<'a>)->bool,to_rational:impl Fn(&Tally)->BigRational) -> Vec<(TransferValue,BallotPaperCount,BigRational)> {
+        let mut res : Vec<(TransferValue,BallotPaperCount,BigRational)> = vec![];
+        for ((_,tv),(provenance,votes)) in &self.by_provenance {
+            let papers : BallotPaperCount = votes.votes.iter().filter(|v|matches(v)).map(|v|v.n).sum();
+            if papers.0>0 {
+                let value = to_rational(&provenance.tally)*BigInt::from(papers.0)/BigInt::from(votes.num_ballots.0);
+                if let Some((_,existing,existing_value)) = res.iter_mut().find(|(t,_,_)|t==tv) { *existing+=papers; *existing_value+=value; } else { res.push((tv.clone(),papers,value)); }
+            }
+        }
+        res.sort_by(|(tv1,_,_),(tv2,_,_)|tv2.cmp(tv1));
+        res
+    }
+
+    /// Replace each vote for which `matches` is true by the votes produced by `replace`, which must add up to the
+    /// same number of ballots, all above the line iff the original vote was. Parcels are preserved.
+    pub(crate) fn replace_votes(&mut self,matches:impl Fn(&PartiallyDistributedVote<'a>)->bool,replace:impl Fn(&PartiallyDistributedVote<'a>)->Vec<PartiallyDistributedVote<'a>>) {
+        let last_parcel_key = self.last_parcel.as_ref().map(|lp|(S::key(lp.count_index,lp.when_tv_created),lp.transfer_value.clone()));
+        for (key,(provenance,votes)) in self.by_provenance.iter_mut() {
+            let mut i = 0;
+            while i<votes.votes.len() {
+                if matches(&votes.votes[i]) {
+                    let replacement = replace(&votes.votes[i]);
+                    let added = replacement.len();
+                    votes.votes.splice(i..i+1,replacement);
+                    // votes after i have moved, so adjust anything that remembers positions.
+                    let adjust = |state:&mut StateBeforeAddition| if state.votes_len>i { state.votes_len=state.votes_len+added-1; };
+                    for source in &mut provenance.source_counts { adjust(&mut source.state_at_start_of_count); }
+                    if last_parcel_key.as_ref()==Some(key) { if let Some(last_parcel) = self.last_parcel.as_mut() { adjust(&mut last_parcel.prior_state); } }
+                    i+=added;
+                } else { i+=1; }
+            }
+        }
+    }
+
     pub fn last_parcel_count_index(&self) -> Option<CountIndex> {
         self.last_parcel.as_ref().map(|lp|lp.count_index)
     }
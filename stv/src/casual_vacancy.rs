@@ -0,0 +1,107 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Filling a casual vacancy by a countback.
+//!
+//! In some jurisdictions (e.g. the ACT and Tasmania) a casual vacancy is filled by taking some of the
+//! votes of the member who left, and recounting them amongst the candidates who chose to contest the
+//! vacancy. This is done in three steps:
+//! * Count the original election, extracting the votes of the vacating member that are to be recounted.
+//!   Which votes these are depends upon the jurisdiction, and is described by a [CasualVacancyMethod].
+//! * Exclude every candidate who is not contesting the vacancy.
+//! * Count the extracted votes (with their transfer values) for one vacancy.
+//!
+//! A jurisdiction plugs in by implementing [CasualVacancyMethod], giving the [WhatToExtract] that
+//! produces its votes to recount. For example, a Tasmanian Hare-Clark countback would extract the
+//! papers that made up the vacating member's quota.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, OnceLock};
+use serde::{Serialize,Deserialize};
+use crate::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use crate::count_error::CountError;
+use crate::count_observer::IgnoreCountProgress;
+use crate::distribution_of_preferences_transcript::Transcript;
+use crate::election_data::ElectionData;
+use crate::extract_votes_in_pile::{ExtractionRequest, WhatToDoWithExtractedVotes, WhatToExtract};
+use crate::preference_distribution::{distribute_preferences_with_extractors, CountOptions, PreferenceDistributionRules};
+use crate::random_util::Randomness;
+
+/// How a jurisdiction chooses the votes to recount for a casual vacancy.
+pub trait CasualVacancyMethod {
+    /// The votes of the vacating member that are recounted, extracted during the original count.
+    fn votes_to_recount(&self,vacating:CandidateIndex) -> WhatToExtract;
+}
+
+/// The ACT method, recounting the votes used to elect the vacating member, as defined in Schedule 4,
+/// part 4.3 Casual Vacancies in _Electoral Act 1992_.
+pub struct ACTCasualVacancy {}
+
+impl CasualVacancyMethod for ACTCasualVacancy {
+    fn votes_to_recount(&self, vacating: CandidateIndex) -> WhatToExtract { WhatToExtract::ACTVotesUsedToElectCandidate(vacating) }
+}
+
+#[derive(thiserror::Error,Debug,Clone,Eq,PartialEq)]
+pub enum CasualVacancyError {
+    #[error("{0}")]
+    Count(#[from] CountError),
+    /// The votes to recount were not produced by the original count, usually because the vacating member was not elected.
+    #[error("No votes were extracted for candidate {0}, who was probably not elected.")]
+    NoVotesExtracted(CandidateIndex),
+    #[error("Candidate {0} cannot both vacate and contest the vacancy.")]
+    VacatingCandidateIsContesting(CandidateIndex),
+    #[error("No candidates are contesting the vacancy.")]
+    NoCandidatesContesting,
+}
+
+/// The original count, the votes recounted, and the countback, suitable for saving as one file.
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct CasualVacancyReport<Original,Countback> {
+    /// The member who left.
+    pub vacating : CandidateIndex,
+    /// The candidates who chose to contest the vacancy.
+    pub contesting : Vec<CandidateIndex>,
+    pub original_count : Original,
+    /// The votes recounted, with their transfer values, and all non-contesting candidates excluded.
+    pub recounted_votes : ElectionData,
+    pub countback : Countback,
+}
+
+/// Do a casual vacancy countback, using `count_original` to count the original election with the given extraction requests,
+/// and `countback` to count the recounted votes, which have the number of vacancies and excluded candidates set.
+/// The closures allow the rules to be chosen at run time; see [count_casual_vacancy_with_rules] for the usual case.
+pub fn count_casual_vacancy<Original,Countback>(data:&ElectionData,method:&dyn CasualVacancyMethod,vacating:CandidateIndex,contesting:&[CandidateIndex],count_original:impl FnOnce(&ElectionData,&[ExtractionRequest])->Result<Original,CountError>,countback:impl FnOnce(&ElectionData)->Result<Countback,CountError>) -> Result<CasualVacancyReport<Original,Countback>,CasualVacancyError> {
+    let num_candidates = data.metadata.candidates.len();
+    if let Some(&candidate) = std::iter::once(&vacating).chain(contesting).find(|c|c.0>=num_candidates) { return Err(CountError::InvalidCandidate{candidate,num_candidates}.into()); }
+    if contesting.is_empty() { return Err(CasualVacancyError::NoCandidatesContesting); }
+    if contesting.contains(&vacating) { return Err(CasualVacancyError::VacatingCandidateIsContesting(vacating)); }
+    let extracted : Arc<OnceLock<ElectionData>> = Arc::new(OnceLock::new());
+    let extracted_clone = extracted.clone();
+    let what_to_do_with_it = WhatToDoWithExtractedVotes::CallFunction(Arc::new(Mutex::new(move |e:ElectionData|{ let _ = extracted_clone.set(e); })));
+    let extractors = [ExtractionRequest{ what_to_extract: method.votes_to_recount(vacating), what_to_do_with_it }];
+    let original_count = count_original(data,&extractors)?;
+    let mut recounted_votes = extracted.get().cloned().ok_or(CasualVacancyError::NoVotesExtracted(vacating))?;
+    let contesting_set : HashSet<CandidateIndex> = contesting.iter().cloned().collect();
+    recounted_votes.metadata.vacancies=Some(NumberOfCandidates(1));
+    recounted_votes.metadata.excluded=(0..recounted_votes.metadata.candidates.len()).map(CandidateIndex).filter(|c|!contesting_set.contains(c)).collect();
+    let countback = countback(&recounted_votes)?;
+    Ok(CasualVacancyReport{ vacating, contesting: contesting.to_vec(), original_count, recounted_votes, countback })
+}
+
+/// A countback where both counts use the same rules.
+pub type CasualVacancyTranscripts<Tally> = CasualVacancyReport<Transcript<Tally>,Transcript<Tally>>;
+
+/// Do a casual vacancy countback, with the same rules for the original count and the countback,
+/// and the vacancies, excluded candidates and EC decisions from the metadata.
+pub fn count_casual_vacancy_with_rules<Rules:PreferenceDistributionRules>(rules:&Rules,data:&ElectionData,method:&dyn CasualVacancyMethod,vacating:CandidateIndex,contesting:&[CandidateIndex],randomness:&mut Randomness) -> Result<CasualVacancyTranscripts<Rules::Tally>,CasualVacancyError> {
+    let count = |data:&ElectionData,extractors:&[ExtractionRequest],randomness:&mut Randomness| {
+        let options = CountOptions{ extractors, ..CountOptions::from_metadata(data)? };
+        distribute_preferences_with_extractors(rules,data,&options,&mut IgnoreCountProgress{},randomness)
+    };
+    let mut countback_randomness = randomness.clone();
+    let report = count_casual_vacancy(data,method,vacating,contesting,|data,extractors|count(data,extractors,randomness),|recounted|count(recounted,&[],&mut countback_randomness))?;
+    Ok(report)
+}
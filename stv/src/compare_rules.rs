@@ -17,7 +17,6 @@ use std::fmt::{Debug, Display, Formatter};
 use crate::ballot_pile::BallotPaperCount;
 use crate::transfer_value::TransferValue;
 use crate::tie_resolution::MethodOfTieResolution;
-use std::marker::PhantomData;
 use std::str::FromStr;
 use num::BigRational;
 use crate::official_dop_transcript::CanConvertToF64PossiblyLossily;
@@ -68,50 +67,52 @@ impl CompareRules {
         Ok(())
     }
 
-    fn compute<Rules:PreferenceDistributionRules>(&self,data:&ElectionData) -> anyhow::Result<Transcript<Rules::Tally>> {
-        let transcript = data.distribute_preferences::<Rules>(&mut Randomness::ReverseDonkeyVote);
+    fn compute<Rules:PreferenceDistributionRules+Default>(&self,data:&ElectionData) -> anyhow::Result<Transcript<Rules::Tally>> {
+        let transcript = data.distribute_preferences::<Rules>(&mut Randomness::ReverseDonkeyVote)?;
         let transcript = TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript };
-        let name = data.metadata.name.identifier()+"_"+&Rules::name()+".transcript";
+        let name = data.metadata.name.identifier()+"_"+&transcript.transcript.rules+".transcript";
         self.save(&transcript,&name)?;
         Ok(transcript.transcript)
     }
 
-    pub fn compare_alternate_rules_2_of_1<R:PreferenceDistributionRules>(&self,transcript:&Transcript<R::Tally>,data:&ElectionData,comparisons : &mut Vec<RuleComparisonDefinition>,results :&mut Vec<DifferenceBetweenTranscripts>) -> anyhow::Result<()> {
-        if R::when_to_check_if_just_two_standing_for_shortcut_election()!=WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapers {
+    pub fn compare_alternate_rules_2_of_1<R:PreferenceDistributionRules+Default>(&self,transcript:&Transcript<R::Tally>,data:&ElectionData,comparisons : &mut Vec<RuleComparisonDefinition>,results :&mut Vec<DifferenceBetweenTranscripts>) -> anyhow::Result<()> {
+        let rules = R::default();
+        if rules.when_to_check_if_just_two_standing_for_shortcut_election()!=WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapers {
+            #[derive(Default)]
             struct AltRule<R:PreferenceDistributionRules> {
-                junk : PhantomData<R>,
+                rules : R,
             }
             impl <R:PreferenceDistributionRules> PreferenceDistributionRules for AltRule<R> {
                 type Tally = R::Tally;
                 type SplitByNumber = R::SplitByNumber;
 
-                fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { R::use_last_parcel_for_surplus_distribution() }
-                fn transfer_value_method() -> TransferValueMethod { R::transfer_value_method() }
+                fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { self.rules.use_last_parcel_for_surplus_distribution() }
+                fn transfer_value_method(&self) -> TransferValueMethod { self.rules.transfer_value_method() }
                 fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { R::convert_tally_to_rational(tally) }
                 fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { R::convert_rational_to_tally_after_applying_transfer_value(rational) }
-                fn make_transfer_value(surplus: Self::Tally, ballots: BallotPaperCount) -> TransferValue { R::make_transfer_value(surplus,ballots) }
-                fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> Self::Tally { R::use_transfer_value(transfer_value,ballots) }
+                fn make_transfer_value(&self,surplus: Self::Tally, ballots: BallotPaperCount) -> TransferValue { self.rules.make_transfer_value(surplus,ballots) }
+                fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> Self::Tally { self.rules.use_transfer_value(transfer_value,ballots) }
 
-                fn surplus_distribution_subdivisions() -> SurplusTransferMethod { R::surplus_distribution_subdivisions() }
-                fn sort_exclusions_by_transfer_value() -> bool { R::sort_exclusions_by_transfer_value() }
+                fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { self.rules.surplus_distribution_subdivisions() }
+                fn sort_exclusions_by_transfer_value(&self) -> bool { self.rules.sort_exclusions_by_transfer_value() }
 
-                fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { R::resolve_ties_elected_one_of_last_two() }
-                fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { R::resolve_ties_elected_by_quota() }
-                fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { R::resolve_ties_elected_all_remaining() }
-                fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { R::resolve_ties_choose_lowest_candidate_for_exclusion() }
+                fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { self.rules.resolve_ties_elected_one_of_last_two() }
+                fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { self.rules.resolve_ties_elected_by_quota() }
+                fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { self.rules.resolve_ties_elected_all_remaining() }
+                fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { self.rules.resolve_ties_choose_lowest_candidate_for_exclusion() }
 
-                fn check_elected_if_in_middle_of_surplus_distribution() -> bool { R::check_elected_if_in_middle_of_surplus_distribution() }
-                fn check_elected_if_in_middle_of_exclusion() -> bool { R::check_elected_if_in_middle_of_exclusion() }
-                fn finish_all_counts_in_elimination_when_all_elected() -> bool { R::finish_all_counts_in_elimination_when_all_elected() }
-                fn finish_all_surplus_distributions_when_all_elected() -> bool { R::finish_all_surplus_distributions_when_all_elected() }
-                fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapers }
-                fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { R::when_to_check_if_all_remaining_should_get_elected() }
+                fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { self.rules.check_elected_if_in_middle_of_surplus_distribution() }
+                fn check_elected_if_in_middle_of_exclusion(&self) -> bool { self.rules.check_elected_if_in_middle_of_exclusion() }
+                fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { self.rules.finish_all_counts_in_elimination_when_all_elected() }
+                fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { self.rules.finish_all_surplus_distributions_when_all_elected() }
+                fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapers }
+                fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { self.rules.when_to_check_if_all_remaining_should_get_elected() }
 
-                fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { R::when_to_check_if_top_few_have_overwhelming_votes() }
+                fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { self.rules.when_to_check_if_top_few_have_overwhelming_votes() }
 
-                fn should_eliminate_multiple_candidates_federal_rule_13a() -> bool { R::should_eliminate_multiple_candidates_federal_rule_13a() }
-                fn count_set_aside_due_to_transfer_value_limit_as_rounding() -> bool { R::count_set_aside_due_to_transfer_value_limit_as_rounding() }
-                fn name() -> String { R::name()+"_Earliest1of2" }
+                fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { self.rules.should_eliminate_multiple_candidates_federal_rule_13a() }
+                fn count_set_aside_due_to_transfer_value_limit_as_rounding(&self) -> bool { self.rules.count_set_aside_due_to_transfer_value_limit_as_rounding() }
+                fn name(&self) -> String { self.rules.name()+"_Earliest1of2" }
 
             }
             let alt_transcript = self.compute::<AltRule<R>>(data)?;
@@ -119,39 +120,40 @@ impl CompareRules {
             comparisons.push(RuleComparisonDefinition{ rule1: transcript.rules.clone(), rule2: alt_transcript.rules.clone() });
             results.push(diff);
         }
-        if R::when_to_check_if_just_two_standing_for_shortcut_election()!=WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing {
+        if rules.when_to_check_if_just_two_standing_for_shortcut_election()!=WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing {
+            #[derive(Default)]
             struct AltRule<R:PreferenceDistributionRules> {
-                junk : PhantomData<R>,
+                rules : R,
             }
             impl <R:PreferenceDistributionRules> PreferenceDistributionRules for AltRule<R> {
                 type Tally = R::Tally;
                 type SplitByNumber = R::SplitByNumber;
-                fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { R::use_last_parcel_for_surplus_distribution() }
-                fn transfer_value_method() -> TransferValueMethod { R::transfer_value_method() }
+                fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { self.rules.use_last_parcel_for_surplus_distribution() }
+                fn transfer_value_method(&self) -> TransferValueMethod { self.rules.transfer_value_method() }
                 fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { R::convert_tally_to_rational(tally) }
                 fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { R::convert_rational_to_tally_after_applying_transfer_value(rational) }
-                fn make_transfer_value(surplus: Self::Tally, ballots: BallotPaperCount) -> TransferValue { R::make_transfer_value(surplus,ballots) }
-                fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> Self::Tally { R::use_transfer_value(transfer_value,ballots) }
-
-                fn surplus_distribution_subdivisions() -> SurplusTransferMethod { R::surplus_distribution_subdivisions() }
-                fn sort_exclusions_by_transfer_value() -> bool { R::sort_exclusions_by_transfer_value() }
-
-                fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { R::resolve_ties_elected_one_of_last_two() }
-                fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { R::resolve_ties_elected_by_quota() }
-                fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { R::resolve_ties_elected_all_remaining() }
-                fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { R::resolve_ties_choose_lowest_candidate_for_exclusion() }
-
-                fn check_elected_if_in_middle_of_surplus_distribution() -> bool { R::check_elected_if_in_middle_of_surplus_distribution() }
-                fn check_elected_if_in_middle_of_exclusion() -> bool { R::check_elected_if_in_middle_of_exclusion() }
-                fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { R::when_to_check_if_top_few_have_overwhelming_votes() }
-
-                fn finish_all_counts_in_elimination_when_all_elected() -> bool { R::finish_all_counts_in_elimination_when_all_elected() }
-                fn finish_all_surplus_distributions_when_all_elected() -> bool { R::finish_all_surplus_distributions_when_all_elected() }
-                fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
-                fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { R::when_to_check_if_all_remaining_should_get_elected() }
-                fn should_eliminate_multiple_candidates_federal_rule_13a() -> bool { R::should_eliminate_multiple_candidates_federal_rule_13a() }
-                fn count_set_aside_due_to_transfer_value_limit_as_rounding() -> bool { R::count_set_aside_due_to_transfer_value_limit_as_rounding() }
-                fn name() -> String { R::name()+"_Latest1of2" }
+                fn make_transfer_value(&self,surplus: Self::Tally, ballots: BallotPaperCount) -> TransferValue { self.rules.make_transfer_value(surplus,ballots) }
+                fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> Self::Tally { self.rules.use_transfer_value(transfer_value,ballots) }
+
+                fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { self.rules.surplus_distribution_subdivisions() }
+                fn sort_exclusions_by_transfer_value(&self) -> bool { self.rules.sort_exclusions_by_transfer_value() }
+
+                fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { self.rules.resolve_ties_elected_one_of_last_two() }
+                fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { self.rules.resolve_ties_elected_by_quota() }
+                fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { self.rules.resolve_ties_elected_all_remaining() }
+                fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { self.rules.resolve_ties_choose_lowest_candidate_for_exclusion() }
+
+                fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { self.rules.check_elected_if_in_middle_of_surplus_distribution() }
+                fn check_elected_if_in_middle_of_exclusion(&self) -> bool { self.rules.check_elected_if_in_middle_of_exclusion() }
+                fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { self.rules.when_to_check_if_top_few_have_overwhelming_votes() }
+
+                fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { self.rules.finish_all_counts_in_elimination_when_all_elected() }
+                fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { self.rules.finish_all_surplus_distributions_when_all_elected() }
+                fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+                fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { self.rules.when_to_check_if_all_remaining_should_get_elected() }
+                fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { self.rules.should_eliminate_multiple_candidates_federal_rule_13a() }
+                fn count_set_aside_due_to_transfer_value_limit_as_rounding(&self) -> bool { self.rules.count_set_aside_due_to_transfer_value_limit_as_rounding() }
+                fn name(&self) -> String { self.rules.name()+"_Latest1of2" }
             }
             let alt_transcript = self.compute::<AltRule<R>>(data)?;
             let diff = compare_transcripts(transcript,&alt_transcript);
@@ -161,7 +163,7 @@ impl CompareRules {
         Ok(())
     }
 
-    pub fn compare_alternate_rules<R:PreferenceDistributionRules>(&self,transcript:&Transcript<R::Tally>,data:&ElectionData,comparisons : &mut Vec<RuleComparisonDefinition>,results :&mut Vec<DifferenceBetweenTranscripts>) -> anyhow::Result<()> {
+    pub fn compare_alternate_rules<R:PreferenceDistributionRules+Default>(&self,transcript:&Transcript<R::Tally>,data:&ElectionData,comparisons : &mut Vec<RuleComparisonDefinition>,results :&mut Vec<DifferenceBetweenTranscripts>) -> anyhow::Result<()> {
         self.compare_alternate_rules_2_of_1::<R>(transcript,data,comparisons,results)?;
         Ok(())
     }
@@ -169,10 +171,10 @@ impl CompareRules {
     /// This should be more general, rather than restricted to 4 rules.
     pub fn compute_dataset<CommonTally:PartialEq+Clone+FromStr+Display+Debug+CanConvertToF64PossiblyLossily,R1,R2,R3,R4>(&self,data:&ElectionData) -> anyhow::Result<(Vec<RuleComparisonDefinition>,CompareRulesOneDataset)>
     where
-        R1: PreferenceDistributionRules<Tally=CommonTally>,
-        R2: PreferenceDistributionRules<Tally=CommonTally>,
-        R3: PreferenceDistributionRules<Tally=CommonTally>,
-        R4: PreferenceDistributionRules<Tally=CommonTally>,
+        R1: PreferenceDistributionRules<Tally=CommonTally>+Default,
+        R2: PreferenceDistributionRules<Tally=CommonTally>+Default,
+        R3: PreferenceDistributionRules<Tally=CommonTally>+Default,
+        R4: PreferenceDistributionRules<Tally=CommonTally>+Default,
     {
         let mut comparisons : Vec<RuleComparisonDefinition> = vec![];
         let mut results = vec![];
@@ -199,10 +201,10 @@ impl CompareRules {
 
     pub fn compare_datasets<CommonTally:PartialEq+Clone+FromStr+Display+Debug+CanConvertToF64PossiblyLossily,R1,R2,R3,R4,I>(&self,data_iterator:I) -> anyhow::Result<CompareRulesResults>
         where
-            R1: PreferenceDistributionRules<Tally=CommonTally>,
-            R2: PreferenceDistributionRules<Tally=CommonTally>,
-            R3: PreferenceDistributionRules<Tally=CommonTally>,
-            R4: PreferenceDistributionRules<Tally=CommonTally>,
+            R1: PreferenceDistributionRules<Tally=CommonTally>+Default,
+            R2: PreferenceDistributionRules<Tally=CommonTally>+Default,
+            R3: PreferenceDistributionRules<Tally=CommonTally>+Default,
+            R4: PreferenceDistributionRules<Tally=CommonTally>+Default,
             I : IntoIterator<Item = anyhow::Result<ElectionData>>,
     {
         let mut comparisons : Vec<RuleComparisonDefinition> = vec![];
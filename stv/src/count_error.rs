@@ -0,0 +1,61 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Reasons why a distribution of preferences could not be completed.
+//!
+//! These are problems with the input (votes, metadata, EC decisions) rather than bugs, so they are
+//! returned as errors rather than panicking, which matters for long running programs like the webserver.
+
+use crate::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use crate::distribution_of_preferences_transcript::CountIndex;
+use crate::quota::QuotaMethod;
+use serde::{Serialize,Deserialize};
+
+#[derive(thiserror::Error,Debug,Clone,Eq,PartialEq,Serialize,Deserialize)]
+pub enum CountError {
+    #[error("The number of vacancies was not specified.")]
+    MissingVacancies,
+    /// Needed for counts with two numbers of vacancies, such as allocating long terms after a double dissolution.
+    #[error("The secondary number of vacancies was not specified.")]
+    MissingSecondaryVacancies,
+    /// A tie needed a random draw, but [crate::random_util::Randomness::NoDraws] was specified. The candidates are in ballot paper order.
+    #[error("A tie between candidates {} on count #{} could not be resolved by the rules or an EC decision.",.candidates.iter().map(|c|c.to_string()).collect::<Vec<_>>().join(","),.count)]
+    UnresolvableTie{ candidates : Vec<CandidateIndex>, count : CountIndex },
+    /// A vote, excluded candidate or EC tie resolution refers to a candidate that does not exist.
+    #[error("Candidate index {} was used, but there are only {} candidates.",.candidate,.num_candidates)]
+    InvalidCandidate{ candidate : CandidateIndex, num_candidates : usize },
+    /// The tally type used by the rules is not large enough to hold the number of votes.
+    #[error("There are {} ballot papers, but the rules can only count up to {}.",.ballots,.max_ballots)]
+    ArithmeticOverflow{ ballots : usize, max_ballots : usize },
+    /// A draw being replayed with [crate::random_util::Randomness::Replay] is not the draw the count needed, e.g. the votes or rules are different.
+    #[error("Replayed draw #{} does not match the draw needed on count #{}.",.draw,.count)]
+    ReplayedDrawMismatch{ draw : usize, count : CountIndex },
+    /// The count finished without using all the draws being replayed with [crate::random_util::Randomness::Replay].
+    #[error("Only {} of the {} replayed draws were needed.",.used,.recorded)]
+    UnusedReplayedDraws{ used : usize, recorded : usize },
+    /// Analyses that follow individual ballot papers from pile to pile, such as searching for margins, cannot follow
+    /// methods like Meek's where each ballot paper is split between several candidates by keep values.
+    #[error("Count #{} distributes votes by keep values, so ballot papers cannot be followed from pile to pile.",.count)]
+    KeepValuesNotSupported{ count : CountIndex },
+    /// The quota cannot be computed, e.g. the [QuotaMethod::Hare] quota with no vacancies.
+    #[error("The {} quota is not defined for {} vacancies.",.method,.vacancies)]
+    QuotaUndefined{ method : QuotaMethod, vacancies : NumberOfCandidates },
+    /// The rules compute the quota in their own way, and cannot use a different quota method.
+    #[error("These rules cannot use the {} quota.",.method)]
+    QuotaMethodNotSupported{ method : QuotaMethod },
+    /// Enumerating the possible results of draws to resolve ties would need more than the allowed number of counts.
+    #[error("Enumerating the results of tie resolution draws would need more than {} branches.",.max_branches)]
+    TooManyTieBranches{ max_branches : usize },
+    /// Votes were to be extracted at the end of a count that never happened.
+    #[error("Votes were to be extracted at count #{}, but there were only {} counts.",.count,.counts)]
+    ExtractionCountNotReached{ count : CountIndex, counts : usize },
+    /// The counting engine used by the rules, such as Meek's method, cannot extract votes part way through a count.
+    #[error("These rules cannot extract votes during the count.")]
+    ExtractionNotSupported,
+    /// A [crate::dynamic_rules::RuleDescription] was used with a tally or split by number type other than the one it specifies.
+    #[error("The rule description {} specifies a different tally or split by number type to the one used to count it.",.name)]
+    RuleDescriptionTypeMismatch{ name : String },
+}
@@ -0,0 +1,80 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Callbacks that are told what is happening during a distribution of preferences, e.g. for
+//! printing progress, logging, or driving a user interface.
+
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+use crate::ballot_metadata::{CandidateIndex, ElectionMetadata};
+use crate::distribution_of_preferences_transcript::{ElectionReason, QuotaInfo, SingleCount};
+use crate::tie_resolution::TieResolutionUsage;
+
+/// Something that wants to be told about events during a count. All methods do nothing by default,
+/// so an implementation only needs to provide the ones it cares about.
+///
+/// The events are in the order they happen. In particular [CountObserver::candidate_elected],
+/// [CountObserver::candidates_excluded], [CountObserver::surplus_distribution_started] and
+/// [CountObserver::tie_resolved] are called before the [CountObserver::count_finished] of the count
+/// in which they happened.
+pub trait CountObserver<Tally:PartialEq+Clone+Display+FromStr+Debug> {
+    /// Called once, before anything else.
+    fn count_started(&mut self,_metadata:&ElectionMetadata) {}
+    /// The quota has been determined. Not called for rules that do not have a quota.
+    fn quota_computed(&mut self,_quota:&QuotaInfo<Tally>) {}
+    /// A candidate has been elected.
+    fn candidate_elected(&mut self,_who:CandidateIndex,_why:ElectionReason) {}
+    /// Some candidates have been chosen for exclusion. The transfer of their papers follows.
+    fn candidates_excluded(&mut self,_who:&[CandidateIndex]) {}
+    /// The distribution of a candidate's surplus is about to start.
+    fn surplus_distribution_started(&mut self,_who:CandidateIndex,_surplus:&Tally) {}
+    /// Some candidates with equal tallies have been put into an order, lowest first.
+    /// `needed_ec_decision` is true if the rules could not separate them and the
+    /// electoral commission's decisions (possibly random) had to be used.
+    fn tie_resolved(&mut self,_usage:TieResolutionUsage,_resolved_order:&[CandidateIndex],_needed_ec_decision:bool) {}
+    /// A count has finished, and has been added to the transcript.
+    fn count_finished(&mut self,_count:&SingleCount<Tally>) {}
+}
+
+/// Don't do anything.
+pub struct IgnoreCountProgress {}
+
+impl <Tally:PartialEq+Clone+Display+FromStr+Debug> CountObserver<Tally> for IgnoreCountProgress {}
+
+/// Print the candidate names, and then the tallies after each count, the quota, and who is elected and excluded, to stdout.
+#[derive(Default)]
+pub struct PrintProgressToStdout {
+    names : Vec<String>,
+}
+
+impl PrintProgressToStdout {
+    fn names(&self,who:&[CandidateIndex]) -> String {
+        who.iter().map(|c|self.names.get(c.0).cloned().unwrap_or_else(||c.to_string())).collect::<Vec<String>>().join(", ")
+    }
+}
+
+impl <Tally:PartialEq+Clone+Display+FromStr+Debug> CountObserver<Tally> for PrintProgressToStdout {
+    fn count_started(&mut self, metadata: &ElectionMetadata) {
+        self.names = metadata.candidates.iter().map(|c|c.name.clone()).collect();
+        println!("{}",self.names.join("\t")+"\tExhausted");
+    }
+
+    fn quota_computed(&mut self, quota: &QuotaInfo<Tally>) {
+        println!("Quota = {}", quota.quota);
+    }
+
+    fn candidate_elected(&mut self, who: CandidateIndex, _why: ElectionReason) {
+        println!("Elected {}", self.names(&[who]));
+    }
+
+    fn candidates_excluded(&mut self, who: &[CandidateIndex]) {
+        println!("Excluded {}", self.names(who));
+    }
+
+    fn count_finished(&mut self, count: &SingleCount<Tally>) {
+        println!("{}",count.status.tallies.candidate.iter().map(|t|t.to_string()).collect::<Vec<String>>().join("\t")+"\t"+&count.status.papers.exhausted.0.to_string());
+    }
+}
@@ -19,6 +19,9 @@ use std::str::FromStr;
 use crate::official_dop_transcript::CanConvertToF64PossiblyLossily;
 use crate::simple_list_of_votes::ListOfVotes;
 use crate::tie_resolution::TieResolutionExplicitDecision;
+use crate::quota::QuotaMethod;
+use crate::random_util::RandomDraw;
+use crate::verifiable_draw::PublicSeed;
 
 
 /// The index of a count. 0 means the first. This is different from the human readable
@@ -203,6 +206,8 @@ pub enum ReasonForCount {
     FirstPreferenceCount,
     ExcessDistribution(CandidateIndex),
     Elimination(Vec<CandidateIndex>),  // usually just one candidate, but federal rules allow multiple elimination
+    /// Surpluses of all the listed elected candidates distributed simultaneously by adjusting keep values, as in Meek's method.
+    ExcessDistributionByKeepValues(Vec<CandidateIndex>),
 }
 
 impl ReasonForCount {
@@ -215,6 +220,7 @@ impl ReasonForCount {
     pub fn is_surplus(&self) -> bool {
         match self {
             ReasonForCount::ExcessDistribution(_) => true,
+            ReasonForCount::ExcessDistributionByKeepValues(_) => true,
             _ => false,
         }
     }
@@ -259,6 +265,18 @@ pub struct TransferValueCreation<Tally> {
     pub source : TransferValueMethod,
 }
 
+/// For iterative counting methods such as Meek's method, the state at the end of one iteration
+/// of a count. Each count may have many iterations while keep values converge.
+#[derive(Clone,Serialize,Deserialize,Debug)]
+pub struct KeepValueIteration<Tally> {
+    /// The keep value (the proportion of each vote reaching them that they keep) used for each candidate in this iteration. Excluded candidates have keep value 0.
+    pub keep_values : Vec<Tally>,
+    /// The quota computed from the votes not exhausted in this iteration.
+    pub quota : Tally,
+    /// The sum of the amounts by which the tallies of elected candidates exceed the quota in this iteration.
+    pub total_surplus : Tally,
+}
+
 /// Sometimes the Electoral Commission needs to make a decision, such as tie resolution.
 /// Sometimes legislation mandates this be random, sometimes the returning officer.
 /// Regardless, this records that the decision needs to be made.
@@ -302,6 +320,9 @@ pub struct SingleCount<Tally:PartialEq+Clone+Display+FromStr> {
     /// A special name for the count, if not 1,2,3,... Mainly used so that each exclusion or surplus distribution is a single "major" count with possibly minor counts included.
     #[serde(skip_serializing_if = "Option::is_none",default)]
     pub count_name : Option<String>,
+    /// For iterative methods such as Meek's method, the keep values and quota after each iteration in this count. None if not applicable.
+    #[serde(skip_serializing_if = "Option::is_none",default="produce_none")]
+    pub keep_values : Option<Vec<KeepValueIteration<Tally>>>,
 }
 
 #[derive(Clone,Serialize,Deserialize,Debug)]
@@ -309,11 +330,17 @@ pub struct QuotaInfo<Tally:Debug> {
     pub papers : BallotPaperCount,
     pub vacancies : NumberOfCandidates,
     pub quota : Tally,
+    /// How the quota was computed from papers and vacancies. Missing in old transcripts, where it was Droop.
+    #[serde(default)]
+    pub method : QuotaMethod,
 }
 
 impl <Tally:Display+Debug> Display for QuotaInfo<Tally> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"({} Papers)/({} vacancies+1) -> quota {}",self.papers,self.vacancies,self.quota)
+        match self.method {
+            QuotaMethod::Droop => write!(f,"({} Papers)/({} vacancies+1) -> quota {}",self.papers,self.vacancies,self.quota),
+            method => write!(f,"{} quota for {} Papers and {} vacancies -> quota {}",method,self.papers,self.vacancies,self.quota),
+        }
     }
 }
 
@@ -325,6 +352,12 @@ pub struct Transcript<Tally:PartialEq+Clone+Display+FromStr+Debug> {
     pub quota : Option<QuotaInfo<Tally>>,
     pub counts : Vec<SingleCount<Tally>>,
     pub elected : Vec<CandidateIndex>,
+    /// The random draws made, if recorded with [crate::random_util::Randomness::Record].
+    #[serde(skip_serializing_if = "Vec::is_empty",default)]
+    pub draws : Vec<RandomDraw>,
+    /// The public seed used for the draws, if made with [crate::random_util::Randomness::Verifiable].
+    #[serde(skip_serializing_if = "Option::is_none",default)]
+    pub draw_seed : Option<PublicSeed>,
 }
 
 fn produce_none<T>() -> Option<T> { None }
@@ -0,0 +1,308 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rules described by a data file (TOML or JSON) rather than Rust code, so that one can
+//! try out a variation of some rules ("what if clause X were interpreted differently")
+//! without recompiling.
+//!
+//! A [RuleDescription] lists the answer to each of the questions asked by [PreferenceDistributionRules].
+//! [DynamicRules] is a [PreferenceDistributionRules] whose answers come from the [RuleDescription]
+//! it was made with, see [DynamicRules::new].
+//!
+//! An example file, equivalent to FederalPost2021, is
+//! ```toml
+//! name = "MyFederalPost2021"
+//! tally = "Integers"
+//! split_by_number = "DoNotSplitByCountNumber"
+//! last_parcel = "No"
+//! transfer_value_method = "SurplusOverBallots"
+//! surplus_transfer_method = "JustOneTransferValue"
+//! sort_exclusions_by_transfer_value = true
+//! resolve_ties_elected_one_of_last_two = "None"
+//! resolve_ties_elected_by_quota = "RequireHistoricalCountsToBeAllDifferent"
+//! resolve_ties_elected_all_remaining = "RequireHistoricalCountsToBeAllDifferent"
+//! resolve_ties_choose_lowest_candidate_for_exclusion = "AnyDifferenceIsADiscriminator"
+//! check_elected_if_in_middle_of_surplus_distribution = true
+//! check_elected_if_in_middle_of_exclusion = true
+//! finish_all_counts_in_elimination_when_all_elected = false
+//! finish_all_surplus_distributions_when_all_elected = false
+//! when_to_check_if_just_two_standing_for_shortcut_election = "AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing"
+//! when_to_check_if_all_remaining_should_get_elected = "AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing"
+//! when_to_check_if_top_few_have_overwhelming_votes = "Never"
+//! ```
+
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::iter::Sum;
+use std::marker::PhantomData;
+use std::ops::{AddAssign, Sub, SubAssign};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use anyhow::anyhow;
+use num::{BigInt, BigRational, Zero};
+use serde::{Deserialize, Serialize};
+use crate::ballot_pile::{BallotPaperCount, DoNotSplitByCountNumber, FullySplitByCountNumber, HowSplitByCountNumber, SplitByWhenTransferValueWasCreated, SplitFirstCount};
+use crate::count_error::CountError;
+use crate::fixed_precision_decimal::FixedPrecisionDecimal;
+use crate::official_dop_transcript::CanConvertToF64PossiblyLossily;
+use crate::preference_distribution::{CountNamingMethod, DeferSurplusDistribution, LastParcelUse, PreferenceDistributionRules, RoundUpToUsize, SurplusTransferMethod, TransferValueMethod, WhenToDoElectCandidateClauseChecking};
+use crate::quota::QuotaMethod;
+use crate::tie_resolution::MethodOfTieResolution;
+use crate::transfer_value::{convert_usize_to_rational, round_rational_down_to_usize, TransferValue};
+
+/// The type used to hold a tally. Determines the Tally type of the [DynamicRules] to use.
+#[derive(Copy,Clone,Serialize,Deserialize,Debug,Eq,PartialEq)]
+pub enum DynamicTallyType {
+    Integers,
+    FiveDigitDecimals,
+    SixDigitDecimals,
+}
+
+/// How parcels of votes are split by provenance. Determines the SplitByNumber type of the [DynamicRules] to use.
+#[derive(Copy,Clone,Serialize,Deserialize,Debug,Eq,PartialEq)]
+pub enum DynamicSplitByNumber {
+    DoNotSplitByCountNumber,
+    FullySplitByCountNumber,
+    SplitFirstCount,
+    SplitByWhenTransferValueWasCreated,
+}
+
+/// How to round a transfer value after it is computed.
+#[derive(Copy,Clone,Serialize,Deserialize,Debug,Eq,PartialEq)]
+pub enum TransferValueRounding {
+    /// Keep the exact fraction.
+    Exact,
+    /// Truncate to the given number of decimal places.
+    RoundDownToDecimalDigits(u32),
+    /// Round to nearest with the given number of decimal places.
+    RoundToDecimalDigits(u32),
+}
+
+impl TransferValueRounding {
+    fn apply(&self,tv:TransferValue) -> TransferValue {
+        match *self {
+            TransferValueRounding::Exact => tv,
+            TransferValueRounding::RoundDownToDecimalDigits(digits) => tv.round_down_to_decimal_digits(digits),
+            TransferValueRounding::RoundToDecimalDigits(digits) => tv.round_to_decimal_digits(digits),
+        }
+    }
+}
+
+/// How to round the votes obtained by multiplying a number of papers by a transfer value to the precision of the tally.
+#[derive(Copy,Clone,Serialize,Deserialize,Debug,Eq,PartialEq)]
+pub enum VoteRounding {
+    Down,
+    Nearest,
+}
+
+fn default_true() -> bool { true }
+fn default_exact() -> TransferValueRounding { TransferValueRounding::Exact }
+fn default_vote_rounding() -> VoteRounding { VoteRounding::Down }
+fn default_count_naming() -> CountNamingMethod { CountNamingMethod::SimpleNumber }
+fn default_defer() -> DeferSurplusDistribution { DeferSurplusDistribution::AlwaysDistributeAllSurplusBeforeAnyExclusions }
+
+/// A description of a set of rules. See [PreferenceDistributionRules] for the meaning of each field.
+/// Fields with defaults in [PreferenceDistributionRules] are optional.
+#[derive(Clone,Serialize,Deserialize,Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RuleDescription {
+    /// A name describing these rules. Used as the name of the rules in the transcript.
+    pub name : String,
+    pub tally : DynamicTallyType,
+    pub split_by_number : DynamicSplitByNumber,
+    #[serde(default="default_true")]
+    pub has_quota : bool,
+    #[serde(default)]
+    pub quota_method : QuotaMethod,
+    pub last_parcel : LastParcelUse,
+    pub transfer_value_method : TransferValueMethod,
+    /// How to round a transfer value computed from a surplus.
+    #[serde(default="default_exact")]
+    pub transfer_value_rounding : TransferValueRounding,
+    /// If true, a transfer value computed from a surplus that would be greater than 1 is 1. E.g. Tasmania.
+    #[serde(default)]
+    pub limit_transfer_value_to_one : bool,
+    #[serde(default="default_vote_rounding")]
+    pub vote_rounding : VoteRounding,
+    pub surplus_transfer_method : SurplusTransferMethod,
+    /// How to round a transfer value produced by scaling an incoming transfer value. See [PreferenceDistributionRules::munge_scaled_transfer_value].
+    #[serde(default="default_exact")]
+    pub scaled_transfer_value_rounding : TransferValueRounding,
+    pub sort_exclusions_by_transfer_value : bool,
+    pub resolve_ties_elected_one_of_last_two : MethodOfTieResolution,
+    pub resolve_ties_elected_by_quota : MethodOfTieResolution,
+    pub resolve_ties_elected_all_remaining : MethodOfTieResolution,
+    pub resolve_ties_choose_lowest_candidate_for_exclusion : MethodOfTieResolution,
+    pub check_elected_if_in_middle_of_surplus_distribution : bool,
+    pub check_elected_if_in_middle_of_exclusion : bool,
+    pub finish_all_counts_in_elimination_when_all_elected : bool,
+    pub finish_all_surplus_distributions_when_all_elected : bool,
+    pub when_to_check_if_just_two_standing_for_shortcut_election : WhenToDoElectCandidateClauseChecking,
+    pub when_to_check_if_all_remaining_should_get_elected : WhenToDoElectCandidateClauseChecking,
+    pub when_to_check_if_top_few_have_overwhelming_votes : WhenToDoElectCandidateClauseChecking,
+    #[serde(default)]
+    pub when_checking_if_top_few_have_overwhelming_votes_require_exactly_one : bool,
+    #[serde(default="default_defer")]
+    pub when_should_surplus_distribution_be_deferred : DeferSurplusDistribution,
+    #[serde(default)]
+    pub should_eliminate_multiple_candidates_federal_rule_13a : bool,
+    #[serde(default)]
+    pub should_eliminate_multiple_candidates_below_next_highest : bool,
+    #[serde(default)]
+    pub count_set_aside_due_to_transfer_value_limit_as_rounding : bool,
+    #[serde(default="default_count_naming")]
+    pub how_to_name_counts : CountNamingMethod,
+    #[serde(default)]
+    pub should_exhausted_votes_count_for_quota_computation : bool,
+    #[serde(default)]
+    pub major_count_if_someone_elected : bool,
+    #[serde(default)]
+    pub use_f32_arithmetic_when_applying_transfer_values_instead_of_exact : bool,
+    #[serde(default="default_true")]
+    pub prohibit_negative_surplus_fraction : bool,
+    /// If true, exhausted votes in an exclusion are rounded down to an integer, the remainder being lost to rounding. E.g. ACT.
+    #[serde(default)]
+    pub round_exhausted_votes_down_in_exclusions : bool,
+    #[serde(default)]
+    pub do_all_transfer_values_in_one_count : bool,
+    #[serde(default)]
+    pub distribute_largest_surplus_first : bool,
+}
+
+impl RuleDescription {
+    /// Read a rule description from a file. The format is JSON if the file name ends in .json, otherwise TOML.
+    pub fn load(path:&Path) -> anyhow::Result<RuleDescription> {
+        let text = std::fs::read_to_string(path).map_err(|e|anyhow!("Could not read rule description {} : {}",path.to_string_lossy(),e))?;
+        if path.extension().map(|e|e=="json").unwrap_or(false) {
+            Ok(serde_json::from_str(&text)?)
+        } else {
+            Ok(toml::from_str(&text)?)
+        }
+    }
+}
+
+/// The operations on a tally type needed by [DynamicRules].
+pub trait DynamicTally : Clone+AddAssign+SubAssign+From<BallotPaperCount>+Display+PartialEq+Serialize+FromStr+Debug+Ord+Sub<Output=Self>+Zero+Hash+Sum<Self>+RoundUpToUsize+CanConvertToF64PossiblyLossily+Send+Sync {
+    /// The value of [RuleDescription::tally] that uses this type, if any.
+    fn tally_type() -> Option<DynamicTallyType>;
+    fn to_rational(&self) -> BigRational;
+    fn from_rational_rounding_down(rational:BigRational) -> Self;
+    fn from_rational_rounding_nearest(rational:BigRational) -> Self;
+    /// Round down to an integer.
+    fn round_down_to_integer(&self) -> Self;
+}
+
+impl DynamicTally for usize {
+    fn tally_type() -> Option<DynamicTallyType> { Some(DynamicTallyType::Integers) }
+    fn to_rational(&self) -> BigRational { convert_usize_to_rational(*self) }
+    fn from_rational_rounding_down(rational: BigRational) -> Self { round_rational_down_to_usize(rational) }
+    fn from_rational_rounding_nearest(rational: BigRational) -> Self { round_rational_down_to_usize(rational.round()) }
+    fn round_down_to_integer(&self) -> Self { *self }
+}
+
+impl <const DIGITS:usize> DynamicTally for FixedPrecisionDecimal<DIGITS> {
+    fn tally_type() -> Option<DynamicTallyType> {
+        match DIGITS {
+            5 => Some(DynamicTallyType::FiveDigitDecimals),
+            6 => Some(DynamicTallyType::SixDigitDecimals),
+            _ => None,
+        }
+    }
+    fn to_rational(&self) -> BigRational { FixedPrecisionDecimal::to_rational(self) }
+    fn from_rational_rounding_down(rational: BigRational) -> Self { FixedPrecisionDecimal::from_rational_rounding_down(rational) }
+    fn from_rational_rounding_nearest(rational: BigRational) -> Self {
+        let scale = BigRational::from_integer(BigInt::from(Self::SCALE));
+        FixedPrecisionDecimal::from_rational_rounding_down((rational*scale.clone()).round()/scale)
+    }
+    fn round_down_to_integer(&self) -> Self { self.round_down() }
+}
+
+/// How parcels of votes are split by provenance, for the split by number types that can be used by [DynamicRules].
+pub trait DynamicSplit : HowSplitByCountNumber {
+    /// The value of [RuleDescription::split_by_number] that uses this type.
+    const SPLIT_BY_NUMBER : DynamicSplitByNumber;
+}
+
+impl DynamicSplit for DoNotSplitByCountNumber { const SPLIT_BY_NUMBER: DynamicSplitByNumber = DynamicSplitByNumber::DoNotSplitByCountNumber; }
+impl DynamicSplit for FullySplitByCountNumber { const SPLIT_BY_NUMBER: DynamicSplitByNumber = DynamicSplitByNumber::FullySplitByCountNumber; }
+impl DynamicSplit for SplitFirstCount { const SPLIT_BY_NUMBER: DynamicSplitByNumber = DynamicSplitByNumber::SplitFirstCount; }
+impl DynamicSplit for SplitByWhenTransferValueWasCreated { const SPLIT_BY_NUMBER: DynamicSplitByNumber = DynamicSplitByNumber::SplitByWhenTransferValueWasCreated; }
+
+/// Rules whose behaviour is given by a [RuleDescription].
+/// The tally and split by number types must match the `tally` and `split_by_number` fields of the description.
+pub struct DynamicRules<Tally:DynamicTally,Split:DynamicSplit=DoNotSplitByCountNumber> {
+    description : Arc<RuleDescription>,
+    phantom : PhantomData<(Tally,Split)>,
+}
+
+impl <Tally:DynamicTally,Split:DynamicSplit> DynamicRules<Tally,Split> {
+    /// Make rules following the description, which must use this Tally and Split type.
+    pub fn new(description:Arc<RuleDescription>) -> Result<Self,CountError> {
+        if Tally::tally_type()!=Some(description.tally) || Split::SPLIT_BY_NUMBER!=description.split_by_number {
+            return Err(CountError::RuleDescriptionTypeMismatch{ name: description.name.clone() });
+        }
+        Ok(DynamicRules{ description, phantom: PhantomData })
+    }
+}
+
+impl <Tally:DynamicTally,Split:DynamicSplit> PreferenceDistributionRules for DynamicRules<Tally,Split> {
+    type Tally = Tally;
+    type SplitByNumber = Split;
+
+    fn has_quota(&self) -> bool { self.description.has_quota }
+    fn quota_method(&self) -> QuotaMethod { self.description.quota_method }
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { self.description.last_parcel }
+    fn transfer_value_method(&self) -> TransferValueMethod { self.description.transfer_value_method }
+    fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { tally.to_rational() }
+    fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { Tally::from_rational_rounding_down(rational) }
+
+    fn make_transfer_value(&self,surplus: Self::Tally, ballots: BallotPaperCount) -> TransferValue {
+        let surplus = surplus.to_rational();
+        let (limit_to_one,rounding) = (self.description.limit_transfer_value_to_one,self.description.transfer_value_rounding);
+        if limit_to_one && surplus>=BigRational::from_integer(BigInt::from(ballots.0)) { TransferValue::one() }
+        else { rounding.apply(TransferValue::new(surplus.numer().clone(),surplus.denom().clone()*BigInt::from(ballots.0))) }
+    }
+
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> Self::Tally {
+        let exact = transfer_value.mul(ballots);
+        match self.description.vote_rounding {
+            VoteRounding::Down => Tally::from_rational_rounding_down(exact),
+            VoteRounding::Nearest => Tally::from_rational_rounding_nearest(exact),
+        }
+    }
+
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { self.description.surplus_transfer_method }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { self.description.sort_exclusions_by_transfer_value }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { self.description.resolve_ties_elected_one_of_last_two }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { self.description.resolve_ties_elected_by_quota }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { self.description.resolve_ties_elected_all_remaining }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { self.description.resolve_ties_choose_lowest_candidate_for_exclusion }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { self.description.check_elected_if_in_middle_of_surplus_distribution }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { self.description.check_elected_if_in_middle_of_exclusion }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { self.description.finish_all_counts_in_elimination_when_all_elected }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { self.description.finish_all_surplus_distributions_when_all_elected }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { self.description.when_to_check_if_just_two_standing_for_shortcut_election }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { self.description.when_to_check_if_all_remaining_should_get_elected }
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { self.description.when_to_check_if_top_few_have_overwhelming_votes }
+    fn when_checking_if_top_few_have_overwhelming_votes_require_exactly_one(&self) -> bool { self.description.when_checking_if_top_few_have_overwhelming_votes_require_exactly_one }
+    fn when_should_surplus_distribution_be_deferred(&self) -> DeferSurplusDistribution { self.description.when_should_surplus_distribution_be_deferred }
+    fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { self.description.should_eliminate_multiple_candidates_federal_rule_13a }
+    fn should_eliminate_multiple_candidates_below_next_highest(&self) -> bool { self.description.should_eliminate_multiple_candidates_below_next_highest }
+    fn count_set_aside_due_to_transfer_value_limit_as_rounding(&self) -> bool { self.description.count_set_aside_due_to_transfer_value_limit_as_rounding }
+    fn name(&self) -> String { self.description.name.clone() }
+    fn how_to_name_counts(&self) -> CountNamingMethod { self.description.how_to_name_counts }
+    fn should_exhausted_votes_count_for_quota_computation(&self) -> bool { self.description.should_exhausted_votes_count_for_quota_computation }
+    fn major_count_if_someone_elected(&self) -> bool { self.description.major_count_if_someone_elected }
+    fn use_f32_arithmetic_when_applying_transfer_values_instead_of_exact(&self) -> bool { self.description.use_f32_arithmetic_when_applying_transfer_values_instead_of_exact }
+    fn prohibit_negative_surplus_fraction(&self) -> bool { self.description.prohibit_negative_surplus_fraction }
+    fn munge_exhausted_votes(&self,exhausted: Self::Tally, is_exclusion: bool) -> Self::Tally {
+        if is_exclusion && self.description.round_exhausted_votes_down_in_exclusions { exhausted.round_down_to_integer() } else { exhausted }
+    }
+    fn munge_scaled_transfer_value(&self,original: TransferValue) -> TransferValue { self.description.scaled_transfer_value_rounding.apply(original) }
+    fn do_all_transfer_values_in_one_count(&self) -> bool { self.description.do_all_transfer_values_in_one_count }
+    fn distribute_largest_surplus_first(&self) -> bool { self.description.distribute_largest_surplus_first }
+}
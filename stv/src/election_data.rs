@@ -6,7 +6,7 @@
 
 
 use std::collections::{HashMap, HashSet};
-use crate::ballot_metadata::{ElectionMetadata, CandidateIndex};
+use crate::ballot_metadata::{ElectionMetadata, CandidateIndex, Candidate, NumberOfCandidates};
 use crate::ballot_paper::{ATL, BTL, VoteSource};
 use crate::ballot_pile::{PartiallyDistributedVote};
 use std::fs::File;
@@ -17,6 +17,8 @@ use crate::distribution_of_preferences_transcript::Transcript;
 use crate::preference_distribution::{BigRational, distribute_preferences, PreferenceDistributionRules};
 use crate::random_util::Randomness;
 use crate::transfer_value::TransferValue;
+use crate::count_error::CountError;
+use crate::count_observer::IgnoreCountProgress;
 
 /*
 /// Complete list of raw ballot markings.
@@ -119,6 +121,21 @@ impl VoteTypeSpecification {
 }
 
 impl ElectionData {
+    /// A synthetic election, such as those made up for tests, with candidates that have just a name,
+    /// only below the line votes, and no informal votes.
+    pub fn from_btl(candidates:&[&str],vacancies:usize,btl:Vec<BTL>) -> Self {
+        ElectionData{
+            metadata: ElectionMetadata::from_candidates(candidates.iter().map(|c|Candidate::from_name(c)).collect(),NumberOfCandidates(vacancies)),
+            atl: vec![],
+            atl_types: vec![],
+            atl_transfer_values: vec![],
+            btl,
+            btl_types: vec![],
+            btl_transfer_values: vec![],
+            informal: 0
+        }
+    }
+
     /// Number of formal above the line votes
     pub fn num_atl(&self) -> usize {
         self.atl.iter().map(|v|v.n).sum()
@@ -268,8 +285,8 @@ impl ElectionData {
     pub fn is_btl_verifiable(&self,btl_index:usize,ballot_types_considered_unverifiable:&HashSet<String>) -> bool { Self::is_verifiable(&self.btl_types,btl_index,ballot_types_considered_unverifiable) }
 
     /// run the distribution of preferences with the values given in the metadata for the number of vacancies, who is ineligible, and EC resolutions. Convenience method.
-    pub fn distribute_preferences<Rules:PreferenceDistributionRules>(&self,randomness:&mut Randomness) -> Transcript<Rules::Tally> {
-        distribute_preferences::<Rules>(self,self.metadata.vacancies.unwrap(),&self.metadata.excluded.iter().cloned().collect::<HashSet<_>>(),&self.metadata.tie_resolutions,None,false,randomness)
+    pub fn distribute_preferences<Rules:PreferenceDistributionRules+Default>(&self,randomness:&mut Randomness) -> Result<Transcript<Rules::Tally>,CountError> {
+        distribute_preferences::<Rules>(self,self.metadata.vacancies.ok_or(CountError::MissingVacancies)?,&self.metadata.excluded.iter().cloned().collect::<HashSet<_>>(),&self.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},randomness)
     }
 
 }
@@ -0,0 +1,145 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Count any set of rules using exact rational arithmetic, with no rounding.
+//!
+//! Legislation generally specifies that votes are rounded down to an integer (or some
+//! number of decimal places) after applying a transfer value. To study how much this
+//! rounding matters, [ExactArithmetic] takes a set of rules and counts it with
+//! [ExactTally] tallies, which are arbitrary precision fractions. The resulting transcript
+//! can be compared with the transcript from the original rules using [crate::compare_transcripts::compare_transcripts]
+//! to find the first count at which rounding makes a difference.
+
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use num::{BigInt, BigRational, ToPrimitive, Zero};
+use crate::ballot_pile::BallotPaperCount;
+use crate::official_dop_transcript::CanConvertToF64PossiblyLossily;
+use crate::preference_distribution::{CountNamingMethod, DeferSurplusDistribution, LastParcelUse, PreferenceDistributionRules, RoundUpToUsize, SurplusTransferMethod, TransferValueMethod, WhenToDoElectCandidateClauseChecking};
+use crate::quota::QuotaMethod;
+use crate::tie_resolution::MethodOfTieResolution;
+use crate::transfer_value::{StringSerializedRational, TransferValue};
+
+/// An exact tally, serialized as a string like "1234/7".
+pub type ExactTally = StringSerializedRational;
+
+impl AddAssign for StringSerializedRational {
+    fn add_assign(&mut self, rhs: Self) { self.0+=rhs.0 }
+}
+impl SubAssign for StringSerializedRational {
+    fn sub_assign(&mut self, rhs: Self) { self.0-=rhs.0 }
+}
+impl Add for StringSerializedRational {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output { StringSerializedRational(self.0+rhs.0) }
+}
+impl Sub for StringSerializedRational {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output { StringSerializedRational(self.0-rhs.0) }
+}
+impl Zero for StringSerializedRational {
+    fn zero() -> Self { StringSerializedRational(BigRational::zero()) }
+    fn is_zero(&self) -> bool { self.0.is_zero() }
+}
+impl Sum for StringSerializedRational {
+    fn sum<I: Iterator<Item=Self>>(iter: I) -> Self {
+        StringSerializedRational(iter.map(|v|v.0).sum())
+    }
+}
+impl From<BallotPaperCount> for StringSerializedRational {
+    fn from(papers: BallotPaperCount) -> Self { StringSerializedRational(BigRational::from_integer(BigInt::from(papers.0))) }
+}
+impl RoundUpToUsize for StringSerializedRational {
+    fn ceil(&self) -> usize { self.0.ceil().to_integer().to_usize().unwrap_or(0) }
+}
+impl CanConvertToF64PossiblyLossily for StringSerializedRational {
+    fn convert_to_f64(&self) -> f64 { self.0.to_f64().unwrap_or(f64::NAN) }
+}
+
+/// The same rules as `Rules`, except that tallies are kept as exact fractions.
+///
+/// Everything that is just rounding is removed: votes are not rounded after applying a transfer value,
+/// transfer values are not rounded, and exhausted votes are not rounded. Things that are not rounding,
+/// such as limiting a transfer value to 1 or to the prior transfer value, are kept.
+/// Emulations of floating point arithmetic are replaced by exact arithmetic, and
+/// [PreferenceDistributionRules::sort_subcounts_by_count] is not supported as it is specific to the original tally type.
+#[derive(Default)]
+pub struct ExactArithmetic<Rules:PreferenceDistributionRules> {
+    rules : Rules,
+}
+
+impl <Rules:PreferenceDistributionRules> ExactArithmetic<Rules> {
+    pub fn new(rules:Rules) -> Self { ExactArithmetic{ rules } }
+    /// Whether the original rules give a transfer value of 1 when the surplus is greater than the number of ballots.
+    fn limits_transfer_value_to_one(&self) -> bool {
+        self.rules.make_transfer_value(Rules::Tally::from(BallotPaperCount(2)),BallotPaperCount(1)).is_one()
+    }
+}
+
+impl <Rules:PreferenceDistributionRules> PreferenceDistributionRules for ExactArithmetic<Rules> {
+    type Tally = ExactTally;
+    type SplitByNumber = Rules::SplitByNumber;
+
+    fn has_quota(&self) -> bool { self.rules.has_quota() }
+    fn quota_method(&self) -> QuotaMethod { self.rules.quota_method() }
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { self.rules.use_last_parcel_for_surplus_distribution() }
+    fn transfer_value_method(&self) -> TransferValueMethod { self.rules.transfer_value_method() }
+    fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { tally.0 }
+    fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { StringSerializedRational(rational) }
+
+    fn make_transfer_value(&self,surplus: Self::Tally, ballots: BallotPaperCount) -> TransferValue {
+        let surplus = surplus.0;
+        if self.limits_transfer_value_to_one() && surplus>=BigRational::from_integer(BigInt::from(ballots.0)) { TransferValue::one() }
+        else { TransferValue::new(surplus.numer().clone(),surplus.denom().clone()*BigInt::from(ballots.0)) }
+    }
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> Self::Tally { StringSerializedRational(transfer_value.mul(ballots)) }
+
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { self.rules.surplus_distribution_subdivisions() }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { self.rules.sort_exclusions_by_transfer_value() }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { self.rules.resolve_ties_elected_one_of_last_two() }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { self.rules.resolve_ties_elected_by_quota() }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { self.rules.resolve_ties_elected_all_remaining() }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { self.rules.resolve_ties_choose_lowest_candidate_for_exclusion() }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { self.rules.check_elected_if_in_middle_of_surplus_distribution() }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { self.rules.check_elected_if_in_middle_of_exclusion() }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { self.rules.finish_all_counts_in_elimination_when_all_elected() }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { self.rules.finish_all_surplus_distributions_when_all_elected() }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { self.rules.when_to_check_if_just_two_standing_for_shortcut_election() }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { self.rules.when_to_check_if_all_remaining_should_get_elected() }
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { self.rules.when_to_check_if_top_few_have_overwhelming_votes() }
+    fn when_checking_if_top_few_have_overwhelming_votes_require_exactly_one(&self) -> bool { self.rules.when_checking_if_top_few_have_overwhelming_votes_require_exactly_one() }
+    fn when_should_surplus_distribution_be_deferred(&self) -> DeferSurplusDistribution { self.rules.when_should_surplus_distribution_be_deferred() }
+    fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { self.rules.should_eliminate_multiple_candidates_federal_rule_13a() }
+    fn should_eliminate_multiple_candidates_below_next_highest(&self) -> bool { self.rules.should_eliminate_multiple_candidates_below_next_highest() }
+    fn count_set_aside_due_to_transfer_value_limit_as_rounding(&self) -> bool { self.rules.count_set_aside_due_to_transfer_value_limit_as_rounding() }
+    fn name(&self) -> String { self.rules.name()+"_Exact" }
+    fn how_to_name_counts(&self) -> CountNamingMethod { self.rules.how_to_name_counts() }
+    fn should_exhausted_votes_count_for_quota_computation(&self) -> bool { self.rules.should_exhausted_votes_count_for_quota_computation() }
+    fn major_count_if_someone_elected(&self) -> bool { self.rules.major_count_if_someone_elected() }
+    fn prohibit_negative_surplus_fraction(&self) -> bool { self.rules.prohibit_negative_surplus_fraction() }
+    fn do_all_transfer_values_in_one_count(&self) -> bool { self.rules.do_all_transfer_values_in_one_count() }
+    fn distribute_largest_surplus_first(&self) -> bool { self.rules.distribute_largest_surplus_first() }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::Zero;
+    use crate::ballot_pile::BallotPaperCount;
+    use crate::exact_arithmetic::ExactTally;
+    use crate::preference_distribution::RoundUpToUsize;
+
+    #[test]
+    fn test_exact_tally() {
+        let third : ExactTally = "1/3".parse().unwrap();
+        let two : ExactTally = BallotPaperCount(2).into();
+        let sum : ExactTally = vec![third.clone(),third.clone(),two.clone()].into_iter().sum();
+        assert_eq!("8/3",sum.to_string());
+        assert_eq!(3,sum.ceil());
+        assert_eq!("2",(sum-third.clone()-third).to_string());
+        assert!(ExactTally::zero().is_zero());
+        assert_eq!("\"8/3\"",serde_json::to_string(&"8/3".parse::<ExactTally>().unwrap()).unwrap());
+    }
+}
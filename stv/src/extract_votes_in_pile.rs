@@ -7,18 +7,45 @@
 //! Utilities designed to extract the votes in a particular pile
 
 
+use std::collections::HashMap;
 use std::fs::File;
+use std::io::Write;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
-use crate::ballot_metadata::CandidateIndex;
-use crate::election_data::ElectionData;
+use crate::ballot_metadata::{CandidateIndex, ElectionMetadata};
+use crate::ballot_paper::{ATL, BTL, VoteSource};
+use crate::ballot_pile::VotesWithSameTransferValue;
+use crate::distribution_of_preferences_transcript::CountIndex;
+use crate::election_data::{ElectionData, VoteValueSpecification};
+use crate::transfer_value::TransferValue;
 
 /// Which votes you want to extract from the transcript.
 #[derive(Debug,Clone)]
 pub enum WhatToExtract {
     /// The votes used to elect a particular candidate, as defined in Schedule 4, part 4.3 Casual Vacancies in _Electoral Act 1992_
-    ACTVotesUsedToElectCandidate(CandidateIndex)
+    ACTVotesUsedToElectCandidate(CandidateIndex),
+    /// The papers in a candidate's pile at the end of a given count, with the transfer values they have in that pile.
+    PileAtCount{ candidate : CandidateIndex, count : CountIndex },
+    /// The papers exhausted at or before a given count, with the transfer values applied to them when they exhausted.
+    /// This is zero if the rules did not give exhausted papers any value, e.g. in a surplus distribution where the transfer value is based only on continuing papers.
+    ExhaustedAtCount(CountIndex),
+    /// The papers some of whose value was set aside at or before a given count due to a rule limiting a transfer value
+    /// to the incoming transfer value. The transfer value given is the portion of the value set aside. This is done even
+    /// for rules that count the value set aside as lost to rounding.
+    SetAsideAtCount(CountIndex),
+}
+
+impl WhatToExtract {
+    /// The count at the end of which the votes should be extracted, if it is a specific count.
+    pub fn count(&self) -> Option<CountIndex> {
+        match self {
+            WhatToExtract::ACTVotesUsedToElectCandidate(_) => None,
+            WhatToExtract::PileAtCount { count, .. } => Some(*count),
+            WhatToExtract::ExhaustedAtCount(count) => Some(*count),
+            WhatToExtract::SetAsideAtCount(count) => Some(*count),
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -27,6 +54,10 @@ pub enum ExtractError {
     UnknownThingToExtract,
     #[error("could not parse as a candidate number")]
     CouldNotParseCandidateNumber,
+    #[error("could not parse as a count number")]
+    CouldNotParseCountNumber,
+    #[error("expecting candidate_number@count_number")]
+    ExpectingCandidateAndCount,
     #[error("unknown thing to do with extracted votes")]
     UnknownThingToDo,
     #[error("an extraction request should be what to extract, followed by a semicolon, followed by what to do with it, and there was no semicolon")]
@@ -37,15 +68,30 @@ impl FromStr for WhatToExtract {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Some(candidate) = s.strip_prefix("UsedToElectACT:") {
             candidate.parse::<CandidateIndex>().map_err(|_| ExtractError::CouldNotParseCandidateNumber).map(|c|WhatToExtract::ACTVotesUsedToElectCandidate(c))
+        } else if let Some(candidate_and_count) = s.strip_prefix("Pile:") {
+            let (candidate,count) = candidate_and_count.split_once('@').ok_or(ExtractError::ExpectingCandidateAndCount)?;
+            let candidate = candidate.parse::<CandidateIndex>().map_err(|_| ExtractError::CouldNotParseCandidateNumber)?;
+            Ok(WhatToExtract::PileAtCount{ candidate, count: parse_count(count)? })
+        } else if let Some(count) = s.strip_prefix("Exhausted@") {
+            Ok(WhatToExtract::ExhaustedAtCount(parse_count(count)?))
+        } else if let Some(count) = s.strip_prefix("SetAside@") {
+            Ok(WhatToExtract::SetAsideAtCount(parse_count(count)?))
         } else {
             Err(ExtractError::UnknownThingToExtract)
         }
     }
 }
 
+fn parse_count(s:&str) -> Result<CountIndex,ExtractError> {
+    s.parse::<usize>().map(CountIndex).map_err(|_| ExtractError::CouldNotParseCountNumber)
+}
+
 #[derive(Clone)]
 pub enum WhatToDoWithExtractedVotes {
+    /// Save as a .stv file.
     SaveToFile(PathBuf),
+    /// Save as a CSV file, as written by [write_votes_as_csv].
+    SaveToCSV(PathBuf),
     CallFunction(Arc<Mutex<dyn FnMut(ElectionData)+ Send + Sync>>),
 }
 
@@ -54,8 +100,10 @@ impl FromStr for WhatToDoWithExtractedVotes {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Some(filename) = s.strip_prefix("file:") {
             Ok(WhatToDoWithExtractedVotes::SaveToFile(PathBuf::from(filename)))
+        } else if let Some(filename) = s.strip_prefix("csv:") {
+            Ok(WhatToDoWithExtractedVotes::SaveToCSV(PathBuf::from(filename)))
         } else {
-            Err(ExtractError::UnknownThingToExtract)
+            Err(ExtractError::UnknownThingToDo)
         }
     }
 }
@@ -75,7 +123,7 @@ impl FromStr for ExtractionRequest {
             let what_to_do_with_it : WhatToDoWithExtractedVotes = what_to_do_with_it.parse()?;
             Ok(ExtractionRequest{ what_to_extract, what_to_do_with_it })
         } else {
-            Err(ExtractError::UnknownThingToExtract)
+            Err(ExtractError::ExpectingSemicolonInExtractionRequest)
         }
     }
 }
@@ -88,7 +136,73 @@ impl WhatToDoWithExtractedVotes {
                 let out = File::create(path).expect("Error creating file to write out election data");
                 serde_json::to_writer(out,&data).expect("Error writing out election data");
             }
+            WhatToDoWithExtractedVotes::SaveToCSV(path) => {
+                let out = File::create(path).expect("Error creating file to write out election data");
+                write_votes_as_csv(&data,out).expect("Error writing out election data");
+            }
             WhatToDoWithExtractedVotes::CallFunction(f) => { f.lock().unwrap()(data) }
         }
     }
 }
+
+/// Write the votes as a CSV file with one row per ATL or BTL entry. The columns are the transfer value,
+/// the number of ballot papers, ATL or BTL, and the party (ATL) or candidate (BTL) numbers in preference order, separated by spaces.
+pub fn write_votes_as_csv(data:&ElectionData,out:impl Write) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(["Transfer Value","Ballot Papers","Type","Preferences"])?;
+    let tv_at = |specs:&[VoteValueSpecification],index:usize| specs.iter().find(|s|s.range().contains(&index)).map(|s|s.value.clone()).unwrap_or(TransferValue::one());
+    for (index,atl) in data.atl.iter().enumerate() {
+        writer.write_record([tv_at(&data.atl_transfer_values,index).to_string(),atl.n.to_string(),"ATL".to_string(),atl.parties.iter().map(|p|p.to_string()).collect::<Vec<_>>().join(" ")])?;
+    }
+    for (index,btl) in data.btl.iter().enumerate() {
+        writer.write_record([tv_at(&data.btl_transfer_values,index).to_string(),btl.n.to_string(),"BTL".to_string(),btl.candidates.iter().map(|c|c.to_string()).collect::<Vec<_>>().join(" ")])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Convert votes grouped by transfer value to election data, with the highest transfer values first.
+pub(crate) fn votes_to_election_data<'a>(metadata:ElectionMetadata,by_transfer_value:HashMap<TransferValue,Vec<VotesWithSameTransferValue<'a>>>) -> ElectionData {
+    let mut atl : Vec<ATL> = vec![];
+    let mut atl_transfer_values : Vec<VoteValueSpecification> = vec![];
+    let mut btl : Vec<BTL> = vec![];
+    let mut btl_transfer_values : Vec<VoteValueSpecification> = vec![];
+    let mut ordered_transfer_values : Vec<(TransferValue,Vec<VotesWithSameTransferValue<'a>>)> = by_transfer_value.into_iter().collect();
+    ordered_transfer_values.sort_by(|(tv1,_),(tv2,_)|tv2.cmp(tv1));
+    for (tv,votes_vec) in ordered_transfer_values {
+        let atl_start = atl.len();
+        let btl_start = btl.len();
+        for votes in votes_vec {
+            for vote in votes.votes {
+                match vote.source {
+                    VoteSource::Btl(source) => { btl.push(BTL{ candidates: source.candidates.clone(), n: vote.n.0 }); }
+                    VoteSource::Atl(source) => { atl.push(ATL{ parties: source.parties.clone(), n: vote.n.0, ticket_index: None }); }
+                }
+            }
+        }
+        if atl_start!=atl.len() {
+            atl_transfer_values.push(VoteValueSpecification{
+                value: tv.clone(),
+                first_index_inclusive: atl_start,
+                last_index_exclusive: atl.len(),
+            })
+        }
+        if btl_start!=btl.len() {
+            btl_transfer_values.push(VoteValueSpecification{
+                value: tv,
+                first_index_inclusive: btl_start,
+                last_index_exclusive: btl.len(),
+            })
+        }
+    };
+    ElectionData{
+        metadata,
+        atl,
+        atl_types: vec![],
+        atl_transfer_values,
+        btl,
+        btl_types: vec![],
+        btl_transfer_values,
+        informal: 0,
+    }
+}
@@ -162,6 +162,7 @@ impl <const DIGITS:usize> RoundUpToUsize for FixedPrecisionDecimal<DIGITS> {
     fn ceil(&self) -> usize {
         ((self.scaled_value+FixedPrecisionDecimal::<DIGITS>::SCALE-1)/FixedPrecisionDecimal::<DIGITS>::SCALE) as usize
     }
+    fn largest_integer() -> usize { Self::MAX as usize }
 }
 
 impl <const DIGITS:usize> Div<usize> for FixedPrecisionDecimal<DIGITS> {
@@ -0,0 +1,153 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Recount an election with some votes changed, reusing as much of the original count as possible.
+//!
+//! Testing a possible change to the votes, as done by the margin computation, requires a recount.
+//! Often the changed votes are not treated differently from the originals until late in the count,
+//! for instance when a vote is changed after a candidate who is excluded late. A [RecordedCount] keeps
+//! the state of the original count after each action (first preferences, a surplus distribution or an exclusion),
+//! and [RecordedCount::recount] resumes from the last state before any changed vote would have been
+//! treated differently. The result is the same transcript as a full recount.
+//!
+//! Only votes changed into other votes (see [VoteDeltas::change_atl] and [VoteDeltas::change_btl]) can
+//! be handled this way; if votes are added or just removed, or the rules pick votes randomly, a full recount is done.
+//!
+//! Note that keeping a copy of all the piles after each action can take a lot of memory for a large election.
+
+use std::collections::HashMap;
+use crate::ballot_paper::{ATL, BTL, VoteSource};
+use crate::ballot_pile::PartiallyDistributedVote;
+use crate::count_error::CountError;
+use crate::count_observer::{CountObserver, IgnoreCountProgress};
+use crate::distribution_of_preferences_transcript::Transcript;
+use crate::preference_distribution::{CountOptions, DistributorState, PreferenceDistributionRules, PreferenceDistributor, SurplusTransferMethod};
+use crate::prepared_election::{PreparedElection, VoteDeltas};
+use crate::random_util::Randomness;
+use crate::transfer_value::TransferValue;
+
+/// A count of a prepared election, with the state after each action kept so that it can be recounted quickly with small changes.
+pub struct RecordedCount<'a,Rules:PreferenceDistributionRules> {
+    rules : &'a Rules,
+    election : &'a PreparedElection<'a>,
+    /// The randomness at the start of the count.
+    randomness : Randomness,
+    /// The state after each action.
+    states : Vec<DistributorState<'a,Rules>>,
+    pub transcript : Transcript<Rules::Tally>,
+}
+
+/// An original vote that has been changed.
+struct ChangedVote<'a> {
+    original : PartiallyDistributedVote<'a>,
+    /// The votes it is changed into.
+    changed_into : Vec<PartiallyDistributedVote<'a>>,
+}
+
+impl <'a> ChangedVote<'a> {
+    /// Whether a vote in a pile that came from this vote would have been treated identically to the changed votes, which is the case if the preferences up to and including the current one are the same.
+    fn is_unaffected(&self,vote:&PartiallyDistributedVote) -> bool {
+        let upto = vote.upto;
+        upto<self.original.prefs.len() && self.changed_into.iter().all(|c|upto<c.prefs.len() && c.prefs[..=upto]==self.original.prefs[..=upto])
+    }
+    fn number_changed(&self) -> usize { self.changed_into.iter().map(|v|v.n.0).sum() }
+}
+
+/// A unique identifier for the ATL or BTL structure a vote came from.
+fn source_address(source:&VoteSource) -> usize {
+    match source {
+        VoteSource::Btl(b) => *b as *const BTL as usize,
+        VoteSource::Atl(a) => *a as *const ATL as usize,
+    }
+}
+
+impl <'a,Rules:PreferenceDistributionRules> RecordedCount<'a,Rules> {
+    /// Count the election using the vacancies, excluded candidates and EC tie resolutions in the metadata, recording the state after each action.
+    /// `votes` should be `election.votes(&VoteDeltas::default())`.
+    pub fn new(rules:&'a Rules,election:&'a PreparedElection<'a>,votes:&'a Vec<(TransferValue,Vec<PartiallyDistributedVote<'a>>)>,observer:&'a mut dyn CountObserver<Rules::Tally>,randomness:&'a mut Randomness) -> Result<Self,CountError> {
+        let initial_randomness = randomness.clone();
+        let mut work : PreferenceDistributor<'a,Rules> = election.distributor(rules,votes,observer,randomness)?;
+        let mut states = vec![];
+        while work.step()?.is_some() {
+            if work.is_between_actions() { states.push(work.save_state()); }
+        }
+        Ok(RecordedCount{ rules, election, randomness:initial_randomness, states, transcript: work.into_transcript() })
+    }
+
+    /// The rules used to count.
+    pub fn rules(&self) -> &'a Rules { self.rules }
+
+    /// The election that was counted.
+    pub fn election(&self) -> &'a PreparedElection<'a> { self.election }
+
+    /// Work out which original votes are changed. None if there are changes other than changing original votes with a transfer value of 1 into other votes of the same type.
+    fn changed_votes<'b>(&'b self,deltas:&'b VoteDeltas) -> Option<HashMap<usize,ChangedVote<'b>>> {
+        let mut changed : HashMap<usize,ChangedVote<'b>> = HashMap::new();
+        for (from,vote) in deltas.added_votes() {
+            let original = self.election.original_vote_with_transfer_value_one(from?)?;
+            if original.is_atl()!=vote.is_atl() { return None; }
+            changed.entry(source_address(&original.source)).or_insert_with(||ChangedVote{ original, changed_into: vec![] }).changed_into.push(vote);
+        }
+        for (&index,&removed) in deltas.all_removed() {
+            let original = self.election.original_vote_with_transfer_value_one(index)?;
+            if changed.get(&source_address(&original.source)).map(|c|c.number_changed())!=Some(removed) { return None; } // some votes just removed.
+        }
+        Some(changed)
+    }
+
+    /// Whether all the changed votes are treated identically to the originals up to a saved state.
+    fn is_unaffected(state:&DistributorState<'a,Rules>,changed:&HashMap<usize,ChangedVote>) -> bool {
+        let mut found : HashMap<usize,usize> = HashMap::new(); // number of papers found for each changed vote.
+        for vote in state.papers.iter().flat_map(|p|p.votes()) {
+            let address = source_address(&vote.source);
+            if let Some(change) = changed.get(&address) {
+                if !change.is_unaffected(vote) { return false; }
+                let papers = found.entry(address).or_insert(0);
+                if *papers>0 { return false; } // a vote split between multiple piles is too complex to deal with.
+                *papers+=vote.n.0;
+            }
+        }
+        changed.iter().all(|(address,change)|found.get(address)==Some(&change.original.n.0)) // e.g. not exhausted
+    }
+
+    /// The index of the last saved state in which the changed votes have been treated identically to the originals, if any.
+    /// If None, a recount has to start from the beginning.
+    pub fn last_unaffected_state(&self,deltas:&VoteDeltas) -> Option<usize> {
+        self.changed_votes(deltas).and_then(|changed|self.last_state_unaffected_by(&changed))
+    }
+
+    fn last_state_unaffected_by(&self,changed:&HashMap<usize,ChangedVote>) -> Option<usize> {
+        if matches!(self.rules.surplus_distribution_subdivisions(),SurplusTransferMethod::PickRandomlyAfterDistribution) { return None; } // depends upon the order of votes in a pile.
+        let first_affected = self.states.iter().position(|state|!Self::is_unaffected(state,changed)).unwrap_or(self.states.len());
+        first_affected.checked_sub(1)
+    }
+
+    /// Recount with the given changes to the votes, starting from the last state unaffected by the changes.
+    /// The result is the same as `election.distribute_preferences(rules,deltas,randomness)`.
+    pub fn recount(&self,deltas:&VoteDeltas) -> Result<Transcript<Rules::Tally>,CountError> {
+        let votes = self.election.votes(deltas);
+        let mut observer = IgnoreCountProgress{};
+        let mut randomness = self.randomness.clone();
+        let resume_from = self.changed_votes(deltas).and_then(|changed|self.last_state_unaffected_by(&changed).map(|state_index|(state_index,changed)));
+        let mut work : PreferenceDistributor<'_,Rules> = match resume_from {
+            Some((state_index,changed)) => {
+                let mut state : DistributorState<'_,Rules> = self.states[state_index].clone();
+                for pile in &mut state.papers {
+                    pile.replace_votes(|v|changed.contains_key(&source_address(&v.source)),|v|{
+                        let change = &changed[&source_address(&v.source)];
+                        let mut res = vec![PartiallyDistributedVote{ n: v.n-change.changed_into.iter().map(|c|c.n).sum(), ..*v }];
+                        res.extend(change.changed_into.iter().map(|c|PartiallyDistributedVote{ upto: v.upto, ..*c }));
+                        res
+                    });
+                }
+                PreferenceDistributor::from_state(self.rules,self.election.data,&votes,&CountOptions::from_metadata(self.election.data)?,&mut observer,&mut randomness,state)
+            }
+            None => self.election.distributor(self.rules,&votes,&mut observer,&mut randomness)?,
+        };
+        work.go()?;
+        Ok(work.into_transcript())
+    }
+}
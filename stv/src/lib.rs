@@ -29,4 +29,16 @@ pub mod find_vote;
 pub mod verify_official_transcript;
 pub mod random_util;
 pub mod extract_votes_in_pile;
-pub mod simple_list_of_votes;
\ No newline at end of file
+pub mod simple_list_of_votes;
+pub mod count_observer;
+pub mod dynamic_rules;
+pub mod rule_card;
+pub mod quota;
+pub mod exact_arithmetic;
+pub mod prepared_election;
+pub mod incremental_recount;
+pub mod count_error;
+pub mod tie_enumeration;
+pub mod verifiable_draw;
+pub mod casual_vacancy;
+pub mod trace_vote;
\ No newline at end of file
@@ -402,7 +402,7 @@ pub fn candidate_elem<T:Default+Clone>(vec : &mut Vec<T>, who:CandidateIndex) ->
 /// Innermost error is discrepancies with the official DoP.
 ///
 /// If `save_transcripts` is true, transcripts will be saved to the `test_transcripts` folder.
-pub fn test_official_dop_without_actual_votes<Rules:PreferenceDistributionRules,Source:ElectionDataSource>(source:&Source,year:&str,state:&str,save_transcripts:bool) -> anyhow::Result<Result<Option<TieResolutionExplicitDecisionInCount>, DifferenceBetweenOfficialDoPAndComputed<Rules::Tally>>> where <Rules as PreferenceDistributionRules>::Tally: Send+Sync+'static {
+pub fn test_official_dop_without_actual_votes<Rules:PreferenceDistributionRules+Default,Source:ElectionDataSource>(source:&Source,year:&str,state:&str,save_transcripts:bool) -> anyhow::Result<Result<Option<TieResolutionExplicitDecisionInCount>, DifferenceBetweenOfficialDoPAndComputed<Rules::Tally>>> where <Rules as PreferenceDistributionRules>::Tally: Send+Sync+'static {
     let loader = source.get_loader_for_year(year,&FileFinder::find_ec_data_repository())?;
     let metadata = loader.read_raw_metadata(state)?;
     let official_transcript = loader.read_official_dop_transcript(&metadata)?;
@@ -412,7 +412,7 @@ pub fn test_official_dop_without_actual_votes<Rules:PreferenceDistributionRules,
     if save_transcripts {
         let transcript = TranscriptWithMetadata{ metadata, transcript };
         std::fs::create_dir_all("test_transcripts")?;
-        let file = File::create(format!("test_transcripts/transcript{}{}_{}.json",state,year,Rules::name()))?;
+        let file = File::create(format!("test_transcripts/transcript{}{}_{}.json",state,year,transcript.transcript.rules))?;
         serde_json::to_writer_pretty(file,&transcript)?;
     }
     result
@@ -22,7 +22,7 @@ use std::process::Command;
 use std::str::FromStr;
 use std::sync::Mutex;
 use reqwest::Url;
-use crate::ballot_paper::{RawBallotMarkings};
+use crate::ballot_paper::{FormalityRules, RawBallotMarkings};
 use crate::compare_transcripts::{DeltasInCandidateLists, DifferentCandidateLists};
 use crate::datasource_description::{AssociatedRules, Copyright};
 use crate::errors_btl::ObviousErrorsInBTLVotes;
@@ -127,11 +127,11 @@ impl Error for MissingAlternateNamedFiles {
 pub trait RawDataSource : KnowsAboutRawMarkings {
     fn name(&self,electorate:&str) -> ElectionName;
     /// The number of candidates to be elected in this election.
-    fn candidates_to_be_elected(&self,electorate:&str) -> NumberOfCandidates;
+    fn candidates_to_be_elected(&self,electorate:&str) -> anyhow::Result<NumberOfCandidates>;
     /// Get tie breaking decisions made by the EC.
     fn ec_decisions(&self,electorate:&str) -> TieResolutionsMadeByEC;
     /// Get candidates that are excluded by default for whatever reason.
-    fn excluded_candidates(&self,electorate:&str) -> Vec<CandidateIndex>;
+    fn excluded_candidates(&self,electorate:&str) -> anyhow::Result<Vec<CandidateIndex>>;
     /// Read the data for a given electorate.
     fn read_raw_data(&self,electorate:&str) -> anyhow::Result<ElectionData>;
     /// Get a list of all the electorates
@@ -173,6 +173,8 @@ pub trait RawDataSource : KnowsAboutRawMarkings {
     fn copyright(&self) -> Copyright;
     fn rules(&self,electorate:&str) -> AssociatedRules;
     fn can_read_raw_markings(&self) -> bool { false}
+    /// The rules used to interpret raw markings as a formal vote.
+    fn formality_rules(&self,_electorate:&str) -> FormalityRules { FormalityRules::default() }
     /// Get the official transcript for the election. May not be available for all electorates.
     fn read_official_dop_transcript(&self,metadata:&ElectionMetadata) -> anyhow::Result<OfficialDistributionOfPreferencesTranscript>;
 
@@ -213,7 +215,7 @@ pub trait CanReadRawMarkings {
 /// Like read_raw_data, except also try to deduce the tie breaking decisions that were used by the electoral commission.
 /// This is a powerful function, but it will be slow and panic if anything goes even slightly wrong.
 /// Also deduce the offical results, possibly reordering to better match the actual order here.
-pub fn read_raw_data_checking_against_official_transcript_to_deduce_ec_resolutions<Rules:PreferenceDistributionRules,Source:RawDataSource>(loader:&Source, electorate: &str) -> anyhow::Result<ElectionData> where <Rules as PreferenceDistributionRules>::Tally : Send+Sync+'static {
+pub fn read_raw_data_checking_against_official_transcript_to_deduce_ec_resolutions<Rules:PreferenceDistributionRules+Default,Source:RawDataSource>(loader:&Source, electorate: &str) -> anyhow::Result<ElectionData> where <Rules as PreferenceDistributionRules>::Tally : Send+Sync+'static {
     println!("Trying to deduce ec resolutions for {}",electorate);
     let mut data = loader.read_raw_data(electorate)?;
     if electorate.ends_with("Mayoral") { return Ok(data); } // don't have DOP file for mayoral elections. Besides, STV is not necessarily exactly a generalization of IRV... e.g. early termination conditions.
@@ -223,7 +225,7 @@ pub fn read_raw_data_checking_against_official_transcript_to_deduce_ec_resolutio
     data.metadata.tie_resolutions=TieResolutionsMadeByEC::default(); // Get rid of less fine grained decisions that may be entered.
     loop {
         println!("Looping...");
-        let transcript = data.distribute_preferences::<Rules>(&mut Randomness::ReverseDonkeyVote);
+        let transcript = data.distribute_preferences::<Rules>(&mut Randomness::ReverseDonkeyVote)?;
         if let Some(decision) = official_transcript.compare_with_transcript_checking_for_ec_decisions(&transcript,false).context("Trying to determine EC decisions")? {
             println!("Observed tie resolution {}", decision.decision);
             assert!(!decision.decision.is_reverse_donkey_vote(), "favoured candidate should be lower as higher candidates are assumed favoured.");
@@ -11,7 +11,7 @@
 
 use num::{BigInt, Signed, ToPrimitive, Zero};
 pub use num::BigRational as BigRational;
-use crate::election_data::{ElectionData, VoteValueSpecification};
+use crate::election_data::ElectionData;
 use crate::ballot_pile::{VotesWithMultipleTransferValues, HowSplitByCountNumber, PartiallyDistributedVote, BallotPaperCount, DistributedVotes, VotesWithSameTransferValue};
 use std::collections::{HashMap, HashSet, VecDeque};
 use crate::ballot_metadata::{CandidateIndex, NumberOfCandidates};
@@ -26,19 +26,22 @@ use std::iter::Sum;
 use std::cmp::{min, Ordering};
 use serde::{Serialize,Deserialize};
 use std::str::FromStr;
-use crate::ballot_paper::{ATL, BTL, VoteSource};
-use crate::extract_votes_in_pile::{ExtractionRequest, WhatToExtract};
+use crate::extract_votes_in_pile::{votes_to_election_data, ExtractionRequest, WhatToExtract};
 use crate::official_dop_transcript::CanConvertToF64PossiblyLossily;
 use crate::random_util::Randomness;
 use crate::signed_version::SignedVersion;
 use crate::simple_list_of_votes::ListOfVotes;
 use crate::verify_official_transcript::OracleFromOfficialDOP;
+use crate::count_observer::CountObserver;
+use crate::quota::QuotaMethod;
+use crate::count_error::CountError;
+use crate::trace_vote::{TracedVoteAtCount, TracedVoteLocation, TracedVoteStatus, VoteToTrace, VoteTrace};
 
 
 /// Many systems have a special rules for termination when there are a small number of
 /// candidates left (e.g. equal to the number of if there are exactly 2 candidates left
 /// and 1 vacancy. This can be done at a variety of times.
-#[derive(Copy, Clone,Debug,Eq, PartialEq)]
+#[derive(Copy, Clone,Debug,Eq, PartialEq,Serialize,Deserialize)]
 pub enum WhenToDoElectCandidateClauseChecking {
     /// Don't do this type of check
     Never,
@@ -82,7 +85,7 @@ impl TransferValueMethod {
     }
 }
 
-#[derive(Copy,Clone,Serialize,Deserialize)]
+#[derive(Copy,Clone,Serialize,Deserialize,Debug)]
 /// How to name counts.
 pub enum CountNamingMethod {
     SimpleNumber, // 1,2,3,4,... the most common method.
@@ -92,7 +95,7 @@ pub enum CountNamingMethod {
 
 
 /// What general algorithm to use to do surplus transfers.
-#[derive(Copy,Clone,Serialize,Deserialize,Eq, PartialEq)]
+#[derive(Copy,Clone,Serialize,Deserialize,Eq, PartialEq,Debug)]
 pub enum SurplusTransferMethod {
     JustOneTransferValue, // Bunch votes together and do a single transfer. E.g. Federal.
     ScaleTransferValues, // Do separate transfers based on provenance, with transfer values scaled.
@@ -101,12 +104,14 @@ pub enum SurplusTransferMethod {
 }
 
 /// In most STV you do surplus distributions before exclusions. But some (cough cough NSW) defer the surplus distributions under some conditions.
+#[derive(Copy,Clone,Serialize,Deserialize,Debug)]
 pub enum DeferSurplusDistribution {
     AlwaysDistributeAllSurplusBeforeAnyExclusions, // the thing done by almost everyone
     DeferIfSumOfUndistributedSurplussesLessThanDifferenceBetweenTwoLowestContinuingCandidates, // NSW Randomized algorithm LC
     DeferIfSumOfUndistributedSurplussesLessThanOrEqualToDifferenceBetweenTwoLowestContinuingCandidates, // NSW Randomized algorithm LGE
 }
 
+#[derive(Copy,Clone,Serialize,Deserialize,Debug)]
 pub enum LastParcelUse {
     No, // Consider all votes (normal)
     LiterallyLast, // ACT
@@ -118,6 +123,8 @@ pub enum LastParcelUse {
 pub trait RoundUpToUsize {
     /// round up to the next integer.
     fn ceil(&self) -> usize;
+    /// The largest integer that can be represented, used to check that the number of ballots will not overflow.
+    fn largest_integer() -> usize { usize::MAX }
 }
 
 impl RoundUpToUsize for usize {
@@ -125,77 +132,89 @@ impl RoundUpToUsize for usize {
 }
 impl RoundUpToUsize for isize {
     fn ceil(&self) -> usize { self.to_usize().unwrap_or(0) } // not ideal but we shouldn't expect negative vote tallies to behave sensibly. Used in margin computation and surplus distribution so should not be a problem unless its use grows.
+    fn largest_integer() -> usize { isize::MAX as usize }
 }
 
+/// The rules for a distribution of preferences.
+///
+/// Most rules are fully determined by their type, and implement [Default] so a value can be made when needed.
+/// Others, such as [crate::dynamic_rules::DynamicRules], depend upon the value, which is passed to [PreferenceDistributor::new].
+/// Conversions between tallies and rationals depend only on the Tally type, and so do not take `self`.
 pub trait PreferenceDistributionRules {
     /// The type for the number of votes. Usually an integer.
-    type Tally : Clone+AddAssign+SubAssign+From<BallotPaperCount>+Display+PartialEq+Serialize+FromStr+Debug+Ord+Sub<Output=Self::Tally>+Zero+Hash+Sum<Self::Tally>+RoundUpToUsize/*+Div<usize,Output=Self::Tally>*/+CanConvertToF64PossiblyLossily;
+    type Tally : Clone+AddAssign+SubAssign+From<BallotPaperCount>+Display+PartialEq+Serialize+FromStr+Debug+Ord+Sub<Output=Self::Tally>+Zero+Hash+Sum<Self::Tally>+RoundUpToUsize/*+Div<usize,Output=Self::Tally>*/+CanConvertToF64PossiblyLossily+Send+Sync;
     type SplitByNumber : HowSplitByCountNumber;
 
     /// Whether or not the system has a quota. False for IRV.
-    fn has_quota() -> bool { true }
+    fn has_quota(&self) -> bool { true }
+    /// How the quota is computed, if there is one. This can be overridden for a particular count by [CountOptions::quota_method].
+    fn quota_method(&self) -> QuotaMethod { QuotaMethod::Droop }
     /// Whether to transfer all the votes or just the last parcel.
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse;
-    fn transfer_value_method() -> TransferValueMethod;
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse;
+    fn transfer_value_method(&self) -> TransferValueMethod;
     fn convert_tally_to_rational(tally:Self::Tally) -> BigRational;
     /// convert a rational value to the tally type, rounding as if one would do after applying a transfer value.
     fn convert_rational_to_tally_after_applying_transfer_value(rational:BigRational) -> Self::Tally;
-    fn make_transfer_value(surplus:Self::Tally,ballots:BallotPaperCount) -> TransferValue; // could be implemented using Self::convert_tally_to_rational { TransferValue::new(BigInt::from(surplus),BigInt::from(ballots.0)) }
-    fn use_transfer_value(transfer_value:&TransferValue,ballots:BallotPaperCount) -> Self::Tally;
+    fn make_transfer_value(&self,surplus:Self::Tally,ballots:BallotPaperCount) -> TransferValue; // could be implemented using Self::convert_tally_to_rational { TransferValue::new(BigInt::from(surplus),BigInt::from(ballots.0)) }
+    fn use_transfer_value(&self,transfer_value:&TransferValue,ballots:BallotPaperCount) -> Self::Tally;
     /// if true, then distribute all votes with a single transfer value. If false, separate by incoming transfer value
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod;
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod;
     /// if true, sort votes during an exclusion by transfer value, highest first.
-    fn sort_exclusions_by_transfer_value() -> bool;
+    fn sort_exclusions_by_transfer_value(&self) -> bool;
 
     // ***  Tie resolution issues ***
 
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution;
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution;
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution;
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution;
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution;
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution;
     // Note that it is assumed that surplus distribution is done in the same order as election. True for AEC.
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution;
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution;
 
     // *** When the actual counting stops ***
 
     /// If false, then don't check quota or for election when part way through a surplus distribution.
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool;
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool;
     /// If false, then don't check quota or for election when part way through an exclusion.
-    fn check_elected_if_in_middle_of_exclusion() -> bool;
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool;
     /// An elimination may involve multiple steps. If all vacancies are filled but not all steps are finished, do you finish all the counts, even though it cannot change the result of the election?
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool;
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool;
     /// If all vacancies are filled but not all surplus distributions are done, do you finish the surplus distributions, even though it cannot change the result of the election?
-    fn finish_all_surplus_distributions_when_all_elected() -> bool;
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool;
 
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking;
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking;
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking;
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking;
     /// if there are V vacancies, and the candidate ranked V highest has more votes than all lower put together plus undistributed surpluses, then elect V highest.
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking;
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking;
     /// only relevant if above is chosen, in which case require V=1.
-    fn when_checking_if_top_few_have_overwhelming_votes_require_exactly_one() -> bool { false }
+    fn when_checking_if_top_few_have_overwhelming_votes_require_exactly_one(&self) -> bool { false }
 
     // how to do the elimination
 
     /// Whether to defer surplus distribution and do elimination instead. This is only done in the NSW randomized algorithm, as no one else would do such a terrible thing, and so is defaulted to never.
-    fn when_should_surplus_distribution_be_deferred() -> DeferSurplusDistribution { DeferSurplusDistribution::AlwaysDistributeAllSurplusBeforeAnyExclusions }
+    fn when_should_surplus_distribution_be_deferred(&self) -> DeferSurplusDistribution { DeferSurplusDistribution::AlwaysDistributeAllSurplusBeforeAnyExclusions }
 
     /// Whether the Commonwealth Electoral Act 1918, Section 273, subsection 13A multiple elimination abomination should be used. This is defaulted to false as no one else would do such a terrible thing, and even the AEC has only sometimes done it.
-    fn should_eliminate_multiple_candidates_federal_rule_13a() -> bool { false }
+    fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { false }
+
+    /// Whether to exclude together all the lowest candidates whose combined votes (plus undistributed surpluses) are fewer than the next highest candidate's votes, as done in Ireland.
+    /// See [PreferenceDistributor::find_candidates_for_multiple_elimination_below_next_highest]. Defaulted to false.
+    fn should_eliminate_multiple_candidates_below_next_highest(&self) -> bool { false }
 
     /// If the TV calculation is limited due to incoming TV (such as in ACT) this causes votes to be set aside. These will normally be counted as set aside, but Elections ACT counts them as lost to rounding. Set to true if you want to do this. This is defaulted to false as no one else would do such a terrible thing. Yes, I know, there are a lot of terrible things that no one else would do, but sic.
-    fn count_set_aside_due_to_transfer_value_limit_as_rounding() -> bool { false }
+    fn count_set_aside_due_to_transfer_value_limit_as_rounding(&self) -> bool { false }
 
     /// A name describing these rules.
-    fn name() -> String;
+    fn name(&self) -> String;
 
     /// how counts should be named.
-    fn how_to_name_counts() -> CountNamingMethod { CountNamingMethod::SimpleNumber }
+    fn how_to_name_counts(&self) -> CountNamingMethod { CountNamingMethod::SimpleNumber }
 
     /// whether exhausted votes in count 1 (first preferences) count towards the quota calculation.
     /// (this happens in the case of candidates ruled ineligible).
-    fn should_exhausted_votes_count_for_quota_computation() -> bool { false }
+    fn should_exhausted_votes_count_for_quota_computation(&self) -> bool { false }
 
     /// If someone is elected in the middle of an exclusion or surplus, should we start a new major count?
-    fn major_count_if_someone_elected() -> bool { false }
+    fn major_count_if_someone_elected(&self) -> bool { false }
 
     //
     // Things just to support weird bugs. Defaults are given as who would otherwise do these?
@@ -203,23 +222,32 @@ pub trait PreferenceDistributionRules {
 
     /// Instead of doing exact computations using transfer values, use f32 approximate floating point computations. Needed to emulate an NSWEC bug.
     /// Only currently implemented for the NSWEC randomized algorithm.
-    fn use_f32_arithmetic_when_applying_transfer_values_instead_of_exact() -> bool { false }
+    fn use_f32_arithmetic_when_applying_transfer_values_instead_of_exact(&self) -> bool { false }
 
     /// If a surplus distribution transfer is computed by a "surplus fraction" times the original transfer value,
     /// and the "surplus fraction" is computed by surplus/(votes-exhausted votes), and the votes is rounded down,
     /// and the exhausted votes isn't, then the denominator could be negative. The NSW LGE legislation seems to
     /// allow this. Set this to be false if you want to allow this behaviour, which then causes all sorts of terrible problems.
-    fn prohibit_negative_surplus_fraction() -> bool { true }
+    fn prohibit_negative_surplus_fraction(&self) -> bool { true }
 
     /// Change the votes otherwise being classified as exhausted. Changes will go into the lost due to rounding tally.
-    fn munge_exhausted_votes(exhausted:Self::Tally,_is_exclusion:bool) -> Self::Tally { exhausted }
+    fn munge_exhausted_votes(&self,exhausted:Self::Tally,_is_exclusion:bool) -> Self::Tally { exhausted }
     /// Change the transfer value when it is being used as a limit (e.g. in ACT rule 1C(4))
-    fn munge_transfer_value_when_used_as_limit(original:TransferValue) -> TransferValue { original }
+    fn munge_transfer_value_when_used_as_limit(&self,original:TransferValue) -> TransferValue { original }
     /// In exclusions, sort the sub-counts by this function. Used to support wierd bug in NSWEC2021.
     /// Also used in surplus distributions when the surplus transfer method is ScaleTransferValues.
-    fn sort_subcounts_by_count() -> Option<Box<dyn FnMut(&Transcript<Self::Tally>,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide) -> Ordering>> { None }
+    fn sort_subcounts_by_count(&self) -> Option<Box<dyn FnMut(&Transcript<Self::Tally>,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide,<<Self as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide) -> Ordering>> { None }
+    /// Change the transfer value produced by multiplying an incoming transfer value by the surplus fraction,
+    /// when the surplus transfer method is ScaleTransferValues or MergeSameTransferValuesAndScale. Used for Scotland, where it is truncated to 5 decimal places.
+    fn munge_scaled_transfer_value(&self,original:TransferValue) -> TransferValue { original }
+    /// If true, a surplus distribution or exclusion involving papers with multiple transfer values is done as a single count,
+    /// rather than one count for each transfer value. Used for Scotland.
+    fn do_all_transfer_values_in_one_count(&self) -> bool { false }
+    /// If true, when multiple surpluses are pending, distribute the largest first rather than in order of election. Used for Scotland.
+    fn distribute_largest_surplus_first(&self) -> bool { false }
 }
 
+#[derive(Clone)]
 struct PendingTranscript<Tally> {
     elected : Vec<CandidateElected>,
     not_continuing : Vec<CandidateIndex>,
@@ -228,13 +256,43 @@ struct PendingTranscript<Tally> {
     set_aside_for_quota: Option<PerCandidate<BallotPaperCount>>
 }
 
+/// What to count, and how, other than the votes, the rules, and the observer and randomness used.
+#[derive(Clone)]
+pub struct CountOptions<'a> {
+    pub candidates_to_be_elected : NumberOfCandidates,
+    pub excluded_candidates : HashSet<CandidateIndex>,
+    pub ec_resolutions : &'a TieResolutionsMadeByEC,
+    /// If given, only count votes of these types.
+    pub vote_types : Option<&'a [String]>,
+    /// If given, use this instead of [PreferenceDistributionRules::quota_method].
+    pub quota_method : Option<QuotaMethod>,
+    pub extractors : &'a [ExtractionRequest],
+    pub include_list_of_votes_in_transcript : bool,
+}
+
+impl <'a> CountOptions<'a> {
+    /// Count all votes with the rules' quota method, no extractors, and no list of votes in the transcript.
+    pub fn new(candidates_to_be_elected:NumberOfCandidates,excluded_candidates:HashSet<CandidateIndex>,ec_resolutions:&'a TieResolutionsMadeByEC) -> Self {
+        CountOptions{ candidates_to_be_elected, excluded_candidates, ec_resolutions, vote_types: None, quota_method: None, extractors: &[], include_list_of_votes_in_transcript: false }
+    }
+
+    /// Like [CountOptions::new], using the vacancies, excluded candidates and EC tie resolutions in the metadata.
+    pub fn from_metadata(data:&'a ElectionData) -> Result<Self,CountError> {
+        let metadata = &data.metadata;
+        Ok(CountOptions::new(metadata.vacancies.ok_or(CountError::MissingVacancies)?,metadata.excluded.iter().cloned().collect(),&metadata.tie_resolutions))
+    }
+}
+
 /// The main workhorse class that does preference distribution.
 pub struct PreferenceDistributor<'a,Rules:PreferenceDistributionRules> {
+    rules : &'a Rules,
     data : &'a ElectionData,
     ec_resolutions: &'a TieResolutionsMadeByEC,
     original_votes:&'a Vec<(TransferValue,Vec<PartiallyDistributedVote<'a>>)>,
     num_candidates : usize,
     candidates_to_be_elected : NumberOfCandidates,
+    /// How the quota is computed, usually [PreferenceDistributionRules::quota_method].
+    quota_method : QuotaMethod,
     quota : Rules::Tally,
     /// The tally, by candidate.
     tallys : Vec<Rules::Tally>,
@@ -259,19 +317,108 @@ pub struct PreferenceDistributor<'a,Rules:PreferenceDistributionRules> {
     // information about what is going on in this count.
     in_this_count : PendingTranscript<Rules::Tally>,
     pub(crate) transcript : Transcript<Rules::Tally>,
-    print_progress_to_stdout : bool, // if true, then print tallys etc to stdout.
+    observer : &'a mut dyn CountObserver<Rules::Tally>,
     oracle : Option<OracleFromOfficialDOP<'a>>,
     extractors : &'a [ExtractionRequest],
     include_list_of_votes_in_transcript : bool,
     exhausted_list_of_votes : ListOfVotes,
     set_aside_list_of_votes : ListOfVotes,
     last_written_list_of_votes_by_candidate : Vec<ListOfVotes>,
+    /// true if an extractor needs [Self::exhausted_votes] and [Self::set_aside_votes] to be kept.
+    keep_exhausted_and_set_aside_votes : bool,
+    /// The votes exhausted so far, with their transfer values and the value they added to the exhausted tally. Only kept if [Self::keep_exhausted_and_set_aside_votes].
+    exhausted_votes : Vec<(TransferValue,VotesWithSameTransferValue<'a>,Rules::Tally)>,
+    /// The votes some of whose value has been set aside so far, with the value set aside. Only kept if [Self::keep_exhausted_and_set_aside_votes].
+    set_aside_votes : Vec<(TransferValue,VotesWithSameTransferValue<'a>)>,
+    /// Where some ballots have gone at the end of each count so far, if they are being traced. See [PreferenceDistributor::trace_vote].
+    vote_trace : Option<VoteTrace>,
+    /// true once the first preferences have been distributed.
+    started : bool,
+    /// The number of counts in the transcript that have been returned by [PreferenceDistributor::step].
+    counts_returned_by_step : usize,
+    /// A problem found that means the count cannot continue, to be returned by [PreferenceDistributor::step].
+    failure : Option<CountError>,
+}
+
+/// A copy of the state of a [PreferenceDistributor] in between actions, from which the distribution can be resumed.
+/// Created by [PreferenceDistributor::save_state] and resumed by [PreferenceDistributor::from_state].
+pub struct DistributorState<'a,Rules:PreferenceDistributionRules> {
+    num_candidates : usize,
+    candidates_to_be_elected : NumberOfCandidates,
+    quota_method : QuotaMethod,
+    quota : Rules::Tally,
+    tallys : Vec<Rules::Tally>,
+    pub(crate) papers : Vec<VotesWithMultipleTransferValues<'a,Rules::SplitByNumber,Rules::Tally>>,
+    continuing_candidates : HashSet<CandidateIndex>,
+    continuing_candidates_sorted_by_tally : Vec<CandidateIndex>,
+    exhausted : BallotPaperCount,
+    exhausted_atl : BallotPaperCount,
+    tally_lost_to_rounding : SignedVersion<Rules::Tally>,
+    tally_exhausted : Rules::Tally,
+    tally_set_aside : Option<Rules::Tally>,
+    current_count : CountIndex,
+    current_major_count : CountIndex,
+    current_minor_count : CountIndex,
+    pending_surplus_distribution : VecDeque<CandidateIndex>,
+    elected_candidates : Vec<CandidateIndex>,
+    candidate_elected_at_count : Vec<Option<CountIndex>>,
+    randomness : Randomness,
+    in_this_count : PendingTranscript<Rules::Tally>,
+    pub(crate) transcript : Transcript<Rules::Tally>,
+    include_list_of_votes_in_transcript : bool,
+    exhausted_list_of_votes : ListOfVotes,
+    set_aside_list_of_votes : ListOfVotes,
+    last_written_list_of_votes_by_candidate : Vec<ListOfVotes>,
+    exhausted_votes : Vec<(TransferValue,VotesWithSameTransferValue<'a>,Rules::Tally)>,
+    set_aside_votes : Vec<(TransferValue,VotesWithSameTransferValue<'a>)>,
+    started : bool,
+    counts_returned_by_step : usize,
+}
+
+impl <'a,Rules:PreferenceDistributionRules> Clone for DistributorState<'a,Rules> {
+    fn clone(&self) -> Self {
+        DistributorState{
+            num_candidates: self.num_candidates,
+            candidates_to_be_elected: self.candidates_to_be_elected,
+            quota_method: self.quota_method,
+            quota: self.quota.clone(),
+            tallys: self.tallys.clone(),
+            papers: self.papers.clone(),
+            continuing_candidates: self.continuing_candidates.clone(),
+            continuing_candidates_sorted_by_tally: self.continuing_candidates_sorted_by_tally.clone(),
+            exhausted: self.exhausted,
+            exhausted_atl: self.exhausted_atl,
+            tally_lost_to_rounding: self.tally_lost_to_rounding.clone(),
+            tally_exhausted: self.tally_exhausted.clone(),
+            tally_set_aside: self.tally_set_aside.clone(),
+            current_count: self.current_count,
+            current_major_count: self.current_major_count,
+            current_minor_count: self.current_minor_count,
+            pending_surplus_distribution: self.pending_surplus_distribution.clone(),
+            elected_candidates: self.elected_candidates.clone(),
+            candidate_elected_at_count: self.candidate_elected_at_count.clone(),
+            in_this_count: self.in_this_count.clone(),
+            transcript: self.transcript.clone(),
+            include_list_of_votes_in_transcript: self.include_list_of_votes_in_transcript,
+            exhausted_list_of_votes: self.exhausted_list_of_votes.clone(),
+            set_aside_list_of_votes: self.set_aside_list_of_votes.clone(),
+            last_written_list_of_votes_by_candidate: self.last_written_list_of_votes_by_candidate.clone(),
+            exhausted_votes: self.exhausted_votes.clone(),
+            set_aside_votes: self.set_aside_votes.clone(),
+            started: self.started,
+            counts_returned_by_step: self.counts_returned_by_step,
+            randomness: self.randomness.clone(),
+        }
+    }
 }
 
 impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
 {
-    pub fn new(data : &'a ElectionData,original_votes:&'a Vec<(TransferValue,Vec<PartiallyDistributedVote<'a>>)>,candidates_to_be_elected : NumberOfCandidates,excluded_candidates:&HashSet<CandidateIndex>,ec_resolutions:&'a TieResolutionsMadeByEC,print_progress_to_stdout : bool,oracle : Option<OracleFromOfficialDOP<'a>>,randomness:&'a mut Randomness,extractors:&'a [ExtractionRequest],include_list_of_votes_in_transcript:bool) -> Self {
+    /// Make a distributor for the given votes. The `vote_types` in `options` are not used, as they should already have been used to get `original_votes`.
+    pub fn new(rules : &'a Rules,data : &'a ElectionData,original_votes:&'a Vec<(TransferValue,Vec<PartiallyDistributedVote<'a>>)>,options:&CountOptions<'a>,observer:&'a mut dyn CountObserver<Rules::Tally>,oracle : Option<OracleFromOfficialDOP<'a>>,randomness:&'a mut Randomness) -> Self {
+        let excluded_candidates = &options.excluded_candidates;
         let num_candidates = data.metadata.candidates.len();
+        let draw_seed = randomness.public_seed().cloned();
         let tallys = vec![Rules::Tally::zero();num_candidates];
         let mut papers = vec![];
         for _ in 0..num_candidates { papers.push(VotesWithMultipleTransferValues::<'a,Rules::SplitByNumber,Rules::Tally>::default()); }
@@ -284,11 +431,13 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
             }
         }
         PreferenceDistributor{
+            rules,
             data,
-            ec_resolutions,
+            ec_resolutions: options.ec_resolutions,
             original_votes,
             num_candidates,
-            candidates_to_be_elected,
+            candidates_to_be_elected: options.candidates_to_be_elected,
+            quota_method : options.quota_method.unwrap_or_else(||rules.quota_method()),
             quota : Rules::Tally::zero(), // dummy until computed.
             tallys,
             papers,
@@ -314,21 +463,40 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
                 set_aside_for_quota: None,
             },
             transcript : Transcript {
-                rules : Rules::name(),
+                rules : rules.name(),
                 quota: None,
                 counts: vec![],
-                elected: vec![]
+                elected: vec![],
+                draws: vec![],
+                draw_seed,
             },
-            print_progress_to_stdout,
+            observer,
             oracle,
-            extractors,
-            include_list_of_votes_in_transcript,
+            extractors: options.extractors,
+            include_list_of_votes_in_transcript: options.include_list_of_votes_in_transcript,
             exhausted_list_of_votes: Default::default(),
             set_aside_list_of_votes: Default::default(),
             last_written_list_of_votes_by_candidate: vec![ListOfVotes::default();num_candidates],
+            keep_exhausted_and_set_aside_votes: needs_exhausted_and_set_aside_votes(options.extractors),
+            exhausted_votes: vec![],
+            set_aside_votes: vec![],
+            vote_trace: None,
+            started: false,
+            counts_returned_by_step: 0,
+            failure: excluded_candidates.iter().find(|c|c.0>=num_candidates).map(|&candidate|CountError::InvalidCandidate{candidate,num_candidates}),
         }
     }
 
+    /// Record where the ballots with the given preferences are at the end of every count. Must be called before the first preferences are distributed.
+    /// The result is available from [PreferenceDistributor::take_vote_trace].
+    pub fn trace_vote(&mut self,vote:VoteToTrace) {
+        self.vote_trace=Some(VoteTrace{ papers: vote.num_papers(self.data), vote, counts: vec![] });
+        self.keep_exhausted_and_set_aside_votes=true;
+    }
+
+    /// Where the ballots given to [PreferenceDistributor::trace_vote] have gone so far.
+    pub fn take_vote_trace(&mut self) -> Option<VoteTrace> { self.vote_trace.take() }
+
     /// distribute the given votes by the next preference (unless there is an oracle overriding it).
     pub fn distribute(&mut self,votes:&Vec<PartiallyDistributedVote<'a>>) -> DistributedVotes<'a> {
         if let Some(oracle) = &mut self.oracle {
@@ -344,10 +512,10 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         for (tv,original_votes) in self.original_votes {
             let mut ballots_with_this_tv = BallotPaperCount(0);
             for v in original_votes { ballots_with_this_tv+=v.n; }
-            let original_worth = Rules::use_transfer_value(tv,ballots_with_this_tv);
+            let original_worth = self.rules.use_transfer_value(tv,ballots_with_this_tv);
             let distributed = self.distribute(original_votes);
             let (tally_distributed_to_candidates,tally_distributed_to_candidates_and_exhausted) = self.parcel_out_votes_with_given_transfer_value(tv.clone(),distributed,Some(self.current_count),original_worth,true,false,None);
-            if Rules::should_exhausted_votes_count_for_quota_computation() {
+            if self.rules.should_exhausted_votes_count_for_quota_computation() {
                 total_first_preferences+=tally_distributed_to_candidates_and_exhausted;
             } else {
                 total_first_preferences+=tally_distributed_to_candidates;
@@ -370,17 +538,25 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         self.continuing_candidates_sorted_by_tally.sort_by_key(key);
     }
 
+    /// Compute the quota using [Self::quota_method], usually
     /// quota = round_down(first_preferences/(1+num_to_elect))+1
+    /// [Self::check_input] makes sure the quota is defined.
     pub fn compute_quota(&mut self,total_first_preferences:Rules::Tally) {
-        if Rules::has_quota() {
+        if self.rules.has_quota() {
             let total_first_preferences = BallotPaperCount(Rules::convert_tally_to_rational(total_first_preferences.clone()).to_integer().to_usize().unwrap()); // usually trivial and valid, unless there are papers with TV other than 1, in which case rounded down.
-            self.quota = Rules::Tally::from(BallotPaperCount(total_first_preferences.0/(1+self.candidates_to_be_elected.0)+1));
-            self.transcript.quota = Some(QuotaInfo{
+            let method = self.quota_method;
+            self.quota = match method.compute::<Rules>(total_first_preferences,self.candidates_to_be_elected) {
+                Ok(quota) => quota,
+                Err(e) => { self.failure.get_or_insert(e); return; }
+            };
+            let quota = QuotaInfo{
                 papers: total_first_preferences,
                 vacancies: self.candidates_to_be_elected,
                 quota: self.quota.clone(),
-            });
-            if self.print_progress_to_stdout { println!("Quota = {}", self.quota); }
+                method,
+            };
+            self.observer.quota_computed(&quota);
+            self.transcript.quota = Some(quota);
         } else {
             self.quota = total_first_preferences+Rules::Tally::from(BallotPaperCount(1000)); // effectively infinity.
         }
@@ -396,7 +572,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
     }
     fn declare_elected(&mut self,who:CandidateIndex,why:ElectionReason) {
         self.in_this_count.elected.push(CandidateElected{who,why});
-        if self.print_progress_to_stdout { println!("Elected {}", self.data.metadata.candidate(who).name); }
+        self.observer.candidate_elected(who,why);
         self.elected_candidates.push(who);
         self.transcript.elected.push(who);
         self.no_longer_continuing(who,true);
@@ -457,48 +633,56 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
             }
         };
         // got all the votes in by_transfer_value, now just need to order them and convert to ElectionData.
-        let mut atl : Vec<ATL> = vec![];
-        let mut atl_transfer_values : Vec<VoteValueSpecification> = vec![];
-        let mut btl : Vec<BTL> = vec![];
-        let mut btl_transfer_values : Vec<VoteValueSpecification> = vec![];
-        let mut ordered_transfer_values : Vec<(TransferValue,Vec<VotesWithSameTransferValue<'a>>)> = by_transfer_value.into_iter().collect();
-        ordered_transfer_values.sort_by(|(tv1,_),(tv2,_)|tv2.cmp(tv1));
-        for (tv,votes_vec) in ordered_transfer_values {
-            let atl_start = atl.len();
-            let btl_start = btl.len();
-            for votes in votes_vec {
-                for vote in votes.votes {
-                    match vote.source {
-                        VoteSource::Btl(source) => { btl.push(BTL{ candidates: source.candidates.clone(), n: vote.n.0 }); }
-                        VoteSource::Atl(source) => { atl.push(ATL{ parties: source.parties.clone(), n: vote.n.0, ticket_index: None }); }
-                    }
-                }
-            }
-            if atl_start!=atl.len() {
-                atl_transfer_values.push(VoteValueSpecification{
-                    value: tv.clone(),
-                    first_index_inclusive: atl_start,
-                    last_index_exclusive: atl.len(),
-                })
+        votes_to_election_data(metadata,by_transfer_value)
+    }
+
+    /// Make new election data consisting of the votes in the given candidate's pile, with their transfer values.
+    fn extract_votes_in_pile(&self, who:CandidateIndex) -> ElectionData {
+        let mut metadata = self.data.metadata.clone();
+        metadata.name.modifications.push(format!("Votes in pile of {} at count {}",self.data.metadata.candidate(who).name,self.current_count));
+        let mut by_transfer_value : HashMap<TransferValue,Vec<VotesWithSameTransferValue<'a>>> = HashMap::new();
+        for (tv,votes) in self.papers[who.0].duplicate_all_votes(false).0 {
+            by_transfer_value.entry(tv).or_default().push(votes);
+        }
+        votes_to_election_data(metadata,by_transfer_value)
+    }
+
+    /// Find where the ballots with the given preferences are now.
+    fn locate_traced_vote(&self, vote:&VoteToTrace) -> Vec<TracedVoteLocation> {
+        let mut res = vec![];
+        for (candidate,papers) in self.papers.iter().enumerate() {
+            let candidate = CandidateIndex(candidate);
+            let status = if self.continuing_candidates.contains(&candidate) { TracedVoteStatus::Continuing }
+                else if self.pending_surplus_distribution.contains(&candidate) { TracedVoteStatus::AwaitingSurplusDistribution }
+                else if self.elected_candidates.contains(&candidate) { TracedVoteStatus::InQuota }
+                else { TracedVoteStatus::AwaitingExclusion };
+            for (tv,num,value) in papers.count_matching_votes(|v|vote.matches(&v.source),|tally|Rules::convert_tally_to_rational(tally.clone())) {
+                res.push(TracedVoteLocation{ candidate: Some(candidate), status, papers: num, transfer_value: tv, value: StringSerializedRational(value) });
             }
-            if btl_start!=btl.len() {
-                btl_transfer_values.push(VoteValueSpecification{
-                    value: tv,
-                    first_index_inclusive: btl_start,
-                    last_index_exclusive: btl.len(),
-                })
+        }
+        let mut exhausted : Vec<(TransferValue,BallotPaperCount,BigRational)> = vec![];
+        for (tv,votes,worth) in &self.exhausted_votes {
+            let num : BallotPaperCount = votes.votes.iter().filter(|v|vote.matches(&v.source)).map(|v|v.n).sum();
+            if num.0>0 {
+                let value = Rules::convert_tally_to_rational(worth.clone())*BigInt::from(num.0)/BigInt::from(votes.num_ballots.0);
+                if let Some((_,existing,existing_value)) = exhausted.iter_mut().find(|(t,_,_)|t==tv) { *existing+=num; *existing_value+=value; } else { exhausted.push((tv.clone(),num,value)); }
             }
-        };
-        ElectionData{
-            metadata,
-            atl,
-            atl_types: vec![],
-            atl_transfer_values,
-            btl,
-            btl_types: vec![],
-            btl_transfer_values,
-            informal: 0,
         }
+        for (tv,num,value) in exhausted {
+            res.push(TracedVoteLocation{ candidate: None, status: TracedVoteStatus::Exhausted, papers: num, transfer_value: tv, value: StringSerializedRational(value) });
+        }
+        res
+    }
+
+    /// Make new election data out of votes recorded as exhausted or set aside.
+    fn extract_recorded_votes<'v>(&self, votes:impl Iterator<Item=(&'v TransferValue,&'v VotesWithSameTransferValue<'a>)>, description:&str) -> ElectionData where 'a:'v {
+        let mut metadata = self.data.metadata.clone();
+        metadata.name.modifications.push(format!("Votes {} by count {}",description,self.current_count));
+        let mut by_transfer_value : HashMap<TransferValue,Vec<VotesWithSameTransferValue<'a>>> = HashMap::new();
+        for (tv,votes) in votes {
+            by_transfer_value.entry(tv.clone()).or_default().push(votes.clone());
+        }
+        votes_to_election_data(metadata,by_transfer_value)
     }
 
 
@@ -520,7 +704,9 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
                     TieResolutionGranularityNeeded::LowestSeparated(n) if n<=differs && n>i  => Some(TieResolutionGranularityNeeded::LowestSeparated(n-i)),
                     _ => None, // no resolution needed as all in or all not in.
                 } {
+                    let mut needed_ec_decision = false;
                     for (still_tied,remaining_granularity) in how.resolve(tied,&self.transcript,sub_granularity) {
+                        needed_ec_decision = true;
                         let solved_by_oracle = if let Some(oracle) = &mut self.oracle {
                             if let Some(solution) = oracle.resolve_tie_resolution(self.current_count,remaining_granularity,still_tied) {
                                 let resolutions = TieResolutionsMadeByEC{ tie_resolutions: vec![solution] };
@@ -532,8 +718,10 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
                         if !solved_by_oracle {
                             let decision = self.ec_resolutions.resolve(still_tied,remaining_granularity,usage,self.current_count,&mut self.randomness);
                             self.in_this_count.decisions.push(decision);
+                            self.check_for_refused_draw();
                         }
                     }
+                    self.observer.tie_resolved(usage,tied,needed_ec_decision);
                 }
             }
             i=differs;
@@ -551,7 +739,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
     pub fn check_elected_by_quota(&mut self) {
         let mut elected_by_quota : Vec<CandidateIndex> = self.continuing_candidates_sorted_by_tally.iter().rev().take_while(|&&c|self.tally(c)>=self.quota).cloned().collect();
         elected_by_quota.reverse(); // make sure low to high so that tie checking ordering is compatible.
-        self.check_for_ties_and_resolve(&mut elected_by_quota,Rules::resolve_ties_elected_by_quota(),TieResolutionGranularityNeeded::Total,TieResolutionUsage::OrderElected);
+        self.check_for_ties_and_resolve(&mut elected_by_quota,self.rules.resolve_ties_elected_by_quota(),TieResolutionGranularityNeeded::Total,TieResolutionUsage::OrderElected);
         for &c in elected_by_quota.iter().rev() {
             self.declare_elected(c,ElectionReason::ReachedQuota);
             if self.tally(c)>self.quota { self.pending_surplus_distribution.push_back(c); }
@@ -579,7 +767,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
     pub fn check_elected_by_highest_of_remaining_2_when_1_needed_no_tie_resolution(&mut self) {
         if self.continuing_candidates_sorted_by_tally.len()==2 && self.remaining_to_elect()==NumberOfCandidates(1) {
             let mut possibilities = self.continuing_candidates_sorted_by_tally.clone();
-            self.check_for_ties_and_resolve(&mut possibilities,Rules::resolve_ties_elected_one_of_last_two(),TieResolutionGranularityNeeded::Total,TieResolutionUsage::ShortcutWinner);
+            self.check_for_ties_and_resolve(&mut possibilities,self.rules.resolve_ties_elected_one_of_last_two(),TieResolutionGranularityNeeded::Total,TieResolutionUsage::ShortcutWinner);
             // elect the highest, Electoral officer resolved ties.
             self.declare_elected(possibilities[1],ElectionReason::HighestOfLastTwoStanding);
         }
@@ -592,7 +780,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
     pub fn check_if_should_elect_all_remaining(&mut self) {
         if self.number_continuing_candidates()==self.remaining_to_elect() {
             let mut elected_group = self.continuing_candidates_sorted_by_tally.clone();
-            self.check_for_ties_and_resolve(&mut elected_group,Rules::resolve_ties_elected_all_remaining(),TieResolutionGranularityNeeded::Total,TieResolutionUsage::ShortcutWinner);
+            self.check_for_ties_and_resolve(&mut elected_group,self.rules.resolve_ties_elected_all_remaining(),TieResolutionGranularityNeeded::Total,TieResolutionUsage::ShortcutWinner);
             for &c in elected_group.iter().rev() {
                 self.declare_elected(c,ElectionReason::AllRemainingMustBeElected);
             }
@@ -616,7 +804,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
     /// ```
     pub fn check_if_top_few_have_overwhelming_votes(&mut self) {
         if self.remaining_to_elect().0>0 && self.continuing_candidates_sorted_by_tally.len()>=self.remaining_to_elect().0 {
-            if Rules::when_checking_if_top_few_have_overwhelming_votes_require_exactly_one() && self.remaining_to_elect().0>1 { return; }
+            if self.rules.when_checking_if_top_few_have_overwhelming_votes_require_exactly_one() && self.remaining_to_elect().0>1 { return; }
             let num_candidates_below_potential_winners = self.continuing_candidates_sorted_by_tally.len()-self.remaining_to_elect().0;
             let possibly_overwhelming_tally = self.tally(self.continuing_candidates_sorted_by_tally[num_candidates_below_potential_winners]);
             let mut others : Rules::Tally = Rules::Tally::zero();
@@ -647,23 +835,23 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         }
     }
     pub fn check_elected(&mut self,reason : &ReasonForCount,reason_completed : bool) {
-        let check_quota = match Rules::when_to_check_if_all_remaining_should_get_elected() {
+        let check_quota = match self.rules.when_to_check_if_all_remaining_should_get_elected() {
             WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapersOrQuotaButOnlyIfContinuingCandidatesEqualsUnfilledVacanciesAndNotAfterSurplusIfMoreSurplusesAvailable =>
                 match reason {
                     ReasonForCount::FirstPreferenceCount => true,
-                    ReasonForCount::ExcessDistribution(_) => !self.has_distributable_surplus(),
+                    ReasonForCount::ExcessDistribution(_) | ReasonForCount::ExcessDistributionByKeepValues(_) => !self.has_distributable_surplus(),
                     ReasonForCount::Elimination(_) => reason_completed,
                 },
             _ => true,
         };
         if check_quota  { self.check_elected_by_quota(); }
-        if self.should_check(Rules::when_to_check_if_just_two_standing_for_shortcut_election(),reason,reason_completed) {
+        if self.should_check(self.rules.when_to_check_if_just_two_standing_for_shortcut_election(),reason,reason_completed) {
             self.check_elected_by_highest_of_remaining_2_when_1_needed_no_tie_resolution();
         }
-        if self.should_check(Rules::when_to_check_if_all_remaining_should_get_elected(),reason,reason_completed) {
+        if self.should_check(self.rules.when_to_check_if_all_remaining_should_get_elected(),reason,reason_completed) {
             self.check_if_should_elect_all_remaining();
         }
-        if self.should_check(Rules::when_to_check_if_top_few_have_overwhelming_votes(),reason,reason_completed) {
+        if self.should_check(self.rules.when_to_check_if_top_few_have_overwhelming_votes(),reason,reason_completed) {
             self.check_if_top_few_have_overwhelming_votes();
         }
     }
@@ -673,20 +861,19 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         self.resort_candidates();
         let should_check_elected = reason_completed || match reason {
             ReasonForCount::FirstPreferenceCount => reason_completed, // don't check in the middle of the first preference count.
-            ReasonForCount::ExcessDistribution(_) => Rules::check_elected_if_in_middle_of_surplus_distribution(),
-            ReasonForCount::Elimination(_) => Rules::check_elected_if_in_middle_of_exclusion(),
+            ReasonForCount::ExcessDistribution(_) | ReasonForCount::ExcessDistributionByKeepValues(_) => self.rules.check_elected_if_in_middle_of_surplus_distribution(),
+            ReasonForCount::Elimination(_) => self.rules.check_elected_if_in_middle_of_exclusion(),
         };
         if should_check_elected {
             self.check_elected(&reason,reason_completed);
         }
-        if self.print_progress_to_stdout { self.print_tallys(); }
-        let count_name : Option<String> = match Rules::how_to_name_counts() {
+        let count_name : Option<String> = match self.rules.how_to_name_counts() {
             CountNamingMethod::SimpleNumber => None,
             CountNamingMethod::MajorMinor => Some(format!("{}.{}",self.current_major_count.0,self.current_minor_count.0)),
             CountNamingMethod::BasedOnSourceName => Some({
                 match reason {
                     ReasonForCount::FirstPreferenceCount => "1".to_string(),
-                    ReasonForCount::ExcessDistribution(_) if Rules::surplus_distribution_subdivisions()!=SurplusTransferMethod::ScaleTransferValues => format!("{}_{}",self.current_major_count.0,self.current_minor_count.0),
+                    ReasonForCount::ExcessDistribution(_) if self.rules.surplus_distribution_subdivisions()!=SurplusTransferMethod::ScaleTransferValues => format!("{}_{}",self.current_major_count.0,self.current_minor_count.0),
                     _ => {
                         if portion.papers_came_from_counts.is_empty() {
                             format!("{}",self.current_major_count.0)
@@ -704,9 +891,21 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
                 WhatToExtract::ACTVotesUsedToElectCandidate(who) if self.in_this_count.elected.iter().any(|c|c.who==*who) => {
                     e.what_to_do_with_it.do_it(self.extract_votes_electing_act(*who))
                 }
+                WhatToExtract::PileAtCount { candidate, count } if *count==self.current_count => {
+                    e.what_to_do_with_it.do_it(self.extract_votes_in_pile(*candidate))
+                }
+                WhatToExtract::ExhaustedAtCount(count) if *count==self.current_count => {
+                    e.what_to_do_with_it.do_it(self.extract_recorded_votes(self.exhausted_votes.iter().map(|(tv,votes,_)|(tv,votes)),"exhausted"))
+                }
+                WhatToExtract::SetAsideAtCount(count) if *count==self.current_count => {
+                    e.what_to_do_with_it.do_it(self.extract_recorded_votes(self.set_aside_votes.iter().map(|(tv,votes)|(tv,votes)),"set aside"))
+                }
                 _ => {}
             }
         }
+        if let Some(locations) = self.vote_trace.as_ref().map(|trace|self.locate_traced_vote(&trace.vote)) {
+            if let Some(trace) = &mut self.vote_trace { trace.counts.push(TracedVoteAtCount{ count: self.current_count, count_name: count_name.clone(), locations }); }
+        }
         self.transcript.counts.push(SingleCount{
             reason,
             portion,
@@ -752,9 +951,11 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
                 } else {None},
             },
             count_name,
+            keep_values: None,
         });
+        if let Some(count) = self.transcript.counts.last() { self.observer.count_finished(count); }
         self.current_count=CountIndex(self.current_count.0+1);
-        if reason_completed || (Rules::major_count_if_someone_elected() && !self.in_this_count.elected.is_empty()) { self.current_major_count=CountIndex(self.current_major_count.0+1); self.current_minor_count=CountIndex(1); }
+        if reason_completed || (self.rules.major_count_if_someone_elected() && !self.in_this_count.elected.is_empty()) { self.current_major_count=CountIndex(self.current_major_count.0+1); self.current_minor_count=CountIndex(1); }
         else { self.current_minor_count=CountIndex(self.current_minor_count.0+1); }
         self.in_this_count.not_continuing=self.in_this_count.elected.drain(..).map(|e|e.who).collect();
     }
@@ -776,7 +977,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
     fn last_count_used_for_nsw_last_parcel(&self,candidate:CandidateIndex,bonus:bool) -> CountIndex {
         let count_elected = self.candidate_elected_at_count[candidate.0].expect("Candidate was not elected");
         let should_do_lookback = match self.transcript.counts[count_elected.0].reason {
-             ReasonForCount::ExcessDistribution(_) => true,
+             ReasonForCount::ExcessDistribution(_) | ReasonForCount::ExcessDistributionByKeepValues(_) => true,
              ReasonForCount::Elimination(_) => bonus,
              ReasonForCount::FirstPreferenceCount => false,
         };
@@ -814,7 +1015,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         self.tallys[candidate_to_distribute.0]=self.quota.clone();
         // the code below is not production but is to find the oddity of a transfer value being increased.
         // let original_provinances = self.papers[candidate_to_distribute.0].get_all_provenance_keys();
-        let (_tally_here,ballots,provenance) = match Rules::use_last_parcel_for_surplus_distribution() {
+        let (_tally_here,ballots,provenance) = match self.rules.use_last_parcel_for_surplus_distribution() {
             LastParcelUse::No => self.papers[candidate_to_distribute.0].extract_all_ballots_ignoring_transfer_value(),
             LastParcelUse::LiterallyLast => self.papers[candidate_to_distribute.0].extract_last_parcel(),
             LastParcelUse::LastPlusIfItWasSurplusDistributionPriorSurplusDistributionsWithoutAnyoneElected => {
@@ -829,8 +1030,8 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         let ballots_considered : BallotPaperCount = ballots.num_ballots;
         let distributed = self.distribute(&ballots.votes);
         let continuing_ballots = ballots_considered-distributed.exhausted;
-        let tv_denom = if Rules::transfer_value_method().denom_is_just_continuing() {continuing_ballots} else {ballots.num_ballots};
-        let mut transfer_value : TransferValue = if tv_denom.is_zero() { TransferValue::one() } else {Rules::make_transfer_value(surplus.clone(),tv_denom)};
+        let tv_denom = if self.rules.transfer_value_method().denom_is_just_continuing() {continuing_ballots} else {ballots.num_ballots};
+        let mut transfer_value : TransferValue = if tv_denom.is_zero() { TransferValue::one() } else {self.rules.make_transfer_value(surplus.clone(),tv_denom)};
         // the code below is not production but is to find the oddity of a transfer value being increased.
         //for (_,original_tv) in original_provinances {
         //    if original_tv.lt(&transfer_value) {
@@ -840,17 +1041,24 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         //}
         //
         let mut original_worth : Rules::Tally = surplus.clone();
-        if Rules::transfer_value_method().limit_to_incoming_transfer_value() {
+        if self.rules.transfer_value_method().limit_to_incoming_transfer_value() {
             let old_tv = provenance.transfer_value.clone().expect("If you are going to limit to an incoming transfer value, there must be a unique one.");
-            let old_tv = Rules::munge_transfer_value_when_used_as_limit(old_tv);
+            let old_tv = self.rules.munge_transfer_value_when_used_as_limit(old_tv);
             if old_tv<transfer_value {
-                if !Rules::count_set_aside_due_to_transfer_value_limit_as_rounding() {
+                if !self.rules.count_set_aside_due_to_transfer_value_limit_as_rounding() {
                     // work out how many votes lost this way.
-                    let set_aside : Rules::Tally = surplus.clone()-Rules::use_transfer_value(&old_tv,tv_denom);
+                    let set_aside : Rules::Tally = surplus.clone()-self.rules.use_transfer_value(&old_tv,tv_denom);
                     // println!("Set aside {} of {} votes to deal with rule limiting transfer value to incoming",set_aside,original_worth);
                     original_worth-=set_aside.clone();
                     self.add_set_aside(set_aside);
                 }
+                if self.keep_exhausted_and_set_aside_votes { // the papers used to compute the transfer value have the excess value set aside (or lost to rounding).
+                    let mut papers_used_for_tv = VotesWithSameTransferValue::default();
+                    for vote in &ballots.votes {
+                        if !self.rules.transfer_value_method().denom_is_just_continuing() || vote.next(&self.continuing_candidates).is_some() { papers_used_for_tv.add_vote(*vote); }
+                    }
+                    self.set_aside_votes.push((TransferValue(transfer_value.0.clone()-&old_tv.0),papers_used_for_tv));
+                }
                 transfer_value=old_tv;
             }
         }
@@ -858,7 +1066,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         if distribute_randomly_nsw { // this is a terrible thing.
             self.parcel_out_votes_random_portion_set_by_transfer_value(transfer_value.clone(),distributed,BallotPaperCount(surplus.ceil()),candidate_to_distribute);
         } else {
-            self.parcel_out_votes_with_given_transfer_value(transfer_value.clone(),distributed,Some(self.current_count),original_worth,!Rules::transfer_value_method().denom_is_just_continuing(),false,None);
+            self.parcel_out_votes_with_given_transfer_value(transfer_value.clone(),distributed,Some(self.current_count),original_worth,!self.rules.transfer_value_method().denom_is_just_continuing(),false,None);
         }
         self.in_this_count.created_transfer_value=Some(TransferValueCreation{
             surplus,
@@ -869,7 +1077,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
             ballots_considered,
             continuing_ballots,
             transfer_value,
-            source: Rules::transfer_value_method(),
+            source: self.rules.transfer_value_method(),
         });
         provenance
     }
@@ -882,7 +1090,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         let votes_to_distribute : Vec<(TransferValue,(Rules::Tally,VotesWithSameTransferValue,PortionOfReasonBeingDoneThisCount))> =
             if merge_same_tv { self.papers[candidate_to_distribute.0].extract_all_ballots_separated_by_transfer_value() } // sorted highest TV to lowest
             else {
-                if let Some(custom_sorter) = Rules::sort_subcounts_by_count() {
+                if let Some(custom_sorter) = self.rules.sort_subcounts_by_count() {
                     /*let transcript = Transcript::<Rules::Tally>{
                         rules: "".to_string(),
                         quota: QuotaInfo {
@@ -897,7 +1105,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
                 } else {
                     self.papers[candidate_to_distribute.0].extract_all_ballots_separated_by_key(None,&self.transcript)
                 }
-                //let custom_sort_function : Option<Box<(dyn FnMut(<<Rules as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide, <<Rules as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide) -> std::cmp::Ordering + 'static)>> = Rules::sort_subcounts_by_count().map(|f|Box::new(|a1:<<Rules as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide,a2:<<Rules as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide|f(&self.transcript,a1,a2)));
+                //let custom_sort_function : Option<Box<(dyn FnMut(<<Rules as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide, <<Rules as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide) -> std::cmp::Ordering + 'static)>> = self.rules.sort_subcounts_by_count().map(|f|Box::new(|a1:<<Rules as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide,a2:<<Rules as PreferenceDistributionRules>::SplitByNumber as HowSplitByCountNumber>::KeyToDivide|f(&self.transcript,a1,a2)));
                 //self.papers[candidate_to_distribute.0].extract_all_ballots_separated_by_key(&custom_sort_function)
             }; // sorted by key
         let mut partially_distributed = vec![];
@@ -909,16 +1117,20 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
             total_value_of_exhausted_votes+=exhausted_value.clone();
             partially_distributed.push((tv,step_tally,ballots,prov,distributed,exhausted_value));
         }
-        let general_tv_denom : BigRational = if Rules::transfer_value_method().denom_is_just_continuing()  { Rules::convert_tally_to_rational(votes.clone())-total_value_of_exhausted_votes.clone() } else { Rules::convert_tally_to_rational(votes.clone()) } ;
+        let general_tv_denom : BigRational = if self.rules.transfer_value_method().denom_is_just_continuing()  { Rules::convert_tally_to_rational(votes.clone())-total_value_of_exhausted_votes.clone() } else { Rules::convert_tally_to_rational(votes.clone()) } ;
         let quota : BigRational = Rules::convert_tally_to_rational(self.quota.clone());
-        let special_factor_excluded : Option<BigRational> = if Rules::transfer_value_method().denom_is_just_continuing() || total_value_of_exhausted_votes<=quota || Rules::count_set_aside_due_to_transfer_value_limit_as_rounding() { None } else { Some((total_value_of_exhausted_votes.clone()-quota.clone())/total_value_of_exhausted_votes.clone()) }; // (AV-Q)/Av
+        let special_factor_excluded : Option<BigRational> = if self.rules.transfer_value_method().denom_is_just_continuing() || total_value_of_exhausted_votes<=quota || self.rules.count_set_aside_due_to_transfer_value_limit_as_rounding() { None } else { Some((total_value_of_exhausted_votes.clone()-quota.clone())/total_value_of_exhausted_votes.clone()) }; // (AV-Q)/Av
         let original_worth_ratio = Rules::convert_tally_to_rational(surplus.clone())/Rules::convert_tally_to_rational(votes.clone());
         let surplus_rational = Rules::convert_tally_to_rational(surplus.clone());
         //println!("TV based on surplus {} = {}-{} divided by {} = {}-{}",surplus_rational,votes,self.quota,general_tv_denom,votes,total_value_of_exhausted_votes);
-        let general_tv = if general_tv_denom<=surplus_rational && (Rules::prohibit_negative_surplus_fraction() || !general_tv_denom.is_negative()) { TransferValue::one() } else { TransferValue(surplus_rational/general_tv_denom) };
+        let general_tv = if general_tv_denom<=surplus_rational && (self.rules.prohibit_negative_surplus_fraction() || !general_tv_denom.is_negative()) { TransferValue::one() } else { TransferValue(surplus_rational/general_tv_denom) };
         //println!("quota {} exhausted {} special factor excluded {:?} TV {}",self.quota,total_value_of_exhausted_votes,special_factor_excluded,general_tv);
         let mut current_remaining_tally_for_candidate_being_distributed : BigRational = Rules::convert_tally_to_rational(votes.clone());
         let mut togo = partially_distributed.len();
+        // only used if self.rules.do_all_transfer_values_in_one_count()
+        let mut all_ballots_considered = BallotPaperCount::zero();
+        let mut all_continuing_ballots = BallotPaperCount::zero();
+        let mut all_papers_came_from_counts : Vec<CountIndex> = vec![];
         for (tv,step_tally,ballots,provenance,distributed,_exhausted_value) in partially_distributed {
             togo-=1;
             let is_final_step = togo==0;
@@ -930,22 +1142,43 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
             self.tallys[candidate_to_distribute.0] = after.clone();
             let original_worth = before-after;
             let distributed = if continuing_candidates_when_distribution_done == self.continuing_candidates_sorted_by_tally.len() && self.oracle.is_none() {distributed} else { self.distribute(&ballots.votes) }; // recompute if the continuing candidates list changed, or oracle exists
-            let transfer_value = TransferValue(tv.0*general_tv.0.clone());
+            let transfer_value = self.rules.munge_scaled_transfer_value(TransferValue(tv.0*general_tv.0.clone()));
             let continuing_ballots = ballots.num_ballots-distributed.exhausted;
-            self.parcel_out_votes_with_given_transfer_value(transfer_value.clone(),distributed,Some(self.current_count),original_worth,special_factor_excluded.is_some() || !Rules::transfer_value_method().denom_is_just_continuing(),false,special_factor_excluded.as_ref());
-            self.in_this_count.created_transfer_value=Some(TransferValueCreation{
-                surplus: surplus.clone(),
-                votes : votes.clone(),
-                excluded_exhausted_tally: Some(StringSerializedRational(total_value_of_exhausted_votes.clone())),
-                original_transfer_value: provenance.transfer_value.clone(),
-                multiplied_transfer_value : Some(general_tv.clone()),
-                ballots_considered : ballots.num_ballots,
-                continuing_ballots,
-                transfer_value,
-                source: Rules::transfer_value_method(),
-            });
-            self.end_of_count_step(ReasonForCount::ExcessDistribution(candidate_to_distribute), provenance, is_final_step);
-
+            self.parcel_out_votes_with_given_transfer_value(transfer_value.clone(),distributed,Some(self.current_count),original_worth,special_factor_excluded.is_some() || !self.rules.transfer_value_method().denom_is_just_continuing(),false,special_factor_excluded.as_ref());
+            if self.rules.do_all_transfer_values_in_one_count() {
+                all_ballots_considered+=ballots.num_ballots;
+                all_continuing_ballots+=continuing_ballots;
+                all_papers_came_from_counts.extend(provenance.papers_came_from_counts);
+                if !is_final_step { continue; }
+                all_papers_came_from_counts.sort();
+                all_papers_came_from_counts.dedup();
+                self.in_this_count.created_transfer_value=Some(TransferValueCreation{
+                    surplus: surplus.clone(),
+                    votes : votes.clone(),
+                    excluded_exhausted_tally: Some(StringSerializedRational(total_value_of_exhausted_votes.clone())),
+                    original_transfer_value: None,
+                    multiplied_transfer_value : Some(general_tv.clone()),
+                    ballots_considered : all_ballots_considered,
+                    continuing_ballots : all_continuing_ballots,
+                    transfer_value : self.rules.munge_scaled_transfer_value(general_tv.clone()),
+                    source: self.rules.transfer_value_method(),
+                });
+                let provenance = PortionOfReasonBeingDoneThisCount{ transfer_value: None, when_tv_created: None, papers_came_from_counts: std::mem::take(&mut all_papers_came_from_counts) };
+                self.end_of_count_step(ReasonForCount::ExcessDistribution(candidate_to_distribute), provenance, true);
+            } else {
+                self.in_this_count.created_transfer_value=Some(TransferValueCreation{
+                    surplus: surplus.clone(),
+                    votes : votes.clone(),
+                    excluded_exhausted_tally: Some(StringSerializedRational(total_value_of_exhausted_votes.clone())),
+                    original_transfer_value: provenance.transfer_value.clone(),
+                    multiplied_transfer_value : Some(general_tv.clone()),
+                    ballots_considered : ballots.num_ballots,
+                    continuing_ballots,
+                    transfer_value,
+                    source: self.rules.transfer_value_method(),
+                });
+                self.end_of_count_step(ReasonForCount::ExcessDistribution(candidate_to_distribute), provenance, is_final_step);
+            }
         }
     }
 
@@ -955,7 +1188,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         let mut tally_distributed = Rules::Tally::zero();
         for (candidate_index,candidate_ballots) in distributed.by_candidate.into_iter().enumerate() {
             if candidate_ballots.num_ballots.0>0 {
-                let worth:Rules::Tally = Rules::use_transfer_value(&transfer_value,candidate_ballots.num_ballots);
+                let worth:Rules::Tally = self.rules.use_transfer_value(&transfer_value,candidate_ballots.num_ballots);
                 self.tallys[candidate_index]+=worth.clone();
                 tally_distributed +=worth.clone();
                 self.papers[candidate_index].add(&candidate_ballots, transfer_value.clone(), self.current_count, when_tv_created, worth);
@@ -963,16 +1196,18 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         }
         let tally_distributed_to_candidates = tally_distributed.clone();
         if distributed.exhausted.0>0 {
-            if distribute_exhausted_votes {
-                let exhausted_tv = if let Some(em) = extra_multiple_for_exhausted { TransferValue(transfer_value.0*em) } else { transfer_value };
-                let worth:Rules::Tally = Rules::use_transfer_value(&exhausted_tv,distributed.exhausted);
-                let worth:Rules::Tally = Rules::munge_exhausted_votes(worth,is_exclusion); // support emulation of weird bugs.
+            let (exhausted_tv,exhausted_worth) = if distribute_exhausted_votes {
+                let exhausted_tv = if let Some(em) = extra_multiple_for_exhausted { TransferValue(transfer_value.0.clone()*em) } else { transfer_value.clone() };
+                let worth:Rules::Tally = self.rules.use_transfer_value(&exhausted_tv,distributed.exhausted);
+                let worth:Rules::Tally = self.rules.munge_exhausted_votes(worth,is_exclusion); // support emulation of weird bugs.
                 self.tally_exhausted+=worth.clone();
                 tally_distributed+=worth.clone();
-            }
+                (exhausted_tv,worth)
+            } else { (TransferValue(BigRational::zero()),Rules::Tally::zero()) }; // the papers exhaust without any value.
             // always distribute the papers.
             self.exhausted+=distributed.exhausted;
             self.exhausted_atl+=distributed.exhausted_atl;
+            if self.keep_exhausted_and_set_aside_votes { self.exhausted_votes.push((exhausted_tv,distributed.exhausted_votes,exhausted_worth)); }
         }
         self.tally_lost_to_rounding+=original_worth;
         self.tally_lost_to_rounding-=tally_distributed.clone();
@@ -984,13 +1219,13 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
     /// Parcel out votes by next continuing candidate with a given transfer value.
     /// Returns to the candidate being distributed the ones kept for quota.
     fn parcel_out_votes_random_portion_set_by_transfer_value(&mut self,transfer_value:TransferValue,distributed:DistributedVotes<'a>,surplus:BallotPaperCount,candidate_being_distributed:CandidateIndex)  {
-        let (set_aside_by_candidate,ec_decisions) = transfer_value.calculate_number_of_ballot_papers_to_be_set_aside(surplus,self.num_candidates,&self.transcript,&distributed,Rules::use_f32_arithmetic_when_applying_transfer_values_instead_of_exact(),self.ec_resolutions,self.current_count,&mut self.randomness);
+        let (set_aside_by_candidate,ec_decisions) = transfer_value.calculate_number_of_ballot_papers_to_be_set_aside(surplus,self.num_candidates,&self.transcript,&distributed,self.rules.use_f32_arithmetic_when_applying_transfer_values_instead_of_exact(),self.ec_resolutions,self.current_count,&mut self.randomness);
         self.in_this_count.decisions.extend(ec_decisions);
         // do the actual distribution
         let mut total_transferred : BallotPaperCount = BallotPaperCount::zero();
         for (candidate_index,candidate_ballots) in distributed.by_candidate.iter().enumerate() {
             if candidate_ballots.num_ballots.0>0 {
-                let (chosen,unchosen) = candidate_ballots.set_aside(set_aside_by_candidate[candidate_index],self.randomness);
+                let (chosen,unchosen) = candidate_ballots.set_aside(set_aside_by_candidate[candidate_index],self.randomness,self.current_count,CandidateIndex(candidate_index));
                 if chosen.num_ballots.0>0 {
                     let worth = chosen.num_ballots;
                     total_transferred +=worth;
@@ -1014,6 +1249,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         self.exhausted += exhausted_that_would_be_distributed_if_they_could_be;
         self.tally_exhausted += exhausted_that_would_be_distributed_if_they_could_be.into();
         self.exhausted_atl += exhausted_set_aside.num_atl_ballots;
+        if self.keep_exhausted_and_set_aside_votes && exhausted_set_aside.num_ballots.0>0 { let worth = exhausted_set_aside.num_ballots.into(); self.exhausted_votes.push((TransferValue::one(),exhausted_set_aside,worth)); }
         self.in_this_count.set_aside_for_quota = Some(PerCandidate {
             candidate: set_aside_by_candidate,
             exhausted: exhausted_that_are_set_aside_for_quota,
@@ -1024,7 +1260,9 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
 
     pub fn distribute_surplus(&mut self,candidate_to_distribute:CandidateIndex) {
         // println!("Distributing surplus for {}",self.data.metadata.candidate(candidate_to_distribute).name);
-        match Rules::surplus_distribution_subdivisions() {
+        let surplus = self.tally(candidate_to_distribute)-self.quota.clone();
+        self.observer.surplus_distribution_started(candidate_to_distribute,&surplus);
+        match self.rules.surplus_distribution_subdivisions() {
             SurplusTransferMethod::JustOneTransferValue => {
                 let provenance = self.distribute_surplus_all_with_same_transfer_value(candidate_to_distribute,false);
                 self.end_of_count_step(ReasonForCount::ExcessDistribution(candidate_to_distribute), provenance, true);
@@ -1038,17 +1276,10 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         }
     }
 
-    pub fn print_candidates_names(&self) {
-        println!("{}",self.data.metadata.candidates.iter().map(|c|c.name.clone()).collect::<Vec<String>>().join("\t")+"\tExhausted");
-    }
-    pub fn print_tallys(&self) {
-        println!("{}",self.tallys.iter().map(|t|t.to_string()).collect::<Vec<String>>().join("\t")+"\t"+&self.exhausted.0.to_string());
-    }
-
     pub fn find_lowest_candidate(&mut self) -> Vec<CandidateIndex> {
         let lowest_tally = self.tally(self.continuing_candidates_sorted_by_tally[0]);
         let mut possibilities : Vec<CandidateIndex> = self.continuing_candidates_sorted_by_tally.iter().take_while(|&&c|self.tally(c)==lowest_tally).cloned().collect();
-        self.check_for_ties_and_resolve(&mut possibilities,Rules::resolve_ties_choose_lowest_candidate_for_exclusion(),TieResolutionGranularityNeeded::LowestSeparated(1),TieResolutionUsage::Exclusion);
+        self.check_for_ties_and_resolve(&mut possibilities,self.rules.resolve_ties_choose_lowest_candidate_for_exclusion(),TieResolutionGranularityNeeded::LowestSeparated(1),TieResolutionUsage::Exclusion);
         possibilities.truncate(1);
         possibilities
     }
@@ -1188,12 +1419,31 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
             // Use rule 31(b) to resolve ties.
             let mut tie_start : usize = candidates_to_exclude-1;
             while tie_start>0 && tally_of_highest_excluded==self.tally(self.continuing_candidates_sorted_by_tally[tie_start-1]) { tie_start-=1; }
-            self.check_for_ties_and_resolve_inplace(tie_start..tie_end,Rules::resolve_ties_choose_lowest_candidate_for_exclusion(),TieResolutionGranularityNeeded::LowestSeparated(candidates_to_exclude-tie_start),TieResolutionUsage::Exclusion);
+            self.check_for_ties_and_resolve_inplace(tie_start..tie_end,self.rules.resolve_ties_choose_lowest_candidate_for_exclusion(),TieResolutionGranularityNeeded::LowestSeparated(candidates_to_exclude-tie_start),TieResolutionUsage::Exclusion);
         }
         // exclude the lowest candidates_to_exclude candidates.
         Some(self.continuing_candidates_sorted_by_tally[0..candidates_to_exclude].to_vec())
     }
 
+    /// A much simpler multiple exclusion, used in Ireland. Exclude the largest group of (at least two) lowest
+    /// candidates whose combined votes, plus all undistributed surpluses, are fewer than the votes of
+    /// the next highest continuing candidate. As in subsection 13B above, never exclude so many that there are
+    /// fewer continuing candidates than remaining vacancies.
+    ///
+    /// As the combined votes are strictly fewer than the next highest, there can be no tie that matters.
+    pub fn find_candidates_for_multiple_elimination_below_next_highest(&self) -> Option<Vec<CandidateIndex>> {
+        let max_to_exclude = (self.number_continuing_candidates()-self.remaining_to_elect()).0;
+        let mut sum_lowest = self.total_undistributed_surplus_votes();
+        let mut candidates_to_exclude = 0;
+        for (i,&candidate) in self.continuing_candidates_sorted_by_tally.iter().enumerate().take(max_to_exclude) {
+            sum_lowest+=self.tally(candidate);
+            if let Some(&next) = self.continuing_candidates_sorted_by_tally.get(i+1) {
+                if sum_lowest<self.tally(next) { candidates_to_exclude=i+1; }
+            }
+        }
+        if candidates_to_exclude<2 { None } else { Some(self.continuing_candidates_sorted_by_tally[0..candidates_to_exclude].to_vec()) }
+    }
+
     /// Federal legislation:
     /// > (13AA) Where a candidate is, or candidates are, excluded in accordance
     /// > with this section, the ballot papers of the excluded candidate or
@@ -1224,15 +1474,16 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
     /// > (iii) all those ballot papers must be transferred to the
     /// > continuing candidate.
     pub fn exclude(&mut self, candidates_to_exclude:Vec<CandidateIndex>) {
+        self.observer.candidates_excluded(&candidates_to_exclude);
         for &candidate in &candidates_to_exclude {
             // println!("Excluding {}",self.data.metadata.candidate(candidate).name);
             self.no_longer_continuing(candidate,false);
         }
-        let stop_now = match Rules::when_to_check_if_all_remaining_should_get_elected() {
+        let stop_now = match self.rules.when_to_check_if_all_remaining_should_get_elected() {
             WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapers=> self.number_continuing_candidates()==self.remaining_to_elect(),
             WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapersOrQuotaButOnlyIfContinuingCandidatesEqualsUnfilledVacanciesAndNotAfterSurplusIfMoreSurplusesAvailable => self.number_continuing_candidates()==self.remaining_to_elect(),
             _ => false,
-        } || match Rules::when_to_check_if_just_two_standing_for_shortcut_election() {
+        } || match self.rules.when_to_check_if_just_two_standing_for_shortcut_election() {
             WhenToDoElectCandidateClauseChecking::AfterDeterminingWhoToExcludeButBeforeTransferringAnyPapers=> self.number_continuing_candidates()==NumberOfCandidates(2) && self.remaining_to_elect()==NumberOfCandidates(1),
             _ => false,
         };
@@ -1262,13 +1513,14 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         // First sort by S::KeyToDivide
         provenances.sort_by_key(|f|f.0.clone()); // stable sort, will preserve ordering of other key stuff
         // Then stable sort by TransferValue
-        if Rules::sort_exclusions_by_transfer_value() { provenances.sort_by_key(|f|f.1.0.clone().neg()); } // stable sort, will preserve ordering of other key stuff
-        if let Some(mut sorting_rule) = Rules::sort_subcounts_by_count() {
+        if self.rules.sort_exclusions_by_transfer_value() { provenances.sort_by_key(|f|f.1.0.clone().neg()); } // stable sort, will preserve ordering of other key stuff
+        if let Some(mut sorting_rule) = self.rules.sort_subcounts_by_count() {
             provenances.sort_by(|(a,_),(b,_)|{
                 sorting_rule(&self.transcript,a.clone(),b.clone())
             });
         }
         let mut togo = provenances.len();
+        let mut all_papers_came_from_counts : Vec<CountIndex> = vec![]; // only used if self.rules.do_all_transfer_values_in_one_count()
         for key in provenances {
             // doing the transfer for this key.
             let mut all_votes = VotesWithSameTransferValue::default();
@@ -1289,19 +1541,32 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
             let distributed = self.distribute(&all_votes.votes);
             self.parcel_out_votes_with_given_transfer_value(key.1.clone(),distributed,when_tv_created,original_worth,true,true,None);
             togo-=1;
-            self.end_of_count_step(ReasonForCount::Elimination(candidates_to_exclude.clone()), PortionOfReasonBeingDoneThisCount {
-                transfer_value: Some(key.1),
-                when_tv_created,
-                papers_came_from_counts: papers_came_from_counts.take(),
-            }, togo==0);
-            if self.remaining_to_elect()==NumberOfCandidates(0) && !Rules::finish_all_counts_in_elimination_when_all_elected() { break; }
+            if self.rules.do_all_transfer_values_in_one_count() {
+                all_papers_came_from_counts.extend(papers_came_from_counts.take());
+                if togo>0 { continue; }
+                all_papers_came_from_counts.sort();
+                all_papers_came_from_counts.dedup();
+                self.end_of_count_step(ReasonForCount::Elimination(candidates_to_exclude.clone()), PortionOfReasonBeingDoneThisCount {
+                    transfer_value: None,
+                    when_tv_created: None,
+                    papers_came_from_counts: std::mem::take(&mut all_papers_came_from_counts),
+                }, true);
+            } else {
+                self.end_of_count_step(ReasonForCount::Elimination(candidates_to_exclude.clone()), PortionOfReasonBeingDoneThisCount {
+                    transfer_value: Some(key.1),
+                    when_tv_created,
+                    papers_came_from_counts: papers_came_from_counts.take(),
+                }, togo==0);
+            }
+            if self.remaining_to_elect()==NumberOfCandidates(0) && !self.rules.finish_all_counts_in_elimination_when_all_elected() { break; }
         }
     }
 
 
     pub fn exclude_lowest(&mut self) {
         let candidates_to_exclude : Vec<CandidateIndex> =
-            if Rules::should_eliminate_multiple_candidates_federal_rule_13a() { self.find_candidates_for_multiple_elimination_federal_rule_13a().unwrap_or_else(||self.find_lowest_candidate()) }
+            if self.rules.should_eliminate_multiple_candidates_federal_rule_13a() { self.find_candidates_for_multiple_elimination_federal_rule_13a().unwrap_or_else(||self.find_lowest_candidate()) }
+            else if self.rules.should_eliminate_multiple_candidates_below_next_highest() { self.find_candidates_for_multiple_elimination_below_next_highest().unwrap_or_else(||self.find_lowest_candidate()) }
             else { self.find_lowest_candidate() };
         self.exclude(candidates_to_exclude);
     }
@@ -1309,7 +1574,7 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
         !(self.pending_surplus_distribution.is_empty() || self.should_defer_surplus())
     }
     pub fn should_defer_surplus(&self) -> bool {
-        match Rules::when_should_surplus_distribution_be_deferred() {
+        match self.rules.when_should_surplus_distribution_be_deferred() {
             DeferSurplusDistribution::AlwaysDistributeAllSurplusBeforeAnyExclusions => false,
             DeferSurplusDistribution::DeferIfSumOfUndistributedSurplussesLessThanDifferenceBetweenTwoLowestContinuingCandidates => {
                 self.continuing_candidates_sorted_by_tally.len()>=2 && self.total_undistributed_surplus_votes() < self.tally(self.continuing_candidates_sorted_by_tally[1])-self.tally(self.continuing_candidates_sorted_by_tally[0])
@@ -1319,31 +1584,263 @@ impl <'a,Rules:PreferenceDistributionRules> PreferenceDistributor<'a,Rules>
             }
         }
     }
-    pub fn go(&mut self) {
-        if self.print_progress_to_stdout { self.print_candidates_names(); }
-        self.distribute_first_preferences();
-        while (self.remaining_to_elect()>NumberOfCandidates(0) && self.continuing_candidates.len()>0) || (Rules::finish_all_surplus_distributions_when_all_elected() && (!self.continuing_candidates_sorted_by_tally.is_empty()) && !self.pending_surplus_distribution.is_empty()) {
-            if self.should_defer_surplus() {
-                self.exclude_lowest();
-            } else {
-                if let Some(candidate) = self.pending_surplus_distribution.pop_front() {
-                    self.distribute_surplus(candidate);
-                } else {
-                    self.exclude_lowest();
-                }
+    /// Get the next surplus to distribute. This is usually the earliest elected, unless [PreferenceDistributionRules::distribute_largest_surplus_first]
+    /// in which case it is the largest, with ties going to the earliest elected.
+    fn pop_next_pending_surplus(&mut self) -> Option<CandidateIndex> {
+        if self.rules.distribute_largest_surplus_first() {
+            let mut best : Option<usize> = None;
+            for (i,&c) in self.pending_surplus_distribution.iter().enumerate() {
+                if best.map(|b|self.tally(c)>self.tally(self.pending_surplus_distribution[b])).unwrap_or(true) { best=Some(i); }
+            }
+            best.and_then(|b|self.pending_surplus_distribution.remove(b))
+        } else { self.pending_surplus_distribution.pop_front() }
+    }
+
+    /// true if all the first preferences have been distributed, and there is nothing more to do.
+    pub fn is_finished(&self) -> bool {
+        self.started && !((self.remaining_to_elect()>NumberOfCandidates(0) && self.continuing_candidates.len()>0) || (self.rules.finish_all_surplus_distributions_when_all_elected() && (!self.continuing_candidates_sorted_by_tally.is_empty()) && !self.pending_surplus_distribution.is_empty()))
+    }
+
+    /// If a tie needed a draw that [Randomness::NoDraws] did not allow, or a draw did not match the one being replayed, note it as a failure.
+    fn check_for_refused_draw(&mut self) {
+        if let Some(failure) = self.randomness.take_draw_failure(self.current_count) {
+            self.failure.get_or_insert(failure);
+        }
+    }
+
+    /// Check that the votes, EC decisions and extractors only mention valid candidates, that there are not too many ballots for the tally type, and that the quota is defined.
+    fn check_input(&self) -> Result<(),CountError> {
+        if self.rules.has_quota() { self.quota_method.check_defined(self.candidates_to_be_elected)?; }
+        let check_candidate = |candidate:CandidateIndex| if candidate.0<self.num_candidates { Ok(()) } else { Err(CountError::InvalidCandidate{candidate,num_candidates:self.num_candidates}) };
+        let mut ballots : usize = 0;
+        for (_,votes) in self.original_votes {
+            for vote in votes {
+                for &candidate in vote.prefs { check_candidate(candidate)?; }
+                ballots=ballots.saturating_add(vote.n.0);
+            }
+        }
+        for candidate in self.ec_resolutions.candidates_mentioned() { check_candidate(candidate)?; }
+        check_extractor_candidates(self.extractors,self.num_candidates)?;
+        let max_ballots = Rules::Tally::largest_integer();
+        if ballots>max_ballots { return Err(CountError::ArithmeticOverflow{ballots,max_ballots}); }
+        Ok(())
+    }
+
+    /// Do the next action : first preferences, a surplus distribution, or an exclusion.
+    /// This may produce more than one count.
+    fn do_next_action(&mut self) -> Result<(),CountError> {
+        if let Some(failure) = self.failure.take() { return Err(failure); }
+        if !self.started {
+            self.check_input()?;
+            self.observer.count_started(&self.data.metadata);
+            self.distribute_first_preferences();
+            self.started=true;
+        } else if self.should_defer_surplus() {
+            self.exclude_lowest();
+        } else if let Some(candidate) = self.pop_next_pending_surplus() {
+            self.distribute_surplus(candidate);
+        } else {
+            self.exclude_lowest();
+        }
+        self.check_for_refused_draw(); // e.g. ties in rounding.
+        self.transcript.draws.extend(self.randomness.take_recorded_draws());
+        match self.failure.take() {
+            Some(failure) => Err(failure),
+            None => Ok(()),
+        }
+    }
+
+    /// Advance the distribution of preferences by one count, returning that count, or None if the distribution is finished.
+    ///
+    /// Calling this until it returns None produces the same transcript as [PreferenceDistributor::go].
+    ///
+    /// The state ([PreferenceDistributor::tally], [PreferenceDistributor::papers], [PreferenceDistributor::continuing_candidates_sorted_by_tally] etc.)
+    /// is only consistent with the returned count at the boundary between actions, which is when [PreferenceDistributor::is_between_actions] is true.
+    /// A single action such as a surplus distribution or exclusion may take several counts (e.g. one per transfer value).
+    /// The whole action is done when the first of its counts is returned, and the remaining counts are returned
+    /// on subsequent calls without further work, so in the middle of an action the state is that at the end of the action.
+    /// Use [PreferenceDistributor::step_action] to inspect the state between steps.
+    ///
+    /// If the count cannot be completed, an error is returned, and the distributor should not be used further.
+    pub fn step(&mut self) -> Result<Option<&SingleCount<Rules::Tally>>,CountError> {
+        while self.counts_returned_by_step==self.transcript.counts.len() {
+            if self.is_finished() {
+                self.check_finished_properly()?;
+                return Ok(None);
             }
+            self.do_next_action()?;
+        }
+        self.counts_returned_by_step+=1;
+        Ok(Some(&self.transcript.counts[self.counts_returned_by_step-1]))
+    }
+
+    /// Advance the distribution of preferences by one action (first preferences, a surplus distribution, or an exclusion),
+    /// returning the counts it took, or None if the distribution is finished.
+    ///
+    /// Afterwards the state ([PreferenceDistributor::tally], [PreferenceDistributor::papers] etc.) is that at the end of the last returned count.
+    /// If some counts of the current action have already been returned by [PreferenceDistributor::step], just the rest of them are returned.
+    ///
+    /// If the count cannot be completed, an error is returned, and the distributor should not be used further.
+    pub fn step_action(&mut self) -> Result<Option<&[SingleCount<Rules::Tally>]>,CountError> {
+        let first = self.counts_returned_by_step;
+        while first==self.transcript.counts.len() {
+            if self.is_finished() {
+                self.check_finished_properly()?;
+                return Ok(None);
+            }
+            self.do_next_action()?;
+        }
+        self.counts_returned_by_step=self.transcript.counts.len();
+        Ok(Some(&self.transcript.counts[first..]))
+    }
+
+    /// Check that everything asked for was used up by the time the count finished : the draws being replayed, and the counts to extract votes at.
+    fn check_finished_properly(&mut self) -> Result<(),CountError> {
+        self.randomness.check_all_replayed_draws_used()?;
+        let counts = self.transcript.counts.len();
+        if let Some(count) = self.extractors.iter().filter_map(|e|e.what_to_extract.count()).find(|c|c.0>=counts) {
+            return Err(CountError::ExtractionCountNotReached{count,counts});
+        }
+        Ok(())
+    }
+
+    /// Do the whole distribution of preferences.
+    pub fn go(&mut self) -> Result<(),CountError> {
+        while self.step()?.is_some() {}
+        Ok(())
+    }
+
+    /// The transcript so far.
+    pub fn transcript(&self) -> &Transcript<Rules::Tally> { &self.transcript }
+    /// Finish with this distributor, getting the transcript.
+    pub fn into_transcript(self) -> Transcript<Rules::Tally> { self.transcript }
+    /// The candidates still in the count, sorted lowest tally first.
+    pub fn continuing_candidates_sorted_by_tally(&self) -> &[CandidateIndex] { &self.continuing_candidates_sorted_by_tally }
+    /// The candidates elected so far, in order of election.
+    pub fn elected_candidates(&self) -> &[CandidateIndex] { &self.elected_candidates }
+    /// Elected candidates whose surplus has not yet been distributed.
+    pub fn pending_surplus_distributions(&self) -> &VecDeque<CandidateIndex> { &self.pending_surplus_distribution }
+    /// The ballot papers a candidate currently has.
+    pub fn papers(&self,candidate:CandidateIndex) -> &VotesWithMultipleTransferValues<'a,Rules::SplitByNumber,Rules::Tally> { &self.papers[candidate.0] }
+    /// True if the last count returned by [PreferenceDistributor::step] or [PreferenceDistributor::step_action] finished an action, so the state reflects the end of that count.
+    pub fn is_between_actions(&self) -> bool { self.counts_returned_by_step==self.transcript.counts.len() }
+
+    /// Save the current state, so that the distribution can be resumed later with [PreferenceDistributor::from_state].
+    /// This should only be done when [PreferenceDistributor::is_between_actions].
+    pub fn save_state(&self) -> DistributorState<'a,Rules> {
+        DistributorState{
+            num_candidates: self.num_candidates,
+            candidates_to_be_elected: self.candidates_to_be_elected,
+            quota_method: self.quota_method,
+            quota: self.quota.clone(),
+            tallys: self.tallys.clone(),
+            papers: self.papers.clone(),
+            continuing_candidates: self.continuing_candidates.clone(),
+            continuing_candidates_sorted_by_tally: self.continuing_candidates_sorted_by_tally.clone(),
+            exhausted: self.exhausted,
+            exhausted_atl: self.exhausted_atl,
+            tally_lost_to_rounding: self.tally_lost_to_rounding.clone(),
+            tally_exhausted: self.tally_exhausted.clone(),
+            tally_set_aside: self.tally_set_aside.clone(),
+            current_count: self.current_count,
+            current_major_count: self.current_major_count,
+            current_minor_count: self.current_minor_count,
+            pending_surplus_distribution: self.pending_surplus_distribution.clone(),
+            elected_candidates: self.elected_candidates.clone(),
+            candidate_elected_at_count: self.candidate_elected_at_count.clone(),
+            in_this_count: self.in_this_count.clone(),
+            transcript: self.transcript.clone(),
+            include_list_of_votes_in_transcript: self.include_list_of_votes_in_transcript,
+            exhausted_list_of_votes: self.exhausted_list_of_votes.clone(),
+            set_aside_list_of_votes: self.set_aside_list_of_votes.clone(),
+            last_written_list_of_votes_by_candidate: self.last_written_list_of_votes_by_candidate.clone(),
+            exhausted_votes: self.exhausted_votes.clone(),
+            set_aside_votes: self.set_aside_votes.clone(),
+            started: self.started,
+            counts_returned_by_step: self.counts_returned_by_step,
+            randomness: self.randomness.clone(),
         }
     }
+
+    /// Resume a distribution from a saved state. The other arguments are as for [PreferenceDistributor::new], and should be the same as
+    /// those used for the distribution whose state was saved, except that `original_votes` may differ provided the votes
+    /// in `state` have been made consistent with it. `randomness` is overwritten with the randomness as it was when the state was saved.
+    /// The number of vacancies, quota method and whether to include lists of votes in the transcript are taken from `state` rather than `options`.
+    pub fn from_state(rules : &'a Rules,data : &'a ElectionData,original_votes:&'a Vec<(TransferValue,Vec<PartiallyDistributedVote<'a>>)>,options:&CountOptions<'a>,observer:&'a mut dyn CountObserver<Rules::Tally>,randomness:&'a mut Randomness,state:DistributorState<'a,Rules>) -> Self {
+        *randomness = state.randomness;
+        PreferenceDistributor{
+            rules,
+            data,
+            ec_resolutions: options.ec_resolutions,
+            original_votes,
+            num_candidates: state.num_candidates,
+            candidates_to_be_elected: state.candidates_to_be_elected,
+            quota_method: state.quota_method,
+            quota: state.quota,
+            tallys: state.tallys,
+            papers: state.papers,
+            continuing_candidates: state.continuing_candidates,
+            continuing_candidates_sorted_by_tally: state.continuing_candidates_sorted_by_tally,
+            exhausted: state.exhausted,
+            exhausted_atl: state.exhausted_atl,
+            tally_lost_to_rounding: state.tally_lost_to_rounding,
+            tally_exhausted: state.tally_exhausted,
+            tally_set_aside: state.tally_set_aside,
+            current_count: state.current_count,
+            current_major_count: state.current_major_count,
+            current_minor_count: state.current_minor_count,
+            pending_surplus_distribution: state.pending_surplus_distribution,
+            elected_candidates: state.elected_candidates,
+            candidate_elected_at_count: state.candidate_elected_at_count,
+            in_this_count: state.in_this_count,
+            transcript: state.transcript,
+            include_list_of_votes_in_transcript: state.include_list_of_votes_in_transcript,
+            exhausted_list_of_votes: state.exhausted_list_of_votes,
+            set_aside_list_of_votes: state.set_aside_list_of_votes,
+            last_written_list_of_votes_by_candidate: state.last_written_list_of_votes_by_candidate,
+            keep_exhausted_and_set_aside_votes: needs_exhausted_and_set_aside_votes(options.extractors),
+            exhausted_votes: state.exhausted_votes,
+            set_aside_votes: state.set_aside_votes,
+            vote_trace: None,
+            started: state.started,
+            counts_returned_by_step: state.counts_returned_by_step,
+            randomness,
+            observer,
+            oracle: None,
+            extractors: options.extractors,
+            failure: check_extractor_candidates(options.extractors,state.num_candidates).err(),
+        }
+    }
+}
+
+/// Check that the candidates whose votes are to be extracted exist.
+fn check_extractor_candidates(extractors:&[ExtractionRequest],num_candidates:usize) -> Result<(),CountError> {
+    for e in extractors {
+        if let WhatToExtract::ACTVotesUsedToElectCandidate(candidate) | WhatToExtract::PileAtCount { candidate, .. } = &e.what_to_extract {
+            if candidate.0>=num_candidates { return Err(CountError::InvalidCandidate{candidate:*candidate,num_candidates}); }
+        }
+    }
+    Ok(())
+}
+
+/// Whether any of the extractors need the exhausted or set aside votes to be kept.
+fn needs_exhausted_and_set_aside_votes(extractors:&[ExtractionRequest]) -> bool {
+    extractors.iter().any(|e|matches!(e.what_to_extract,WhatToExtract::ExhaustedAtCount(_)|WhatToExtract::SetAsideAtCount(_)))
 }
 
-pub fn distribute_preferences_with_extractors<Rules:PreferenceDistributionRules>(data:&ElectionData,candidates_to_be_elected : NumberOfCandidates,excluded_candidates:&HashSet<CandidateIndex>,ec_resolutions:& TieResolutionsMadeByEC,vote_types : Option<&[String]>,print_progress_to_stdout:bool,randomness:&mut Randomness,extractors:&[ExtractionRequest],include_list_of_votes_in_transcript:bool) -> Transcript<Rules::Tally> {
+/// Like [distribute_preferences], but with the rules given as a value, and with the options for extractors, a different quota method etc. in `options`.
+pub fn distribute_preferences_with_extractors<Rules:PreferenceDistributionRules>(rules:&Rules,data:&ElectionData,options:&CountOptions,observer:&mut dyn CountObserver<Rules::Tally>,randomness:&mut Randomness) -> Result<Transcript<Rules::Tally>,CountError> {
     let arena = typed_arena::Arena::<CandidateIndex>::new();
-    let votes = data.resolve_atl_including_weights(&arena,vote_types);
-    let mut work : PreferenceDistributor<'_,Rules> = PreferenceDistributor::new(data,&votes,candidates_to_be_elected,excluded_candidates,ec_resolutions,print_progress_to_stdout,None,randomness,extractors,include_list_of_votes_in_transcript);
-    work.go();
-    work.transcript
+    let votes = data.resolve_atl_including_weights(&arena,options.vote_types);
+    let mut work : PreferenceDistributor<'_,Rules> = PreferenceDistributor::new(rules,data,&votes,options,observer,None,randomness);
+    work.go()?;
+    Ok(work.transcript)
 }
 
-pub fn distribute_preferences<Rules:PreferenceDistributionRules>(data:&ElectionData,candidates_to_be_elected : NumberOfCandidates,excluded_candidates:&HashSet<CandidateIndex>,ec_resolutions:& TieResolutionsMadeByEC,vote_types : Option<&[String]>,print_progress_to_stdout:bool,randomness:&mut Randomness) -> Transcript<Rules::Tally> {
-    distribute_preferences_with_extractors::<Rules>(data,candidates_to_be_elected,excluded_candidates,ec_resolutions,vote_types,print_progress_to_stdout,randomness,&[],false)
+/// Do a distribution of preferences, telling `observer` what happens as it goes.
+///
+/// Rules whose behaviour depends on a value, such as [crate::dynamic_rules::DynamicRules], should use [distribute_preferences_with_extractors] instead.
+pub fn distribute_preferences<Rules:PreferenceDistributionRules+Default>(data:&ElectionData,candidates_to_be_elected : NumberOfCandidates,excluded_candidates:&HashSet<CandidateIndex>,ec_resolutions:& TieResolutionsMadeByEC,vote_types : Option<&[String]>,observer:&mut impl CountObserver<Rules::Tally>,randomness:&mut Randomness) -> Result<Transcript<Rules::Tally>,CountError> {
+    let options = CountOptions{ vote_types, ..CountOptions::new(candidates_to_be_elected,excluded_candidates.clone(),ec_resolutions) };
+    distribute_preferences_with_extractors(&Rules::default(),data,&options,observer,randomness)
 }
\ No newline at end of file
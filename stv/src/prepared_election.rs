@@ -0,0 +1,193 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Efficiently recount the same election many times, possibly with small changes to the votes.
+//!
+//! [ElectionData::distribute_preferences] resolves every above the line vote into candidates
+//! each time it is called. Applications like margin searches or repeated counts with random tie
+//! resolution count the same data thousands of times. A [PreparedElection] does the resolution
+//! once, and can then be counted repeatedly, with a lightweight set of [VoteDeltas] applied each
+//! time instead of a modified copy of the whole [ElectionData].
+
+use std::collections::HashMap;
+use std::ops::Range;
+use crate::ballot_metadata::{CandidateIndex, ElectionMetadata};
+use crate::ballot_paper::{ATL, BTL, VoteSource};
+use crate::ballot_pile::PartiallyDistributedVote;
+use crate::count_error::CountError;
+use crate::count_observer::{CountObserver, IgnoreCountProgress};
+use crate::distribution_of_preferences_transcript::Transcript;
+use crate::election_data::{ElectionData, VoteTypeSpecification};
+use crate::preference_distribution::{CountOptions, PreferenceDistributionRules, PreferenceDistributor};
+use crate::quota::QuotaMethod;
+use crate::random_util::Randomness;
+use crate::transfer_value::TransferValue;
+
+/// Election data with the above the line votes resolved to candidates, ready to be counted many times.
+pub struct PreparedElection<'a> {
+    pub data : &'a ElectionData,
+    /// The candidates each entry in `data.atl` is equivalent to.
+    atl_preferences : Vec<Vec<CandidateIndex>>,
+    /// The distinct transfer values of the votes, highest first.
+    transfer_values : Vec<TransferValue>,
+    /// For each ATL then each BTL entry in data, the index into `transfer_values` of its weight, or None if it is not used (due to vote type restrictions).
+    weights : Vec<Option<usize>>,
+    /// The quota method to use instead of the one specified by the rules, if any.
+    pub quota_method : Option<QuotaMethod>,
+}
+
+/// Changes to the votes in a [PreparedElection], in the same form as applied to [ElectionData] by the margin computation.
+///
+/// Votes are removed from original entries, identified by their index in `data.atl` followed by `data.btl`,
+/// and new votes are added. Added votes have a transfer value of 1 and are not subject to any vote type restriction.
+#[derive(Default,Clone,Debug)]
+pub struct VoteDeltas {
+    /// The number of votes removed from the original vote with the given index (ATLs first, then BTLs).
+    removed : HashMap<usize,usize>,
+    added_atl : Vec<ATL>,
+    /// The candidates each entry in `added_atl` is equivalent to.
+    added_atl_preferences : Vec<Vec<CandidateIndex>>,
+    /// For each entry in `added_atl`, the index of the original vote it was changed from, if any.
+    added_atl_from : Vec<Option<usize>>,
+    added_btl : Vec<BTL>,
+    /// For each entry in `added_btl`, the index of the original vote it was changed from, if any.
+    added_btl_from : Vec<Option<usize>>,
+}
+
+impl VoteDeltas {
+    /// Remove n votes from the original vote with the given index, ATLs first, then BTLs.
+    pub fn remove(&mut self,index:usize,n:usize) {
+        *self.removed.entry(index).or_insert(0)+=n;
+    }
+    /// Add an ATL vote, resolving it to candidates.
+    pub fn add_atl(&mut self,atl:ATL,metadata:&ElectionMetadata) {
+        self.added_atl_preferences.push(atl.resolve_to_candidates(metadata));
+        self.added_atl.push(atl);
+        self.added_atl_from.push(None);
+    }
+    pub fn add_btl(&mut self,btl:BTL) {
+        self.added_btl.push(btl);
+        self.added_btl_from.push(None);
+    }
+    /// Change some of the votes in the original ATL vote with the given index into the given vote.
+    pub fn change_atl(&mut self,index:usize,atl:ATL,metadata:&ElectionMetadata) {
+        self.remove(index,atl.n);
+        self.add_atl(atl,metadata);
+        *self.added_atl_from.last_mut().unwrap()=Some(index);
+    }
+    /// Change some of the votes in the original vote with the given index (ATLs first, then BTLs) into the given BTL vote.
+    pub fn change_btl(&mut self,index:usize,btl:BTL) {
+        self.remove(index,btl.n);
+        self.add_btl(btl);
+        *self.added_btl_from.last_mut().unwrap()=Some(index);
+    }
+    pub fn is_empty(&self) -> bool { self.removed.is_empty() && self.added_atl.is_empty() && self.added_btl.is_empty() }
+    fn removed(&self,index:usize) -> usize { self.removed.get(&index).cloned().unwrap_or(0) }
+    /// The number of votes removed from each original vote, by index.
+    pub(crate) fn all_removed(&self) -> &HashMap<usize,usize> { &self.removed }
+    /// The added votes, with the index of the original vote each was changed from, if any.
+    pub(crate) fn added_votes(&self) -> Vec<(Option<usize>,PartiallyDistributedVote<'_>)> {
+        let atl = self.added_atl.iter().zip(self.added_atl_preferences.iter()).zip(self.added_atl_from.iter()).map(|((a,prefs),from)|(*from,PartiallyDistributedVote::new(a.n,prefs,VoteSource::Atl(a))));
+        let btl = self.added_btl.iter().zip(self.added_btl_from.iter()).map(|(b,from)|(*from,PartiallyDistributedVote::new(b.n,&b.candidates,VoteSource::Btl(b))));
+        atl.chain(btl).collect()
+    }
+}
+
+impl <'a> PreparedElection<'a> {
+    /// Resolve the votes in data. If vote_types is None, use all votes, otherwise only use vote types specified in it.
+    pub fn new(data:&'a ElectionData,vote_types : Option<&[String]>) -> Self {
+        let num_atl = data.atl.len();
+        let atl_preferences = data.atl.iter().map(|a|a.resolve_to_candidates(&data.metadata)).collect();
+        let mut transfer_values = vec![];
+        let mut weights : Vec<Option<usize>> = vec![None;num_atl+data.btl.len()];
+        let mut add_votes = |tv:TransferValue,range_with_tv:Range<usize>,allowed:&[Range<usize>],offset:usize| {
+            let tv_index = transfer_values.iter().position(|t|*t==tv).unwrap_or_else(||{transfer_values.push(tv); transfer_values.len()-1});
+            for range in allowed {
+                for i in range.start.max(range_with_tv.start)..range.end.min(range_with_tv.end) { weights[offset+i]=Some(tv_index); }
+            }
+        };
+        let allowed_atl = VoteTypeSpecification::restrict(vote_types,&data.atl_types,num_atl);
+        if data.atl_transfer_values.is_empty() { add_votes(TransferValue::one(),0..num_atl,&allowed_atl,0) }
+        else { for v in &data.atl_transfer_values { add_votes(v.value.clone(),v.range(),&allowed_atl,0) } }
+        let allowed_btl = VoteTypeSpecification::restrict(vote_types,&data.btl_types,data.btl.len());
+        if data.btl_transfer_values.is_empty() { add_votes(TransferValue::one(),0..data.btl.len(),&allowed_btl,num_atl) }
+        else { for v in &data.btl_transfer_values { add_votes(v.value.clone(),v.range(),&allowed_btl,num_atl) } }
+        // sort transfer values descending, keeping weights consistent.
+        let mut order : Vec<usize> = (0..transfer_values.len()).collect();
+        order.sort_by(|&i1,&i2|transfer_values[i2].cmp(&transfer_values[i1]));
+        let mut new_position = vec![0;order.len()];
+        for (position,&old) in order.iter().enumerate() { new_position[old]=position; }
+        for w in weights.iter_mut().flatten() { *w=new_position[*w]; }
+        let transfer_values = order.into_iter().map(|i|transfer_values[i].clone()).collect();
+        PreparedElection{ data, atl_preferences, transfer_values, weights, quota_method: None }
+    }
+
+    /// Use the given quota method, if any, instead of the one specified by the rules when counting.
+    pub fn with_quota_method(self,quota_method:Option<QuotaMethod>) -> Self {
+        PreparedElection{ quota_method, ..self }
+    }
+
+    /// Make a distributor for the given votes, using the vacancies, excluded candidates and EC tie resolutions in the metadata.
+    pub(crate) fn distributor<'b,Rules:PreferenceDistributionRules>(&'b self,rules:&'b Rules,votes:&'b Vec<(TransferValue,Vec<PartiallyDistributedVote<'b>>)>,observer:&'b mut dyn CountObserver<Rules::Tally>,randomness:&'b mut Randomness) -> Result<PreferenceDistributor<'b,Rules>,CountError> {
+        let options = CountOptions{ quota_method: self.quota_method, ..CountOptions::from_metadata(self.data)? };
+        Ok(PreferenceDistributor::new(rules,self.data,votes,&options,observer,None,randomness))
+    }
+
+    /// The original vote with the given index (ATLs first, then BTLs), with no deltas applied, if it is used and has a transfer value of 1.
+    pub(crate) fn original_vote_with_transfer_value_one(&self,index:usize) -> Option<PartiallyDistributedVote<'_>> {
+        if !self.weights[index].map(|w|self.transfer_values[w].is_one()).unwrap_or(false) { return None; }
+        let num_atl = self.data.atl.len();
+        Some(if index<num_atl {
+            let a = &self.data.atl[index];
+            PartiallyDistributedVote::new(a.n,&self.atl_preferences[index],VoteSource::Atl(a))
+        } else {
+            let b = &self.data.btl[index-num_atl];
+            PartiallyDistributedVote::new(b.n,&b.candidates,VoteSource::Btl(b))
+        })
+    }
+
+    /// Get a list of all votes, grouped by transfer value in descending order, with the deltas applied.
+    /// Votes are in the same order as [crate::election_data::ElectionData::resolve_atl_including_weights] would produce, with added votes
+    /// following the original ATL or BTL votes.
+    pub fn votes<'b>(&'b self,deltas:&'b VoteDeltas) -> Vec<(TransferValue,Vec<PartiallyDistributedVote<'b>>)> {
+        let mut res : Vec<(TransferValue,Vec<PartiallyDistributedVote<'b>>)> = self.transfer_values.iter().map(|tv|(tv.clone(),vec![])).collect();
+        let one_index = if deltas.added_atl.is_empty() && deltas.added_btl.is_empty() { 0 } else {
+            match self.transfer_values.iter().position(|tv|tv.is_one()) {
+                Some(index) => index,
+                None => { res.insert(0,(TransferValue::one(),vec![])); 0 } // all other transfer values are less than one.
+            }
+        };
+        let offset = res.len()-self.transfer_values.len();
+        let num_atl = self.data.atl.len();
+        for (i,a) in self.data.atl.iter().enumerate() {
+            if let Some(w) = self.weights[i] {
+                res[w+offset].1.push(PartiallyDistributedVote::new(a.n-deltas.removed(i),&self.atl_preferences[i],VoteSource::Atl(a)));
+            }
+        }
+        for (a,prefs) in deltas.added_atl.iter().zip(deltas.added_atl_preferences.iter()) {
+            res[one_index].1.push(PartiallyDistributedVote::new(a.n,prefs,VoteSource::Atl(a)));
+        }
+        for (i,b) in self.data.btl.iter().enumerate() {
+            if let Some(w) = self.weights[num_atl+i] {
+                res[w+offset].1.push(PartiallyDistributedVote::new(b.n-deltas.removed(num_atl+i),&b.candidates,VoteSource::Btl(b)));
+            }
+        }
+        for b in &deltas.added_btl {
+            res[one_index].1.push(PartiallyDistributedVote::new(b.n,&b.candidates,VoteSource::Btl(b)));
+        }
+        res
+    }
+
+    /// Distribute preferences with the given changes to the votes, using the vacancies, excluded candidates and EC tie resolutions in the metadata.
+    /// This is the equivalent of [ElectionData::distribute_preferences] applied to modified data.
+    pub fn distribute_preferences<Rules:PreferenceDistributionRules>(&self,rules:&Rules,deltas:&VoteDeltas,randomness:&mut Randomness) -> Result<Transcript<Rules::Tally>,CountError> {
+        let votes = self.votes(deltas);
+        let mut observer = IgnoreCountProgress{};
+        let mut work = self.distributor(rules,&votes,&mut observer,randomness)?;
+        work.go()?;
+        Ok(work.into_transcript())
+    }
+}
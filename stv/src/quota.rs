@@ -0,0 +1,114 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Different formulae for computing the quota.
+//!
+//! Australian legislation almost universally uses the [QuotaMethod::Droop] quota, but for comparative
+//! research it is useful to be able to recount an election with some other quota. A set of rules
+//! specifies its quota by [PreferenceDistributionRules::quota_method], and this can be overridden
+//! for a particular count by [crate::preference_distribution::CountOptions::quota_method].
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use num::{BigInt, BigRational, One, Zero};
+use serde::{Deserialize, Serialize};
+use crate::ballot_metadata::NumberOfCandidates;
+use crate::ballot_pile::BallotPaperCount;
+use crate::count_error::CountError;
+use crate::preference_distribution::PreferenceDistributionRules;
+
+/// How the quota is computed from the number of papers P and the number of vacancies V.
+///
+/// A candidate whose tally is greater than or equal to the quota is elected. For quotas defined
+/// as a fraction, the quota is rounded up to the precision of the tally, so this is equivalent
+/// to reaching the fraction. Some quotas require the fraction to be exceeded rather than reached,
+/// in which case the quota is the smallest tally greater than the fraction.
+///
+/// Note that the [QuotaMethod::Hare] and [QuotaMethod::Imperiali] quotas may be reached by more candidates
+/// than there are vacancies, and the [QuotaMethod::Hare] quota is not defined if there are no vacancies.
+#[derive(Copy,Clone,Serialize,Deserialize,Debug,Eq,PartialEq,Default)]
+pub enum QuotaMethod {
+    /// ⌊P/(V+1)⌋+1. Used by all Australian jurisdictions.
+    #[default]
+    Droop,
+    /// P/(V+1), which must be exceeded. Sometimes called the fractional Droop quota.
+    /// For integer tallies this is the same as [QuotaMethod::Droop].
+    ExactDroop,
+    /// P/(V+1), which must be reached.
+    HagenbachBischoff,
+    /// P/V, which must be reached.
+    Hare,
+    /// P/(V+2), which must be reached.
+    Imperiali,
+}
+
+impl QuotaMethod {
+    /// Check that the quota can be computed for the given number of vacancies.
+    pub fn check_defined(&self,vacancies:NumberOfCandidates) -> Result<(),CountError> {
+        if *self==QuotaMethod::Hare && vacancies.0==0 { Err(CountError::QuotaUndefined{ method: *self, vacancies }) } else { Ok(()) }
+    }
+
+    /// The quota as an exact fraction, before rounding to the precision of a tally.
+    pub fn exact(&self,papers:BallotPaperCount,vacancies:NumberOfCandidates) -> Result<BigRational,CountError> {
+        self.check_defined(vacancies)?;
+        let p = BigInt::from(papers.0);
+        let v = BigInt::from(vacancies.0);
+        Ok(match self {
+            QuotaMethod::Droop => BigRational::from_integer(p/(v+1)+1),
+            QuotaMethod::ExactDroop | QuotaMethod::HagenbachBischoff => BigRational::new(p,v+1),
+            QuotaMethod::Hare => BigRational::new(p,v),
+            QuotaMethod::Imperiali => BigRational::new(p,v+2),
+        })
+    }
+
+    /// Whether a candidate's tally must be strictly greater than [QuotaMethod::exact] to be elected.
+    pub fn must_be_exceeded(&self) -> bool { matches!(self,QuotaMethod::ExactDroop) }
+
+    /// The smallest tally that gets a candidate elected.
+    pub fn compute<Rules:PreferenceDistributionRules>(&self,papers:BallotPaperCount,vacancies:NumberOfCandidates) -> Result<Rules::Tally,CountError> {
+        if *self==QuotaMethod::Droop { // the common case, an integer, so no rounding is needed.
+            return Ok(Rules::Tally::from(BallotPaperCount(papers.0/(1+vacancies.0)+1)));
+        }
+        let exact = self.exact(papers,vacancies)?;
+        let rounded_down = Rules::convert_rational_to_tally_after_applying_transfer_value(exact.clone());
+        Ok(if !self.must_be_exceeded() && Rules::convert_tally_to_rational(rounded_down.clone())==exact { rounded_down }
+        else { rounded_down+smallest_positive_tally::<Rules>() })
+    }
+}
+
+/// The smallest positive value a tally can take, assuming it is a decimal with no more than 18 digits after the decimal point.
+fn smallest_positive_tally<Rules:PreferenceDistributionRules>() -> Rules::Tally {
+    let mut res = Rules::Tally::from(BallotPaperCount(1));
+    let mut unit = BigRational::one();
+    for _ in 0..18 {
+        unit/=BigInt::from(10);
+        let smaller = Rules::convert_rational_to_tally_after_applying_transfer_value(unit.clone());
+        if smaller.is_zero() { break; }
+        res = smaller;
+    }
+    res
+}
+
+impl Display for QuotaMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{:?}",self)
+    }
+}
+
+impl FromStr for QuotaMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Droop" => Ok(QuotaMethod::Droop),
+            "ExactDroop" => Ok(QuotaMethod::ExactDroop),
+            "HagenbachBischoff" => Ok(QuotaMethod::HagenbachBischoff),
+            "Hare" => Ok(QuotaMethod::Hare),
+            "Imperiali" => Ok(QuotaMethod::Imperiali),
+            _ => Err("No such quota method. Options are Droop, ExactDroop, HagenbachBischoff, Hare and Imperiali".to_string()),
+        }
+    }
+}
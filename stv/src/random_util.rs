@@ -10,7 +10,13 @@
 
 use rand::distributions::{Distribution, Uniform};
 use rand::prelude::SliceRandom;
+use serde::{Serialize,Deserialize};
 use crate::ballot_metadata::CandidateIndex;
+use crate::count_error::CountError;
+use crate::distribution_of_preferences_transcript::CountIndex;
+use crate::tie_enumeration::DrawBranches;
+use crate::tie_resolution::{TieResolutionGranularityNeeded, TieResolutionUsage};
+use crate::verifiable_draw::{PublicSeed, VerifiableDraws};
 
 
 /// There is need of randomness in a variety of situations in STV counting
@@ -18,44 +24,221 @@ use crate::ballot_metadata::CandidateIndex;
 /// * For the NSW randomized algorithms, for selecting which votes are the surplus.
 ///
 /// This defines how that randomness is generated.
+#[derive(Clone)]
 pub enum Randomness {
     /// Resolve ties by favouring candidates who are lower down on the ballot. This seems to have been done by the AEC some years, but may have been coincidence.
     /// Resolve the NSW random selection of excess by choosing them chronologically from the start.
     ReverseDonkeyVote,
     /// Use a pseudo random number generator.
-    PRNG(rand_chacha::ChaCha20Rng)
+    PRNG(rand_chacha::ChaCha20Rng),
+    /// Do not allow ties to be resolved by a draw. The first tie that needs one is recorded here and the count stops
+    /// with [crate::count_error::CountError::UnresolvableTie]. Useful when a count should only use the rules and the EC's decisions.
+    /// The NSW random selection of votes is not a tie, and is done as for ReverseDonkeyVote.
+    NoDraws(Option<Vec<CandidateIndex>>),
+    /// Try a particular result for each draw, recording the draws made. Used to try every possible result, see [crate::tie_enumeration].
+    /// The NSW random selection of votes is done as for ReverseDonkeyVote.
+    Enumerate(DrawBranches),
+    /// Make draws using the contained randomness, and record each one so it can be put in the transcript and later replayed.
+    Record(Box<Randomness>,Vec<RandomDraw>),
+    /// Make each draw as recorded in a prior count, checking that it is the same draw. See [ReplayDraws].
+    Replay(ReplayDraws),
+    /// Draw lots using a seed derived from a publicly committed file, so anyone can check the draws. See [crate::verifiable_draw].
+    /// The draws are recorded in the transcript. The NSW random selection of votes is done as for ReverseDonkeyVote.
+    Verifiable(VerifiableDraws),
+}
+
+/// A draw made by [Randomness], as recorded in the transcript by [Randomness::Record].
+#[derive(Clone,Debug,Serialize,Deserialize,PartialEq,Eq)]
+pub enum RandomDraw {
+    /// Lots drawn to resolve a tie. `tied` is the tied candidates in ballot paper order, `order` is the result of the draw, least favoured first.
+    Tie{ count : CountIndex, usage : TieResolutionUsage, tied : Vec<CandidateIndex>, order : Vec<CandidateIndex> },
+    /// Ballots selected at random from a pile of `population` ballots, as in the NSW random surplus distribution. The ballots are
+    /// numbered from 0 in the order they are in the pile, and `chosen` are the numbers of the selected ballots, ascending.
+    Ballots{ count : CountIndex, candidate : CandidateIndex, population : usize, chosen : Vec<usize> },
+}
+
+/// Draws read from a transcript (produced by [Randomness::Record]), to be replayed in order.
+///
+/// Only the recorded results are used, not a PRNG, so a published randomised count can be reproduced exactly
+/// even by a different version of ConcreteSTV. Each draw needed by the count is checked against the next recorded draw;
+/// if they differ (e.g. different votes or rules) the count stops with [CountError::ReplayedDrawMismatch].
+#[derive(Clone,Debug,Default)]
+pub struct ReplayDraws {
+    draws : Vec<RandomDraw>,
+    /// The number of draws used so far.
+    used : usize,
+    /// The replayed draws not yet put in the transcript.
+    replayed : Vec<RandomDraw>,
+    /// The first draw that did not match, if any.
+    mismatch : Option<CountError>,
+}
+
+impl ReplayDraws {
+    pub fn new(draws:Vec<RandomDraw>) -> Self { ReplayDraws{ draws, ..Default::default() } }
+
+    /// Get the next recorded draw, if it is the same draw as `needed` (apart from the result), otherwise note the mismatch.
+    fn next(&mut self,needed:&RandomDraw) -> Option<RandomDraw> {
+        let recorded = self.draws.get(self.used).filter(|recorded|recorded.same_draw(needed)).cloned();
+        match &recorded {
+            Some(recorded) => { self.used+=1; self.replayed.push(recorded.clone()); }
+            None => { self.mismatch.get_or_insert(CountError::ReplayedDrawMismatch{ draw: self.used, count: needed.count() }); }
+        }
+        recorded
+    }
+}
+
+/// The parts of a transcript describing the random draws made.
+#[derive(Deserialize,Debug,Clone,Default)]
+pub struct DrawsInTranscript {
+    #[serde(default)]
+    pub draw_seed : Option<PublicSeed>,
+    #[serde(default)]
+    pub draws : Vec<RandomDraw>,
+}
+
+impl DrawsInTranscript {
+    /// Read from a transcript file written by ConcreteSTV (with or without metadata).
+    /// Nothing else in the transcript is read, so this works with transcripts from other versions of ConcreteSTV.
+    pub fn read(reader:impl std::io::Read) -> serde_json::Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum TranscriptFile { WithMetadata{ transcript : DrawsInTranscript }, Bare(DrawsInTranscript) }
+        Ok(match serde_json::from_reader(reader)? {
+            TranscriptFile::WithMetadata { transcript } => transcript,
+            TranscriptFile::Bare(transcript) => transcript,
+        })
+    }
+}
+
+/// Read the draws recorded in a transcript file written by ConcreteSTV (with or without metadata), to replay with [ReplayDraws].
+pub fn read_draws_from_transcript(reader:impl std::io::Read) -> serde_json::Result<Vec<RandomDraw>> {
+    Ok(DrawsInTranscript::read(reader)?.draws)
+}
+
+impl RandomDraw {
+    /// The count on which the draw was made.
+    pub fn count(&self) -> CountIndex {
+        match self {
+            RandomDraw::Tie { count, .. } => *count,
+            RandomDraw::Ballots { count, .. } => *count,
+        }
+    }
+
+    /// Whether other is the same draw as self with a valid result, that is, the same count and population.
+    fn same_draw(&self,other:&RandomDraw) -> bool {
+        match (self,other) {
+            (RandomDraw::Tie { count, usage, tied, order }, RandomDraw::Tie { count: count2, usage: usage2, tied: tied2, .. }) => {
+                let mut sorted_order = order.clone();
+                sorted_order.sort_by_key(|c|c.0);
+                count==count2 && usage==usage2 && tied==tied2 && sorted_order==*tied
+            }
+            (RandomDraw::Ballots { count, candidate, population, chosen }, RandomDraw::Ballots { count: count2, candidate: candidate2, population: population2, chosen: chosen2 }) => {
+                count==count2 && candidate==candidate2 && population==population2 && chosen.len()==chosen2.len() && chosen.windows(2).all(|w|w[0]<w[1]) && chosen.last().map(|&last|last<*population).unwrap_or(true)
+            }
+            _ => false,
+        }
+    }
 }
 
 
 impl Randomness {
-    /// If all else fails, resolve draws randomly.
-    pub fn resolve(&mut self,tied_candidates: &mut [CandidateIndex]) {
+    /// If all else fails, resolve draws randomly. The granularity says which results of the draw actually matter.
+    /// The usage and count say what the draw is for, for recording.
+    pub fn resolve(&mut self,tied_candidates: &mut [CandidateIndex],granularity:TieResolutionGranularityNeeded,usage:TieResolutionUsage,count:CountIndex) {
         // Sort by reverse donkey vote. Necessary even if using random sorting so that the same PRNG seed produces the same results (the order may be different because Hash table ordering is not guaranteed to be repeatable).
         tied_candidates.sort_by_key(|c|c.0);
         match self {
             Randomness::ReverseDonkeyVote => {}
             Randomness::PRNG(prng) => { tied_candidates.shuffle(prng); }
+            Randomness::NoDraws(refused) => { if refused.is_none() { *refused=Some(tied_candidates.to_vec()); } }
+            Randomness::Enumerate(branches) => { branches.draw(tied_candidates,granularity); }
+            Randomness::Record(randomness,draws) => {
+                let tied = tied_candidates.to_vec();
+                randomness.resolve(tied_candidates,granularity,usage,count);
+                draws.push(RandomDraw::Tie{ count, usage, tied, order: tied_candidates.to_vec() });
+            }
+            Randomness::Replay(replay) => {
+                if let Some(RandomDraw::Tie{ order, .. }) = replay.next(&RandomDraw::Tie{ count, usage, tied: tied_candidates.to_vec(), order: vec![] }) {
+                    tied_candidates.copy_from_slice(&order);
+                }
+            }
+            Randomness::Verifiable(draws) => { draws.draw(tied_candidates,usage,count); }
+        }
+    }
+
+    /// If a draw was needed but not allowed, get the candidates involved, and reset so the next can be detected.
+    pub fn take_refused_draw(&mut self) -> Option<Vec<CandidateIndex>> {
+        match self {
+            Randomness::NoDraws(refused) => refused.take(),
+            Randomness::Record(randomness,_) => randomness.take_refused_draw(),
+            _ => None,
+        }
+    }
+
+    /// If a draw was needed on the given count but not allowed, or did not match the replayed draw, get the error.
+    pub fn take_draw_failure(&mut self,count:CountIndex) -> Option<CountError> {
+        match self {
+            Randomness::Replay(replay) => replay.mismatch.take(),
+            Randomness::Enumerate(branches) => branches.too_many_branches.take(),
+            _ => self.take_refused_draw().map(|candidates|CountError::UnresolvableTie{candidates,count}),
+        }
+    }
+
+    /// Get the draws recorded (or replayed) since this was last called, to put in the transcript.
+    pub fn take_recorded_draws(&mut self) -> Vec<RandomDraw> {
+        match self {
+            Randomness::Record(randomness,draws) => {
+                randomness.take_recorded_draws(); // the contained randomness may record the same draws itself, e.g. Verifiable.
+                std::mem::take(draws)
+            },
+            Randomness::Replay(replay) => std::mem::take(&mut replay.replayed),
+            Randomness::Verifiable(draws) => draws.take_recorded_draws(),
+            _ => vec![],
+        }
+    }
+
+    /// The public seed used for draws, if any, to put in the transcript.
+    pub fn public_seed(&self) -> Option<&PublicSeed> {
+        match self {
+            Randomness::Verifiable(draws) => Some(draws.seed()),
+            Randomness::Record(randomness,_) => randomness.public_seed(),
+            _ => None,
+        }
+    }
+
+    /// At the end of a count, check that all the draws being replayed were used.
+    pub fn check_all_replayed_draws_used(&self) -> Result<(),CountError> {
+        match self {
+            Randomness::Replay(replay) if replay.used<replay.draws.len() => Err(CountError::UnusedReplayedDraws{ used: replay.used, recorded: replay.draws.len() }),
+            _ => Ok(()),
         }
     }
 
     /// Make a boolean array of length len such that num_true of them are true.
     /// If the randomness is ReverseDonkeyVote, take the first n.
+    /// The count and candidate say what the draw is for, for recording.
     /// ```
     /// use rand::SeedableRng;
-    /// let mut prng = stv::random_util::Randomness::PRNG(rand_chacha::ChaCha20Rng::seed_from_u64(1));
-    /// let a4_10 = prng.make_array_with_some_randomly_true(10,4);
+    /// use stv::ballot_metadata::CandidateIndex;
+    /// use stv::distribution_of_preferences_transcript::CountIndex;
+    /// use stv::random_util::{RandomDraw, Randomness};
+    /// let mut prng = Randomness::PRNG(rand_chacha::ChaCha20Rng::seed_from_u64(1));
+    /// let a4_10 = prng.make_array_with_some_randomly_true(10,4,CountIndex(0),CandidateIndex(0));
     /// assert_eq!(10,a4_10.len());
     /// assert_eq!(4,a4_10.iter().filter(|v|**v).count());
-    /// let a7_10 = prng.make_array_with_some_randomly_true(10,7);
+    /// let a7_10 = prng.make_array_with_some_randomly_true(10,7,CountIndex(0),CandidateIndex(0));
     /// assert_eq!(10,a7_10.len());
     /// assert_eq!(7,a7_10.iter().filter(|v|**v).count());
-    /// let mut donkey = stv::random_util::Randomness::ReverseDonkeyVote;
-    /// let a4_6 = donkey.make_array_with_some_randomly_true(6,4);
+    /// let mut donkey = Randomness::ReverseDonkeyVote;
+    /// let a4_6 = donkey.make_array_with_some_randomly_true(6,4,CountIndex(0),CandidateIndex(0));
     /// assert_eq!(vec![true,true,true,true,false,false],a4_6);
+    /// let mut recorder = Randomness::Record(Box::new(Randomness::ReverseDonkeyVote),vec![]);
+    /// assert_eq!(a4_6,recorder.make_array_with_some_randomly_true(6,4,CountIndex(3),CandidateIndex(2)));
+    /// assert_eq!(vec![RandomDraw::Ballots{ count: CountIndex(3), candidate: CandidateIndex(2), population: 6, chosen: vec![0,1,2,3] }],recorder.take_recorded_draws());
     /// ```
-    pub fn make_array_with_some_randomly_true(&mut self,len:usize,num_true:usize) -> Vec<bool> {
+    pub fn make_array_with_some_randomly_true(&mut self,len:usize,num_true:usize,count:CountIndex,candidate:CandidateIndex) -> Vec<bool> {
         match self {
-            Randomness::ReverseDonkeyVote => {
+            Randomness::ReverseDonkeyVote | Randomness::NoDraws(_) | Randomness::Enumerate(_) | Randomness::Verifiable(_) => {
                 let mut res = vec![false;len];
                 for i in 0..num_true { res[i]=true; }
                 res
@@ -71,6 +254,19 @@ impl Randomness {
                 }
                 res
             }
+            Randomness::Record(randomness,draws) => {
+                let res = randomness.make_array_with_some_randomly_true(len,num_true,count,candidate);
+                draws.push(RandomDraw::Ballots{ count, candidate, population: len, chosen: (0..len).filter(|&i|res[i]).collect() });
+                res
+            }
+            Randomness::Replay(replay) => {
+                let mut res = vec![false;len];
+                match replay.next(&RandomDraw::Ballots{ count, candidate, population: len, chosen: vec![0;num_true] }) {
+                    Some(RandomDraw::Ballots{ chosen, .. }) => { for i in chosen { res[i]=true; } }
+                    _ => { res[..num_true].fill(true); } // keep going with something valid; the count will stop with an error.
+                }
+                res
+            }
         }
     }
 
@@ -0,0 +1,191 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A "rule card" is a structured record of every answer a [PreferenceDistributionRules] gives,
+//! making it easy to see how two sets of rules differ without reading the code.
+//!
+//! Most answers are simple values. Answers that are functions (e.g. how a transfer value is computed,
+//! or how exhausted votes are rounded) are described by what they give for some small example inputs,
+//! chosen so that common variations in rounding and limiting give different answers.
+
+use std::fmt::{Display, Formatter};
+use num::{BigInt, BigRational};
+use serde::{Deserialize, Serialize};
+use crate::ballot_pile::BallotPaperCount;
+use crate::preference_distribution::{CountNamingMethod, DeferSurplusDistribution, LastParcelUse, PreferenceDistributionRules, SurplusTransferMethod, TransferValueMethod, WhenToDoElectCandidateClauseChecking};
+use crate::quota::QuotaMethod;
+use crate::tie_resolution::MethodOfTieResolution;
+use crate::transfer_value::TransferValue;
+
+/// The answers given by a [PreferenceDistributionRules]. See [PreferenceDistributionRules] for the meaning of most fields.
+#[derive(Clone,Serialize,Deserialize,Debug)]
+pub struct RuleCard {
+    pub name : String,
+    /// The type used for tallies, e.g. `usize` or `FixedPrecisionDecimal<6>`.
+    pub tally_type : String,
+    /// The type used to split votes by count number, e.g. `DoNotSplitByCountNumber`.
+    pub split_by_number : String,
+    pub has_quota : bool,
+    pub quota_method : QuotaMethod,
+    pub should_exhausted_votes_count_for_quota_computation : bool,
+    pub use_last_parcel_for_surplus_distribution : LastParcelUse,
+    pub transfer_value_method : TransferValueMethod,
+    pub surplus_distribution_subdivisions : SurplusTransferMethod,
+    pub sort_exclusions_by_transfer_value : bool,
+    pub resolve_ties_elected_one_of_last_two : MethodOfTieResolution,
+    pub resolve_ties_elected_by_quota : MethodOfTieResolution,
+    pub resolve_ties_elected_all_remaining : MethodOfTieResolution,
+    pub resolve_ties_choose_lowest_candidate_for_exclusion : MethodOfTieResolution,
+    pub check_elected_if_in_middle_of_surplus_distribution : bool,
+    pub check_elected_if_in_middle_of_exclusion : bool,
+    pub finish_all_counts_in_elimination_when_all_elected : bool,
+    pub finish_all_surplus_distributions_when_all_elected : bool,
+    pub when_to_check_if_just_two_standing_for_shortcut_election : WhenToDoElectCandidateClauseChecking,
+    pub when_to_check_if_all_remaining_should_get_elected : WhenToDoElectCandidateClauseChecking,
+    pub when_to_check_if_top_few_have_overwhelming_votes : WhenToDoElectCandidateClauseChecking,
+    pub when_checking_if_top_few_have_overwhelming_votes_require_exactly_one : bool,
+    pub when_should_surplus_distribution_be_deferred : DeferSurplusDistribution,
+    pub should_eliminate_multiple_candidates_federal_rule_13a : bool,
+    pub should_eliminate_multiple_candidates_below_next_highest : bool,
+    pub count_set_aside_due_to_transfer_value_limit_as_rounding : bool,
+    pub how_to_name_counts : CountNamingMethod,
+    pub major_count_if_someone_elected : bool,
+    pub use_f32_arithmetic_when_applying_transfer_values_instead_of_exact : bool,
+    pub prohibit_negative_surplus_fraction : bool,
+    pub do_all_transfer_values_in_one_count : bool,
+    pub distribute_largest_surplus_first : bool,
+    /// Whether [PreferenceDistributionRules::sort_subcounts_by_count] gives a custom order.
+    pub custom_sort_of_subcounts : bool,
+    /// [PreferenceDistributionRules::make_transfer_value] for a surplus of 2 over 3 ballots. Shows rounding.
+    pub transfer_value_for_surplus_2_over_3_ballots : String,
+    /// [PreferenceDistributionRules::make_transfer_value] for a surplus of 4 over 3 ballots. Shows whether transfer values are limited to 1.
+    pub transfer_value_for_surplus_4_over_3_ballots : String,
+    /// [PreferenceDistributionRules::use_transfer_value] for 10 ballots with a transfer value of 2/3.
+    pub votes_for_10_ballots_at_transfer_value_2_3 : String,
+    /// [PreferenceDistributionRules::munge_scaled_transfer_value] for 2/3.
+    pub scaled_transfer_value_2_3 : String,
+    /// [PreferenceDistributionRules::munge_transfer_value_when_used_as_limit] for 2/3.
+    pub transfer_value_2_3_used_as_limit : String,
+    /// [PreferenceDistributionRules::munge_exhausted_votes] for the votes in `votes_for_10_ballots_at_transfer_value_2_3` exhausted in an exclusion.
+    pub exhausted_votes_in_exclusion : String,
+    /// [PreferenceDistributionRules::munge_exhausted_votes] for the votes in `votes_for_10_ballots_at_transfer_value_2_3` exhausted in a surplus distribution.
+    pub exhausted_votes_in_surplus_distribution : String,
+}
+
+/// Remove module paths from a type name, e.g. `stv::fixed_precision_decimal::FixedPrecisionDecimal<6>` becomes `FixedPrecisionDecimal<6>`.
+fn short_type_name<T:?Sized>() -> String {
+    let mut res = String::new();
+    let mut token = String::new();
+    for c in std::any::type_name::<T>().chars() {
+        if c.is_alphanumeric() || c=='_' { token.push(c); }
+        else if c==':' { token.clear(); }
+        else { res.push_str(&token); token.clear(); res.push(c); }
+    }
+    res.push_str(&token);
+    res
+}
+
+impl RuleCard {
+    /// Make the rule card for a given set of rules.
+    pub fn new<Rules:PreferenceDistributionRules>(rules:&Rules) -> RuleCard {
+        let rational = |n:usize,d:usize|BigRational::new(BigInt::from(n),BigInt::from(d));
+        let two_thirds = TransferValue(rational(2,3));
+        let votes = rules.use_transfer_value(&two_thirds,BallotPaperCount(10));
+        RuleCard {
+            name: rules.name(),
+            tally_type: short_type_name::<Rules::Tally>(),
+            split_by_number: short_type_name::<Rules::SplitByNumber>(),
+            has_quota: rules.has_quota(),
+            quota_method: rules.quota_method(),
+            should_exhausted_votes_count_for_quota_computation: rules.should_exhausted_votes_count_for_quota_computation(),
+            use_last_parcel_for_surplus_distribution: rules.use_last_parcel_for_surplus_distribution(),
+            transfer_value_method: rules.transfer_value_method(),
+            surplus_distribution_subdivisions: rules.surplus_distribution_subdivisions(),
+            sort_exclusions_by_transfer_value: rules.sort_exclusions_by_transfer_value(),
+            resolve_ties_elected_one_of_last_two: rules.resolve_ties_elected_one_of_last_two(),
+            resolve_ties_elected_by_quota: rules.resolve_ties_elected_by_quota(),
+            resolve_ties_elected_all_remaining: rules.resolve_ties_elected_all_remaining(),
+            resolve_ties_choose_lowest_candidate_for_exclusion: rules.resolve_ties_choose_lowest_candidate_for_exclusion(),
+            check_elected_if_in_middle_of_surplus_distribution: rules.check_elected_if_in_middle_of_surplus_distribution(),
+            check_elected_if_in_middle_of_exclusion: rules.check_elected_if_in_middle_of_exclusion(),
+            finish_all_counts_in_elimination_when_all_elected: rules.finish_all_counts_in_elimination_when_all_elected(),
+            finish_all_surplus_distributions_when_all_elected: rules.finish_all_surplus_distributions_when_all_elected(),
+            when_to_check_if_just_two_standing_for_shortcut_election: rules.when_to_check_if_just_two_standing_for_shortcut_election(),
+            when_to_check_if_all_remaining_should_get_elected: rules.when_to_check_if_all_remaining_should_get_elected(),
+            when_to_check_if_top_few_have_overwhelming_votes: rules.when_to_check_if_top_few_have_overwhelming_votes(),
+            when_checking_if_top_few_have_overwhelming_votes_require_exactly_one: rules.when_checking_if_top_few_have_overwhelming_votes_require_exactly_one(),
+            when_should_surplus_distribution_be_deferred: rules.when_should_surplus_distribution_be_deferred(),
+            should_eliminate_multiple_candidates_federal_rule_13a: rules.should_eliminate_multiple_candidates_federal_rule_13a(),
+            should_eliminate_multiple_candidates_below_next_highest: rules.should_eliminate_multiple_candidates_below_next_highest(),
+            count_set_aside_due_to_transfer_value_limit_as_rounding: rules.count_set_aside_due_to_transfer_value_limit_as_rounding(),
+            how_to_name_counts: rules.how_to_name_counts(),
+            major_count_if_someone_elected: rules.major_count_if_someone_elected(),
+            use_f32_arithmetic_when_applying_transfer_values_instead_of_exact: rules.use_f32_arithmetic_when_applying_transfer_values_instead_of_exact(),
+            prohibit_negative_surplus_fraction: rules.prohibit_negative_surplus_fraction(),
+            do_all_transfer_values_in_one_count: rules.do_all_transfer_values_in_one_count(),
+            distribute_largest_surplus_first: rules.distribute_largest_surplus_first(),
+            custom_sort_of_subcounts: rules.sort_subcounts_by_count().is_some(),
+            transfer_value_for_surplus_2_over_3_ballots: rules.make_transfer_value(Rules::Tally::from(BallotPaperCount(2)),BallotPaperCount(3)).to_string(),
+            transfer_value_for_surplus_4_over_3_ballots: rules.make_transfer_value(Rules::Tally::from(BallotPaperCount(4)),BallotPaperCount(3)).to_string(),
+            votes_for_10_ballots_at_transfer_value_2_3: votes.to_string(),
+            scaled_transfer_value_2_3: rules.munge_scaled_transfer_value(two_thirds.clone()).to_string(),
+            transfer_value_2_3_used_as_limit: rules.munge_transfer_value_when_used_as_limit(two_thirds.clone()).to_string(),
+            exhausted_votes_in_exclusion: rules.munge_exhausted_votes(votes.clone(),true).to_string(),
+            exhausted_votes_in_surplus_distribution: rules.munge_exhausted_votes(votes,false).to_string(),
+        }
+    }
+
+    /// The fields of the card as (field name, value) pairs, in alphabetical order of field name.
+    pub fn fields(&self) -> Vec<(String,String)> {
+        match serde_json::to_value(self).unwrap() {
+            serde_json::Value::Object(map) => map.into_iter().map(|(k,v)|(k,match v { serde_json::Value::String(s)=>s, _ => v.to_string() })).collect(),
+            _ => panic!("A rule card should serialize to an object"),
+        }
+    }
+
+    /// The fields (other than the name) that differ between two rule cards.
+    pub fn diff(&self,other:&RuleCard) -> Vec<RuleCardDifference> {
+        self.fields().into_iter().zip(other.fields()).filter(|((field,v1),(_,v2))|field!="name" && v1!=v2).map(|((field,first),(_,second))|RuleCardDifference{field,first,second}).collect()
+    }
+
+    /// Make a markdown table comparing a set of rule cards, with a row for each field and a column for each card.
+    pub fn markdown_table(cards:&[RuleCard]) -> String {
+        let mut res = String::new();
+        res.push_str(&format!("| Option | {} |\n",cards.iter().map(|c|c.name.as_str()).collect::<Vec<_>>().join(" | ")));
+        res.push_str(&format!("|---|{}\n","---|".repeat(cards.len())));
+        let fields : Vec<Vec<(String,String)>> = cards.iter().map(|c|c.fields()).collect();
+        if let Some(first) = fields.first() {
+            for (row,(field,_)) in first.iter().enumerate() {
+                if field=="name" { continue; }
+                res.push_str(&format!("| {} | {} |\n",field,fields.iter().map(|f|f[row].1.as_str()).collect::<Vec<_>>().join(" | ")));
+            }
+        }
+        res
+    }
+}
+
+impl Display for RuleCard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (field,value) in self.fields() {
+            writeln!(f,"{} : {}",field,value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A field that differs between two rule cards.
+#[derive(Clone,Serialize,Deserialize,Debug)]
+pub struct RuleCardDifference {
+    pub field : String,
+    pub first : String,
+    pub second : String,
+}
+
+impl Display for RuleCardDifference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{} : {} vs {}",self.field,self.first,self.second)
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Find every outcome that the draws for unresolved ties could have produced.
+//!
+//! When a tie cannot be resolved by the rules or an EC decision, the returning officer draws lots.
+//! A normal count uses [Randomness] to pick one result of the draw. Here the count is repeated
+//! with [Randomness::Enumerate], trying each result of each draw that matters. Only the results
+//! that can make a difference are tried : if the tie is to choose the lowest candidate to exclude
+//! from 3 tied candidates, there are 3 possible results, not 3! orderings.
+//!
+//! Each branch has the probability it would have had if every draw were uniform, and branches
+//! with the same elected candidates are combined. The number of branches grows exponentially
+//! with the number of draws, so this is only practical if there are a few ties. The enumeration
+//! stops with [CountError::TooManyTieBranches] rather than try more than a given number of branches.
+//!
+//! The NSW random selection of votes is not a tie, and is done as for [Randomness::ReverseDonkeyVote].
+
+use std::collections::HashMap;
+use num::{BigRational, BigUint, One, ToPrimitive, Zero};
+use serde::{Serialize,Deserialize};
+use crate::ballot_metadata::CandidateIndex;
+use crate::count_error::CountError;
+use crate::preference_distribution::PreferenceDistributionRules;
+use crate::prepared_election::{PreparedElection, VoteDeltas};
+use crate::random_util::Randomness;
+use crate::tie_resolution::TieResolutionGranularityNeeded;
+
+/// The draws made in one branch of the enumeration.
+#[derive(Clone,Debug)]
+pub struct DrawBranches {
+    /// The result to use for the first few draws. Later draws use result 0, which is the reverse donkey vote.
+    forced : Vec<BigUint>,
+    /// The draws made so far in this count.
+    made : Vec<DrawMade>,
+    /// The most branches that may be tried.
+    max_branches : usize,
+    /// Set if a draw had more possible results than [Self::max_branches], to be returned by [Randomness::take_draw_failure].
+    pub(crate) too_many_branches : Option<CountError>,
+}
+
+#[derive(Clone,Debug)]
+struct DrawMade {
+    /// The number of distinct results the draw could have had.
+    options : BigUint,
+    chosen : BigUint,
+}
+
+impl DrawBranches {
+    /// The first branch, in which every draw has result 0.
+    fn new(max_branches:usize) -> Self {
+        DrawBranches{ forced: vec![], made: vec![], max_branches, too_many_branches: None }
+    }
+
+    /// Sort tied_candidates (low to high), using the next result to try for this draw.
+    pub(crate) fn draw(&mut self,tied_candidates: &mut [CandidateIndex],granularity:TieResolutionGranularityNeeded) {
+        let n = tied_candidates.len();
+        let chosen = self.forced.get(self.made.len()).cloned().unwrap_or_else(BigUint::zero);
+        let options = match granularity {
+            TieResolutionGranularityNeeded::Total => { apply_permutation(tied_candidates,chosen.clone()); factorial(n) }
+            TieResolutionGranularityNeeded::LowestSeparated(k) => { apply_combination(tied_candidates,k,chosen.clone()); binomial(n,k) }
+        };
+        if options>BigUint::from(self.max_branches) && self.too_many_branches.is_none() {
+            self.too_many_branches=Some(CountError::TooManyTieBranches{ max_branches: self.max_branches });
+        }
+        self.made.push(DrawMade{options,chosen});
+    }
+
+    /// The branch after this one, in the order draws are tried, or None if this is the last.
+    fn next_branch(&self) -> Option<DrawBranches> {
+        let mut forced : Vec<BigUint> = self.made.iter().map(|d|d.chosen.clone()).collect();
+        while let Some(last) = forced.pop() {
+            let next = last+1u32;
+            if next<self.made[forced.len()].options {
+                forced.push(next);
+                return Some(DrawBranches{ forced, ..DrawBranches::new(self.max_branches) });
+            }
+        }
+        None
+    }
+
+    /// The probability of this branch if each draw were uniform.
+    fn probability(&self) -> BigRational {
+        self.made.iter().map(|d|BigRational::new(1.into(),d.options.clone().into())).fold(BigRational::one(),|acc,p|acc*p)
+    }
+}
+
+fn factorial(n:usize) -> BigUint { (1..=n).fold(BigUint::one(),|acc,i|acc*i) }
+fn binomial(n:usize,k:usize) -> BigUint { (0..k.min(n)).fold(BigUint::one(),|acc,i|acc*(n-i)/(i+1)) }
+
+/// Reorder the (ascending) tied candidates into the permutation with the given index, in lexicographic order.
+fn apply_permutation(tied_candidates: &mut [CandidateIndex],mut index:BigUint) {
+    let mut remaining : Vec<CandidateIndex> = tied_candidates.to_vec();
+    for slot in tied_candidates.iter_mut() {
+        let block = factorial(remaining.len()-1);
+        let position = (&index/&block).to_usize().unwrap(); // less than remaining.len() as index < factorial(remaining.len()).
+        *slot = remaining.remove(position);
+        index%=block;
+    }
+}
+
+/// Move the combination of k (ascending) tied candidates with the given index, in lexicographic order, to the start.
+fn apply_combination(tied_candidates: &mut [CandidateIndex],k:usize,mut index:BigUint) {
+    let n = tied_candidates.len();
+    let mut lowest = vec![];
+    let mut others = vec![];
+    for (i,&c) in tied_candidates.iter().enumerate() {
+        let still_needed = k-lowest.len();
+        if still_needed==0 { others.push(c); continue; }
+        let with_this = binomial(n-i-1,still_needed-1); // number of combinations that include this candidate.
+        if index<with_this { lowest.push(c); } else { index-=with_this; others.push(c); }
+    }
+    lowest.extend(others);
+    tied_candidates.copy_from_slice(&lowest);
+}
+
+/// A set of candidates who could be elected, and how likely it is.
+#[derive(Serialize,Deserialize,Debug,Clone,PartialEq)]
+pub struct PossibleOutcome {
+    /// The elected candidates, sorted by candidate index.
+    pub elected : Vec<CandidateIndex>,
+    /// The probability of this outcome if every draw is uniform.
+    pub probability : f64,
+    /// The number of branches giving this outcome.
+    pub branches : usize,
+}
+
+/// All the possible outcomes of an election with ties, most likely first.
+#[derive(Serialize,Deserialize,Debug,Clone,PartialEq)]
+pub struct TieOutcomes {
+    /// The number of counts done, one per branch.
+    pub branches : usize,
+    /// The maximum number of draws needed in any branch.
+    pub max_draws : usize,
+    pub outcomes : Vec<PossibleOutcome>,
+}
+
+/// Enumerate outcomes, using `count` to do a count with the given randomness and return the candidates elected.
+/// The randomness given to `count` will be [Randomness::Enumerate], and should not be replaced.
+/// Returns [CountError::TooManyTieBranches] if more than `max_branches` counts would be needed.
+pub fn enumerate_tie_outcomes<E:From<CountError>>(max_branches:usize,mut count : impl FnMut(&mut Randomness) -> Result<Vec<CandidateIndex>,E>) -> Result<TieOutcomes,E> {
+    let mut outcomes : HashMap<Vec<CandidateIndex>,(PossibleOutcome,BigRational)> = HashMap::new();
+    let mut num_branches = 0;
+    let mut max_draws = 0;
+    let mut next = Some(DrawBranches::new(max_branches));
+    while let Some(branch) = next {
+        if num_branches>=max_branches { return Err(CountError::TooManyTieBranches{max_branches}.into()); }
+        let mut randomness = Randomness::Enumerate(branch);
+        let mut elected = count(&mut randomness)?;
+        elected.sort_by_key(|c|c.0);
+        let Randomness::Enumerate(branch) = randomness else { panic!("The randomness used to enumerate ties was replaced") };
+        if let Some(error) = branch.too_many_branches { return Err(error.into()); } // in case `count` did not check for draw failures.
+        num_branches+=1;
+        max_draws=max_draws.max(branch.made.len());
+        let (outcome,probability) = outcomes.entry(elected.clone()).or_insert_with(||(PossibleOutcome{ elected, probability: 0.0, branches: 0 },BigRational::zero()));
+        *probability+=branch.probability();
+        outcome.branches+=1;
+        next = branch.next_branch();
+    }
+    let mut outcomes : Vec<PossibleOutcome> = outcomes.into_values().map(|(outcome,probability)|PossibleOutcome{ probability: probability.to_f64().unwrap(), ..outcome }).collect();
+    outcomes.sort_by(|a,b|b.probability.total_cmp(&a.probability).then_with(||a.elected.iter().map(|c|c.0).cmp(b.elected.iter().map(|c|c.0))));
+    Ok(TieOutcomes{ branches: num_branches, max_draws, outcomes })
+}
+
+/// Enumerate the outcomes of a prepared election using the vacancies, excluded candidates and EC tie resolutions in the metadata,
+/// trying at most `max_branches` branches.
+pub fn enumerate_tie_outcomes_of_election<Rules:PreferenceDistributionRules>(rules:&Rules,election:&PreparedElection,max_branches:usize) -> Result<TieOutcomes,CountError> {
+    let no_changes = VoteDeltas::default();
+    enumerate_tie_outcomes(max_branches,|randomness|election.distribute_preferences(rules,&no_changes,randomness).map(|transcript|transcript.elected))
+}
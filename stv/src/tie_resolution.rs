@@ -6,7 +6,7 @@
 
 
 use crate::ballot_metadata::CandidateIndex;
-use crate::distribution_of_preferences_transcript::{CountIndex, Transcript};
+use crate::distribution_of_preferences_transcript::{CountIndex, SingleCount, Transcript};
 use std::collections::{HashSet, HashMap};
 use std::hash::Hash;
 use serde::{Serialize,Deserialize};
@@ -16,7 +16,7 @@ use std::str::FromStr;
 use crate::compare_transcripts::DeltasInCandidateLists;
 use crate::random_util::Randomness;
 
-#[derive(Debug,Clone,Copy)]
+#[derive(Debug,Clone,Copy,Serialize,Deserialize)]
 pub enum MethodOfTieResolution {
     None,
     /// Require that at some prior point *all* the counts were different
@@ -52,6 +52,9 @@ pub enum MethodOfTieResolution {
     AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinishedGiveUpIfNotFullSolution,
     /// Like AnyDifferenceIsADiscriminator but only consider major counts like RequireHistoricalCountsToBeAllDifferentOnlyConsideringCountsWhereAnActionIsFinished
     AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished,
+    /// Like AnyDifferenceIsADiscriminator but look forwards from the first count rather than backwards from the most recent.
+    /// That is, the candidates are distinguished by the first count at which they had different totals. Used in Ireland.
+    AnyDifferenceIsADiscriminatorEarliestCountFirst,
 }
 
 /// Sometimes you need tie resolution to distinguish all candidates (e.g. for order elected),
@@ -73,10 +76,11 @@ impl MethodOfTieResolution {
             MethodOfTieResolution::None => false,
             MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent => resolve_ties_require_all_different(tied_candidates,transcript,false),
             MethodOfTieResolution::AnyDifferenceIsADiscriminatorGiveUpIfNotFullSolution => resolve_ties_any_different_give_up_if_cant_do_everything(tied_candidates, transcript, granularity, false),
-            MethodOfTieResolution::AnyDifferenceIsADiscriminator => return resolve_ties_any_different(tied_candidates, transcript, granularity, false),
+            MethodOfTieResolution::AnyDifferenceIsADiscriminator => return resolve_ties_any_different(tied_candidates, transcript, granularity, false, false),
             MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferentOnlyConsideringCountsWhereAnActionIsFinished => resolve_ties_require_all_different(tied_candidates,transcript,true),
             MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinishedGiveUpIfNotFullSolution => resolve_ties_any_different_give_up_if_cant_do_everything(tied_candidates, transcript, granularity, true),
-            MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished => return resolve_ties_any_different(tied_candidates, transcript, granularity, true),
+            MethodOfTieResolution::AnyDifferenceIsADiscriminatorOnlyConsideringCountsWhereAnActionIsFinished => return resolve_ties_any_different(tied_candidates, transcript, granularity, true, false),
+            MethodOfTieResolution::AnyDifferenceIsADiscriminatorEarliestCountFirst => return resolve_ties_any_different(tied_candidates, transcript, granularity, false, true),
         };
         if resolved { vec![] } else { vec![(tied_candidates,granularity)] }
     }
@@ -316,6 +320,13 @@ impl TieResolutionsMadeByEC {
         let tie_resolutions = tie_resolutions.into_iter().map(|v|TieResolutionAtom::IncreasingFavour(v)).collect();
         Ok(TieResolutionsMadeByEC{tie_resolutions})
     }
+    /// All the candidates mentioned in any decision, possibly with repetitions.
+    pub fn candidates_mentioned(&self) -> impl Iterator<Item=CandidateIndex> + '_ {
+        self.tie_resolutions.iter().flat_map(|atom|match atom {
+            TieResolutionAtom::IncreasingFavour(decision) => decision.clone(),
+            TieResolutionAtom::ExplicitDecision(decision) => decision.decision.increasing_favour.concat(),
+        })
+    }
     /// Sort tied_candidates appropriately (low to high), and then return a description of what was done.
     /// If all else fails, use randomness.
     pub fn resolve(&self, tied_candidates: &mut [CandidateIndex], granularity: TieResolutionGranularityNeeded,usage:TieResolutionUsage,current_count:CountIndex,randomness:&mut Randomness) -> TieResolutionExplicitDecision {
@@ -372,7 +383,7 @@ impl TieResolutionsMadeByEC {
             }
         }
         // If all else fails, we need to do a draw.
-        randomness.resolve(tied_candidates);
+        randomness.resolve(tied_candidates,granularity,usage,current_count);
     }
 }
 
@@ -397,10 +408,11 @@ fn resolve_ties_require_all_different<Tally:Clone+Eq+Hash+Ord+Display+FromStr+De
 
 /// Sort candidates low to high based on a countback where any difference is used as much as possible.
 /// Return remaining need for resolution, if any.
-fn resolve_ties_any_different<'a,Tally:Clone+Eq+Hash+Ord+Display+FromStr+Debug>(tied_candidates: &'a mut [CandidateIndex],transcript:  &Transcript<Tally>,granularity:TieResolutionGranularityNeeded,just_consider_major_counts:bool) -> Vec<(&'a mut [CandidateIndex],TieResolutionGranularityNeeded)> {
+fn resolve_ties_any_different<'a,Tally:Clone+Eq+Hash+Ord+Display+FromStr+Debug>(tied_candidates: &'a mut [CandidateIndex],transcript:  &Transcript<Tally>,granularity:TieResolutionGranularityNeeded,just_consider_major_counts:bool,earliest_first:bool) -> Vec<(&'a mut [CandidateIndex],TieResolutionGranularityNeeded)> {
     //println!("Resolve ties any different between {}",tied_candidates.iter().map(|c|c.to_string()).collect::<Vec<_>>().join(","));
     let mut res = vec![];
-    for count in transcript.counts.iter().rev() {if count.reason_completed || !just_consider_major_counts {
+    let counts : Box<dyn Iterator<Item=&SingleCount<Tally>>> = if earliest_first { Box::new(transcript.counts.iter()) } else { Box::new(transcript.counts.iter().rev()) };
+    for count in counts {if count.reason_completed || !just_consider_major_counts {
         let mut observed : HashMap<Tally,Vec<CandidateIndex>> = HashMap::new();
         for candidate in tied_candidates.iter() {
             observed.entry(count.status.tallies.candidate[candidate.0].clone()).or_insert_with(||vec![]).push(*candidate);
@@ -418,8 +430,8 @@ fn resolve_ties_any_different<'a,Tally:Clone+Eq+Hash+Ord+Display+FromStr+Debug>(
                 candidates_with_this_tally.copy_from_slice(who);
                 if who.len()>1 {
                     match granularity {
-                        TieResolutionGranularityNeeded::Total => {res.extend(resolve_ties_any_different(candidates_with_this_tally,transcript,granularity,just_consider_major_counts)) }  // could optimize to start at count currently up to.
-                        TieResolutionGranularityNeeded::LowestSeparated(loc) if loc>upto && loc<upto+who.len() => {res.extend(resolve_ties_any_different(candidates_with_this_tally,transcript,TieResolutionGranularityNeeded::LowestSeparated(loc-upto),just_consider_major_counts))}
+                        TieResolutionGranularityNeeded::Total => {res.extend(resolve_ties_any_different(candidates_with_this_tally,transcript,granularity,just_consider_major_counts,earliest_first)) }  // could optimize to start at count currently up to.
+                        TieResolutionGranularityNeeded::LowestSeparated(loc) if loc>upto && loc<upto+who.len() => {res.extend(resolve_ties_any_different(candidates_with_this_tally,transcript,TieResolutionGranularityNeeded::LowestSeparated(loc-upto),just_consider_major_counts,earliest_first))}
                         TieResolutionGranularityNeeded::LowestSeparated(_) => {} // granularity means we don't care.
                     }
                 }
@@ -437,6 +449,6 @@ fn resolve_ties_any_different<'a,Tally:Clone+Eq+Hash+Ord+Display+FromStr+Debug>(
 /// Return true iff ties are resolved to the required granularity.
 /// Like resolve_ties_any_different_work but give up if there are any problems.
 fn resolve_ties_any_different_give_up_if_cant_do_everything<Tally:Clone+Eq+Hash+Ord+Display+FromStr+Debug>(tied_candidates: &mut [CandidateIndex],transcript:  &Transcript<Tally>,granularity:TieResolutionGranularityNeeded,just_consider_major_counts:bool) -> bool {
-    resolve_ties_any_different(tied_candidates,transcript,granularity,just_consider_major_counts).is_empty()
+    resolve_ties_any_different(tied_candidates,transcript,granularity,just_consider_major_counts,false).is_empty()
 }
 
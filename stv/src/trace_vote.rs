@@ -0,0 +1,139 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Where did my vote go? Follow a ballot (or rather all ballots with the same preferences) through a count.
+//!
+//! This complements [crate::find_vote], which finds raw ballots matching a query. Given the preferences
+//! on a ballot, this records, at the end of every count, which candidate holds it, at what transfer value,
+//! and whether it has exhausted or is part of an elected candidate's quota.
+//!
+//! Tracing is done by the preference distribution engine (see [PreferenceDistributor::trace_vote]) so it works with any rules
+//! using it. Ballots with identical preferences are indistinguishable to the count, so they are traced together; for rules with
+//! random selection of papers they may end up in different places.
+
+use serde::{Serialize,Deserialize};
+use crate::ballot_metadata::{CandidateIndex, ElectionMetadata, PartyIndex};
+use crate::ballot_paper::{parse_marking, FormalityRules, FormalVote, RawBallotMarking, RawBallotMarkings, VoteSource};
+use crate::ballot_pile::BallotPaperCount;
+use crate::count_error::CountError;
+use crate::count_observer::CountObserver;
+use crate::distribution_of_preferences_transcript::CountIndex;
+use crate::election_data::ElectionData;
+use crate::preference_distribution::{CountOptions, PreferenceDistributionRules, PreferenceDistributor};
+use crate::random_util::Randomness;
+use crate::transfer_value::{StringSerializedRational, TransferValue};
+
+/// The preferences on the ballot to trace.
+#[derive(Clone,Debug,Serialize,Deserialize,PartialEq,Eq)]
+pub enum VoteToTrace {
+    /// A below the line vote, with candidates in preference order.
+    Btl(Vec<CandidateIndex>),
+    /// An above the line vote, with parties in preference order.
+    Atl(Vec<PartyIndex>),
+    /// The markings on a ballot, separated by commas. `atl` is in the order of the parties that can get above the line votes,
+    /// and `btl` is in candidate order, as in [crate::find_vote::FindVoteHit::votes].
+    Markings{
+        #[serde(default)]
+        atl : String,
+        btl : String,
+    },
+}
+
+impl VoteToTrace {
+    /// Convert markings to preferences, using the given formality rules. Returns None if the markings are not a formal vote.
+    pub fn resolve_markings(&self,metadata:&ElectionMetadata,formality_rules:FormalityRules) -> Option<VoteToTrace> {
+        match self {
+            VoteToTrace::Markings{atl,btl} => {
+                let parse = |markings:&str| if markings.is_empty() { vec![] } else { markings.split(',').map(parse_marking).collect::<Vec<RawBallotMarking>>() };
+                let atl_parties : Vec<PartyIndex> = metadata.parties.iter().enumerate().filter(|(_,p)|p.atl_allowed).map(|(i,_)|PartyIndex(i)).collect();
+                let mut atl = parse(atl);
+                atl.truncate(atl_parties.len());
+                let btl = parse(btl);
+                let markings = RawBallotMarkings{ atl: &atl, btl: &btl, atl_parties: &atl_parties };
+                match markings.interpret_vote_with_rules(formality_rules)? {
+                    FormalVote::Btl(btl) => Some(VoteToTrace::Btl(btl.candidates)),
+                    FormalVote::Atl(atl) => Some(VoteToTrace::Atl(atl.parties)),
+                }
+            }
+            _ => Some(self.clone()),
+        }
+    }
+
+    pub(crate) fn matches(&self,source:&VoteSource) -> bool {
+        match (self,source) {
+            (VoteToTrace::Btl(candidates),VoteSource::Btl(btl)) => btl.candidates==*candidates,
+            (VoteToTrace::Atl(parties),VoteSource::Atl(atl)) => atl.parties==*parties,
+            _ => false,
+        }
+    }
+
+    /// The number of ballot papers with these preferences.
+    pub fn num_papers(&self,data:&ElectionData) -> BallotPaperCount {
+        BallotPaperCount(data.btl.iter().filter(|v|self.matches(&VoteSource::Btl(v))).map(|v|v.n).sum::<usize>()+data.atl.iter().filter(|v|self.matches(&VoteSource::Atl(v))).map(|v|v.n).sum::<usize>())
+    }
+}
+
+/// What is happening to some traced ballots.
+#[derive(Clone,Copy,Debug,Serialize,Deserialize,PartialEq,Eq)]
+pub enum TracedVoteStatus {
+    /// Counting for a continuing candidate.
+    Continuing,
+    /// With an elected candidate whose surplus has not yet been distributed.
+    AwaitingSurplusDistribution,
+    /// Part of an elected candidate's quota.
+    InQuota,
+    /// With a candidate who is being excluded, waiting to be distributed.
+    AwaitingExclusion,
+    /// Exhausted.
+    Exhausted,
+}
+
+/// Where some of the traced ballots are.
+#[derive(Clone,Debug,Serialize,Deserialize,PartialEq)]
+pub struct TracedVoteLocation {
+    /// The candidate holding the ballots, or None if exhausted.
+    #[serde(skip_serializing_if = "Option::is_none",default)]
+    pub candidate : Option<CandidateIndex>,
+    pub status : TracedVoteStatus,
+    pub papers : BallotPaperCount,
+    pub transfer_value : TransferValue,
+    /// The number of votes these ballots contribute to the candidate's tally (or the exhausted tally). This is their share of the
+    /// tally from the papers they were transferred with, so includes any rounding done by the rules.
+    pub value : StringSerializedRational,
+}
+
+/// Where the traced ballots are at the end of a count.
+#[derive(Clone,Debug,Serialize,Deserialize,PartialEq)]
+pub struct TracedVoteAtCount {
+    pub count : CountIndex,
+    /// The human readable name of the count, if different from the count index+1.
+    #[serde(skip_serializing_if = "Option::is_none",default)]
+    pub count_name : Option<String>,
+    pub locations : Vec<TracedVoteLocation>,
+}
+
+/// Where a ballot went, count by count.
+#[derive(Clone,Debug,Serialize,Deserialize,PartialEq)]
+pub struct VoteTrace {
+    pub vote : VoteToTrace,
+    /// The number of ballot papers with these preferences. If 0, there is nothing to trace.
+    pub papers : BallotPaperCount,
+    pub counts : Vec<TracedVoteAtCount>,
+}
+
+/// Count the election with the given rules, tracing where the ballots with the given preferences go.
+/// Markings are interpreted using `formality_rules`, which should be those used by the loader of the data, see [crate::parse_util::RawDataSource::formality_rules].
+/// Returns None if the vote is given as markings that are not a formal vote.
+pub fn trace_vote<Rules:PreferenceDistributionRules>(rules:&Rules,data:&ElectionData,vote:&VoteToTrace,formality_rules:FormalityRules,options:&CountOptions,observer:&mut dyn CountObserver<Rules::Tally>,randomness:&mut Randomness) -> Result<Option<VoteTrace>,CountError> {
+    if let Some(vote) = vote.resolve_markings(&data.metadata,formality_rules) {
+        let arena = typed_arena::Arena::<CandidateIndex>::new();
+        let votes = data.resolve_atl_including_weights(&arena,options.vote_types);
+        let mut work : PreferenceDistributor<'_,Rules> = PreferenceDistributor::new(rules,data,&votes,options,observer,None,randomness);
+        work.trace_vote(vote);
+        work.go()?;
+        Ok(work.take_vote_trace())
+    } else { Ok(None) }
+}
@@ -0,0 +1,141 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Publicly verifiable drawing of lots.
+//!
+//! The seed comes from a file committed to in public, such as a record of dice rolls made at a
+//! public ceremony, or a randomness beacon value saved locally. The commitment is the SHA-256 hash
+//! of the file, and the seed is the SHA-256 hash of a fixed prefix followed by the file. For each
+//! draw, each tied candidate gets the SHA-256 hash of the text `seed draw_number candidate_index`
+//! (the seed in hexadecimal, the others in decimal, starting from 0), and the candidates are ordered
+//! by that hash, smallest (least favoured) first.
+//!
+//! The draws are stored in the transcript along with the commitment and seed, and anyone with the
+//! seed file and the votes can check them with [verify_draws] (or the `verify_draws` program). This
+//! redoes the count, as the seed alone does not show that the tied candidates were really tied.
+//!
+//! The NSW random selection of votes is not a draw by lot, and is done as for [crate::random_util::Randomness::ReverseDonkeyVote].
+
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use serde::{Serialize,Deserialize};
+use sha2::{Digest, Sha256};
+use crate::ballot_metadata::CandidateIndex;
+use crate::count_error::CountError;
+use crate::distribution_of_preferences_transcript::CountIndex;
+use crate::random_util::{RandomDraw, Randomness};
+use crate::tie_resolution::TieResolutionUsage;
+
+/// Prefix hashed before the committed file to get the seed, so the seed is not the same as the commitment.
+const SEED_PREFIX : &[u8] = b"ConcreteSTV draw by lot\0";
+
+/// A 256 bit hash, written as hexadecimal.
+#[derive(Clone,Debug,Serialize,Deserialize,PartialEq,Eq)]
+#[serde(transparent)]
+pub struct Sha256Hex(pub String);
+
+impl Sha256Hex {
+    fn of(data:&[u8]) -> Self { Sha256Hex(Sha256::digest(data).iter().map(|b|format!("{:02x}",b)).collect()) }
+}
+
+impl Display for Sha256Hex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f,"{}",self.0) }
+}
+
+/// The seed for verifiable draws, and where it came from. This is stored in the transcript.
+#[derive(Clone,Debug,Serialize,Deserialize,PartialEq,Eq)]
+pub struct PublicSeed {
+    /// The name (without directory) of the committed file.
+    pub file_name : String,
+    /// The SHA-256 hash of the committed file.
+    pub commitment : Sha256Hex,
+    /// The seed derived from the committed file.
+    pub seed : Sha256Hex,
+}
+
+impl PublicSeed {
+    pub fn from_contents(file_name:&str,contents:&[u8]) -> Self {
+        let mut prefixed = SEED_PREFIX.to_vec();
+        prefixed.extend_from_slice(contents);
+        PublicSeed{ file_name: file_name.to_string(), commitment: Sha256Hex::of(contents), seed: Sha256Hex::of(&prefixed) }
+    }
+
+    pub fn from_file(path:&Path) -> std::io::Result<Self> {
+        let file_name = path.file_name().map(|f|f.to_string_lossy().to_string()).unwrap_or_default();
+        Ok(Self::from_contents(&file_name,&std::fs::read(path)?))
+    }
+
+    /// Sort the tied candidates for the draw with the given number (starting at 0), least favoured first.
+    pub fn draw(&self,tied_candidates:&mut [CandidateIndex],draw_number:usize) {
+        tied_candidates.sort_by_cached_key(|c|Sha256Hex::of(format!("{} {} {}",self.seed,draw_number,c.0).as_bytes()).0);
+    }
+}
+
+/// The state of a count using [crate::random_util::Randomness::Verifiable].
+#[derive(Clone,Debug)]
+pub struct VerifiableDraws {
+    seed : PublicSeed,
+    /// The number of draws made so far.
+    draws_made : usize,
+    /// The draws not yet put in the transcript.
+    recorded : Vec<RandomDraw>,
+}
+
+impl VerifiableDraws {
+    pub fn new(seed:PublicSeed) -> Self { VerifiableDraws{ seed, draws_made: 0, recorded: vec![] } }
+
+    pub fn seed(&self) -> &PublicSeed { &self.seed }
+
+    pub(crate) fn draw(&mut self,tied_candidates:&mut [CandidateIndex],usage:TieResolutionUsage,count:CountIndex) {
+        let tied = tied_candidates.to_vec();
+        self.seed.draw(tied_candidates,self.draws_made);
+        self.draws_made+=1;
+        self.recorded.push(RandomDraw::Tie{ count, usage, tied, order: tied_candidates.to_vec() });
+    }
+
+    pub(crate) fn take_recorded_draws(&mut self) -> Vec<RandomDraw> { std::mem::take(&mut self.recorded) }
+}
+
+/// A reason why the draws in a transcript could not be verified.
+#[derive(thiserror::Error,Debug,Clone,Eq,PartialEq)]
+pub enum DrawVerificationError {
+    #[error("The transcript was not produced using a public seed.")]
+    NoSeedInTranscript,
+    #[error("The file has hash {actual}, but the transcript was committed to {committed}.")]
+    CommitmentMismatch{ committed : Sha256Hex, actual : Sha256Hex },
+    #[error("The seed derived from the file is {actual}, but the transcript used {used}.")]
+    SeedMismatch{ used : Sha256Hex, actual : Sha256Hex },
+    #[error("Draw #{draw} on count #{count} should have resulted in {}.",.expected.iter().map(|c|c.to_string()).collect::<Vec<_>>().join(","))]
+    WrongResult{ draw : usize, count : CountIndex, expected : Vec<CandidateIndex> },
+    #[error("Draw #{draw} should have been on count #{count} between candidates {}.",.expected.iter().map(|c|c.to_string()).collect::<Vec<_>>().join(","))]
+    WrongTiedCandidates{ draw : usize, count : CountIndex, expected : Vec<CandidateIndex> },
+    #[error("The transcript has {recorded} draws, but the count needed {needed}.")]
+    WrongNumberOfDraws{ recorded : usize, needed : usize },
+    #[error("Could not redo the count : {0}")]
+    Count(#[from] CountError),
+}
+
+/// Check that the draws in a transcript were the draws the count needed, made using the given committed file contents.
+/// `recount` should redo the count (with the same votes, rules and options as the original) using the given randomness,
+/// and return the draws in its transcript. Returns the number of draws checked.
+/// Draws other than lots for ties (e.g. if the count was also recorded with [Randomness::Record]) are ignored.
+pub fn verify_draws<E:From<DrawVerificationError>>(seed:Option<&PublicSeed>,draws:&[RandomDraw],committed_file_contents:&[u8],recount:impl FnOnce(&mut Randomness)->Result<Vec<RandomDraw>,E>) -> Result<usize,E> {
+    let seed = seed.ok_or(DrawVerificationError::NoSeedInTranscript)?;
+    let actual = PublicSeed::from_contents(&seed.file_name,committed_file_contents);
+    if actual.commitment!=seed.commitment { return Err(DrawVerificationError::CommitmentMismatch{ committed: seed.commitment.clone(), actual: actual.commitment }.into()); }
+    if actual.seed!=seed.seed { return Err(DrawVerificationError::SeedMismatch{ used: seed.seed.clone(), actual: actual.seed }.into()); }
+    let needed = recount(&mut Randomness::Verifiable(VerifiableDraws::new(actual)))?;
+    let ties = |draws:&[RandomDraw]|draws.iter().filter(|d|matches!(d,RandomDraw::Tie{..})).cloned().collect::<Vec<_>>();
+    let (recorded,needed) = (ties(draws),ties(&needed));
+    for (draw_number,(recorded,needed)) in recorded.iter().zip(needed.iter()).enumerate() {
+        if let (RandomDraw::Tie{ count, usage, tied, order },RandomDraw::Tie{ count:needed_count, usage:needed_usage, tied:needed_tied, order:needed_order }) = (recorded,needed) {
+            if count!=needed_count || usage!=needed_usage || tied!=needed_tied { return Err(DrawVerificationError::WrongTiedCandidates{ draw: draw_number, count: *needed_count, expected: needed_tied.clone() }.into()); }
+            if order!=needed_order { return Err(DrawVerificationError::WrongResult{ draw: draw_number, count: *count, expected: needed_order.clone() }.into()); }
+        }
+    }
+    if recorded.len()!=needed.len() { return Err(DrawVerificationError::WrongNumberOfDraws{ recorded: recorded.len(), needed: needed.len() }.into()); }
+    Ok(recorded.len())
+}
@@ -11,6 +11,7 @@
 use std::collections::HashSet;
 use std::fmt::{Debug, Display};
 use std::iter;
+use crate::count_error::CountError;
 use thiserror::Error;
 use crate::ballot_metadata::{CandidateIndex, ElectionMetadata};
 use crate::ballot_paper::BTL;
@@ -18,8 +19,9 @@ use crate::ballot_pile::BallotPaperCount;
 use crate::distribution_of_preferences_transcript::{CountIndex, PerCandidate, QuotaInfo, Transcript};
 use crate::election_data::ElectionData;
 use crate::official_dop_transcript::{CanConvertToF64PossiblyLossily, OfficialDistributionOfPreferencesTranscript};
-use crate::preference_distribution::{PreferenceDistributionRules, PreferenceDistributor};
+use crate::preference_distribution::{CountOptions, PreferenceDistributionRules, PreferenceDistributor};
 use crate::random_util::Randomness;
+use crate::count_observer::IgnoreCountProgress;
 use crate::tie_resolution::{TieResolutionAtom, TieResolutionExplicitDecision, TieResolutionExplicitDecisionInCount, TieResolutionGranularityNeeded, TieResolutionsMadeByEC};
 
 #[derive(Error, Debug)]
@@ -69,6 +71,8 @@ pub enum IssueWithOfficialDOPTranscript<Tally:Display+Debug> {
     FirstPreferenceDeltasAndTotalsDifferentPapers,
     #[error("First preference count has different values for votes and papers")]
     FirstPreferenceVotesAndPapersDifferent,
+    #[error("{0}")]
+    Count(#[from] CountError),
 }
 
 
@@ -80,25 +84,28 @@ struct VerifyOfficialDopTranscript<'a,Rules:PreferenceDistributionRules> {
     quota : Rules::Tally,
 }
 
-impl <'a,Rules:PreferenceDistributionRules> VerifyOfficialDopTranscript<'a,Rules> {
+impl <'a,Rules:PreferenceDistributionRules+Default> VerifyOfficialDopTranscript<'a,Rules> {
 
     // check quota and make new verifier
     fn new(official:&'a OfficialDistributionOfPreferencesTranscript,metadata:&'a ElectionMetadata) -> Result<Self,IssueWithOfficialDOPTranscript<Rules::Tally>> {
         if official.counts.is_empty() { return Err(IssueWithOfficialDOPTranscript::DoesntHaveFirstCount)}
         let num_candidates = metadata.candidates.len();
+        let rules = Rules::default();
         // check quota
         let first_preference_votes  = {
             let vote_source = official.counts[0].vote_total.as_ref().or(official.counts[0].vote_delta.as_ref()).ok_or_else(||IssueWithOfficialDOPTranscript::FirstCountHasNoVotes)?;
-            (vote_source.candidate.iter().sum::<f64>() + (if Rules::should_exhausted_votes_count_for_quota_computation() { vote_source.exhausted } else {0.0})) as usize
+            (vote_source.candidate.iter().sum::<f64>() + (if rules.should_exhausted_votes_count_for_quota_computation() { vote_source.exhausted } else {0.0})) as usize
         };
         let candidates_to_be_elected = metadata.vacancies.ok_or_else(||IssueWithOfficialDOPTranscript::MetadataMissingVacancies)?;
-        let quota = Rules::Tally::from(BallotPaperCount(first_preference_votes/(1+candidates_to_be_elected.0)+1));
+        let method = rules.quota_method();
+        let quota = method.compute::<Rules>(BallotPaperCount(first_preference_votes),candidates_to_be_elected)?;
         if let Some(official_quota) = &official.quota  {
             if official_quota.quota!=quota.convert_to_f64() || official_quota.vacancies!=candidates_to_be_elected || official_quota.papers.0 as f64!=first_preference_votes as f64 {
                 return Err(IssueWithOfficialDOPTranscript::QuotaWrong(official_quota.clone(),QuotaInfo{
                     papers: BallotPaperCount(first_preference_votes),
                     vacancies: candidates_to_be_elected,
                     quota,
+                    method,
                 }))
             }
         }
@@ -204,7 +211,7 @@ impl <'a,Rules:PreferenceDistributionRules> VerifyOfficialDopTranscript<'a,Rules
 }
 
 
-pub fn veryify_official_dop_transcript<Rules:PreferenceDistributionRules>(official:&OfficialDistributionOfPreferencesTranscript,metadata:&ElectionMetadata) -> Result<(),IssueWithOfficialDOPTranscript<Rules::Tally>> {
+pub fn veryify_official_dop_transcript<Rules:PreferenceDistributionRules+Default>(official:&OfficialDistributionOfPreferencesTranscript,metadata:&ElectionMetadata) -> Result<(),IssueWithOfficialDOPTranscript<Rules::Tally>> {
     let work: VerifyOfficialDopTranscript<'_, Rules> = VerifyOfficialDopTranscript::new(official,metadata)?;
     work.check_basic_arithmetic_adding_deltas_to_totals()?;
     work.check_first_preferences_count()?;
@@ -214,10 +221,10 @@ pub fn veryify_official_dop_transcript<Rules:PreferenceDistributionRules>(offici
 }
 
 
-pub fn distribute_preferences_using_official_results<Rules:PreferenceDistributionRules>(official:&OfficialDistributionOfPreferencesTranscript,metadata:&ElectionMetadata) -> Result<Transcript<Rules::Tally>,IssueWithOfficialDOPTranscript<Rules::Tally>> {
+pub fn distribute_preferences_using_official_results<Rules:PreferenceDistributionRules+Default>(official:&OfficialDistributionOfPreferencesTranscript,metadata:&ElectionMetadata) -> Result<Transcript<Rules::Tally>,IssueWithOfficialDOPTranscript<Rules::Tally>> {
     if official.counts.is_empty() { return Err(IssueWithOfficialDOPTranscript::DoesntHaveFirstCount)}
     let excluded_candidates : HashSet<CandidateIndex> = metadata.excluded.iter().cloned().collect();
-    let print_progress_to_stdout:bool = false;
+    let mut observer = IgnoreCountProgress{};
     let num_candidates = metadata.candidates.len();
     // check quota
     let candidates_to_be_elected = metadata.vacancies.ok_or_else(||IssueWithOfficialDOPTranscript::MetadataMissingVacancies)?;
@@ -252,8 +259,9 @@ pub fn distribute_preferences_using_official_results<Rules:PreferenceDistributio
     let votes = data.resolve_atl_including_weights(&arena,None);
     let oracle = OracleFromOfficialDOP{official, tie_resolutions: Default::default() };
     let mut randomness = Randomness::ReverseDonkeyVote;
-    let mut work : PreferenceDistributor<'_,Rules> = PreferenceDistributor::new(&data,&votes,candidates_to_be_elected,&excluded_candidates,&ec_resolutions,print_progress_to_stdout,Some(oracle),&mut randomness,&[],false);
-    work.go();
+    let rules = Rules::default();
+    let mut work : PreferenceDistributor<'_,Rules> = PreferenceDistributor::new(&rules,&data,&votes,&CountOptions::new(candidates_to_be_elected,excluded_candidates,&ec_resolutions),&mut observer,Some(oracle),&mut randomness);
+    work.go()?;
     Ok(work.transcript)
 }
 
@@ -0,0 +1,78 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rules for the Tasmanian House of Assembly, which uses the Hare-Clark system.
+
+use stv::ballot_pile::{BallotPaperCount, DoNotSplitByCountNumber};
+use stv::preference_distribution::{BigRational, LastParcelUse, PreferenceDistributionRules, SurplusTransferMethod, TransferValueMethod, WhenToDoElectCandidateClauseChecking};
+use stv::tie_resolution::MethodOfTieResolution;
+use stv::transfer_value::{convert_usize_to_rational, round_rational_down_to_usize, TransferValue};
+
+pub mod parse;
+
+/// Hare-Clark as used by the Tasmanian Electoral Commission for the House of Assembly.
+///
+/// Votes are whole ballot papers. A surplus is distributed by looking at just the last
+/// parcel of papers received by the elected candidate (all their papers on first preferences),
+/// sorting them by next available preference, and working out how many papers each continuing
+/// candidate should get from the transfer value surplus/(continuing papers in the last parcel).
+/// The particular papers transferred are then chosen at random, the rest being set aside as
+/// part of the elected candidate's quota. This is the same random sampling machinery as the
+/// NSW random rules.
+#[derive(Default)]
+pub struct TasHareClark {
+}
+
+impl PreferenceDistributionRules for TasHareClark {
+    type Tally = usize;
+    type SplitByNumber = DoNotSplitByCountNumber;
+
+    /// Only the last parcel is examined for a surplus.
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::LiterallyLast }
+    /// Exhausted papers in the last parcel are not counted in the denominator.
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverContinuingBallots }
+
+    fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { convert_usize_to_rational(tally)  }
+    fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { round_rational_down_to_usize(rational)  }
+
+    /// If there are fewer continuing papers than the surplus, all are transferred.
+    fn make_transfer_value(&self,surplus: usize, ballots: BallotPaperCount) -> TransferValue {
+        if surplus>=ballots.0 { TransferValue::one() }
+        else { TransferValue::from_surplus(surplus,ballots) }
+    }
+
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
+        transfer_value.mul_rounding_down(ballots)
+    }
+
+    /// The papers to be transferred are selected at random from each candidate's bundle.
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::PickRandomlyAfterDistribution }
+    /// All papers have value 1, so this is not relevant.
+    fn sort_exclusions_by_transfer_value(&self) -> bool { false }
+
+    /// NA
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
+    /// Countback to the last count at which the tied candidates had different totals, failing which by lot.
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    /// Order of election does not otherwise matter, so do the same as for quota.
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+    /// Countback to the last count at which the tied candidates had different totals, failing which by lot.
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::AnyDifferenceIsADiscriminator }
+
+    /// NA as there is no middle
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { false }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { true }
+
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
+
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+    /// When the number of continuing candidates equals the number of unfilled vacancies, they are all elected.
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never }
+
+    fn name(&self) -> String { "TasHareClark".to_string() }
+}
@@ -0,0 +1,337 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Parse files used by the Tasmanian Electoral Commission for House of Assembly vote data.
+//!
+//! The files expected, in the folder `Tas/<year>` of the data repository, are
+//! * `Candidates.csv`, with columns `Division`, `Group` and `Name`, in ballot paper order. Ungrouped candidates have a blank group.
+//! * `<Division> Preferences.csv`, one row per ballot paper, with columns `Batch`, `Paper` and then one column per candidate (headed by name) containing the preference number, or blank.
+//! * `<Division> Distribution of Preferences.csv`, the official distribution of preferences. This has columns `Count`, `Row`, one column per candidate, `Exhausted`, `Loss by Fraction` and `Remarks`.
+//!   Each count has three rows. `Papers` is the number of papers examined, `Votes` is the change in votes, and `Total` is the progressive total.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use anyhow::anyhow;
+use serde::Deserialize;
+use stv::ballot_metadata::{Candidate, CandidateIndex, DataSource, ElectionMetadata, ElectionName, NumberOfCandidates, Party, PartyIndex};
+use stv::ballot_paper::UniqueBTLBuilder;
+use stv::datasource_description::{AssociatedRules, Copyright, ElectionDataSource};
+use stv::distribution_of_preferences_transcript::PerCandidate;
+use stv::election_data::ElectionData;
+use stv::official_dop_transcript::{OfficialDistributionOfPreferencesTranscript, OfficialDOPForOneCount};
+use stv::parse_util::{FileFinder, KnowsAboutRawMarkings, MissingFile, RawDataSource};
+use stv::tie_resolution::TieResolutionsMadeByEC;
+
+pub fn get_tas_data_loader_2018(finder:&FileFinder) -> anyhow::Result<TasDataLoader> {
+    TasDataLoader::new(finder,"2018","https://www.tec.tas.gov.au/House_of_Assembly/")
+}
+pub fn get_tas_data_loader_2021(finder:&FileFinder) -> anyhow::Result<TasDataLoader> {
+    TasDataLoader::new(finder,"2021","https://www.tec.tas.gov.au/House_of_Assembly/")
+}
+pub fn get_tas_data_loader_2024(finder:&FileFinder) -> anyhow::Result<TasDataLoader> {
+    TasDataLoader::new(finder,"2024","https://www.tec.tas.gov.au/House_of_Assembly/")
+}
+
+pub struct TasDataSource {}
+
+impl ElectionDataSource for TasDataSource {
+    fn name(&self) -> Cow<'static, str> { "Tasmanian House of Assembly".into() }
+    fn ec_name(&self) -> Cow<'static, str> { "Tasmanian Electoral Commission".into() }
+    fn ec_url(&self) -> Cow<'static, str> { "https://www.tec.tas.gov.au/".into() }
+    fn years(&self) -> Vec<String> { vec!["2018".to_string(),"2021".to_string(),"2024".to_string()] }
+    fn get_loader_for_year(&self,year: &str,finder:&FileFinder) -> anyhow::Result<Box<dyn RawDataSource+Send+Sync>> {
+        match year {
+            "2018" => Ok(Box::new(get_tas_data_loader_2018(finder)?)),
+            "2021" => Ok(Box::new(get_tas_data_loader_2021(finder)?)),
+            "2024" => Ok(Box::new(get_tas_data_loader_2024(finder)?)),
+            _ => Err(anyhow!("Not a valid year")),
+        }
+    }
+}
+
+pub struct TasDataLoader {
+    finder : FileFinder,
+    archive_location : String,
+    year : String,
+    /// Members elected per division, which depends upon the year.
+    vacancies : NumberOfCandidates,
+    page_url : String,
+}
+
+impl KnowsAboutRawMarkings for TasDataLoader {}
+
+impl RawDataSource for TasDataLoader {
+    fn name(&self,electorate:&str) -> ElectionName {
+        ElectionName{
+            year: self.year.clone(),
+            authority: "Tasmanian Electoral Commission".to_string(),
+            name: "Tasmanian House of Assembly".to_string(),
+            electorate: electorate.to_string(),
+            modifications: vec![],
+            comment: None,
+        }
+    }
+
+    fn candidates_to_be_elected(&self,_region:&str) -> anyhow::Result<NumberOfCandidates> { Ok(self.vacancies) }
+
+    fn ec_decisions(&self,_electorate:&str) -> TieResolutionsMadeByEC {
+        TieResolutionsMadeByEC::default()
+    }
+
+    fn excluded_candidates(&self,_electorate:&str) -> anyhow::Result<Vec<CandidateIndex>> {
+        Ok(Default::default())
+    }
+
+    /// Papers with a gap or repeated preference are cut off just before the problem. Papers with fewer
+    /// preferences than there are vacancies are informal.
+    fn read_raw_data(&self,electorate:&str) -> anyhow::Result<ElectionData> {
+        let mut metadata = self.read_raw_metadata(electorate)?;
+        let filename = electorate.to_string()+" Preferences.csv";
+        let preferences_file = self.find_raw_data_file(&filename)?;
+        println!("Parsing {}",&preferences_file.to_string_lossy());
+        metadata.source[0].files.push(filename.clone());
+        let minimum_preferences = metadata.vacancies.map(|v|v.0).unwrap_or(1);
+        let candidate_of_name = metadata.get_candidate_name_lookup_multiple_ways();
+        let mut rdr = csv::Reader::from_path(&preferences_file)?;
+        let column_candidates : Vec<Option<CandidateIndex>> = rdr.headers()?.iter().map(|h|candidate_of_name.get(h.trim()).cloned()).collect();
+        if column_candidates.iter().flatten().count()!=metadata.candidates.len() { return Err(anyhow!("Could not find all candidates in the header of {}",filename)); }
+        let mut btl = UniqueBTLBuilder::default();
+        let mut informal : usize = 0;
+        for record in rdr.records() {
+            let record = record?;
+            let mut marked : Vec<Option<CandidateIndex>> = vec![None;metadata.candidates.len()];
+            let mut repeated : HashSet<usize> = HashSet::default();
+            for (column,value) in record.iter().enumerate() {
+                if let Some(candidate) = column_candidates[column] {
+                    let value = value.trim();
+                    if !value.is_empty() {
+                        let preference : usize = value.parse()?;
+                        if preference>0 && preference<=marked.len() {
+                            if marked[preference-1].is_some() { repeated.insert(preference); }
+                            marked[preference-1]=Some(candidate);
+                        }
+                    }
+                }
+            }
+            let mut prefs : Vec<CandidateIndex> = vec![];
+            for (i,candidate) in marked.iter().enumerate() {
+                match candidate {
+                    Some(candidate) if !repeated.contains(&(i+1)) => prefs.push(*candidate),
+                    _ => break,
+                }
+            }
+            if prefs.len()<minimum_preferences { informal+=1; } else { btl.add(prefs); }
+        }
+        Ok(ElectionData{ metadata, atl:vec![], atl_types: vec![], atl_transfer_values: vec![], btl:btl.to_btls(), btl_types: vec![], btl_transfer_values: vec![], informal })
+    }
+
+    fn find_raw_data_file(&self,filename:&str) -> Result<PathBuf,MissingFile> {
+        self.finder.find_raw_data_file(filename,&self.archive_location,&self.page_url)
+    }
+
+    fn all_electorates(&self) -> Vec<String> {
+        self.read_candidate_records().map(|records|{
+            let mut res : Vec<String> = vec![];
+            for record in records {
+                if !res.contains(&record.division) { res.push(record.division) }
+            }
+            res
+        }).unwrap_or_default()
+    }
+
+    fn read_raw_metadata(&self,electorate:&str) -> anyhow::Result<ElectionMetadata> {
+        let mut parties : Vec<Party> = vec![];
+        let mut candidates : Vec<Candidate> = vec![];
+        for record in self.read_candidate_records()? {
+            if record.division==electorate {
+                let party = if record.group.is_empty() { None } else {
+                    let index = parties.iter().position(|p|p.column_id==record.group).unwrap_or_else(||{
+                        parties.push(Party{
+                            column_id: record.group.clone(),
+                            name: record.group.clone(),
+                            abbreviation: None,
+                            atl_allowed: false,
+                            candidates: vec![],
+                            tickets: vec![],
+                        });
+                        parties.len()-1
+                    });
+                    parties[index].candidates.push(CandidateIndex(candidates.len()));
+                    Some(PartyIndex(index))
+                };
+                candidates.push(Candidate{
+                    name: record.name,
+                    party,
+                    position: party.map(|p|parties[p.0].candidates.len()),
+                    ec_id: None
+                });
+            }
+        }
+        if candidates.is_empty() { return Err(self.bad_electorate(electorate)); }
+        Ok(ElectionMetadata{
+            name: self.name(electorate),
+            candidates,
+            parties,
+            source: vec![DataSource{
+                url: self.page_url.clone(),
+                files: vec!["Candidates.csv".to_string()],
+                comments: None
+            }],
+            results: None,
+            vacancies: Some(self.candidates_to_be_elected(electorate)?),
+            enrolment: None,
+            secondary_vacancies: None,
+            excluded: self.excluded_candidates(electorate)?,
+            tie_resolutions : self.ec_decisions(electorate),
+        })
+    }
+
+    fn copyright(&self) -> Copyright {
+        Copyright{
+            statement: Some("© Tasmanian Electoral Commission".into()),
+            url: Some("https://www.tec.tas.gov.au/".into()),
+            license_name: None,
+            license_url: None,
+        }
+    }
+
+    fn rules(&self, _electorate: &str) -> AssociatedRules {
+        AssociatedRules{
+            rules_used: Some("TasHareClark".into()),
+            rules_recommended: Some("TasHareClark".into()),
+            comment: None,
+            reports: vec![]
+        }
+    }
+
+    fn read_official_dop_transcript(&self, metadata: &ElectionMetadata) -> anyhow::Result<OfficialDistributionOfPreferencesTranscript> {
+        let path = self.find_raw_data_file(&(metadata.name.electorate.clone()+" Distribution of Preferences.csv"))?;
+        parse_tec_official_dop_transcript(&path,metadata)
+    }
+}
+
+#[derive(Deserialize)]
+struct CandidateRecord {
+    #[serde(rename = "Division")]
+    division : String,
+    #[serde(rename = "Group")]
+    group : String,
+    #[serde(rename = "Name")]
+    name : String,
+}
+
+impl TasDataLoader {
+
+    pub fn new(finder:&FileFinder,year:&'static str,page_url:&'static str) -> anyhow::Result<Self> {
+        let year_number : u32 = year.parse().map_err(|_|anyhow!("{} is not a valid year",year))?;
+        Ok(TasDataLoader {
+            finder : finder.clone(),
+            archive_location: "Tas/".to_string()+year,
+            year: year.to_string(),
+            // Each division elected 5 members from 1998 until the House was enlarged for the 2024 election.
+            vacancies: NumberOfCandidates(if year_number>=2024 {7} else {5}),
+            page_url: page_url.to_string(),
+        })
+    }
+
+    fn read_candidate_records(&self) -> anyhow::Result<Vec<CandidateRecord>> {
+        let path = self.find_raw_data_file("Candidates.csv")?;
+        let mut rdr = csv::Reader::from_path(path)?;
+        let mut res = vec![];
+        for record in rdr.deserialize() {
+            let record : CandidateRecord = record?;
+            res.push(record);
+        }
+        Ok(res)
+    }
+}
+
+/// Parse the TEC official distribution of preferences.
+///
+/// Papers not transferred in a surplus distribution (the `Papers` row minus the `Votes` row) are
+/// recorded as set aside for the quota, so the random selection can be replayed without the votes.
+pub fn parse_tec_official_dop_transcript(path:&PathBuf,metadata:&ElectionMetadata) -> anyhow::Result<OfficialDistributionOfPreferencesTranscript> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let headers = rdr.headers()?.clone();
+    let find_column = |name:&str| headers.iter().position(|h|h.trim()==name).ok_or_else(||anyhow!("Could not find column {} in distribution of preferences",name));
+    let count_col = find_column("Count")?;
+    let row_col = find_column("Row")?;
+    let exhausted_col = find_column("Exhausted")?;
+    let loss_by_fraction_col = find_column("Loss by Fraction")?;
+    let remarks_col = find_column("Remarks")?;
+    let mut candidate_cols = vec![usize::MAX;metadata.candidates.len()];
+    for (col,heading) in headers.iter().enumerate() {
+        for (candidate,candidate_col) in metadata.candidates.iter().zip(candidate_cols.iter_mut()) {
+            if heading.trim()==candidate.name || heading.trim()==candidate.no_comma_name() { *candidate_col=col; }
+        }
+    }
+    if candidate_cols.contains(&usize::MAX) { return Err(anyhow!("Could not find all candidates in distribution of preferences")); }
+    let candidate_of_name = metadata.get_candidate_name_lookup_multiple_ways();
+    fn parse_num(s:&str) -> anyhow::Result<f64> {
+        let s = s.trim().replace(',',"");
+        if s.is_empty() { Ok(0.0) } else { Ok(s.parse::<f64>()?) }
+    }
+    let read_row = |record:&csv::StringRecord| -> anyhow::Result<PerCandidate<f64>> {
+        Ok(PerCandidate{
+            candidate: candidate_cols.iter().map(|&c|parse_num(&record[c])).collect::<anyhow::Result<Vec<f64>>>()?,
+            exhausted: parse_num(&record[exhausted_col])?,
+            rounding: parse_num(&record[loss_by_fraction_col])?.into(),
+            set_aside: None,
+        })
+    };
+    let mut counts : Vec<OfficialDOPForOneCount> = vec![];
+    let mut rows_by_count : HashMap<String,usize> = HashMap::default();
+    let mut papers_examined : Vec<Option<PerCandidate<f64>>> = vec![];
+    let mut not_continuing : HashSet<CandidateIndex> = HashSet::default();
+    for record in rdr.records() {
+        let record = record?;
+        let count_name = record[count_col].trim().to_string();
+        if count_name.is_empty() { continue; }
+        let index = *rows_by_count.entry(count_name.clone()).or_insert_with(||{
+            counts.push(OfficialDOPForOneCount{ count_name:Some(count_name.clone()), ..Default::default() });
+            papers_examined.push(None);
+            counts.len()-1
+        });
+        match record[row_col].trim() {
+            "Papers" => papers_examined[index]=Some(read_row(&record)?),
+            "Votes" => counts[index].vote_delta=Some(read_row(&record)?),
+            "Total" => counts[index].vote_total=Some(read_row(&record)?),
+            other => return Err(anyhow!("Unexpected row type {} in count {}",other,count_name)),
+        }
+        for remark in record[remarks_col].split('.') {
+            let remark = remark.trim();
+            if let Some(name) = remark.strip_suffix(" elected") {
+                let candidate = *candidate_of_name.get(name.trim()).ok_or_else(|| anyhow!("Can't find elected candidate name {}",name))?;
+                if not_continuing.insert(candidate) { counts[index].elected.push(candidate); }
+            } else if let Some(name) = remark.strip_suffix(" excluded") {
+                let candidate = *candidate_of_name.get(name.trim()).ok_or_else(|| anyhow!("Can't find excluded candidate name {}",name))?;
+                if not_continuing.insert(candidate) { counts[index].excluded.push(candidate); }
+            }
+        }
+    }
+    for (count,papers) in counts.iter_mut().zip(papers_examined) {
+        if let Some(votes) = &count.vote_delta {
+            // each paper is worth one vote, so the papers transferred are the votes transferred.
+            count.paper_delta = Some(PerCandidate{
+                candidate: votes.candidate.iter().map(|v|*v as isize).collect(),
+                exhausted: votes.exhausted as isize,
+                rounding: 0.into(),
+                set_aside: None,
+            });
+            if let Some(papers) = papers {
+                let set_aside = |papers:f64,votes:f64| if papers>votes && votes>=0.0 { (papers-votes) as usize } else { 0 };
+                count.paper_set_aside_for_quota = Some(PerCandidate{
+                    candidate: papers.candidate.iter().zip(votes.candidate.iter()).map(|(p,v)|set_aside(*p,*v)).collect(),
+                    exhausted: set_aside(papers.exhausted,votes.exhausted),
+                    rounding: 0.into(),
+                    set_aside: None,
+                });
+            }
+        }
+    }
+    Ok(OfficialDistributionOfPreferencesTranscript{ quota:None, counts, missing_negatives_in_papers_delta: false, elected_candidates_are_in_order: true, all_exhausted_go_to_rounding: false, negative_values_in_surplus_distributions_and_rounding_may_be_off: false })
+}
@@ -0,0 +1,99 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! This tests how the official transcripts compare to the rules, with no knowledge of the actual votes.
+
+use stv::official_dop_transcript::{DifferenceBetweenOfficialDoPAndComputed, test_official_dop_without_actual_votes};
+use stv::preference_distribution::PreferenceDistributionRules;
+use stv::tie_resolution::TieResolutionExplicitDecisionInCount;
+use tas::parse::TasDataSource;
+use tas::TasHareClark;
+
+/// Test a particular year & electorate against a particular set of rules.
+/// Outermost error is IO type errors.
+/// Innermost error is discrepancies with the official DoP.
+fn test<Rules:PreferenceDistributionRules+Default>(year:&str,electorate:&str) -> anyhow::Result<Result<Option<TieResolutionExplicitDecisionInCount>, DifferenceBetweenOfficialDoPAndComputed<Rules::Tally>>> where <Rules as PreferenceDistributionRules>::Tally: Send+Sync+'static {
+    test_official_dop_without_actual_votes::<Rules,_>(&TasDataSource{},year,electorate,false)
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn test_Bass2018() {
+    assert_eq!(test::<TasHareClark>("2018","Bass").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Braddon2018() {
+    assert_eq!(test::<TasHareClark>("2018","Braddon").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Clark2018() {
+    assert_eq!(test::<TasHareClark>("2018","Clark").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Franklin2018() {
+    assert_eq!(test::<TasHareClark>("2018","Franklin").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Lyons2018() {
+    assert_eq!(test::<TasHareClark>("2018","Lyons").unwrap(),Ok(None));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn test_Bass2021() {
+    assert_eq!(test::<TasHareClark>("2021","Bass").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Braddon2021() {
+    assert_eq!(test::<TasHareClark>("2021","Braddon").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Clark2021() {
+    assert_eq!(test::<TasHareClark>("2021","Clark").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Franklin2021() {
+    assert_eq!(test::<TasHareClark>("2021","Franklin").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Lyons2021() {
+    assert_eq!(test::<TasHareClark>("2021","Lyons").unwrap(),Ok(None));
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn test_Bass2024() {
+    assert_eq!(test::<TasHareClark>("2024","Bass").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Braddon2024() {
+    assert_eq!(test::<TasHareClark>("2024","Braddon").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Clark2024() {
+    assert_eq!(test::<TasHareClark>("2024","Clark").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Franklin2024() {
+    assert_eq!(test::<TasHareClark>("2024","Franklin").unwrap(),Ok(None));
+}
+#[test]
+#[allow(non_snake_case)]
+fn test_Lyons2024() {
+    assert_eq!(test::<TasHareClark>("2024","Lyons").unwrap(),Ok(None));
+}
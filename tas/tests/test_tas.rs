@@ -0,0 +1,80 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Test the Tasmanian Hare-Clark rules and the TEC file parsing on small examples.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use stv::ballot_metadata::{CandidateIndex, NumberOfCandidates};
+use stv::ballot_paper::BTL;
+use stv::ballot_pile::BallotPaperCount;
+use stv::distribution_of_preferences_transcript::{CountIndex, ReasonForCount};
+use stv::election_data::ElectionData;
+use stv::parse_util::{FileFinder, RawDataSource};
+use stv::preference_distribution::distribute_preferences;
+use stv::random_util::Randomness;
+use tas::parse::get_tas_data_loader_2021;
+use tas::TasHareClark;
+use stv::count_observer::IgnoreCountProgress;
+
+#[test]
+fn test_random_sample_of_surplus() {
+    let data = ElectionData::from_btl(&["A","B","C","D"],2,vec![
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(1)], n: 40 },
+        BTL{ candidates: vec![CandidateIndex(0),CandidateIndex(2)], n: 20 },
+        BTL{ candidates: vec![CandidateIndex(1)], n: 10 },
+        BTL{ candidates: vec![CandidateIndex(2)], n: 25 },
+        BTL{ candidates: vec![CandidateIndex(3)], n: 5 },
+    ]);
+    let transcript = distribute_preferences::<TasHareClark>(&data,NumberOfCandidates(2),&HashSet::default(),&data.metadata.tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
+    assert_eq!(34,transcript.quota.as_ref().unwrap().quota);
+    // A's surplus of 26 is 26/60 of the last parcel, so B gets 17 of 40 papers, and C 9 (8 and the largest remainder) of 20.
+    let surplus = transcript.count(CountIndex(1));
+    assert!(matches!(surplus.reason,ReasonForCount::ExcessDistribution(CandidateIndex(0))));
+    assert_eq!(vec![34,27,34,5],surplus.status.tallies.candidate);
+    let set_aside = surplus.set_aside_for_quota.as_ref().unwrap();
+    assert_eq!(BallotPaperCount(23),set_aside.candidate[1]);
+    assert_eq!(BallotPaperCount(11),set_aside.candidate[2]);
+    assert_eq!(transcript.elected,vec![CandidateIndex(0),CandidateIndex(2)]);
+}
+
+/// Make a small data repository in a temporary directory.
+fn make_test_files() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("concrete_stv_tas_test_{}",std::process::id()));
+    let year_dir = dir.join("Tas").join("2021");
+    std::fs::create_dir_all(&year_dir).unwrap();
+    std::fs::write(year_dir.join("Candidates.csv"),"Division,Group,Name\nBass,A,Ann\nBass,A,Bob\nBass,B,Cat\nBass,B,Dan\nBass,,Eve\nBass,,Fay\nLyons,A,Gus\n").unwrap();
+    std::fs::write(year_dir.join("Bass Preferences.csv"),"Batch,Paper,Ann,Bob,Cat,Dan,Eve,Fay\n1,1,1,2,3,4,5,\n1,2,1,2,3,,,\n1,3,2,1,3,3,4,5\n1,4,6,5,4,3,2,1\n").unwrap();
+    std::fs::write(year_dir.join("Bass Distribution of Preferences.csv"),"Count,Row,Ann,Bob,Cat,Dan,Eve,Fay,Exhausted,Loss by Fraction,Remarks\n\
+1,Papers,60,10,12,8,6,4,0,0,\n1,Votes,60,10,12,8,6,4,0,0,Ann elected\n1,Total,60,10,12,8,6,4,0,0,\n\
+2,Papers,,40,10,4,3,2,1,0,\n2,Votes,-43,23,5,2,1,1,0,0,\n2,Total,17,33,17,10,7,5,0,0,Bob elected. Fay excluded\n").unwrap();
+    dir
+}
+
+#[test]
+fn test_parse_tec_files() {
+    let dir = make_test_files();
+    let loader = get_tas_data_loader_2021(&FileFinder{path:dir.clone()}).unwrap();
+    assert_eq!(vec!["Bass".to_string(),"Lyons".to_string()],loader.all_electorates());
+    let data = loader.read_raw_data("Bass").unwrap();
+    assert_eq!(6,data.metadata.candidates.len());
+    assert_eq!(2,data.metadata.parties.len());
+    assert_eq!(Some(2),data.metadata.candidates[1].position);
+    assert_eq!(None,data.metadata.candidates[4].party);
+    // paper 2 has too few preferences, paper 3 has a repeated 3 so only 2 valid preferences.
+    assert_eq!(2,data.informal);
+    assert_eq!(2,data.btl.len());
+    let official = loader.read_official_dop_transcript(&data.metadata).unwrap();
+    assert_eq!(2,official.counts.len());
+    assert_eq!(vec![CandidateIndex(0)],official.counts[0].elected);
+    assert_eq!(vec![CandidateIndex(1)],official.counts[1].elected);
+    assert_eq!(vec![CandidateIndex(5)],official.counts[1].excluded);
+    let set_aside = official.counts[1].paper_set_aside_for_quota.as_ref().unwrap();
+    assert_eq!(vec![0,17,5,2,2,1],set_aside.candidate);
+    assert_eq!(1,set_aside.exhausted);
+    assert_eq!(Some(33.0),official.counts[1].vote_total.as_ref().map(|t|t.candidate[1]));
+    std::fs::remove_dir_all(dir).unwrap();
+}
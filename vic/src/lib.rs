@@ -18,6 +18,7 @@ pub mod parse_vic;
 /// Appropriate legislation is the Electoral Act 2002, Section 114A.
 /// Called 2018 as the legislation changed 114A(28)(c) in 2018 to match this behaviour, although
 /// it was used by the VEC in 2014 anyway IMAO.
+#[derive(Default)]
 pub struct Vic2018LegislativeCouncil {
 }
 
@@ -30,33 +31,33 @@ impl PreferenceDistributionRules for Vic2018LegislativeCouncil {
     /// expect the legislators probably intended based on the wording of (12).
     type SplitByNumber = SplitFirstCount;
 
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { LastParcelUse::No }
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::No }
     /// (7)(a)
-    fn transfer_value_method() -> TransferValueMethod { TransferValueMethod::SurplusOverBallots }
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverBallots }
 
     fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { convert_usize_to_rational(tally)  }
     fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { round_rational_down_to_usize(rational)  }
 
-    fn make_transfer_value(surplus: usize, ballots: BallotPaperCount) -> TransferValue {
+    fn make_transfer_value(&self,surplus: usize, ballots: BallotPaperCount) -> TransferValue {
         TransferValue::from_surplus(surplus,ballots)
     }
 
     /// 12(b)(ii) ...`(disregarding any fraction)`...
-    fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
         transfer_value.mul_rounding_down(ballots)
     }
 
     /// All done by clause 7.
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod { SurplusTransferMethod::JustOneTransferValue }
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::JustOneTransferValue }
 
-    fn sort_exclusions_by_transfer_value() -> bool { true }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { true }
 
     /// ```text
     /// (25) If on the final count or transfer 2 candidates have
     /// an equal number of votes, the result is to be
     /// determined by lot by the election manager.
     /// ```
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { MethodOfTieResolution::None }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
     /// ```text
     /// (21) Subject to subsection (23), if after any count or
     /// transfer, 2 or more candidates have equal
@@ -69,9 +70,9 @@ impl PreferenceDistributionRules for Vic2018LegislativeCouncil {
     /// number of votes at that count or transfer being
     /// transferred first.
     /// ```
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
     /// Not specified
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { MethodOfTieResolution::None }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
     /// ```text
     /// (24) If on any count or transfer 2 or more candidates
     /// have the fewest number of votes and the candidate
@@ -87,10 +88,10 @@ impl PreferenceDistributionRules for Vic2018LegislativeCouncil {
     /// ```
     /// This is not as unambiguous as section (21) which explicitly state `each of` before `those candidates had a different number of votes`
     /// but the implication that there is a well defined `whichever of those candidates had the fewest votes` is something.
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
 
     /// No such middle.
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool { false }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { false }
     ///
     /// ```text
     /// (13) Any continuing candidate who has received a
@@ -125,7 +126,7 @@ impl PreferenceDistributionRules for Vic2018LegislativeCouncil {
     /// accordance with subsection (12) to continuing
     /// candidates.
     /// ```
-    fn check_elected_if_in_middle_of_exclusion() -> bool { true }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { true }
 
     /// This is not at all clear. Section 12 says `all that candidate's votes are to be transferred` implying it should be finished.
     /// but sections (19) may shortcut this by `Despite any other provision of this section`.
@@ -133,7 +134,7 @@ impl PreferenceDistributionRules for Vic2018LegislativeCouncil {
     /// * evidence : 2014, northern metropolitan region, ended on count 263 in the middle
     ///   of eliminating YIGIT, Burhan when there were still 171 votes with TV≈0.0437416 to distribute.
     /// So I have adopted the same interpretation.
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool { false }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
 
     /// The clause talking about distribution starts:
     /// ```text
@@ -142,7 +143,7 @@ impl PreferenceDistributionRules for Vic2018LegislativeCouncil {
     // transferred to the continuing candidates as
     // follows—
     /// ```
-    fn finish_all_surplus_distributions_when_all_elected() -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
 
 
     /// This doesn't seem well defined by the legislation. (and it does affect who is elected)
@@ -159,7 +160,7 @@ impl PreferenceDistributionRules for Vic2018LegislativeCouncil {
     /// two continuing candidates left and 1 remaining vacancy. No one was elected
     /// until count 248 which was the distribution of Harriet SHING's surplus.
     /// This seems a reasonable interpretation.
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
 
     /// This doesn't seem well defined by the legislation. (but it doesn't affect who is elected)
     /// ```text
@@ -169,15 +170,15 @@ impl PreferenceDistributionRules for Vic2018LegislativeCouncil {
     /// candidates are to be declared elected by the
     /// election manager.
     /// ```
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuota }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuota }
 
     /// No such clause
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never}
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never}
 
     /// No such clause
-    fn should_eliminate_multiple_candidates_federal_rule_13a() -> bool { false }
+    fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { false }
 
-    fn name() -> String { "Vic2018".to_string() }
-    fn how_to_name_counts() -> CountNamingMethod { CountNamingMethod::SimpleNumber }
+    fn name(&self) -> String { "Vic2018".to_string() }
+    fn how_to_name_counts(&self) -> CountNamingMethod { CountNamingMethod::SimpleNumber }
 }
 
@@ -14,13 +14,14 @@ use stv::ballot_metadata::{Candidate, CandidateIndex, DataSource, ElectionMetada
 use stv::datasource_description::{AssociatedRules, Copyright, ElectionDataSource};
 use stv::election_data::ElectionData;
 use stv::official_dop_transcript::{OfficialDistributionOfPreferencesTranscript, OfficialDOPForOneCount};
-use stv::parse_util::{CalamineLikeWrapper, FileFinder, KnowsAboutRawMarkings, MissingFile, RawDataSource, read_raw_data_checking_against_official_transcript_to_deduce_ec_resolutions};
+use stv::parse_util::{CalamineLikeWrapper, CanReadRawMarkings, FileFinder, MissingFile, RawBallotPaperMetadata, RawDataSource, read_raw_data_checking_against_official_transcript_to_deduce_ec_resolutions};
 use stv::tie_resolution::TieResolutionsMadeByEC;
 use crate::Vic2018LegislativeCouncil;
 use calamine::{DataType, open_workbook_auto};
 use stv::ballot_paper::{ATL, BTL, parse_marking, RawBallotMarking, RawBallotMarkings, UniqueBTLBuilder};
 use stv::ballot_pile::BallotPaperCount;
 use stv::distribution_of_preferences_transcript::{CountIndex, PerCandidate, QuotaInfo};
+use stv::quota::QuotaMethod;
 use stv::signed_version::SignedVersion;
 
 pub fn get_vic_data_loader_2014(finder:&FileFinder) -> anyhow::Result<VicDataLoader> {
@@ -61,7 +62,34 @@ pub struct VicDataLoader {
     page_url : String,
 }
 
-impl KnowsAboutRawMarkings for VicDataLoader {}
+impl CanReadRawMarkings for VicDataLoader {
+    /// The VEC provides votes with above the line votes already converted to below the line equivalents,
+    /// so only below the line markings are available.
+    fn iterate_over_raw_markings<F>(&self,electorate:&str,mut callback:F)  -> anyhow::Result<ElectionMetadata>
+        where F:FnMut(&RawBallotMarkings,RawBallotPaperMetadata)
+    {
+        let (mut metadata,_) = self.read_raw_metadata_and_atl_votes(electorate)?;
+        let filename = self.ballot_paper_details_filename(electorate)?;
+        let path = self.find_raw_data_file(&filename)?;
+        let mut reader = csv::ReaderBuilder::new().flexible(true).has_headers(false).from_path(&path)?;
+        let no_parties = vec![];
+        for result in reader.records() {
+            let record = result?;
+            if let Some(ballot_index_in_batch) = record.get(0) {
+                if !ballot_index_in_batch.is_empty() && ballot_index_in_batch.chars().all(|c|c.is_ascii_digit()) && record.len()==1+metadata.candidates.len() { // check it is not metadata.
+                    let btl_markings : Vec<RawBallotMarking> = (0..metadata.candidates.len()).map(|i|parse_marking(&record[1+i])).collect();
+                    callback(&RawBallotMarkings::new(&no_parties,&btl_markings),&[("Ballot Index in Batch",ballot_index_in_batch)]);
+                }
+            }
+        }
+        metadata.source.push(DataSource{
+            url: "VEC does not publish unfortunately".to_string(),
+            files: vec![filename],
+            comments: None
+        });
+        Ok(metadata)
+    }
+}
 
 impl RawDataSource for VicDataLoader {
     fn name(&self, electorate: &str) -> ElectionName {
@@ -75,8 +103,8 @@ impl RawDataSource for VicDataLoader {
         }
     }
 
-    fn candidates_to_be_elected(&self, _region: &str) -> NumberOfCandidates {
-        NumberOfCandidates(5)
+    fn candidates_to_be_elected(&self, _region: &str) -> anyhow::Result<NumberOfCandidates> {
+        Ok(NumberOfCandidates(5))
     }
 
     /// These are deduced by looking at the actual transcript of results.
@@ -86,8 +114,8 @@ impl RawDataSource for VicDataLoader {
     }
 
     /// These are due to a variety of events.
-    fn excluded_candidates(&self, _electorate: &str) -> Vec<CandidateIndex> {
-        Default::default()
+    fn excluded_candidates(&self, _electorate: &str) -> anyhow::Result<Vec<CandidateIndex>> {
+        Ok(Default::default())
     }
 
     fn find_raw_data_file(&self, filename: &str) -> Result<PathBuf, MissingFile> {
@@ -112,11 +140,7 @@ impl RawDataSource for VicDataLoader {
     fn read_raw_data(&self, electorate: &str) -> anyhow::Result<ElectionData> {
         let (mut metadata,atl_votes) = self.read_raw_metadata_and_atl_votes(electorate)?;
         // println!("{:?}",metadata);
-        let filename = match self.year.as_str() {
-            "2014" => format!("received_from_ec/Ballot Paper Details - {}.csv",electorate),
-            "2022" => format!("received_from_ec/BallotPaperDetails-{}.csv",electorate.trim_end_matches(" Region").trim_end_matches("politan")),
-            _ => {return Err(anyhow!("Do not know the file naming convention for votes received in {}",self.year))}
-        };
+        let filename = self.ballot_paper_details_filename(electorate)?;
         let path = self.find_raw_data_file(&filename)?;
         let mut reader = csv::ReaderBuilder::new().flexible(true).has_headers(false).from_path(&path)?;
         let mut builder = UniqueBTLBuilder::default();
@@ -328,6 +352,15 @@ fn find_atl_votes_in_btl(result:&mut ElectionData,atl_votes:&[BallotPaperCount])
     Ok(())
 }
 impl VicDataLoader {
+    /// The name of the file containing the ballot paper details received from the VEC.
+    fn ballot_paper_details_filename(&self,electorate:&str) -> anyhow::Result<String> {
+        match self.year.as_str() {
+            "2014" => Ok(format!("received_from_ec/Ballot Paper Details - {}.csv",electorate)),
+            "2022" => Ok(format!("received_from_ec/BallotPaperDetails-{}.csv",electorate.trim_end_matches(" Region").trim_end_matches("politan"))),
+            _ => Err(anyhow!("Do not know the file naming convention for votes received in {}",self.year)),
+        }
+    }
+
     /// Get the metadata from the file like south-easternmetropolitanregionvotesreceived.xls
     fn read_raw_metadata_and_atl_votes(&self,electorate:&str) -> anyhow::Result<(ElectionMetadata,Vec<BallotPaperCount>)> {
         if self.election_count_not_published_yet() {
@@ -433,7 +466,7 @@ impl VicDataLoader {
                 comments: None
             }],
             results: None,
-            vacancies: Some(self.candidates_to_be_elected(electorate)),
+            vacancies: Some(self.candidates_to_be_elected(electorate)?),
             enrolment: None,
             secondary_vacancies: None,
             excluded: vec![],
@@ -496,7 +529,7 @@ impl VicDataLoader {
                 comments: None
             }],
             results: None,
-            vacancies: Some(self.candidates_to_be_elected(electorate)),
+            vacancies: Some(self.candidates_to_be_elected(electorate)?),
             enrolment: None,
             secondary_vacancies: None,
             excluded: vec![],
@@ -754,7 +787,8 @@ impl DOPFileFormat {
                     let quota = QuotaInfo{
                         papers: formal_ballots.ok_or_else(||anyhow!("No formal ballots heading found"))?,
                         vacancies: NumberOfCandidates(5),
-                        quota: quota_size.ok_or_else(||anyhow!("No quota heading found"))?
+                        quota: quota_size.ok_or_else(||anyhow!("No quota heading found"))?,
+                        method: QuotaMethod::Droop,
                     };
                     return Ok(DOPFileFormat{
                         quota,
@@ -13,10 +13,11 @@ use stv::random_util::Randomness;
 use stv::tie_resolution::{TieResolutionAtom, TieResolutionsMadeByEC};
 use vic::parse_vic::{get_vic_data_loader_2014, get_vic_data_loader_2022, VicDataLoader};
 use vic::Vic2018LegislativeCouncil;
+use stv::count_observer::IgnoreCountProgress;
 
 const SAVE_VOTE_DATA : bool = false;
 
-fn test<Rules:PreferenceDistributionRules>(electorate:&str, loader:&VicDataLoader) {
+fn test<Rules:PreferenceDistributionRules+Default>(electorate:&str, loader:&VicDataLoader) {
     let mut data = loader.read_raw_data(electorate).unwrap();
     data.print_summary();
     if data.metadata.name.year=="2022" && data.metadata.name.electorate=="North-Eastern Metropolitan Region" {
@@ -37,7 +38,7 @@ fn test<Rules:PreferenceDistributionRules>(electorate:&str, loader:&VicDataLoade
     let mut tie_resolutions = TieResolutionsMadeByEC::default();
     let official_transcript = loader.read_official_dop_transcript(&data.metadata).unwrap();
     loop {
-        let transcript = distribute_preferences::<Rules>(&data, loader.candidates_to_be_elected(electorate), &data.metadata.excluded.iter().cloned().collect(), &tie_resolutions,None,false,&mut Randomness::ReverseDonkeyVote);
+        let transcript = distribute_preferences::<Rules>(&data, loader.candidates_to_be_elected(electorate).unwrap(), &data.metadata.excluded.iter().cloned().collect(), &tie_resolutions,None,&mut IgnoreCountProgress{},&mut Randomness::ReverseDonkeyVote).unwrap();
         let transcript = TranscriptWithMetadata{ metadata: data.metadata.clone(), transcript };
         std::fs::create_dir_all("test_transcripts").unwrap();
         {
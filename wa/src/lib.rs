@@ -16,6 +16,7 @@ pub mod parse_wa;
 /// My guess at what the legislation means.
 /// Appropriate legislation is the "Electoral Act 1907", Schedule 1, "Counting of votes at Legislative Council elections"
 /// from which comments below are drawn.
+#[derive(Default)]
 pub struct WALegislativeCouncil {
 }
 
@@ -33,19 +34,19 @@ impl PreferenceDistributionRules for WALegislativeCouncil {
     type SplitByNumber = FullySplitByCountNumber;
 
     /// 5(a) `divided by the number of votes received by him`
-    fn use_last_parcel_for_surplus_distribution() -> LastParcelUse { LastParcelUse::No }
+    fn use_last_parcel_for_surplus_distribution(&self) -> LastParcelUse { LastParcelUse::No }
     /// 5(a) `divided by the number of votes received by him`
-    fn transfer_value_method() -> TransferValueMethod { TransferValueMethod::SurplusOverBallots }
+    fn transfer_value_method(&self) -> TransferValueMethod { TransferValueMethod::SurplusOverBallots }
 
     fn convert_tally_to_rational(tally: Self::Tally) -> BigRational { convert_usize_to_rational(tally)  }
     fn convert_rational_to_tally_after_applying_transfer_value(rational: BigRational) -> Self::Tally { round_rational_down_to_usize(rational)  }
 
-    fn make_transfer_value(surplus: usize, ballots: BallotPaperCount) -> TransferValue {
+    fn make_transfer_value(&self,surplus: usize, ballots: BallotPaperCount) -> TransferValue {
         TransferValue::from_surplus(surplus,ballots)
     }
 
     /// 4(b) and 5(c) and 8b(iii), ...`(disregarding any fraction)`...
-    fn use_transfer_value(transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
+    fn use_transfer_value(&self,transfer_value: &TransferValue, ballots: BallotPaperCount) -> usize {
         transfer_value.mul_rounding_down(ballots)
     }
 
@@ -60,7 +61,7 @@ impl PreferenceDistributionRules for WALegislativeCouncil {
     ///
     /// So I have adopted what the WAEC actually did, as the legislation seems ambiguous.
     ///
-    fn surplus_distribution_subdivisions() -> SurplusTransferMethod { SurplusTransferMethod::ScaleTransferValues }
+    fn surplus_distribution_subdivisions(&self) -> SurplusTransferMethod { SurplusTransferMethod::ScaleTransferValues }
 
     /// 8(b)
     /// ```text
@@ -71,10 +72,10 @@ impl PreferenceDistributionRules for WALegislativeCouncil {
     /// earliest transfer being transferred first, as follows —
     /// ```
     /// They are done by originating count, not transfer value.
-    fn sort_exclusions_by_transfer_value() -> bool { false }
+    fn sort_exclusions_by_transfer_value(&self) -> bool { false }
 
     /// Section 12 says it is random.
-    fn resolve_ties_elected_one_of_last_two() -> MethodOfTieResolution { MethodOfTieResolution::None }
+    fn resolve_ties_elected_one_of_last_two(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
     /// Clause 15:
     /// ```text
     /// Subject to clause 16, where, after any count or transfer under this
@@ -93,10 +94,10 @@ impl PreferenceDistributionRules for WALegislativeCouncil {
     /// shall, as between those candidates, be deemed to have had the
     /// largest surplus.
     /// ```
-    fn resolve_ties_elected_by_quota() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn resolve_ties_elected_by_quota(&self) -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
     /// Not specified (section 13). Order elected is generally not mentioned in
     /// the legislation, and tie resolution is mentioned when it affects surplus distribution etc.
-    fn resolve_ties_elected_all_remaining() -> MethodOfTieResolution { MethodOfTieResolution::None }
+    fn resolve_ties_elected_all_remaining(&self) -> MethodOfTieResolution { MethodOfTieResolution::None }
     /// ```text
     /// Where the candidate who has the fewest votes is required to be
     /// excluded under clause 8 or 10, and 2 or more candidates (in this clause
@@ -107,7 +108,7 @@ impl PreferenceDistributionRules for WALegislativeCouncil {
     /// excluded, but if there has been no such count or transfer —
     /// ```
     /// continues like 15 to random lot.
-    fn resolve_ties_choose_lowest_candidate_for_exclusion() -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
+    fn resolve_ties_choose_lowest_candidate_for_exclusion(&self) -> MethodOfTieResolution { MethodOfTieResolution::RequireHistoricalCountsToBeAllDifferent }
 
     /// 5 is ambiguous, but presumably by analogy with 9 the same thing is meant by a transfer
     /// ```text
@@ -132,7 +133,7 @@ impl PreferenceDistributionRules for WALegislativeCouncil {
     /// Furthermore, looking at what the WAEC have actually done,
     /// In 2018 East Metro, count 28.5, XAMON, Alison goes over quota
     /// but is not elected until much later when the surplus distribution is finished.
-    fn check_elected_if_in_middle_of_surplus_distribution() -> bool { false }
+    fn check_elected_if_in_middle_of_surplus_distribution(&self) -> bool { false }
     /// 9
     /// ```text
     /// Any continuing candidate who has received a number of votes equal
@@ -169,7 +170,7 @@ impl PreferenceDistributionRules for WALegislativeCouncil {
     /// they are five different transfers, but 19 implies that they are 1 different
     /// transfer.
     ///
-    fn check_elected_if_in_middle_of_exclusion() -> bool { true }
+    fn check_elected_if_in_middle_of_exclusion(&self) -> bool { true }
 
     /// There is no real mention of when the election ends, just things that should be done.
     /// So there is an argument that one should continue and do the things that are said to be done,
@@ -178,15 +179,15 @@ impl PreferenceDistributionRules for WALegislativeCouncil {
     ///
     /// 2008 Agricultural region ended at count 25.36, rather than finishing the exclusion of FELS, Anthony,
     /// when DAVIES, Mia went over quota.
-    fn finish_all_counts_in_elimination_when_all_elected() -> bool { false }
+    fn finish_all_counts_in_elimination_when_all_elected(&self) -> bool { false }
 
     /// See comments on finish_all_counts_in_elimination_when_all_elected
-    fn finish_all_surplus_distributions_when_all_elected() -> bool { false }
+    fn finish_all_surplus_distributions_when_all_elected(&self) -> bool { false }
 
 
     /// This doesn't seem well defined by the legislation (clause 12). (and it does affect who is elected)
     /// The only sane thing is to wait until an exclusion is finished though.
-    fn when_to_check_if_just_two_standing_for_shortcut_election() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn when_to_check_if_just_two_standing_for_shortcut_election(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
 
     /// Section 13:
     /// ```text
@@ -200,18 +201,18 @@ impl PreferenceDistributionRules for WALegislativeCouncil {
     /// excluded from the count.
     /// ```
     /// Is a candidate excluded before or after his/her votes are distributed?
-    fn when_to_check_if_all_remaining_should_get_elected() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
+    fn when_to_check_if_all_remaining_should_get_elected(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::AfterCheckingQuotaIfNoUndistributedSurplusExistsAndExclusionNotOngoing }
 
     /// No such clause
-    fn when_to_check_if_top_few_have_overwhelming_votes() -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never}
+    fn when_to_check_if_top_few_have_overwhelming_votes(&self) -> WhenToDoElectCandidateClauseChecking { WhenToDoElectCandidateClauseChecking::Never}
 
     /// No such clause
-    fn should_eliminate_multiple_candidates_federal_rule_13a() -> bool { false }
+    fn should_eliminate_multiple_candidates_federal_rule_13a(&self) -> bool { false }
 
-    fn name() -> String { "WA2008".to_string() }
-    fn how_to_name_counts() -> CountNamingMethod { CountNamingMethod::MajorMinor }
+    fn name(&self) -> String { "WA2008".to_string() }
+    fn how_to_name_counts(&self) -> CountNamingMethod { CountNamingMethod::MajorMinor }
 
     /// In 2008, South West region, at count 26.1 during the exclusion of SULLIVAN, Dan, a candidate HOLT, Colin reached quota. The next count was named 27.1 rather than 26.2
-    fn major_count_if_someone_elected() -> bool { true }
+    fn major_count_if_someone_elected(&self) -> bool { true }
 }
 
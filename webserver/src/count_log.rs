@@ -0,0 +1,63 @@
+// Copyright 2024 Andrew Conway.
+// This file is part of ConcreteSTV.
+// ConcreteSTV is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or (at your option) any later version.
+// ConcreteSTV is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU Affero General Public License for more details.
+// You should have received a copy of the GNU Affero General Public License along with ConcreteSTV.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Log recounts requested over the web, so whoever runs the server can see what is being counted, how long it takes, and who gets elected.
+
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+use std::time::Instant;
+use stv::ballot_metadata::{CandidateIndex, ElectionMetadata};
+use stv::count_observer::CountObserver;
+use stv::distribution_of_preferences_transcript::{ElectionReason, SingleCount};
+use stv::tie_resolution::TieResolutionUsage;
+
+/// A [CountObserver] that writes one line to stdout for each candidate elected, and a summary when [LogCountProgress::finished] is called.
+pub struct LogCountProgress {
+    /// what is being counted, prefixed to every line.
+    label : String,
+    names : Vec<String>,
+    counts : usize,
+    ties_needing_ec_decision : usize,
+    started : Instant,
+}
+
+impl LogCountProgress {
+    pub fn new(label:String) -> Self {
+        LogCountProgress{ label, names: vec![], counts: 0, ties_needing_ec_decision: 0, started: Instant::now() }
+    }
+
+    fn name(&self,who:CandidateIndex) -> String {
+        self.names.get(who.0).cloned().unwrap_or_else(||who.to_string())
+    }
+
+    /// Log a summary of the count. Call when the count has finished, successfully or not.
+    pub fn finished<T,E:Display>(&self,result:&Result<T,E>) {
+        let elapsed = self.started.elapsed().as_millis();
+        match result {
+            Ok(_) => println!("{} : finished {} counts in {} ms, {} ties needed an EC decision",self.label,self.counts,elapsed,self.ties_needing_ec_decision),
+            Err(e) => println!("{} : failed after {} counts in {} ms : {}",self.label,self.counts,elapsed,e),
+        }
+    }
+}
+
+impl <Tally:PartialEq+Clone+Display+FromStr+Debug> CountObserver<Tally> for LogCountProgress {
+    fn count_started(&mut self, metadata: &ElectionMetadata) {
+        self.names = metadata.candidates.iter().map(|c|c.name.clone()).collect();
+        println!("{} : started counting {} candidates",self.label,self.names.len());
+    }
+
+    fn candidate_elected(&mut self, who: CandidateIndex, _why: ElectionReason) {
+        println!("{} : elected {} in count {}",self.label,self.name(who),self.counts+1);
+    }
+
+    fn tie_resolved(&mut self, _usage: TieResolutionUsage, _resolved_order: &[CandidateIndex], needed_ec_decision: bool) {
+        if needed_ec_decision { self.ties_needing_ec_decision+=1; }
+    }
+
+    fn count_finished(&mut self, _count: &SingleCount<Tally>) {
+        self.counts+=1;
+    }
+}
@@ -6,6 +6,7 @@
 
 mod find_election;
 mod cache;
+mod count_log;
 
 use std::path::PathBuf;
 use actix_files::NamedFile;
@@ -16,11 +17,13 @@ use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionT
 use main_app::rules::{PossibleTranscripts, Rules, RulesDetails};
 use statistics::correlations::{CorrelationDendrogramsAndSVD, CorrelationOptions, SquareMatrix};
 use stv::find_vote::{FindMyVoteQuery, FindMyVoteResult};
+use stv::trace_vote::{VoteToTrace, VoteTrace};
 use statistics::intent_table::{IntentTable, IntentTableOptions};
 use statistics::mean_preference::MeanPreferences;
 use statistics::who_got_votes::WhoGotVotes;
 use stv::ballot_metadata::{CandidateIndex, ElectionMetadata, NumberOfCandidates};
 use stv::errors_btl::ObviousErrorsInBTLVotes;
+use stv::preference_distribution::CountOptions;
 use stv::tie_resolution::TieResolutionsMadeByEC;
 use crate::cache::cache_json;
 use crate::find_election::{ALL_ELECTIONS_AS_LIST, ElectionInfo, ElectionsOfOneType, FoundElection};
@@ -28,6 +31,7 @@ use serde::{Serialize,Deserialize};
 use examples::example_datasource::ExampleDataSource;
 use stv::datasource_description::ElectionDataSource;
 use stv::random_util::Randomness;
+use crate::count_log::LogCountProgress;
 
 #[get("/get_all_contests.json")]
 async fn get_all_contests() -> Json<Result<Vec<ElectionsOfOneType>,String>> {
@@ -130,17 +134,50 @@ pub struct RecountQuery {
     pub seed : Option<u64>,
 }
 
+impl RecountQuery {
+    /// The options to count with, other than the rules and randomness.
+    fn count_options(&self) -> CountOptions<'_> {
+        CountOptions{ vote_types: self.vote_types.as_deref(), ..CountOptions::new(self.candidates_to_be_elected,self.excluded.iter().cloned().collect(),&self.tie_resolutions) }
+    }
+}
+
 #[post("/{name}/{year}/{electorate}/recount")]
 async fn recount(election : web::Path<FoundElection>,query:Json<RecountQuery>) -> Json<Result<PossibleTranscripts,String>> {
     async fn recount_uncached(election : &web::Path<FoundElection>,query:&RecountQuery) -> Result<PossibleTranscripts,String> {
-        let vote_types : Option<&[String]> = if let Some(vt) = &query.vote_types { Some(vt) } else { None };
         let mut randomness : Randomness = query.seed.into();
         let include_list_of_votes_in_transcript = election.spec.name.as_str()==ExampleDataSource{}.name().as_ref();
-        Ok(query.rules.count(&election.data().await?,query.candidates_to_be_elected,&query.excluded.iter().cloned().collect(),&query.tie_resolutions,vote_types,false,&mut randomness,&[],include_list_of_votes_in_transcript))
+        let options = CountOptions{ include_list_of_votes_in_transcript, ..query.count_options() };
+        let data = election.data().await?;
+        let mut observer = LogCountProgress::new(format!("recount {} {} {}",election.spec.name,election.spec.year,election.spec.electorate));
+        let result = query.rules.count(&data,&options,&mut observer,&mut randomness);
+        observer.finished(&result);
+        result.map_err(|e|e.to_string())
     }
     cache_json("recount",&(election.spec.clone(),query.clone()),||recount_uncached(&election,&query)).await
 }
 
+#[derive(Serialize,Deserialize,Clone)]
+pub struct TraceVoteQuery {
+    #[serde(flatten)]
+    pub recount : RecountQuery,
+    pub vote : VoteToTrace,
+}
+
+/// Where did my vote go? Recount, following ballots with the given preferences.
+#[post("/{name}/{year}/{electorate}/trace_my_vote")]
+async fn trace_my_vote(election : web::Path<FoundElection>,query:Json<TraceVoteQuery>) -> Json<Result<VoteTrace,String>> {
+    async fn trace_my_vote_uncached(election : &web::Path<FoundElection>,query:&TraceVoteQuery) -> Result<VoteTrace,String> {
+        let mut randomness : Randomness = query.recount.seed.into();
+        let options = query.recount.count_options();
+        let data = election.data().await?;
+        let formality_rules = election.loader.formality_rules(election.electorate());
+        let mut observer = LogCountProgress::new(format!("trace vote {} {} {}",election.spec.name,election.spec.year,election.spec.electorate));
+        let result = query.recount.rules.trace_vote(&data,&query.vote,formality_rules,&options,&mut observer,&mut randomness);
+        observer.finished(&result);
+        result.map_err(|e|e.to_string())?.ok_or_else(||"The vote is not formal".to_string())
+    }
+    cache_json("trace_my_vote",&(election.spec.clone(),query.clone()),||trace_my_vote_uncached(&election,&query)).await
+}
 
 
 /// find the path containing web resources, static web files that will be served.
@@ -188,6 +225,7 @@ async fn main() -> anyhow::Result<()> {
             .service(get_data)
             .service(get_rules)
             .service(recount)
+            .service(trace_my_vote)
             .service(actix_files::Files::new("/{a}/{b}/{c}/", find_web_resources().join("ContestDirectory")).use_last_modified(true).use_etag(true).index_file("index.html"))
             .service(actix_files::Files::new("/Viewer/", find_viewer_resources()).use_last_modified(true).use_etag(true))
             .service(actix_files::Files::new("/", find_web_resources().join("RootDirectory")).use_last_modified(true).use_etag(true).index_file("index.html"))